@@ -0,0 +1,455 @@
+use crate::db::tables::device::{insert_device, Device};
+use crate::db::tables::skipped_app::get_skipped_apps;
+use crate::db::{get_pool, Error};
+use anyhow::Context;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufReader;
+use std::path::Path;
+
+const WINDOW_BUCKET: &str = "currentwindow";
+const AFK_BUCKET: &str = "afkstatus";
+const DEVICE_PREFIX: &str = "activitywatch-";
+
+#[derive(Debug, Deserialize)]
+struct AwExport {
+    buckets: HashMap<String, AwBucket>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AwBucket {
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    hostname: Option<String>,
+    #[serde(default)]
+    events: Vec<AwEvent>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AwEvent {
+    timestamp: String,
+    #[serde(default)]
+    duration: f64,
+    #[serde(default)]
+    data: AwEventData,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct AwEventData {
+    app: Option<String>,
+    title: Option<String>,
+    status: Option<String>,
+}
+
+impl AwEvent {
+    /// Whole seconds covered by the event; `None` for unparsable timestamps.
+    fn span(&self) -> Option<(i64, i64)> {
+        let start = chrono::DateTime::parse_from_rfc3339(&self.timestamp).ok()?;
+        let start_ms = start.timestamp_millis();
+        let end_ms = start_ms + (self.duration.max(0.0) * 1000.0) as i64;
+        Some((start_ms.div_euclid(1000), end_ms.div_euclid(1000)))
+    }
+}
+
+/// An imported log before it gets an id: (timestamp, duration, app).
+pub type ImportedLog = (i64, i64, String);
+
+/// Formats ActivityWatch's app and title the way the tracker records foreground windows,
+/// `Title - App`, unless the title already names the app.
+pub fn window_name(app: &str, title: &str) -> String {
+    let (app, title) = (app.trim(), title.trim());
+    if title.is_empty() {
+        return app.to_string();
+    }
+    let app_name = app.trim_end_matches(".exe");
+    if app_name.is_empty() || title.to_lowercase().contains(&app_name.to_lowercase()) {
+        title.to_string()
+    } else {
+        format!("{title} - {app_name}")
+    }
+}
+
+/// Merged periods in which the afk watcher reported the user as present.
+fn active_periods(afk_events: &[AwEvent]) -> Vec<(i64, i64)> {
+    let mut periods: Vec<(i64, i64)> = afk_events
+        .iter()
+        .filter(|event| event.data.status.as_deref() == Some("not-afk"))
+        .filter_map(AwEvent::span)
+        .filter(|(start, end)| end > start)
+        .collect();
+    periods.sort_unstable();
+    let mut merged: Vec<(i64, i64)> = Vec::with_capacity(periods.len());
+    for (start, end) in periods {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct HostLogs {
+    logs: Vec<ImportedLog>,
+    afk_seconds: i64,
+    skipped_seconds: i64,
+}
+
+/// Turns window events into non-overlapping logs, dropping time outside `active` periods (when
+/// afk data exists) and windows matching a skipped-app pattern.
+fn build_logs(
+    window_events: &[AwEvent],
+    active: Option<&[(i64, i64)]>,
+    is_skipped: impl Fn(&str) -> bool,
+) -> HostLogs {
+    let mut spans: Vec<(i64, i64, String)> = window_events
+        .iter()
+        .filter_map(|event| {
+            let (start, end) = event.span()?;
+            let app = window_name(
+                event.data.app.as_deref().unwrap_or(""),
+                event.data.title.as_deref().unwrap_or(""),
+            );
+            Some((start, end, app))
+        })
+        .collect();
+    spans.sort();
+
+    let mut result = HostLogs::default();
+    let mut covered_until = i64::MIN;
+    for (start, end, app) in spans {
+        // Window buckets can overlap briefly; the earlier event keeps the shared seconds.
+        let start = start.max(covered_until);
+        if end <= start {
+            continue;
+        }
+        covered_until = end;
+        if is_skipped(&app) {
+            result.skipped_seconds += end - start;
+            continue;
+        }
+        let Some(active) = active else {
+            result.logs.push((start, end - start, app));
+            continue;
+        };
+        let mut kept = 0;
+        for &(active_start, active_end) in active {
+            if active_end <= start {
+                continue;
+            }
+            if active_start >= end {
+                break;
+            }
+            let piece_start = start.max(active_start);
+            let piece_end = end.min(active_end);
+            kept += piece_end - piece_start;
+            result
+                .logs
+                .push((piece_start, piece_end - piece_start, app.clone()));
+        }
+        result.afk_seconds += end - start - kept;
+    }
+    result
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LogWriteSummary {
+    pub inserted: usize,
+    pub updated: usize, // Previously imported while still ongoing, now longer
+    pub duplicates: usize,
+}
+
+/// Inserts logs for `device_uuid`, matching earlier imports by timestamp and app so importing
+/// an overlapping export again does not double count. Deleted logs stay deleted.
+async fn write_logs(
+    pool: &SqlitePool,
+    device_uuid: &str,
+    logs: &[ImportedLog],
+) -> Result<LogWriteSummary, Error> {
+    let mut tx = pool.begin().await?;
+    let existing: Vec<(i64, i64, String, i64)> =
+        sqlx::query_as("SELECT id, timestamp, app, duration FROM logs WHERE device_uuid = ?1")
+            .bind(device_uuid)
+            .fetch_all(&mut *tx)
+            .await?;
+    let mut existing: HashMap<(i64, String), (i64, i64)> = existing
+        .into_iter()
+        .map(|(id, timestamp, app, duration)| ((timestamp, app), (id, duration)))
+        .collect();
+    let mut next_id: i64 =
+        sqlx::query_scalar("SELECT COALESCE(MAX(id), 0) + 1 FROM logs WHERE device_uuid = ?1")
+            .bind(device_uuid)
+            .fetch_one(&mut *tx)
+            .await?;
+
+    let mut summary = LogWriteSummary::default();
+    for (timestamp, duration, app) in logs {
+        match existing.get_mut(&(*timestamp, app.clone())) {
+            Some((id, known)) if *known < *duration => {
+                sqlx::query("UPDATE logs SET duration = ?1 WHERE id = ?2 AND device_uuid = ?3")
+                    .bind(duration)
+                    .bind(*id)
+                    .bind(device_uuid)
+                    .execute(&mut *tx)
+                    .await?;
+                *known = *duration;
+                summary.updated += 1;
+            }
+            Some(_) => summary.duplicates += 1,
+            None => {
+                sqlx::query(
+                    "INSERT INTO logs (id, device_uuid, app, timestamp, duration, is_deleted)
+                     VALUES (?1, ?2, ?3, ?4, ?5, 0)",
+                )
+                .bind(next_id)
+                .bind(device_uuid)
+                .bind(app)
+                .bind(timestamp)
+                .bind(duration)
+                .execute(&mut *tx)
+                .await?;
+                existing.insert((*timestamp, app.clone()), (next_id, *duration));
+                next_id += 1;
+                summary.inserted += 1;
+            }
+        }
+    }
+    tx.commit().await?;
+    Ok(summary)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedHost {
+    pub hostname: String,
+    pub device_uuid: String,
+    pub device_name: String,
+    pub inserted: usize,
+    pub updated: usize,
+    pub duplicates: usize,
+    pub afk_seconds: i64,     // Window time dropped because the user was away
+    pub skipped_seconds: i64, // Window time matching a skipped app
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ActivityWatchImportReport {
+    pub hosts: Vec<ImportedHost>,
+    pub ignored_buckets: Vec<String>, // Buckets other than window and afk, e.g. web watchers
+    pub warnings: Vec<String>,
+}
+
+fn bucket_hostname(id: &str, bucket: &AwBucket) -> String {
+    bucket
+        .hostname
+        .clone()
+        .filter(|hostname| !hostname.trim().is_empty())
+        .or_else(|| id.rsplit_once('_').map(|(_, host)| host.to_string()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Imports an ActivityWatch export (`/api/0/export` or a single bucket export). Each host gets
+/// a synthetic remote device holding its window events as logs, so they appear in the calendar
+/// and statistics like synced devices.
+#[tauri::command]
+pub async fn import_activitywatch(path: String) -> Result<ActivityWatchImportReport, Error> {
+    let path = Path::new(path.trim());
+    let file =
+        std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let export: AwExport = serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("{} is not an ActivityWatch export", path.display()))?;
+
+    let skipped: Vec<Regex> = get_skipped_apps()
+        .await?
+        .iter()
+        .filter_map(|app| Regex::new(&app.regex).ok())
+        .collect();
+    let is_skipped = |app: &str| skipped.iter().any(|regex| regex.is_match(app));
+
+    let mut report = ActivityWatchImportReport::default();
+    let mut windows: BTreeMap<String, Vec<AwEvent>> = BTreeMap::new();
+    let mut afk: HashMap<String, Vec<AwEvent>> = HashMap::new();
+    for (key, bucket) in export.buckets {
+        let id = bucket.id.clone().unwrap_or(key);
+        let hostname = bucket_hostname(&id, &bucket);
+        match bucket.kind.as_str() {
+            WINDOW_BUCKET => windows.entry(hostname).or_default().extend(bucket.events),
+            AFK_BUCKET => afk.entry(hostname).or_default().extend(bucket.events),
+            _ => report.ignored_buckets.push(id),
+        }
+    }
+    report.ignored_buckets.sort();
+    if windows.is_empty() {
+        return Err(anyhow::anyhow!("The export contains no window buckets").into());
+    }
+
+    let pool = get_pool().await?;
+    for (hostname, events) in windows {
+        let active = afk.get(&hostname).map(|events| active_periods(events));
+        if active.is_none() {
+            report.warnings.push(format!(
+                "{hostname}: no afk bucket, all window time was imported as active"
+            ));
+        }
+        let host_logs = build_logs(&events, active.as_deref(), is_skipped);
+
+        let device = Device::new(
+            format!("{DEVICE_PREFIX}{hostname}"),
+            format!("{hostname} (ActivityWatch)"),
+        );
+        insert_device(&pool, &device).await?;
+        let written = write_logs(&pool, &device.uuid, &host_logs.logs).await?;
+        report.hosts.push(ImportedHost {
+            hostname,
+            device_uuid: device.uuid,
+            device_name: device.name,
+            inserted: written.inserted,
+            updated: written.updated,
+            duplicates: written.duplicates,
+            afk_seconds: host_logs.afk_seconds,
+            skipped_seconds: host_logs.skipped_seconds,
+        });
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(timestamp: &str, duration: f64, app: &str, title: &str) -> AwEvent {
+        AwEvent {
+            timestamp: timestamp.to_string(),
+            duration,
+            data: AwEventData {
+                app: Some(app.to_string()),
+                title: Some(title.to_string()),
+                status: None,
+            },
+        }
+    }
+
+    fn afk(timestamp: &str, duration: f64, status: &str) -> AwEvent {
+        AwEvent {
+            timestamp: timestamp.to_string(),
+            duration,
+            data: AwEventData {
+                status: Some(status.to_string()),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn window_names_follow_the_tracker_format() {
+        assert_eq!(
+            window_name("Code.exe", "main.rs - app"),
+            "main.rs - app - Code"
+        );
+        assert_eq!(
+            window_name("firefox", "Docs — Mozilla Firefox"),
+            "Docs — Mozilla Firefox"
+        );
+        assert_eq!(window_name("Slack", "  "), "Slack");
+    }
+
+    #[test]
+    fn logs_are_clipped_to_active_periods_and_filtered() {
+        let export: AwExport = serde_json::from_str(
+            r#"{"buckets": {
+                "aw-watcher-window_desk": {"id": "aw-watcher-window_desk", "type": "currentwindow",
+                  "hostname": "desk", "events": [
+                    {"id": 1, "timestamp": "2024-05-10T09:00:00.500+00:00", "duration": 600.7,
+                     "data": {"app": "Code", "title": "main.rs"}}]}
+            }}"#,
+        )
+        .unwrap();
+        let bucket = &export.buckets["aw-watcher-window_desk"];
+        assert_eq!(bucket.kind, WINDOW_BUCKET);
+        assert_eq!(
+            bucket.events[0].span(),
+            Some((1_715_331_600, 1_715_332_201))
+        );
+
+        let start = 1_715_331_600;
+        let windows = [
+            event("2024-05-10T09:00:00Z", 600.0, "Code", "main.rs"),
+            // Overlaps the previous event by a minute.
+            event("2024-05-10T09:09:00Z", 300.0, "Slack", "general"),
+            event("2024-05-10T09:14:00Z", 60.0, "LockApp", ""),
+        ];
+        let active = active_periods(&[
+            afk("2024-05-10T09:00:00Z", 300.0, "not-afk"),
+            afk("2024-05-10T09:05:00Z", 180.0, "afk"),
+            afk("2024-05-10T09:08:00Z", 600.0, "not-afk"),
+        ]);
+        assert_eq!(
+            active,
+            vec![(start, start + 300), (start + 480, start + 1080)]
+        );
+
+        let logs = build_logs(&windows, Some(&active), |app| app == "LockApp");
+        assert_eq!(
+            logs.logs,
+            vec![
+                (start, 300, "main.rs - Code".to_string()),
+                (start + 480, 120, "main.rs - Code".to_string()),
+                (start + 600, 240, "general - Slack".to_string()),
+            ]
+        );
+        assert_eq!(logs.afk_seconds, 180);
+        assert_eq!(logs.skipped_seconds, 60);
+
+        let without_afk = build_logs(&windows, None, |_| false);
+        assert_eq!(without_afk.logs.len(), 3);
+        assert_eq!(
+            without_afk.logs[1],
+            (start + 600, 240, "general - Slack".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn reimporting_updates_instead_of_duplicating() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::db::tables::log::create_table(&pool).await.unwrap();
+        let uuid = "activitywatch-desk";
+
+        let first = vec![(100, 30, "A".to_string()), (130, 10, "B".to_string())];
+        let summary = write_logs(&pool, uuid, &first).await.unwrap();
+        assert_eq!(summary.inserted, 2);
+
+        // A later export where B was still running, plus a new event.
+        let second = vec![
+            (100, 30, "A".to_string()),
+            (130, 25, "B".to_string()),
+            (155, 5, "C".to_string()),
+        ];
+        let summary = write_logs(&pool, uuid, &second).await.unwrap();
+        assert_eq!(
+            summary,
+            LogWriteSummary {
+                inserted: 1,
+                updated: 1,
+                duplicates: 1,
+            }
+        );
+        let rows: Vec<(i64, i64, String, i64)> = sqlx::query_as(
+            "SELECT id, timestamp, app, duration FROM logs WHERE device_uuid = ?1 ORDER BY id",
+        )
+        .bind(uuid)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                (1, 100, "A".to_string(), 30),
+                (2, 130, "B".to_string(), 25),
+                (3, 155, "C".to_string(), 5),
+            ]
+        );
+    }
+}
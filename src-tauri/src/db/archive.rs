@@ -0,0 +1,983 @@
+use crate::db::tables::device::KIND_REMOTE;
+use crate::db::tables::log::PENDING_LOCAL_DEVICE_UUID;
+use crate::db::validation::validate_and_repair_database;
+use crate::db::{backup, get_pool, Error};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqliteConnection, TypeInfo, ValueRef};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+pub const ARCHIVE_FORMAT: &str = "time-tracker-archive";
+pub const ARCHIVE_VERSION: u32 = 1;
+const MIGRATIONS_TABLE: &str = "_sqlx_migrations";
+/// Tables holding third-party OAuth tokens; archived only on request.
+const CREDENTIAL_TABLES: [&str; 1] = ["google_oauth"];
+/// Rows are read from the database this many at a time.
+const ROW_BATCH_SIZE: i64 = 5_000;
+/// Schema version that added `logs.device_uuid`.
+const DEVICE_UUID_SCHEMA: i64 = 8;
+/// Restored before the tables pointing at them. When merging, a row whose id belongs to a
+/// different row is matched by name, or imported under a new id.
+const PARENT_TABLES: [&str; 4] = ["clients", "projects", "category", "app_groups"];
+/// This database's own preferences, left alone when merging.
+const MERGE_SKIPPED_TABLES: [&str; 1] = ["app_metadata"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format: String,
+    pub archive_version: u32,
+    pub app_version: String,
+    pub schema_version: i64, // Latest migration, as reported by get_db_schema_version
+    pub created_at: i64,
+    pub tables: BTreeMap<String, u64>, // Row count per table
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct ArchiveTable {
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl ArchiveTable {
+    fn column(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|column| column == name)
+    }
+
+    /// Adds a column, computing each row's value from the row as it was.
+    fn add_column(&mut self, name: &str, value: impl Fn(&ArchiveTable, &[Value]) -> Value) {
+        let values: Vec<Value> = self.rows.iter().map(|row| value(self, row)).collect();
+        self.columns.push(name.to_string());
+        for (row, value) in self.rows.iter_mut().zip(values) {
+            row.push(value);
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Archive {
+    manifest: ArchiveManifest,
+    tables: BTreeMap<String, ArchiveTable>,
+}
+
+#[derive(Debug, Clone)]
+struct ColumnInfo {
+    name: String,
+    sql_type: String,
+    not_null: bool,
+    has_default: bool,
+    primary_key: bool,
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+async fn table_names(conn: &mut SqliteConnection) -> Result<Vec<String>, Error> {
+    Ok(sqlx::query_scalar(
+        "SELECT name FROM sqlite_master
+         WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name != ?1
+         ORDER BY name",
+    )
+    .bind(MIGRATIONS_TABLE)
+    .fetch_all(&mut *conn)
+    .await?)
+}
+
+async fn table_columns(conn: &mut SqliteConnection, table: &str) -> Result<Vec<ColumnInfo>, Error> {
+    let rows = sqlx::query(sqlx::AssertSqlSafe(format!(
+        "PRAGMA table_info({})",
+        quote(table)
+    )))
+    .fetch_all(&mut *conn)
+    .await?;
+    rows.iter()
+        .map(|row| {
+            Ok(ColumnInfo {
+                name: row.try_get("name")?,
+                sql_type: row.try_get::<String, _>("type")?.to_ascii_uppercase(),
+                not_null: row.try_get::<i64, _>("notnull")? != 0,
+                has_default: row.try_get::<Option<String>, _>("dflt_value")?.is_some(),
+                primary_key: row.try_get::<i64, _>("pk")? != 0,
+            })
+        })
+        .collect()
+}
+
+fn json<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    Ok(serde_json::to_string(value).map_err(anyhow::Error::new)?)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    text.len()
+        .is_multiple_of(2)
+        .then(|| {
+            (0..text.len())
+                .step_by(2)
+                .map(|index| u8::from_str_radix(text.get(index..index + 2)?, 16).ok())
+                .collect()
+        })
+        .flatten()
+}
+
+/// SQLite values as JSON; blobs become `{"$blob": "<hex>"}`.
+fn decode_value(row: &SqliteRow, index: usize) -> Result<Value, Error> {
+    let raw = row.try_get_raw(index)?;
+    if raw.is_null() {
+        return Ok(Value::Null);
+    }
+    let type_name = raw.type_info().name().to_string();
+    Ok(match type_name.as_str() {
+        "INTEGER" => Value::from(row.try_get::<i64, _>(index)?),
+        "REAL" => Value::from(row.try_get::<f64, _>(index)?),
+        "BLOB" => serde_json::json!({ "$blob": hex(&row.try_get::<Vec<u8>, _>(index)?) }),
+        _ => Value::from(row.try_get::<String, _>(index)?),
+    })
+}
+
+fn bind_value<'q>(
+    query: sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments>,
+    value: &Value,
+) -> sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments> {
+    match value {
+        Value::Null => query.bind(None::<i64>),
+        Value::Bool(flag) => query.bind(*flag),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) => query.bind(integer),
+            None => query.bind(number.as_f64()),
+        },
+        Value::String(text) => query.bind(text.clone()),
+        Value::Object(object) => {
+            match object.get("$blob").and_then(Value::as_str).and_then(unhex) {
+                Some(bytes) => query.bind(bytes),
+                None => query.bind(value.to_string()),
+            }
+        }
+        Value::Array(_) => query.bind(value.to_string()),
+    }
+}
+
+/// Values equal once booleans and numbers are compared the way SQLite stores them.
+fn same_value(left: &Value, right: &Value) -> bool {
+    let number = |value: &Value| match value {
+        Value::Bool(flag) => Some(f64::from(u8::from(*flag))),
+        Value::Number(number) => number.as_f64(),
+        _ => None,
+    };
+    match (number(left), number(right)) {
+        (Some(left), Some(right)) => left == right,
+        _ => left == right,
+    }
+}
+
+/// The table `column` holds an id of, for the archived `row` of `table`.
+fn referenced_table(
+    table: &str,
+    column: &str,
+    data: &ArchiveTable,
+    row: &[Value],
+) -> Option<&'static str> {
+    match (table, column) {
+        ("category_regex", "cat_id") | ("manual_time_blocks", "category_id") => Some("category"),
+        ("manual_time_blocks", "project_id") | ("project_assignments", "project_id") => {
+            Some("projects")
+        }
+        ("projects", "client_id") => Some("clients"),
+        ("goals", "target_id") => match row[data.column("target_kind")?].as_str()? {
+            "category" => Some("category"),
+            "app_group" => Some("app_groups"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Writes the manifest and then every table, one row per line, so the archive stays readable
+/// and large tables are never held in memory. `conn` should be inside a transaction so the
+/// counts match the rows.
+async fn write_archive<W: Write>(
+    conn: &mut SqliteConnection,
+    out: &mut W,
+    schema_version: i64,
+    include_credentials: bool,
+    created_at: i64,
+) -> Result<ArchiveManifest, Error> {
+    let tables: Vec<String> = table_names(conn)
+        .await?
+        .into_iter()
+        .filter(|table| include_credentials || !CREDENTIAL_TABLES.contains(&table.as_str()))
+        .collect();
+    let mut manifest = ArchiveManifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        archive_version: ARCHIVE_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version,
+        created_at,
+        tables: BTreeMap::new(),
+    };
+    for table in &tables {
+        let count: i64 = sqlx::query_scalar(sqlx::AssertSqlSafe(format!(
+            "SELECT COUNT(*) FROM {}",
+            quote(table)
+        )))
+        .fetch_one(&mut *conn)
+        .await?;
+        manifest.tables.insert(table.clone(), count as u64);
+    }
+
+    writeln!(out, "{{\n\"manifest\": {},", json(&manifest)?)?;
+    write!(out, "\"tables\": {{")?;
+    for (index, table) in tables.iter().enumerate() {
+        let columns: Vec<String> = table_columns(conn, table)
+            .await?
+            .into_iter()
+            .map(|column| column.name)
+            .collect();
+        let separator = if index == 0 { "" } else { "," };
+        write!(
+            out,
+            "{separator}\n{}: {{\"columns\": {}, \"rows\": [",
+            json(table)?,
+            json(&columns)?
+        )?;
+
+        let select = format!(
+            "SELECT rowid, {} FROM {} WHERE rowid > ?1 ORDER BY rowid LIMIT ?2",
+            columns
+                .iter()
+                .map(|column| quote(column))
+                .collect::<Vec<_>>()
+                .join(", "),
+            quote(table)
+        );
+        let mut last_rowid = i64::MIN;
+        let mut first = true;
+        loop {
+            let rows = sqlx::query(sqlx::AssertSqlSafe(select.clone()))
+                .bind(last_rowid)
+                .bind(ROW_BATCH_SIZE)
+                .fetch_all(&mut *conn)
+                .await?;
+            for row in &rows {
+                let values = (1..=columns.len())
+                    .map(|index| decode_value(row, index))
+                    .collect::<Result<Vec<_>, _>>()?;
+                write!(out, "{}\n{}", if first { "" } else { "," }, json(&values)?)?;
+                first = false;
+            }
+            match rows.last() {
+                Some(row) if rows.len() as i64 == ROW_BATCH_SIZE => last_rowid = row.try_get(0)?,
+                _ => break,
+            }
+        }
+        write!(out, "]}}")?;
+    }
+    writeln!(out, "\n}}\n}}")?;
+    out.flush()?;
+    Ok(manifest)
+}
+
+fn validate_manifest(archive: &Archive, current_schema: i64) -> Result<(), Error> {
+    let manifest = &archive.manifest;
+    if manifest.format != ARCHIVE_FORMAT {
+        return Err(anyhow::anyhow!("Not a data archive (format '{}')", manifest.format).into());
+    }
+    if manifest.archive_version > ARCHIVE_VERSION {
+        return Err(anyhow::anyhow!(
+            "Archive format {} is newer than this app supports, update the app first",
+            manifest.archive_version
+        )
+        .into());
+    }
+    if manifest.schema_version > current_schema {
+        return Err(anyhow::anyhow!(
+            "Archive was made with database schema {} (app {}), this app is at schema {}",
+            manifest.schema_version,
+            manifest.app_version,
+            current_schema
+        )
+        .into());
+    }
+    for (table, data) in &archive.tables {
+        let expected = manifest.tables.get(table).copied().unwrap_or(0);
+        if expected != data.rows.len() as u64 {
+            return Err(anyhow::anyhow!(
+                "Table {table} has {} rows but the manifest lists {expected}, the archive may be incomplete",
+                data.rows.len()
+            )
+            .into());
+        }
+        if let Some(row) = data.rows.iter().find(|row| row.len() != data.columns.len()) {
+            return Err(anyhow::anyhow!(
+                "Table {table} has a row with {} values for {} columns",
+                row.len(),
+                data.columns.len()
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// Brings an archive from an older schema up to the shape of the current tables. Columns that
+/// only gained defaults are handled when restoring; this covers data that has to be derived.
+fn upgrade_archive(archive: &mut Archive) -> Vec<String> {
+    let mut notes = Vec::new();
+    let schema = archive.manifest.schema_version;
+
+    if let Some(logs) = archive.tables.get_mut("logs") {
+        if schema < DEVICE_UUID_SCHEMA || logs.column("device_uuid").is_none() {
+            if logs.column("device_uuid").is_none() {
+                logs.add_column("device_uuid", |_, _| Value::Null);
+            }
+            // Logs from before devices existed belong to this machine.
+            let column = logs.column("device_uuid").unwrap_or_default();
+            let mut assigned = 0;
+            for row in &mut logs.rows {
+                if row[column].as_str().is_none_or(str::is_empty) {
+                    row[column] = Value::from(PENDING_LOCAL_DEVICE_UUID);
+                    assigned += 1;
+                }
+            }
+            if assigned > 0 {
+                notes.push(format!(
+                    "{assigned} logs without a device were assigned to this device"
+                ));
+            }
+        }
+    }
+
+    if let Some(category) = archive.tables.get_mut("category") {
+        if let (Some(_), None) = (
+            category.column("calendar_enabled"),
+            category.column("is_visible"),
+        ) {
+            let copy = |table: &ArchiveTable, row: &[Value]| {
+                table
+                    .column("calendar_enabled")
+                    .map(|index| row[index].clone())
+                    .unwrap_or(Value::from(1))
+            };
+            category.add_column("is_visible", copy);
+            category.add_column("in_stats", copy);
+            notes.push("Category visibility was taken from the old calendar setting".to_string());
+        }
+    }
+    notes
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveImportMode {
+    Merge,   // Keep existing rows and add the archive's new ones
+    Replace, // Empty each archived table first
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ArchiveTableReport {
+    pub table: String,
+    pub inserted: u64,
+    pub existing: u64,  // Already present with the same content or key
+    pub conflicts: u64, // Same id with different content; the existing row was kept
+    pub orphaned: u64,  // Pointed at a row that could not be imported
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveImportReport {
+    pub manifest: ArchiveManifest,
+    pub mode: ArchiveImportMode,
+    pub upgraded_from: Option<i64>, // Schema version of an older archive
+    pub tables: Vec<ArchiveTableReport>,
+    pub warnings: Vec<String>,
+    pub backup_created: Option<String>,
+}
+
+/// Inserts the archive's rows into the current tables, matching columns by name. Tables this
+/// version does not have are skipped; required columns the archive lacks get empty values.
+/// Ids pointing at a parent that was matched by name or renumbered follow it.
+async fn restore_tables(
+    conn: &mut SqliteConnection,
+    archive: &Archive,
+    mode: ArchiveImportMode,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<ArchiveTableReport>, Error> {
+    let existing_tables = table_names(conn).await?;
+    let ordered = PARENT_TABLES
+        .iter()
+        .filter_map(|table| archive.tables.get_key_value(*table))
+        .chain(
+            archive
+                .tables
+                .iter()
+                .filter(|(table, _)| !PARENT_TABLES.contains(&table.as_str())),
+        );
+    // Archived id to the id in this database, or None when the row was not imported.
+    let mut remapped: HashMap<&str, HashMap<i64, Option<i64>>> = HashMap::new();
+    let mut reports = Vec::new();
+    for (table, data) in ordered {
+        if !existing_tables.contains(table) {
+            warnings.push(format!(
+                "Table {table} is not used by this version and was skipped"
+            ));
+            continue;
+        }
+        if mode == ArchiveImportMode::Merge && MERGE_SKIPPED_TABLES.contains(&table.as_str()) {
+            continue;
+        }
+        let columns = table_columns(conn, table).await?;
+        let shared: Vec<(usize, &ColumnInfo)> = columns
+            .iter()
+            .filter_map(|column| Some((data.column(&column.name)?, column)))
+            .collect();
+        let filled: Vec<&ColumnInfo> = columns
+            .iter()
+            .filter(|column| {
+                data.column(&column.name).is_none()
+                    && column.not_null
+                    && !column.has_default
+                    && !column.primary_key
+            })
+            .collect();
+        for column in &filled {
+            warnings.push(format!(
+                "{table}.{} is missing from the archive and was left empty",
+                column.name
+            ));
+        }
+        // Only surrogate ids can tell a duplicate row from a different row that reuses the key.
+        let keys: Vec<&ColumnInfo> = columns.iter().filter(|column| column.primary_key).collect();
+        let id_column = match keys.as_slice() {
+            [key] if key.sql_type == "INTEGER" => data
+                .column(&key.name)
+                .map(|index| (index, key.name.clone())),
+            _ => None,
+        };
+
+        if mode == ArchiveImportMode::Replace {
+            sqlx::query(sqlx::AssertSqlSafe(format!("DELETE FROM {}", quote(table))))
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        let insert_into = |skip: Option<usize>| {
+            let names: Vec<String> = shared
+                .iter()
+                .filter(|(index, _)| Some(*index) != skip)
+                .map(|(_, column)| quote(&column.name))
+                .chain(filled.iter().map(|column| quote(&column.name)))
+                .collect();
+            format!(
+                "INSERT OR IGNORE INTO {} ({}) VALUES ({})",
+                quote(table),
+                names.join(", "),
+                vec!["?"; names.len()].join(", ")
+            )
+        };
+        let insert = insert_into(None);
+        let compare = id_column.as_ref().map(|(_, id)| {
+            format!(
+                "SELECT {} FROM {} WHERE {} = ?1",
+                shared
+                    .iter()
+                    .map(|(_, column)| quote(&column.name))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                quote(table),
+                quote(id)
+            )
+        });
+        // Parents whose id is taken are looked up by name, or inserted without their id.
+        let parent = match (&id_column, data.column("name")) {
+            (Some((id_index, _)), Some(name_index)) if PARENT_TABLES.contains(&table.as_str()) => {
+                Some((
+                    *id_index,
+                    name_index,
+                    format!(
+                        "SELECT id FROM {} WHERE name = ?1 ORDER BY id LIMIT 1",
+                        quote(table)
+                    ),
+                    insert_into(Some(*id_index)),
+                ))
+            }
+            _ => None,
+        };
+
+        let mut report = ArchiveTableReport {
+            table: table.clone(),
+            ..Default::default()
+        };
+        let mut renumbered = 0;
+        for archived in &data.rows {
+            let mut row = archived.clone();
+            if mode == ArchiveImportMode::Merge && table == "devices" {
+                // Another machine's devices can only be synced from here, never tracked.
+                if let Some(kind) = data.column("kind") {
+                    row[kind] = Value::from(KIND_REMOTE);
+                }
+                if let Some(token) = data.column("token") {
+                    row[token] = Value::Null;
+                }
+            }
+            let mut orphaned = false;
+            for (index, column) in &shared {
+                let Some(parent) = referenced_table(table, &column.name, data, &row) else {
+                    continue;
+                };
+                let Some(id) = row[*index].as_i64() else {
+                    continue;
+                };
+                match remapped.get(parent).and_then(|ids| ids.get(&id)) {
+                    Some(Some(local)) => row[*index] = Value::from(*local),
+                    Some(None) => orphaned = true,
+                    None => {}
+                }
+            }
+            if orphaned {
+                report.orphaned += 1;
+                continue;
+            }
+
+            let bind_row = |statement: &str, skip: Option<usize>| {
+                let mut query = sqlx::query(sqlx::AssertSqlSafe(statement.to_string()));
+                for (index, _) in shared.iter().filter(|(index, _)| Some(*index) != skip) {
+                    query = bind_value(query, &row[*index]);
+                }
+                for column in &filled {
+                    query = match column.sql_type.as_str() {
+                        "INTEGER" | "REAL" | "BOOLEAN" => query.bind(0_i64),
+                        _ => query.bind(""),
+                    };
+                }
+                query
+            };
+            if bind_row(&insert, None)
+                .execute(&mut *conn)
+                .await?
+                .rows_affected()
+                > 0
+            {
+                report.inserted += 1;
+                continue;
+            }
+            let (Some((id_index, _)), Some(compare)) = (&id_column, &compare) else {
+                report.existing += 1;
+                continue;
+            };
+            let current = bind_value(
+                sqlx::query(sqlx::AssertSqlSafe(compare.clone())),
+                &row[*id_index],
+            )
+            .fetch_optional(&mut *conn)
+            .await?;
+            let identical = current.as_ref().is_some_and(|current| {
+                shared.iter().enumerate().all(|(position, (index, _))| {
+                    decode_value(current, position)
+                        .is_ok_and(|value| same_value(&value, &row[*index]))
+                })
+            });
+            let Some((id_index, name_index, by_name, insert_renumbered)) =
+                parent.as_ref().filter(|_| !identical)
+            else {
+                match current {
+                    // Ignored by another unique constraint, such as a name.
+                    None => report.existing += 1,
+                    Some(_) if identical => report.existing += 1,
+                    Some(_) => report.conflicts += 1,
+                }
+                continue;
+            };
+
+            let archived_id = row[*id_index].as_i64();
+            let matched: Option<i64> = bind_value(
+                sqlx::query(sqlx::AssertSqlSafe(by_name.clone())),
+                &row[*name_index],
+            )
+            .fetch_optional(&mut *conn)
+            .await?
+            .map(|found| found.try_get(0))
+            .transpose()?;
+            let local = match matched {
+                Some(id) => {
+                    if Some(id) == archived_id {
+                        report.conflicts += 1;
+                    } else {
+                        report.existing += 1;
+                    }
+                    Some(id)
+                }
+                None => {
+                    let result = bind_row(insert_renumbered, Some(*id_index))
+                        .execute(&mut *conn)
+                        .await?;
+                    if result.rows_affected() > 0 {
+                        report.inserted += 1;
+                        renumbered += 1;
+                        Some(result.last_insert_rowid())
+                    } else {
+                        report.conflicts += 1;
+                        None
+                    }
+                }
+            };
+            if let Some(archived_id) = archived_id.filter(|id| local != Some(*id)) {
+                remapped
+                    .entry(table.as_str())
+                    .or_default()
+                    .insert(archived_id, local);
+            }
+        }
+        if report.conflicts > 0 {
+            warnings.push(format!(
+                "{table}: {} rows share an id with different existing rows and were not imported",
+                report.conflicts
+            ));
+        }
+        if renumbered > 0 {
+            warnings.push(format!(
+                "{table}: {renumbered} rows were imported under new ids because theirs were taken"
+            ));
+        }
+        if report.orphaned > 0 {
+            warnings.push(format!(
+                "{table}: {} rows belong to rows that were not imported and were skipped",
+                report.orphaned
+            ));
+        }
+        reports.push(report);
+    }
+    Ok(reports)
+}
+
+async fn current_schema_version(conn: &mut SqliteConnection) -> Result<i64, Error> {
+    Ok(sqlx::query_scalar(
+        "SELECT COALESCE(MAX(version), 0) FROM _sqlx_migrations WHERE success = 1",
+    )
+    .fetch_one(&mut *conn)
+    .await?)
+}
+
+fn read_archive(path: &Path) -> Result<Archive, Error> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    Ok(serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("{} is not a valid data archive", path.display()))?)
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ArchiveExportOptions {
+    pub include_credentials: bool, // Google Calendar sign-in tokens
+}
+
+/// Writes every table to a single JSON archive. The archive includes the sync device token, and
+/// Google Calendar tokens when asked for, so it should be kept private.
+#[tauri::command]
+pub async fn export_archive(
+    path: String,
+    options: Option<ArchiveExportOptions>,
+) -> Result<ArchiveManifest, Error> {
+    let options = options.unwrap_or_default();
+    let path = Path::new(path.trim());
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create archive {}", path.display()))?;
+    let pool = get_pool().await?;
+    let mut tx = pool.begin().await?;
+    let schema_version = current_schema_version(&mut tx).await?;
+    let manifest = write_archive(
+        &mut tx,
+        &mut BufWriter::new(file),
+        schema_version,
+        options.include_credentials,
+        chrono::Utc::now().timestamp(),
+    )
+    .await?;
+    tx.rollback().await?;
+    Ok(manifest)
+}
+
+/// Reads and checks an archive's manifest without importing it.
+#[tauri::command]
+pub async fn inspect_archive(path: String) -> Result<ArchiveManifest, Error> {
+    let archive = read_archive(Path::new(path.trim()))?;
+    let mut conn = get_pool().await?.acquire().await?;
+    validate_manifest(&archive, current_schema_version(&mut conn).await?)?;
+    Ok(archive.manifest)
+}
+
+/// Imports an archive after a safety backup, in one transaction. Older archives are upgraded
+/// first; archives from a newer schema are refused.
+#[tauri::command]
+pub async fn import_archive(
+    path: String,
+    mode: ArchiveImportMode,
+) -> Result<ArchiveImportReport, Error> {
+    let mut archive = read_archive(Path::new(path.trim()))?;
+    let pool = get_pool().await?;
+    let current_schema = current_schema_version(&mut *pool.acquire().await?).await?;
+    validate_manifest(&archive, current_schema)?;
+
+    let backup = backup::create_safety_backup("pre_archive_import")
+        .context("Failed to create safety backup")?;
+    let upgraded_from = (archive.manifest.schema_version < current_schema)
+        .then_some(archive.manifest.schema_version);
+    let mut warnings = upgrade_archive(&mut archive);
+
+    let mut tx = pool.begin().await?;
+    let tables = restore_tables(&mut tx, &archive, mode, &mut warnings).await?;
+    tx.commit().await?;
+    // Re-seeds defaults and reclaims logs that were assigned to this device.
+    validate_and_repair_database(&pool).await?;
+
+    Ok(ArchiveImportReport {
+        manifest: archive.manifest,
+        mode,
+        upgraded_from,
+        tables,
+        warnings,
+        backup_created: Some(backup.to_string_lossy().to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::SqlitePool;
+
+    async fn database() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        crate::db::tables::log::create_table(&pool).await.unwrap();
+        crate::db::tables::client::create_table(&pool)
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE google_oauth (id INTEGER PRIMARY KEY, token TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool
+    }
+
+    async fn export(pool: &SqlitePool) -> (ArchiveManifest, Archive) {
+        let mut out = Vec::new();
+        let mut conn = pool.acquire().await.unwrap();
+        let manifest = write_archive(&mut conn, &mut out, 11, false, 1_000)
+            .await
+            .unwrap();
+        (manifest, serde_json::from_slice(&out).unwrap())
+    }
+
+    #[tokio::test]
+    async fn archives_round_trip_and_merge() {
+        let source = database().await;
+        sqlx::query(
+            "INSERT INTO logs (id, device_uuid, app, timestamp, duration) VALUES
+             (1, 'desk', 'Editor \"main\"', 100, 5), (2, 'desk', 'Browser', 105, 7)",
+        )
+        .execute(&source)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO clients (id, name, hourly_rate) VALUES (1, 'Acme', 95.5), (2, 'Beta', NULL)")
+            .execute(&source)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO google_oauth (id, token) VALUES (1, 'secret')")
+            .execute(&source)
+            .await
+            .unwrap();
+
+        let (manifest, archive) = export(&source).await;
+        assert_eq!(
+            manifest.tables,
+            BTreeMap::from([("clients".to_string(), 2), ("logs".to_string(), 2)])
+        );
+        assert_eq!(archive.manifest, manifest);
+        assert_eq!(
+            archive.tables["clients"].rows[0],
+            vec![Value::from(1), Value::from("Acme"), Value::from(95.5)]
+        );
+        validate_manifest(&archive, 11).unwrap();
+        assert!(validate_manifest(&archive, 10).is_err());
+
+        let target = database().await;
+        sqlx::query("INSERT INTO clients (id, name) VALUES (2, 'Other'), (3, 'Acme')")
+            .execute(&target)
+            .await
+            .unwrap();
+        let mut warnings = Vec::new();
+        let mut conn = target.acquire().await.unwrap();
+        let reports = restore_tables(&mut conn, &archive, ArchiveImportMode::Merge, &mut warnings)
+            .await
+            .unwrap();
+        // Acme exists under another id and Beta's id is taken by a different client.
+        assert_eq!(
+            reports[0],
+            ArchiveTableReport {
+                table: "clients".into(),
+                inserted: 1,
+                existing: 1,
+                conflicts: 0,
+                orphaned: 0,
+            }
+        );
+        let beta: i64 = sqlx::query_scalar("SELECT id FROM clients WHERE name = 'Beta'")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(beta, 4);
+        assert_eq!(reports[1].inserted, 2);
+        assert_eq!(warnings.len(), 1);
+
+        let reports = restore_tables(
+            &mut conn,
+            &archive,
+            ArchiveImportMode::Replace,
+            &mut warnings,
+        )
+        .await
+        .unwrap();
+        assert_eq!(reports[0].inserted, 2);
+        let clients: Vec<(i64, String, Option<f64>)> =
+            sqlx::query_as("SELECT id, name, hourly_rate FROM clients ORDER BY id")
+                .fetch_all(&mut *conn)
+                .await
+                .unwrap();
+        assert_eq!(
+            clients,
+            vec![(1, "Acme".into(), Some(95.5)), (2, "Beta".into(), None)]
+        );
+        let app: String = sqlx::query_scalar("SELECT app FROM logs WHERE id = 1")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(app, "Editor \"main\"");
+    }
+
+    #[tokio::test]
+    async fn merging_keeps_references_on_their_parents() {
+        let database = || async {
+            let pool = database().await;
+            crate::db::tables::category::create_table(&pool)
+                .await
+                .unwrap();
+            crate::db::tables::cat_regex::create_table(&pool)
+                .await
+                .unwrap();
+            crate::db::tables::project::create_table(&pool)
+                .await
+                .unwrap();
+            crate::db::tables::device::create_table(&pool)
+                .await
+                .unwrap();
+            sqlx::query("CREATE TABLE app_metadata (key TEXT PRIMARY KEY, value TEXT NOT NULL)")
+                .execute(&pool)
+                .await
+                .unwrap();
+            pool
+        };
+        let source = database().await;
+        sqlx::query(
+            "INSERT INTO category (id, name, priority) VALUES (20, 'Reading list', 5), (21, 'Gym', 5);
+             INSERT INTO category_regex (id, cat_id, regex) VALUES (1, 20, 'Kindle'), (2, 21, 'Strava');
+             INSERT INTO clients (id, name) VALUES (1, 'Acme');
+             INSERT INTO projects (id, name, client_id) VALUES (1, 'Website', 1);
+             INSERT INTO devices (uuid, name, kind, token) VALUES ('desk', 'Desk', 'local', 'secret');
+             INSERT INTO app_metadata (key, value) VALUES ('local-device', 'desk');",
+        )
+        .execute(&source)
+        .await
+        .unwrap();
+        let (_, archive) = export(&source).await;
+
+        let target = database().await;
+        sqlx::query(
+            "INSERT INTO category (id, name, priority) VALUES (20, 'Writing', 5), (30, 'Gym', 5);
+             INSERT INTO clients (id, name) VALUES (1, 'Other');",
+        )
+        .execute(&target)
+        .await
+        .unwrap();
+        let mut warnings = Vec::new();
+        let mut conn = target.acquire().await.unwrap();
+        restore_tables(&mut conn, &archive, ArchiveImportMode::Merge, &mut warnings)
+            .await
+            .unwrap();
+
+        let rules: Vec<(String, String)> = sqlx::query_as(
+            "SELECT r.regex, c.name FROM category_regex r JOIN category c ON c.id = r.cat_id
+             ORDER BY r.id",
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .unwrap();
+        assert_eq!(
+            rules,
+            vec![
+                ("Kindle".into(), "Reading list".into()),
+                ("Strava".into(), "Gym".into())
+            ]
+        );
+        let client: String = sqlx::query_scalar(
+            "SELECT c.name FROM projects p JOIN clients c ON c.id = p.client_id
+             WHERE p.name = 'Website'",
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .unwrap();
+        assert_eq!(client, "Acme");
+        let device: (String, Option<String>) =
+            sqlx::query_as("SELECT kind, token FROM devices WHERE uuid = 'desk'")
+                .fetch_one(&mut *conn)
+                .await
+                .unwrap();
+        assert_eq!(device, (KIND_REMOTE.to_string(), None));
+        let metadata: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM app_metadata")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(metadata, 0);
+    }
+
+    #[test]
+    fn old_archives_are_upgraded_and_damaged_ones_refused() {
+        let mut archive: Archive = serde_json::from_str(
+            r#"{"manifest": {"format": "time-tracker-archive", "archive_version": 1,
+                 "app_version": "0.1.0", "schema_version": 6, "created_at": 0,
+                 "tables": {"logs": 2, "category": 1}},
+                "tables": {
+                  "logs": {"columns": ["id", "app", "timestamp", "duration"],
+                           "rows": [[1, "Editor", 100, 5], [2, "Browser", 105, 7]]},
+                  "category": {"columns": ["id", "name", "calendar_enabled"],
+                               "rows": [[1, "Coding", 0]]}}}"#,
+        )
+        .unwrap();
+        validate_manifest(&archive, 11).unwrap();
+        let notes = upgrade_archive(&mut archive);
+        assert_eq!(notes.len(), 2);
+        let logs = &archive.tables["logs"];
+        assert_eq!(
+            logs.rows[1][logs.column("device_uuid").unwrap()],
+            Value::from(PENDING_LOCAL_DEVICE_UUID)
+        );
+        let category = &archive.tables["category"];
+        assert_eq!(
+            category.rows[0][category.column("in_stats").unwrap()],
+            Value::from(0)
+        );
+
+        archive.manifest.tables.insert("logs".into(), 3);
+        assert!(validate_manifest(&archive, 11).is_err());
+        archive.manifest.format = "something-else".into();
+        assert!(validate_manifest(&archive, 11).is_err());
+    }
+}
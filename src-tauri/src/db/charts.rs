@@ -0,0 +1,529 @@
+use crate::db::queries::heatmap::{get_year_heatmap, YearHeatmap};
+use crate::db::queries::statistics::{
+    get_week_statistics, CategoryStat, DayCategoryStat, HourlyStat,
+};
+use crate::db::queries::time_zone::load_calendar_settings;
+use crate::db::tables::category::get_categories;
+use crate::db::tables::manual_time_block::ManualTimeMode;
+use crate::db::{escape_html, format_duration, Error};
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Used for labels without a valid colour in the category table.
+const PALETTE: [&str; 8] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+];
+const OTHER_COLOR: &str = "#9c9c9c";
+const EMPTY_COLOR: &str = "#ebedf0";
+const TEXT_COLOR: &str = "#1f2933";
+const MUTED_COLOR: &str = "#616e7c";
+const DEFAULT_BAR_COLOR: &str = "#4e79a7";
+const DEFAULT_HEATMAP_COLOR: &str = "#2ea043";
+/// The donut lists this many slices and folds the rest into "Other".
+const MAX_DONUT_SLICES: usize = 8;
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+/// Fill opacity of heatmap levels 1-4.
+const LEVEL_OPACITY: [f64; 4] = [0.3, 0.5, 0.75, 1.0];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartSlice {
+    pub label: String,
+    pub seconds: i64,
+    pub color: Option<String>,
+}
+
+/// `#rgb`, `#rrggbb` or `#rrggbbaa`; anything else could break out of the attribute.
+fn valid_color(color: &str) -> bool {
+    color.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+fn pick_color(color: Option<&str>, index: usize) -> String {
+    color
+        .filter(|color| valid_color(color))
+        .unwrap_or(PALETTE[index % PALETTE.len()])
+        .to_string()
+}
+
+fn svg(width: f64, height: f64, title: &str, body: &str) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\" role=\"img\" font-family=\"sans-serif\" \
+         font-size=\"12\" fill=\"{TEXT_COLOR}\"><title>{}</title>{body}</svg>",
+        escape_html(title)
+    )
+}
+
+fn empty_chart(title: &str) -> String {
+    svg(
+        240.0,
+        60.0,
+        title,
+        &format!("<text x=\"120\" y=\"34\" text-anchor=\"middle\" fill=\"{MUTED_COLOR}\">No time recorded</text>"),
+    )
+}
+
+fn legend_row(x: f64, y: f64, color: &str, text: &str) -> String {
+    format!(
+        "<rect x=\"{x}\" y=\"{}\" width=\"10\" height=\"10\" rx=\"2\" fill=\"{color}\"/>\
+         <text x=\"{}\" y=\"{y}\">{}</text>",
+        y - 9.0,
+        x + 16.0,
+        escape_html(text)
+    )
+}
+
+/// Colours of categories by name, from the category table.
+pub async fn category_colors() -> Result<HashMap<String, String>, Error> {
+    Ok(get_categories()
+        .await?
+        .into_iter()
+        .filter_map(|category| Some((category.name, category.color?)))
+        .collect())
+}
+
+/// A donut of the slices' shares, largest first, with a legend and the total in the middle.
+pub fn donut_chart(title: &str, slices: &[ChartSlice]) -> String {
+    let mut slices: Vec<&ChartSlice> = slices.iter().filter(|slice| slice.seconds > 0).collect();
+    slices.sort_by(|a, b| b.seconds.cmp(&a.seconds).then(a.label.cmp(&b.label)));
+    let total: i64 = slices.iter().map(|slice| slice.seconds).sum();
+    if total == 0 {
+        return empty_chart(title);
+    }
+    let mut shown: Vec<(String, i64, String)> = slices
+        .iter()
+        .take(MAX_DONUT_SLICES)
+        .enumerate()
+        .map(|(index, slice)| {
+            (
+                slice.label.clone(),
+                slice.seconds,
+                pick_color(slice.color.as_deref(), index),
+            )
+        })
+        .collect();
+    let other: i64 = slices
+        .iter()
+        .skip(MAX_DONUT_SLICES)
+        .map(|slice| slice.seconds)
+        .sum();
+    if other > 0 {
+        shown.push(("Other".to_string(), other, OTHER_COLOR.to_string()));
+    }
+
+    // Each slice is a dashed stroke along the ring, starting at twelve o'clock.
+    let (cx, cy, radius, ring) = (100.0, 100.0, 72.5, 35.0);
+    let circumference = 2.0 * std::f64::consts::PI * radius;
+    let mut body = String::new();
+    let mut offset = 0.0;
+    for (label, seconds, color) in &shown {
+        let length = circumference * *seconds as f64 / total as f64;
+        body.push_str(&format!(
+            "<circle cx=\"{cx}\" cy=\"{cy}\" r=\"{radius}\" fill=\"none\" stroke=\"{color}\" \
+             stroke-width=\"{ring}\" stroke-dasharray=\"{length:.3} {:.3}\" \
+             stroke-dashoffset=\"{:.3}\" transform=\"rotate(-90 {cx} {cy})\">\
+             <title>{}: {}</title></circle>",
+            circumference - length,
+            -offset,
+            escape_html(label),
+            format_duration(*seconds)
+        ));
+        offset += length;
+    }
+    body.push_str(&format!(
+        "<text x=\"{cx}\" y=\"{}\" text-anchor=\"middle\" font-size=\"16\" font-weight=\"600\">{}</text>",
+        cy + 6.0,
+        format_duration(total)
+    ));
+    for (index, (label, seconds, color)) in shown.iter().enumerate() {
+        let percent = *seconds as f64 * 100.0 / total as f64;
+        body.push_str(&legend_row(
+            220.0,
+            30.0 + index as f64 * 20.0,
+            color,
+            &format!("{label} · {} ({percent:.0}%)", format_duration(*seconds)),
+        ));
+    }
+    let height = (40.0 + shown.len() as f64 * 20.0).max(200.0);
+    svg(480.0, height, title, &body)
+}
+
+pub fn category_donut(categories: &[CategoryStat]) -> String {
+    let slices: Vec<ChartSlice> = categories
+        .iter()
+        .map(|category| ChartSlice {
+            label: category.category.clone(),
+            seconds: category.total_duration,
+            color: category.color.clone(),
+        })
+        .collect();
+    donut_chart("Time by category", &slices)
+}
+
+/// Bars for each hour of the day.
+pub fn hourly_chart(hours: &[HourlyStat], color: &str) -> String {
+    let title = "Time by hour of day";
+    let mut totals = [0i64; 24];
+    for stat in hours {
+        if let Some(total) = usize::try_from(stat.hour)
+            .ok()
+            .and_then(|hour| totals.get_mut(hour))
+        {
+            *total += stat.total_duration;
+        }
+    }
+    let max = totals.iter().copied().max().unwrap_or(0);
+    if max == 0 {
+        return empty_chart(title);
+    }
+    let color = pick_color(Some(color), 0);
+    let (left, top, plot_height, step, bar) = (56.0, 12.0, 140.0, 18.0, 14.0);
+    let bottom = top + plot_height;
+    let mut body = format!(
+        "<line x1=\"{left}\" y1=\"{bottom}\" x2=\"{}\" y2=\"{bottom}\" stroke=\"{MUTED_COLOR}\"/>\
+         <text x=\"{}\" y=\"{}\" text-anchor=\"end\" fill=\"{MUTED_COLOR}\">{}</text>\
+         <text x=\"{}\" y=\"{bottom}\" text-anchor=\"end\" fill=\"{MUTED_COLOR}\">0</text>",
+        left + 24.0 * step,
+        left - 6.0,
+        top + 10.0,
+        format_duration(max),
+        left - 6.0
+    );
+    for (hour, total) in totals.iter().enumerate() {
+        let x = left + hour as f64 * step + (step - bar) / 2.0;
+        let height = plot_height * *total as f64 / max as f64;
+        if *total > 0 {
+            body.push_str(&format!(
+                "<rect x=\"{x}\" y=\"{:.2}\" width=\"{bar}\" height=\"{height:.2}\" rx=\"2\" fill=\"{color}\">\
+                 <title>{hour:02}:00 · {}</title></rect>",
+                bottom - height,
+                format_duration(*total)
+            ));
+        }
+        if hour % 3 == 0 {
+            body.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" fill=\"{MUTED_COLOR}\">{hour:02}</text>",
+                x + bar / 2.0,
+                bottom + 16.0
+            ));
+        }
+    }
+    svg(left + 24.0 * step + 8.0, bottom + 26.0, title, &body)
+}
+
+/// A stacked bar per day of the week, one segment per category, largest categories at the base.
+pub fn day_category_chart(
+    days: &[DayCategoryStat],
+    colors: &HashMap<String, String>,
+    week_start_day: u32,
+) -> String {
+    let title = "Time by day and category";
+    let day_count = days
+        .iter()
+        .map(|stat| stat.day + 1)
+        .max()
+        .unwrap_or(0)
+        .max(7) as usize;
+    let mut totals: HashMap<&str, i64> = HashMap::new();
+    let mut day_totals = vec![0i64; day_count];
+    for stat in days.iter().filter(|stat| stat.day >= 0) {
+        *totals.entry(stat.category.as_str()).or_default() += stat.total_duration;
+        day_totals[stat.day as usize] += stat.total_duration;
+    }
+    let max = day_totals.iter().copied().max().unwrap_or(0);
+    if max == 0 {
+        return empty_chart(title);
+    }
+    let mut categories: Vec<(&str, i64)> =
+        totals.into_iter().filter(|(_, total)| *total > 0).collect();
+    categories.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    let category_colors: HashMap<&str, String> = categories
+        .iter()
+        .enumerate()
+        .map(|(index, (name, _))| {
+            (
+                *name,
+                pick_color(colors.get(*name).map(String::as_str), index),
+            )
+        })
+        .collect();
+
+    let (left, top, plot_height, step, bar) = (56.0, 12.0, 160.0, 56.0, 36.0);
+    let bottom = top + plot_height;
+    let plot_right = left + day_count as f64 * step;
+    let mut body = format!(
+        "<line x1=\"{left}\" y1=\"{bottom}\" x2=\"{plot_right}\" y2=\"{bottom}\" stroke=\"{MUTED_COLOR}\"/>\
+         <text x=\"{}\" y=\"{}\" text-anchor=\"end\" fill=\"{MUTED_COLOR}\">{}</text>",
+        left - 6.0,
+        top + 10.0,
+        format_duration(max)
+    );
+    for (day, day_total) in day_totals.iter().enumerate() {
+        let x = left + day as f64 * step + (step - bar) / 2.0;
+        let mut y = bottom;
+        for (category, _) in &categories {
+            let seconds: i64 = days
+                .iter()
+                .filter(|stat| stat.day as usize == day && stat.category == *category)
+                .map(|stat| stat.total_duration)
+                .sum();
+            if seconds <= 0 {
+                continue;
+            }
+            let height = plot_height * seconds as f64 / max as f64;
+            y -= height;
+            body.push_str(&format!(
+                "<rect x=\"{x}\" y=\"{y:.2}\" width=\"{bar}\" height=\"{height:.2}\" fill=\"{}\">\
+                 <title>{}: {}</title></rect>",
+                category_colors[category],
+                escape_html(category),
+                format_duration(seconds)
+            ));
+        }
+        let weekday = days
+            .iter()
+            .find(|stat| stat.day as usize == day)
+            .map(|stat| stat.weekday as usize)
+            .unwrap_or((week_start_day as usize + day) % 7);
+        body.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" fill=\"{MUTED_COLOR}\">{}</text>",
+            x + bar / 2.0,
+            bottom + 16.0,
+            WEEKDAYS[weekday % 7]
+        ));
+        if *day_total > 0 {
+            body.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"10\" fill=\"{MUTED_COLOR}\">{}</text>",
+                x + bar / 2.0,
+                y - 4.0,
+                format_duration(*day_total)
+            ));
+        }
+    }
+    for (index, (category, seconds)) in categories.iter().enumerate() {
+        body.push_str(&legend_row(
+            plot_right + 24.0,
+            top + 10.0 + index as f64 * 18.0,
+            &category_colors[category],
+            &format!("{category} · {}", format_duration(*seconds)),
+        ));
+    }
+    let height = (bottom + 26.0).max(top + 20.0 + categories.len() as f64 * 18.0);
+    svg(plot_right + 220.0, height, title, &body)
+}
+
+/// A year of days as a week-by-weekday grid, shaded by the heatmap's levels.
+pub fn heatmap_chart(heatmap: &YearHeatmap, color: &str) -> String {
+    let title = format!("Activity in {}", heatmap.year);
+    let color = pick_color(Some(color), 0);
+    let Some(first) = heatmap
+        .days
+        .first()
+        .and_then(|day| NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok())
+    else {
+        return empty_chart(&title);
+    };
+    let offset = first.weekday().num_days_from_monday() as usize;
+    let (left, top, cell, step) = (32.0, 22.0, 11.0, 13.0);
+    let mut body = String::new();
+    for (row, name) in WEEKDAYS.iter().enumerate().filter(|(row, _)| row % 2 == 0) {
+        body.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\" font-size=\"10\" fill=\"{MUTED_COLOR}\">{name}</text>",
+            left - 6.0,
+            top + row as f64 * step + 9.0
+        ));
+    }
+    let mut columns = 0;
+    for (index, day) in heatmap.days.iter().enumerate() {
+        let date = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok();
+        let index = date.map_or(index, |date| (date - first).num_days().max(0) as usize);
+        let position = index + offset;
+        let (column, row) = (position / 7, position % 7);
+        columns = columns.max(column + 1);
+        let x = left + column as f64 * step;
+        let y = top + row as f64 * step;
+        let fill = match day.level {
+            0 => format!("fill=\"{EMPTY_COLOR}\""),
+            level => format!(
+                "fill=\"{color}\" fill-opacity=\"{}\"",
+                LEVEL_OPACITY[(level as usize).clamp(1, 4) - 1]
+            ),
+        };
+        body.push_str(&format!(
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{cell}\" height=\"{cell}\" rx=\"2\" {fill}>\
+             <title>{}: {}</title></rect>",
+            escape_html(&day.date),
+            format_duration(day.total_duration)
+        ));
+        if let Some(date) = date {
+            if date.day() == 1 {
+                body.push_str(&format!(
+                    "<text x=\"{x}\" y=\"{}\" font-size=\"10\" fill=\"{MUTED_COLOR}\">{}</text>",
+                    top - 8.0,
+                    MONTHS[date.month0() as usize]
+                ));
+            }
+        }
+    }
+    svg(
+        left + columns as f64 * step + 8.0,
+        top + 7.0 * step + 4.0,
+        &title,
+        &body,
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatisticsChart {
+    CategoryDonut,
+    Hourly,
+    DayCategory,
+}
+
+/// Renders a chart of the range's statistics as a standalone SVG document.
+#[tauri::command]
+pub async fn render_statistics_chart(
+    chart: StatisticsChart,
+    range_start: i64,
+    range_end: i64,
+    device_uuids: Option<Vec<String>>,
+    manual_time: Option<ManualTimeMode>,
+) -> Result<String, Error> {
+    let statistics = get_week_statistics(range_start, range_end, device_uuids, manual_time).await?;
+    Ok(match chart {
+        StatisticsChart::CategoryDonut => category_donut(&statistics.categories),
+        StatisticsChart::Hourly => hourly_chart(&statistics.hourly_distribution, DEFAULT_BAR_COLOR),
+        StatisticsChart::DayCategory => day_category_chart(
+            &statistics.day_category_breakdown,
+            &category_colors().await?,
+            load_calendar_settings().await?.week_start_day,
+        ),
+    })
+}
+
+/// Renders the year heatmap as SVG, in the category's colour when filtered by one.
+#[tauri::command]
+pub async fn render_heatmap_chart(
+    year: i32,
+    category: Option<String>,
+    app_group: Option<String>,
+    device_uuids: Option<Vec<String>>,
+) -> Result<String, Error> {
+    let color = match &category {
+        Some(name) => category_colors().await?.remove(name),
+        None => None,
+    };
+    let heatmap = get_year_heatmap(year, category, app_group, device_uuids).await?;
+    Ok(heatmap_chart(
+        &heatmap,
+        color.as_deref().unwrap_or(DEFAULT_HEATMAP_COLOR),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::queries::heatmap::HeatmapDay;
+
+    fn slice(label: &str, seconds: i64, color: Option<&str>) -> ChartSlice {
+        ChartSlice {
+            label: label.to_string(),
+            seconds,
+            color: color.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn donut_uses_category_colors_and_escapes_labels() {
+        let chart = donut_chart(
+            "Week",
+            &[
+                slice("Coding", 5_400, Some("#1100ff")),
+                slice("R&D <lab>", 1_800, Some("red\" onload=\"x")),
+                slice("Idle", 0, None),
+            ],
+        );
+        assert!(chart.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert_eq!(chart.matches("<circle").count(), 2);
+        assert!(chart.contains("stroke=\"#1100ff\""));
+        // The invalid colour falls back to the palette.
+        assert!(chart.contains(&format!("stroke=\"{}\"", PALETTE[1])));
+        assert!(!chart.contains("onload"));
+        assert!(chart.contains("R&amp;D &lt;lab&gt; · 30m (25%)"));
+        assert!(chart.contains(">2h 00m</text>"));
+        assert!(donut_chart("Empty", &[slice("Idle", 0, None)]).contains("No time recorded"));
+
+        let many: Vec<ChartSlice> = (0..10)
+            .map(|index| slice(&format!("C{index}"), 600, None))
+            .collect();
+        let chart = donut_chart("Many", &many);
+        assert_eq!(chart.matches("<circle").count(), MAX_DONUT_SLICES + 1);
+        assert!(chart.contains("Other · 20m (20%)"));
+    }
+
+    #[test]
+    fn stacked_bars_order_categories_by_total() {
+        let stat = |day: i32, category: &str, total_duration: i64| DayCategoryStat {
+            day,
+            weekday: (day + 6) % 7,
+            category: category.to_string(),
+            total_duration,
+        };
+        let colors = HashMap::from([("Coding".to_string(), "#1100ff".to_string())]);
+        let chart = day_category_chart(
+            &[
+                stat(0, "Reading", 600),
+                stat(0, "Coding", 3_600),
+                stat(2, "Reading", 1_200),
+            ],
+            &colors,
+            6,
+        );
+        let coding = chart.find("<title>Coding: 1h 00m</title>").unwrap();
+        let reading = chart.find("<title>Reading: 10m</title>").unwrap();
+        assert!(coding < reading);
+        assert!(chart.contains("fill=\"#1100ff\""));
+        // Day 0 is Sunday when weeks start on Sunday.
+        assert!(chart.contains(">Sun</text>"));
+        assert!(chart.contains("Reading · 30m"));
+    }
+
+    #[test]
+    fn heatmap_places_days_by_weekday() {
+        let day = |date: &str, total_duration: i64, level: u8| HeatmapDay {
+            date: date.to_string(),
+            day_start: 0,
+            total_duration,
+            level,
+        };
+        let heatmap = YearHeatmap {
+            year: 2024,
+            // 2024-01-01 was a Monday.
+            days: vec![
+                day("2024-01-01", 3_600, 4),
+                day("2024-01-02", 0, 0),
+                day("2024-01-08", 600, 1),
+            ],
+            thresholds: vec![600, 1_200, 2_400],
+            total_duration: 4_200,
+            active_days: 2,
+            max_duration: 3_600,
+        };
+        let chart = heatmap_chart(&heatmap, "#a855f7");
+        assert!(chart.contains(
+            "<rect x=\"32\" y=\"22\" width=\"11\" height=\"11\" rx=\"2\" fill=\"#a855f7\" fill-opacity=\"1\">"
+        ));
+        assert!(chart.contains(&format!(
+            "<rect x=\"32\" y=\"35\" width=\"11\" height=\"11\" rx=\"2\" fill=\"{EMPTY_COLOR}\">"
+        )));
+        // The third day is a week later, so it starts the next column.
+        assert!(chart.contains("<rect x=\"45\" y=\"22\" width=\"11\" height=\"11\" rx=\"2\" fill=\"#a855f7\" fill-opacity=\"0.3\">"));
+        assert!(chart.contains(">Jan</text>"));
+    }
+}
@@ -0,0 +1,735 @@
+use crate::db::queries::time_zone::{add_days, load_time_zone, resolve_local};
+use crate::db::tables::manual_time_block::{normalize_tags, Tags};
+use crate::db::{get_pool, Error};
+use anyhow::Context;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+const MAX_TITLE_CHARS: usize = 200;
+const DEFAULT_TITLE: &str = "Imported time";
+const UID_PREFIX: &str = "csv:";
+const DATE_FORMATS: [&str; 4] = ["%Y-%m-%d", "%m/%d/%Y", "%d.%m.%Y", "%Y/%m/%d"];
+const TIME_FORMATS: [&str; 6] = [
+    "%H:%M:%S",
+    "%H:%M",
+    "%I:%M:%S %p",
+    "%I:%M %p",
+    "%I:%M:%S%p",
+    "%I:%M%p",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvPreset {
+    Toggl,
+    Clockify,
+    Generic,
+}
+
+/// Header names for each field, matched case-insensitively. Columns the file does not have are
+/// ignored, except the start date.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnMapping {
+    pub title: Option<String>,
+    pub task: Option<String>, // Used as the title when the title column is empty
+    pub notes: Option<String>,
+    pub project: Option<String>,
+    pub client: Option<String>,
+    pub tags: Option<String>,       // Comma-separated
+    pub start_date: Option<String>, // May hold the date and time together
+    pub start_time: Option<String>,
+    pub end_date: Option<String>,
+    pub end_time: Option<String>,
+    pub duration: Option<String>, // `H:MM:SS` or decimal hours, used without an end
+    pub date_format: Option<String>, // chrono format such as `%d/%m/%Y`, guessed when unset
+}
+
+fn column(name: &str) -> Option<String> {
+    Some(name.to_string())
+}
+
+impl CsvPreset {
+    pub fn mapping(self) -> ColumnMapping {
+        match self {
+            CsvPreset::Toggl => ColumnMapping {
+                title: column("Description"),
+                task: column("Task"),
+                project: column("Project"),
+                client: column("Client"),
+                tags: column("Tags"),
+                start_date: column("Start date"),
+                start_time: column("Start time"),
+                end_date: column("End date"),
+                end_time: column("End time"),
+                duration: column("Duration"),
+                ..Default::default()
+            },
+            CsvPreset::Clockify => ColumnMapping {
+                title: column("Description"),
+                task: column("Task"),
+                project: column("Project"),
+                client: column("Client"),
+                tags: column("Tags"),
+                start_date: column("Start Date"),
+                start_time: column("Start Time"),
+                end_date: column("End Date"),
+                end_time: column("End Time"),
+                duration: column("Duration (h)"),
+                ..Default::default()
+            },
+            CsvPreset::Generic => ColumnMapping {
+                title: column("Title"),
+                notes: column("Notes"),
+                project: column("Project"),
+                client: column("Client"),
+                tags: column("Tags"),
+                start_date: column("Start"),
+                end_date: column("End"),
+                duration: column("Duration"),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Reads RFC 4180 records with their starting line numbers. The delimiter is `;` when the
+/// header has more of those than commas, as spreadsheet exports in some locales do.
+fn parse_csv(text: &str) -> Vec<(usize, Vec<String>)> {
+    let text = text.trim_start_matches('\u{feff}');
+    let header = text.lines().next().unwrap_or("");
+    let delimiter = if header.matches(';').count() > header.matches(',').count() {
+        ';'
+    } else {
+        ','
+    };
+
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.is_empty() => in_quotes = true,
+            '\n' if in_quotes => {
+                line += 1;
+                field.push('\n');
+            }
+            '\r' if !in_quotes => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|value| !value.is_empty()) {
+                    records.push((record_line, std::mem::take(&mut record)));
+                }
+                record.clear();
+                line += 1;
+                record_line = line;
+            }
+            c if c == delimiter && !in_quotes => record.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    record.push(field);
+    if record.iter().any(|value| !value.is_empty()) {
+        records.push((record_line, record));
+    }
+    records
+}
+
+fn parse_date(value: &str, date_format: Option<&str>) -> Option<NaiveDate> {
+    match date_format {
+        Some(format) => NaiveDate::parse_from_str(value, format).ok(),
+        None => DATE_FORMATS
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(value, format).ok()),
+    }
+}
+
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    let value = value.trim();
+    TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(value, format).ok())
+}
+
+/// A date with an optional separate time. Without one, the date column may carry the time after
+/// a space or `T`, or be an RFC 3339 timestamp with its own offset.
+fn parse_moment(
+    date: &str,
+    time: Option<&str>,
+    date_format: Option<&str>,
+    tz: Tz,
+) -> Result<i64, String> {
+    let date = date.trim();
+    let time = time.map(str::trim).filter(|time| !time.is_empty());
+    if time.is_none() {
+        if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(date) {
+            return Ok(datetime.timestamp());
+        }
+    }
+    let (date_part, time_part) = match time {
+        Some(time) => (date, Some(time)),
+        None => match date.split_once([' ', 'T']) {
+            Some((date, time)) => (date, Some(time)),
+            None => (date, None),
+        },
+    };
+    let day = parse_date(date_part, date_format).ok_or_else(|| format!("Invalid date '{date}'"))?;
+    let clock = match time_part {
+        Some(time) => parse_time(time).ok_or_else(|| format!("Invalid time '{time}'"))?,
+        None => NaiveTime::MIN,
+    };
+    Ok(resolve_local(tz, NaiveDateTime::new(day, clock)))
+}
+
+/// `1:30:00`, `01:30` or decimal hours such as `1.5`.
+fn parse_duration(value: &str) -> Option<i64> {
+    let value = value.trim();
+    if value.contains(':') {
+        let parts: Vec<i64> = value
+            .split(':')
+            .map(|part| part.trim().parse().ok())
+            .collect::<Option<_>>()?;
+        return match parts.as_slice() {
+            [hours, minutes, seconds] => Some(hours * 3600 + minutes * 60 + seconds),
+            [hours, minutes] => Some(hours * 3600 + minutes * 60),
+            _ => None,
+        };
+    }
+    let hours: f64 = value.replace(',', ".").parse().ok()?;
+    (hours.is_finite() && hours >= 0.0).then(|| (hours * 3600.0).round() as i64)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CsvRow {
+    pub line: usize,
+    pub title: String,
+    pub notes: Option<String>,
+    pub project: Option<String>,
+    pub client: Option<String>,
+    pub tags: Tags,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub duplicate: bool, // Imported before, or repeated earlier in the file
+}
+
+impl CsvRow {
+    fn external_uid(&self) -> String {
+        format!(
+            "{UID_PREFIX}{}-{}:{}",
+            self.start_time, self.end_time, self.title
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CsvRowError {
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct ParsedCsv {
+    headers: Vec<String>,
+    rows: Vec<CsvRow>,
+    errors: Vec<CsvRowError>,
+    warnings: Vec<String>,
+}
+
+fn parse_rows(text: &str, mapping: &ColumnMapping, tz: Tz) -> Result<ParsedCsv, Error> {
+    let mut records = parse_csv(text).into_iter();
+    let Some((_, headers)) = records.next() else {
+        return Err(anyhow::anyhow!("The file is empty").into());
+    };
+    let headers: Vec<String> = headers
+        .iter()
+        .map(|header| header.trim().to_string())
+        .collect();
+    let mut parsed = ParsedCsv {
+        headers: headers.clone(),
+        ..Default::default()
+    };
+
+    let mut index = |name: &Option<String>, field: &str| -> Option<usize> {
+        let name = name.as_deref()?.trim();
+        let found = headers
+            .iter()
+            .position(|header| header.eq_ignore_ascii_case(name));
+        if found.is_none() {
+            parsed
+                .warnings
+                .push(format!("Column '{name}' for {field} was not found"));
+        }
+        found
+    };
+    let title = index(&mapping.title, "the title");
+    let task = index(&mapping.task, "the task");
+    let notes = index(&mapping.notes, "notes");
+    let project = index(&mapping.project, "the project");
+    let client = index(&mapping.client, "the client");
+    let tags = index(&mapping.tags, "tags");
+    let start_date = index(&mapping.start_date, "the start date");
+    let start_time = index(&mapping.start_time, "the start time");
+    let end_date = index(&mapping.end_date, "the end date");
+    let end_time = index(&mapping.end_time, "the end time");
+    let duration = index(&mapping.duration, "the duration");
+    let Some(start_date) = start_date else {
+        return Err(anyhow::anyhow!("The start date column is required").into());
+    };
+    if end_date.or(end_time).or(duration).is_none() {
+        return Err(anyhow::anyhow!("An end date, end time or duration column is required").into());
+    }
+    let date_format = mapping
+        .date_format
+        .as_deref()
+        .filter(|format| !format.is_empty());
+
+    for (line, record) in records {
+        let value = |column: Option<usize>| -> Option<&str> {
+            column
+                .and_then(|column| record.get(column))
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+        };
+        let row = (|| -> Result<CsvRow, String> {
+            let start_date = value(Some(start_date)).ok_or("Missing start date")?;
+            let start = parse_moment(start_date, value(start_time), date_format, tz)?;
+            let end = match (value(end_date), value(end_time), value(duration)) {
+                (Some(date), time, _) => parse_moment(date, time, date_format, tz)?,
+                (None, Some(time), _) => {
+                    // Only a clock time: same day as the start, or the next if it wrapped.
+                    let end = parse_moment(
+                        start_date.split([' ', 'T']).next().unwrap_or(""),
+                        Some(time),
+                        date_format,
+                        tz,
+                    )?;
+                    if end <= start {
+                        add_days(tz, end, 1)
+                    } else {
+                        end
+                    }
+                }
+                (None, None, Some(duration)) => {
+                    start
+                        + parse_duration(duration)
+                            .ok_or_else(|| format!("Invalid duration '{duration}'"))?
+                }
+                (None, None, None) => return Err("Missing end time or duration".into()),
+            };
+            if end <= start {
+                return Err("End time must be after start time".into());
+            }
+            let title = value(title)
+                .or(value(task))
+                .or(value(project))
+                .unwrap_or(DEFAULT_TITLE);
+            let tags: Vec<String> = value(tags)
+                .map(|tags| tags.split(',').map(str::to_string).collect())
+                .unwrap_or_default();
+            let tags = normalize_tags(&tags).map_err(|e| e.to_string())?;
+            Ok(CsvRow {
+                line,
+                title: title.chars().take(MAX_TITLE_CHARS).collect(),
+                notes: value(notes).map(str::to_string),
+                project: value(project).map(str::to_string),
+                client: value(client).map(str::to_string),
+                tags,
+                start_time: start,
+                end_time: end,
+                duplicate: false,
+            })
+        })();
+        match row {
+            Ok(row) => parsed.rows.push(row),
+            Err(message) => parsed.errors.push(CsvRowError { line, message }),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Flags rows that were imported before or repeat an earlier row of the same file.
+async fn mark_duplicates(pool: &SqlitePool, rows: &mut [CsvRow]) -> Result<(), Error> {
+    let mut seen: HashSet<String> = sqlx::query_scalar(
+        "SELECT external_uid FROM manual_time_blocks WHERE external_uid LIKE 'csv:%'",
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+    for row in rows {
+        row.duplicate = !seen.insert(row.external_uid());
+    }
+    Ok(())
+}
+
+async fn load_rows(
+    path: &str,
+    preset: CsvPreset,
+    mapping: Option<ColumnMapping>,
+) -> Result<ParsedCsv, Error> {
+    let path = Path::new(path.trim());
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut parsed = parse_rows(
+        &text,
+        &mapping.unwrap_or_else(|| preset.mapping()),
+        load_time_zone().await?,
+    )?;
+    mark_duplicates(&get_pool().await?, &mut parsed.rows).await?;
+    Ok(parsed)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CsvImportPreview {
+    pub headers: Vec<String>,
+    pub rows: Vec<CsvRow>,
+    pub errors: Vec<CsvRowError>,
+    pub warnings: Vec<String>,
+    pub new_projects: Vec<String>,
+}
+
+/// Parses the file without writing anything, so the mapping can be checked first.
+#[tauri::command]
+pub async fn preview_csv_import(
+    path: String,
+    preset: CsvPreset,
+    mapping: Option<ColumnMapping>,
+) -> Result<CsvImportPreview, Error> {
+    let parsed = load_rows(&path, preset, mapping).await?;
+    let known: HashSet<String> = sqlx::query_scalar("SELECT name FROM projects")
+        .fetch_all(&get_pool().await?)
+        .await?
+        .into_iter()
+        .map(|name: String| name.to_lowercase())
+        .collect();
+    let mut new_projects: Vec<String> = Vec::new();
+    for name in parsed.rows.iter().filter_map(|row| row.project.as_ref()) {
+        if !known.contains(&name.to_lowercase())
+            && !new_projects
+                .iter()
+                .any(|seen| seen.eq_ignore_ascii_case(name))
+        {
+            new_projects.push(name.clone());
+        }
+    }
+    Ok(CsvImportPreview {
+        headers: parsed.headers,
+        rows: parsed.rows,
+        errors: parsed.errors,
+        warnings: parsed.warnings,
+        new_projects,
+    })
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CsvImportReport {
+    pub created: usize,
+    pub duplicates: usize,
+    pub failed: usize, // Rows with errors, as listed by the preview
+    pub projects_created: usize,
+    pub clients_created: usize,
+}
+
+/// Case-insensitive lookup of an id by name, inserting the name when it is missing.
+async fn find_or_create(
+    tx: &mut sqlx::SqliteConnection,
+    known: &mut HashMap<String, i64>,
+    insert: &'static str,
+    name: &str,
+) -> Result<(i64, bool), Error> {
+    if let Some(id) = known.get(&name.to_lowercase()) {
+        return Ok((*id, false));
+    }
+    let id = sqlx::query(insert)
+        .bind(name)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+    known.insert(name.to_lowercase(), id);
+    Ok((id, true))
+}
+
+async fn write_rows(pool: &SqlitePool, rows: &[CsvRow]) -> Result<CsvImportReport, Error> {
+    let mut report = CsvImportReport::default();
+    let mut tx = pool.begin().await?;
+    let names = |rows: Vec<(i64, String)>| -> HashMap<String, i64> {
+        rows.into_iter()
+            .map(|(id, name)| (name.to_lowercase(), id))
+            .collect()
+    };
+    let mut projects = names(
+        sqlx::query_as("SELECT id, name FROM projects")
+            .fetch_all(&mut *tx)
+            .await?,
+    );
+    let mut clients = names(
+        sqlx::query_as("SELECT id, name FROM clients")
+            .fetch_all(&mut *tx)
+            .await?,
+    );
+
+    let now = chrono::Utc::now().timestamp();
+    for row in rows {
+        if row.duplicate {
+            report.duplicates += 1;
+            continue;
+        }
+        let project_id = match &row.project {
+            None => None,
+            Some(name) => {
+                let (id, created) = find_or_create(
+                    &mut tx,
+                    &mut projects,
+                    "INSERT INTO projects (name, billable) VALUES (?1, 1)",
+                    name,
+                )
+                .await?;
+                if created {
+                    report.projects_created += 1;
+                    if let Some(client) = &row.client {
+                        let (client_id, created) = find_or_create(
+                            &mut tx,
+                            &mut clients,
+                            "INSERT INTO clients (name) VALUES (?1)",
+                            client,
+                        )
+                        .await?;
+                        report.clients_created += usize::from(created);
+                        sqlx::query("UPDATE projects SET client_id = ?1 WHERE id = ?2")
+                            .bind(client_id)
+                            .bind(id)
+                            .execute(&mut *tx)
+                            .await?;
+                    }
+                }
+                Some(id)
+            }
+        };
+        sqlx::query(
+            "INSERT INTO manual_time_blocks
+             (title, notes, start_time, end_time, created_at, updated_at, project_id, external_uid,
+              tags)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5, ?6, ?7, ?8)",
+        )
+        .bind(&row.title)
+        .bind(&row.notes)
+        .bind(row.start_time)
+        .bind(row.end_time)
+        .bind(now)
+        .bind(project_id)
+        .bind(row.external_uid())
+        .bind(row.tags.to_column())
+        .execute(&mut *tx)
+        .await?;
+        report.created += 1;
+    }
+    tx.commit().await?;
+    Ok(report)
+}
+
+/// Creates manual time blocks for the valid rows, along with missing projects and their clients.
+/// Rows imported before are skipped.
+#[tauri::command]
+pub async fn import_csv(
+    path: String,
+    preset: CsvPreset,
+    mapping: Option<ColumnMapping>,
+) -> Result<CsvImportReport, Error> {
+    let parsed = load_rows(&path, preset, mapping).await?;
+    let mut report = write_rows(&get_pool().await?, &parsed.rows).await?;
+    report.failed = parsed.errors.len();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn berlin(day: u32, hour: u32, minute: u32) -> i64 {
+        chrono_tz::Europe::Berlin
+            .with_ymd_and_hms(2024, 5, day, hour, minute, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    #[test]
+    fn reads_quoted_fields_and_semicolons() {
+        let records = parse_csv("\u{feff}a,b\r\n\"x, \"\"y\"\"\",\"multi\nline\"\r\n\r\nz,\n");
+        assert_eq!(
+            records,
+            vec![
+                (1, vec!["a".to_string(), "b".to_string()]),
+                (2, vec!["x, \"y\"".to_string(), "multi\nline".to_string()]),
+                (5, vec!["z".to_string(), String::new()]),
+            ]
+        );
+        assert_eq!(parse_csv("a;b\n1,5;2")[1].1, vec!["1,5", "2"]);
+    }
+
+    #[test]
+    fn toggl_and_clockify_exports_map_to_rows() {
+        let tz = chrono_tz::Europe::Berlin;
+        let toggl = "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags,Amount (USD)\n\
+            Sam,sam@example.com,Acme,Website,,Design review,Yes,2024-05-10,09:00:00,2024-05-10,10:30:00,01:30:00,\"client, review\",150\n\
+            Sam,sam@example.com,,,Emails,,No,2024-05-10,23:30:00,2024-05-11,00:15:00,00:45:00,,0\n\
+            Sam,sam@example.com,,,,,No,10/05/2024,09:00:00,,,,,0\n";
+        let parsed = parse_rows(toggl, &CsvPreset::Toggl.mapping(), tz).unwrap();
+        assert!(parsed.warnings.is_empty());
+        assert_eq!(parsed.rows.len(), 2);
+        let first = &parsed.rows[0];
+        assert_eq!(first.title, "Design review");
+        assert_eq!(first.project.as_deref(), Some("Website"));
+        assert_eq!(first.client.as_deref(), Some("Acme"));
+        assert_eq!(
+            (first.start_time, first.end_time),
+            (berlin(10, 9, 0), berlin(10, 10, 30))
+        );
+        assert_eq!(first.tags, Tags(vec!["client".into(), "review".into()]));
+        assert_eq!(first.notes, None);
+        // The task stands in for a missing description.
+        assert_eq!(parsed.rows[1].title, "Emails");
+        assert_eq!(parsed.rows[1].end_time, berlin(11, 0, 15));
+        // Rows without an end or a duration are reported, not imported.
+        assert_eq!(parsed.errors.len(), 1);
+        assert_eq!(parsed.errors[0].line, 4);
+
+        let clockify = "Project,Client,Description,Task,User,Start Date,Start Time,End Date,End Time,Duration (h),Duration (decimal)\n\
+            Website,Acme,Standup,,Sam,05/10/2024,09:00:00 AM,05/10/2024,09:15:00 AM,00:15:00,0.25\n\
+            Website,Acme,Late fix,,Sam,05/10/2024,11:00 PM,,,1.5,1.5\n";
+        let mut mapping = CsvPreset::Clockify.mapping();
+        let parsed = parse_rows(clockify, &mapping, tz).unwrap();
+        assert_eq!(parsed.rows[0].end_time - parsed.rows[0].start_time, 900);
+        // Without an end date the duration is used.
+        mapping.end_date = None;
+        mapping.end_time = None;
+        mapping.duration = column("Duration (decimal)");
+        let parsed = parse_rows(clockify, &mapping, tz).unwrap();
+        assert_eq!(parsed.errors, vec![]);
+        assert_eq!(parsed.rows[1].start_time, berlin(10, 23, 0));
+        assert_eq!(parsed.rows[1].end_time, berlin(11, 0, 30));
+    }
+
+    #[test]
+    fn generic_mapping_reads_combined_timestamps() {
+        let tz = chrono_tz::Europe::Berlin;
+        let csv = "title,start,end,notes\n\
+            Workshop,2024-05-10 14:00,2024-05-10T16:00:00+02:00,Room 2\n\
+            Broken,2024-05-10 14:00,2024-05-10 13:00,\n";
+        let mut mapping = CsvPreset::Generic.mapping();
+        mapping.date_format = Some("%Y-%m-%d".into());
+        let parsed = parse_rows(csv, &mapping, tz).unwrap();
+        assert_eq!(parsed.rows.len(), 1);
+        assert_eq!(parsed.rows[0].start_time, berlin(10, 14, 0));
+        assert_eq!(parsed.rows[0].end_time, berlin(10, 16, 0));
+        assert_eq!(parsed.rows[0].notes.as_deref(), Some("Room 2"));
+        assert_eq!(
+            parsed.errors[0].message,
+            "End time must be after start time"
+        );
+        // Project, client, tags and duration are not in this file.
+        assert_eq!(parsed.warnings.len(), 4);
+
+        assert!(parse_rows("name,when\nx,y\n", &mapping, tz).is_err());
+    }
+
+    #[test]
+    fn tags_are_normalized_like_manual_blocks() {
+        let tz = chrono_tz::Europe::Berlin;
+        let long = "x".repeat(51);
+        let csv = format!(
+            "title,start,end,tags\n\
+             Review,2024-05-10 09:00,2024-05-10 10:00,\"Client, client ,ops,\"\n\
+             Notes,2024-05-10 11:00,2024-05-10 12:00,{long}\n"
+        );
+        let mut mapping = CsvPreset::Generic.mapping();
+        mapping.date_format = Some("%Y-%m-%d".into());
+        let parsed = parse_rows(&csv, &mapping, tz).unwrap();
+        assert_eq!(parsed.rows.len(), 1);
+        assert_eq!(parsed.rows[0].tags.to_column(), "Client,ops");
+        assert_eq!(
+            parsed.errors,
+            vec![CsvRowError {
+                line: 3,
+                message: "Tags must be 50 characters or fewer".into(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn importing_creates_projects_and_skips_duplicates() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::db::tables::manual_time_block::create_table(&pool)
+            .await
+            .unwrap();
+        crate::db::tables::project::create_table(&pool)
+            .await
+            .unwrap();
+        crate::db::tables::client::create_table(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO projects (name) VALUES ('Website')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let row = |title: &str, project: Option<&str>, start: i64| CsvRow {
+            line: 2,
+            title: title.to_string(),
+            notes: None,
+            project: project.map(str::to_string),
+            client: Some("Acme".to_string()),
+            tags: Tags::default(),
+            start_time: start,
+            end_time: start + 600,
+            duplicate: false,
+        };
+        let mut rows = vec![
+            row("Review", Some("website"), 0),
+            row("Docs", Some("Handbook"), 600),
+            row("Docs", Some("HANDBOOK"), 1200),
+            row("Docs", Some("Handbook"), 600),
+        ];
+        mark_duplicates(&pool, &mut rows).await.unwrap();
+        let report = write_rows(&pool, &rows).await.unwrap();
+        assert_eq!(
+            report,
+            CsvImportReport {
+                created: 3,
+                duplicates: 1,
+                failed: 0,
+                projects_created: 1,
+                clients_created: 1,
+            }
+        );
+
+        let mut again = rows.clone();
+        mark_duplicates(&pool, &mut again).await.unwrap();
+        assert!(again.iter().all(|row| row.duplicate));
+        let projects: Vec<(String, Option<i64>)> =
+            sqlx::query_as("SELECT name, client_id FROM projects ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            projects,
+            vec![
+                ("Website".to_string(), None),
+                ("Handbook".to_string(), Some(1))
+            ]
+        );
+    }
+}
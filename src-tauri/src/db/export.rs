@@ -0,0 +1,653 @@
+use crate::db::queries::statistics::LogLabeller;
+use crate::db::queries::time_zone::{self, load_calendar_settings, load_time_zone};
+use crate::db::queries::week::{get_range, TimeBlockLogs};
+use crate::db::tables::category::get_categories;
+use crate::db::tables::device::{filter_logs_by_devices, get_local_log_device_uuid};
+use crate::db::tables::log::Log;
+use crate::db::tables::manual_time_block::get_manual_time_blocks;
+use crate::db::{get_pool, Error};
+use anyhow::Context;
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Logs are read from the database this many at a time.
+const LOG_BATCH_SIZE: i64 = 5_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportDataset {
+    Logs,
+    TimeBlocks,
+    ManualTimeBlocks,
+    Statistics, // Daily totals per category
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Ndjson,
+}
+
+impl ExportFormat {
+    /// CSV for `.csv`, NDJSON for `.ndjson` and `.jsonl`, JSON otherwise.
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("csv") => Self::Csv,
+            Some("ndjson" | "jsonl") => Self::Ndjson,
+            _ => Self::Json,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExportFilter {
+    #[serde(default)]
+    pub device_uuids: Option<Vec<String>>,
+    #[serde(default)]
+    pub categories: Option<Vec<String>>, // Manual time blocks without a category are kept
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportSummary {
+    pub rows: u64,
+}
+
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn local_time(tz: Tz, timestamp: i64) -> String {
+    time_zone::local_datetime(tz, timestamp).to_rfc3339()
+}
+
+pub trait ExportRow: Serialize {
+    const HEADERS: &'static [&'static str];
+    fn csv_fields(&self) -> Vec<String>;
+}
+
+/// Writes rows one at a time, so an export never holds more than a batch in memory.
+pub struct RowWriter<W: Write> {
+    out: W,
+    format: ExportFormat,
+    rows: u64,
+}
+
+impl<W: Write> RowWriter<W> {
+    pub fn new(mut out: W, format: ExportFormat, headers: &[&str]) -> Result<Self, Error> {
+        match format {
+            ExportFormat::Csv => writeln!(out, "{}", headers.join(","))?,
+            ExportFormat::Json => write!(out, "[")?,
+            ExportFormat::Ndjson => {}
+        }
+        Ok(Self {
+            out,
+            format,
+            rows: 0,
+        })
+    }
+
+    pub fn write<R: ExportRow>(&mut self, row: &R) -> Result<(), Error> {
+        match self.format {
+            ExportFormat::Csv => {
+                let fields: Vec<String> = row
+                    .csv_fields()
+                    .iter()
+                    .map(|field| csv_field(field))
+                    .collect();
+                writeln!(self.out, "{}", fields.join(","))?;
+            }
+            ExportFormat::Json => {
+                let separator = if self.rows == 0 { "\n  " } else { ",\n  " };
+                write!(self.out, "{separator}")?;
+                serde_json::to_writer(&mut self.out, row).map_err(anyhow::Error::new)?;
+            }
+            ExportFormat::Ndjson => {
+                serde_json::to_writer(&mut self.out, row).map_err(anyhow::Error::new)?;
+                writeln!(self.out)?;
+            }
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<u64, Error> {
+        if self.format == ExportFormat::Json {
+            let close = if self.rows == 0 { "]\n" } else { "\n]\n" };
+            write!(self.out, "{close}")?;
+        }
+        self.out.flush()?;
+        Ok(self.rows)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct LogRow {
+    id: i64,
+    device_uuid: Option<String>,
+    start: i64,
+    end: i64,
+    start_local: String,
+    duration: i64,
+    title: String,
+    app_group: String,
+    category: String,
+}
+
+impl ExportRow for LogRow {
+    const HEADERS: &'static [&'static str] = &[
+        "id",
+        "device_uuid",
+        "start",
+        "end",
+        "start_local",
+        "duration",
+        "title",
+        "app_group",
+        "category",
+    ];
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.device_uuid.clone().unwrap_or_default(),
+            self.start.to_string(),
+            self.end.to_string(),
+            self.start_local.clone(),
+            self.duration.to_string(),
+            self.title.clone(),
+            self.app_group.clone(),
+            self.category.clone(),
+        ]
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct TimeBlockRow {
+    id: i32,
+    category: String,
+    start: i64,
+    end: i64,
+    start_local: String,
+    end_local: String,
+    duration: i64,
+    apps: Vec<TimeBlockLogs>,
+}
+
+impl ExportRow for TimeBlockRow {
+    const HEADERS: &'static [&'static str] = &[
+        "id",
+        "category",
+        "start",
+        "end",
+        "start_local",
+        "end_local",
+        "duration",
+        "apps",
+    ];
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.category.clone(),
+            self.start.to_string(),
+            self.end.to_string(),
+            self.start_local.clone(),
+            self.end_local.clone(),
+            self.duration.to_string(),
+            self.apps
+                .iter()
+                .map(|app| format!("{}={}", app.app, app.total_duration))
+                .collect::<Vec<_>>()
+                .join("; "),
+        ]
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ManualTimeBlockRow {
+    id: i64,
+    title: String,
+    notes: Option<String>,
+    start: i64,
+    end: i64,
+    start_local: String,
+    end_local: String,
+    duration: i64,
+    project_id: Option<i64>,
+    category: Option<String>,
+    tags: String, // Comma-separated
+}
+
+impl ExportRow for ManualTimeBlockRow {
+    const HEADERS: &'static [&'static str] = &[
+        "id",
+        "title",
+        "notes",
+        "start",
+        "end",
+        "start_local",
+        "end_local",
+        "duration",
+        "project_id",
+        "category",
+        "tags",
+    ];
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.title.clone(),
+            self.notes.clone().unwrap_or_default(),
+            self.start.to_string(),
+            self.end.to_string(),
+            self.start_local.clone(),
+            self.end_local.clone(),
+            self.duration.to_string(),
+            self.project_id.map(|id| id.to_string()).unwrap_or_default(),
+            self.category.clone().unwrap_or_default(),
+            self.tags.clone(),
+        ]
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct StatisticRow {
+    date: String, // YYYY-MM-DD, honouring the calendar's day start hour
+    category: String,
+    total_duration: i64,
+    share: f64, // Fraction of the day's exported time
+}
+
+impl ExportRow for StatisticRow {
+    const HEADERS: &'static [&'static str] = &["date", "category", "total_duration", "share"];
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.date.clone(),
+            self.category.clone(),
+            self.total_duration.to_string(),
+            format!("{:.4}", self.share),
+        ]
+    }
+}
+
+/// Pages through non-deleted logs in the range ordered by time, keyed on the last row seen.
+struct LogBatches {
+    pool: SqlitePool,
+    range_start: i64,
+    range_end: i64,
+    batch_size: i64,
+    after: Option<(i64, String, i64)>,
+    done: bool,
+}
+
+impl LogBatches {
+    fn new(pool: SqlitePool, range_start: i64, range_end: i64) -> Self {
+        Self {
+            pool,
+            range_start,
+            range_end,
+            batch_size: LOG_BATCH_SIZE,
+            after: None,
+            done: false,
+        }
+    }
+
+    async fn next(&mut self) -> Result<Option<Vec<Log>>, Error> {
+        if self.done {
+            return Ok(None);
+        }
+        let (timestamp, device_uuid, id) =
+            self.after
+                .clone()
+                .unwrap_or((self.range_start - 1, String::new(), i64::MIN));
+        let batch = sqlx::query_as::<_, Log>(
+            "SELECT id, device_uuid, app, timestamp, duration, is_deleted
+             FROM logs
+             WHERE is_deleted = 0
+               AND timestamp >= ?1 AND timestamp <= ?2
+               AND (timestamp, device_uuid, id) > (?3, ?4, ?5)
+             ORDER BY timestamp, device_uuid, id
+             LIMIT ?6",
+        )
+        .bind(self.range_start)
+        .bind(self.range_end)
+        .bind(timestamp)
+        .bind(device_uuid)
+        .bind(id)
+        .bind(self.batch_size)
+        .fetch_all(&self.pool)
+        .await?;
+
+        self.done = (batch.len() as i64) < self.batch_size;
+        match batch.last() {
+            Some(last) => {
+                self.after = Some((
+                    last.timestamp,
+                    last.device_uuid.clone().unwrap_or_default(),
+                    last.id,
+                ));
+                Ok(Some(batch))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+fn category_allowed(filter: &Option<HashSet<String>>, category: &str) -> bool {
+    filter
+        .as_ref()
+        .is_none_or(|categories| categories.contains(category))
+}
+
+async fn export_logs<W: Write>(
+    writer: &mut RowWriter<W>,
+    range_start: i64,
+    range_end: i64,
+    filter: &ExportFilter,
+    categories: &Option<HashSet<String>>,
+    tz: Tz,
+) -> Result<(), Error> {
+    let local_uuid = get_local_log_device_uuid().await?;
+    let mut labeller = LogLabeller::load().await?;
+    let mut batches = LogBatches::new(get_pool().await?, range_start, range_end);
+    while let Some(batch) = batches.next().await? {
+        let batch = filter_logs_by_devices(batch, filter.device_uuids.clone(), local_uuid.clone());
+        for log in batch {
+            if labeller.is_skipped(&log.app) {
+                continue;
+            }
+            let labelled = labeller.label(&log);
+            if !category_allowed(categories, &labelled.category) {
+                continue;
+            }
+            writer.write(&LogRow {
+                id: log.id,
+                device_uuid: log.device_uuid,
+                start: log.timestamp,
+                end: log.timestamp + log.duration,
+                start_local: local_time(tz, log.timestamp),
+                duration: log.duration,
+                title: labelled.title,
+                app_group: labelled.app,
+                category: labelled.category,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+async fn export_statistics<W: Write>(
+    writer: &mut RowWriter<W>,
+    range_start: i64,
+    range_end: i64,
+    filter: &ExportFilter,
+    categories: &Option<HashSet<String>>,
+    tz: Tz,
+) -> Result<(), Error> {
+    let start_hour = load_calendar_settings().await?.start_hour;
+    let local_uuid = get_local_log_device_uuid().await?;
+    let mut labeller = LogLabeller::load().await?;
+    let mut totals: BTreeMap<NaiveDate, BTreeMap<String, i64>> = BTreeMap::new();
+    let mut batches = LogBatches::new(get_pool().await?, range_start, range_end);
+    while let Some(batch) = batches.next().await? {
+        let batch = filter_logs_by_devices(batch, filter.device_uuids.clone(), local_uuid.clone());
+        for log in batch {
+            if labeller.is_skipped(&log.app) {
+                continue;
+            }
+            let category = labeller.label(&log).category;
+            if !category_allowed(categories, &category) {
+                continue;
+            }
+            *totals
+                .entry(time_zone::calendar_date(tz, log.timestamp, start_hour))
+                .or_default()
+                .entry(category)
+                .or_insert(0) += log.duration;
+        }
+    }
+
+    for (date, day) in totals {
+        let day_total: i64 = day.values().sum();
+        let mut rows: Vec<(String, i64)> = day.into_iter().collect();
+        rows.sort_by_key(|(_, total)| std::cmp::Reverse(*total));
+        for (category, total_duration) in rows {
+            writer.write(&StatisticRow {
+                date: date.format("%Y-%m-%d").to_string(),
+                category,
+                total_duration,
+                share: if day_total > 0 {
+                    total_duration as f64 / day_total as f64
+                } else {
+                    0.0
+                },
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Streams one dataset for the range to `path`; the format follows the file extension.
+#[tauri::command]
+pub async fn export_data(
+    path: String,
+    dataset: ExportDataset,
+    range_start: i64,
+    range_end: i64,
+    filter: Option<ExportFilter>,
+) -> Result<ExportSummary, Error> {
+    if range_end <= range_start {
+        return Err(anyhow::anyhow!("Range end must be after range start").into());
+    }
+    let path = Path::new(path.trim());
+    let filter = filter.unwrap_or_default();
+    let categories: Option<HashSet<String>> = filter
+        .categories
+        .as_ref()
+        .map(|names| names.iter().cloned().collect());
+    let tz = load_time_zone().await?;
+    let format = ExportFormat::from_path(path);
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create export file {}", path.display()))?;
+    let out = BufWriter::new(file);
+
+    let rows = match dataset {
+        ExportDataset::Logs => {
+            let mut writer = RowWriter::new(out, format, LogRow::HEADERS)?;
+            export_logs(
+                &mut writer,
+                range_start,
+                range_end,
+                &filter,
+                &categories,
+                tz,
+            )
+            .await?;
+            writer.finish()?
+        }
+        ExportDataset::Statistics => {
+            let mut writer = RowWriter::new(out, format, StatisticRow::HEADERS)?;
+            export_statistics(
+                &mut writer,
+                range_start,
+                range_end,
+                &filter,
+                &categories,
+                tz,
+            )
+            .await?;
+            writer.finish()?
+        }
+        ExportDataset::TimeBlocks => {
+            let mut writer = RowWriter::new(out, format, TimeBlockRow::HEADERS)?;
+            for block in get_range(range_start, range_end, filter.device_uuids.clone()).await? {
+                if !category_allowed(&categories, &block.category) {
+                    continue;
+                }
+                writer.write(&TimeBlockRow {
+                    id: block.id,
+                    start_local: local_time(tz, block.start_time),
+                    end_local: local_time(tz, block.end_time),
+                    duration: block.end_time - block.start_time,
+                    category: block.category,
+                    start: block.start_time,
+                    end: block.end_time,
+                    apps: block.apps,
+                })?;
+            }
+            writer.finish()?
+        }
+        ExportDataset::ManualTimeBlocks => {
+            let names: HashMap<i32, String> = get_categories()
+                .await?
+                .into_iter()
+                .map(|category| (category.id, category.name))
+                .collect();
+            let mut writer = RowWriter::new(out, format, ManualTimeBlockRow::HEADERS)?;
+            for block in get_manual_time_blocks(range_start, range_end).await? {
+                let category = block.category_id.and_then(|id| names.get(&id).cloned());
+                if let Some(category) = &category {
+                    if !category_allowed(&categories, category) {
+                        continue;
+                    }
+                }
+                writer.write(&ManualTimeBlockRow {
+                    id: block.id,
+                    start_local: local_time(tz, block.start_time),
+                    end_local: local_time(tz, block.end_time),
+                    duration: block.end_time - block.start_time,
+                    title: block.title,
+                    notes: block.notes,
+                    start: block.start_time,
+                    end: block.end_time,
+                    project_id: block.project_id,
+                    category,
+                    tags: block.tags.to_column(),
+                })?;
+            }
+            writer.finish()?
+        }
+    };
+    Ok(ExportSummary { rows })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<StatisticRow> {
+        vec![
+            StatisticRow {
+                date: "2024-05-10".into(),
+                category: "Coding, mostly".into(),
+                total_duration: 3600,
+                share: 0.75,
+            },
+            StatisticRow {
+                date: "2024-05-10".into(),
+                category: "Email".into(),
+                total_duration: 1200,
+                share: 0.25,
+            },
+        ]
+    }
+
+    fn export(format: ExportFormat, rows: &[StatisticRow]) -> String {
+        let mut buffer = Vec::new();
+        let mut writer = RowWriter::new(&mut buffer, format, StatisticRow::HEADERS).unwrap();
+        for row in rows {
+            writer.write(row).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap(), rows.len() as u64);
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[tokio::test]
+    async fn log_batches_page_through_the_range_in_time_order() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::db::tables::log::create_table(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO logs (id, device_uuid, app, timestamp, duration, is_deleted) VALUES
+             (1, 'b', 'Editor', 100, 5, 0),
+             (1, 'a', 'Editor', 100, 5, 0),
+             (2, 'a', 'Browser', 105, 5, 0),
+             (3, 'a', 'Deleted', 110, 5, 1),
+             (4, 'a', 'Terminal', 115, 5, 0),
+             (5, 'a', 'Too late', 500, 5, 0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let mut batches = LogBatches::new(pool, 100, 200);
+        batches.batch_size = 2;
+        let mut seen = Vec::new();
+        while let Some(batch) = batches.next().await.unwrap() {
+            assert!(batch.len() <= 2);
+            seen.extend(
+                batch
+                    .into_iter()
+                    .map(|log| (log.device_uuid.unwrap(), log.id)),
+            );
+        }
+        let expected = [("a", 1), ("b", 1), ("a", 2), ("a", 4)];
+        assert_eq!(
+            seen,
+            expected
+                .map(|(device, id)| (device.to_string(), id))
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn formats_follow_the_file_extension() {
+        assert_eq!(
+            ExportFormat::from_path(Path::new("a.CSV")),
+            ExportFormat::Csv
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("a.jsonl")),
+            ExportFormat::Ndjson
+        );
+        assert_eq!(ExportFormat::from_path(Path::new("a")), ExportFormat::Json);
+    }
+
+    #[test]
+    fn rows_are_written_as_csv_json_and_ndjson() {
+        let csv = export(ExportFormat::Csv, &rows());
+        assert_eq!(
+            csv,
+            "date,category,total_duration,share\n\
+             2024-05-10,\"Coding, mostly\",3600,0.7500\n\
+             2024-05-10,Email,1200,0.2500\n"
+        );
+
+        let ndjson = export(ExportFormat::Ndjson, &rows());
+        assert_eq!(ndjson.lines().count(), 2);
+        assert!(ndjson.starts_with("{\"date\":\"2024-05-10\",\"category\":\"Coding, mostly\""));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&export(ExportFormat::Json, &rows())).unwrap();
+        assert_eq!(json.as_array().map(Vec::len), Some(2));
+        let empty: serde_json::Value =
+            serde_json::from_str(&export(ExportFormat::Json, &[])).unwrap();
+        assert_eq!(empty, serde_json::json!([]));
+    }
+}
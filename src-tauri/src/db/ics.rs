@@ -0,0 +1,1105 @@
+use crate::db::queries::time_zone::load_time_zone;
+use crate::db::queries::week::{get_range, TimeBlock};
+use crate::db::tables::manual_time_block::{get_manual_time_blocks, ManualTimeBlock};
+use crate::db::{format_duration, get_pool, Error};
+use anyhow::Context;
+use chrono::{
+    Datelike, Days, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+const PRODUCT_ID: &str = "-//hulks//time-tracker//EN";
+/// Suffix of the UIDs this app writes; such events are skipped on import.
+const UID_DOMAIN: &str = "time-tracker.hulks.ca";
+const MAX_TITLE_CHARS: usize = 200;
+const MAX_TOP_APPS: usize = 5;
+/// Upper bounds that keep a malformed or unbounded rule from expanding forever.
+const MAX_OCCURRENCES: usize = 5_000;
+const MAX_RECURRENCE_PERIODS: i64 = 50_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IcsEvent {
+    pub uid: String,
+    pub start: i64,
+    pub end: i64,
+    pub summary: String,
+    pub description: Option<String>,
+    pub categories: Vec<String>,
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Splits a content line into 75-octet lines without breaking UTF-8 characters.
+fn fold_line(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn utc_stamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+pub fn write_calendar(events: &[IcsEvent], stamp: i64) -> String {
+    let mut out = String::new();
+    for line in [
+        "BEGIN:VCALENDAR",
+        "VERSION:2.0",
+        &format!("PRODID:{PRODUCT_ID}"),
+        "CALSCALE:GREGORIAN",
+    ] {
+        fold_line(line, &mut out);
+    }
+    for event in events {
+        fold_line("BEGIN:VEVENT", &mut out);
+        fold_line(&format!("UID:{}", escape_text(&event.uid)), &mut out);
+        fold_line(&format!("DTSTAMP:{}", utc_stamp(stamp)), &mut out);
+        fold_line(&format!("DTSTART:{}", utc_stamp(event.start)), &mut out);
+        fold_line(&format!("DTEND:{}", utc_stamp(event.end)), &mut out);
+        fold_line(
+            &format!("SUMMARY:{}", escape_text(&event.summary)),
+            &mut out,
+        );
+        if let Some(description) = &event.description {
+            fold_line(
+                &format!("DESCRIPTION:{}", escape_text(description)),
+                &mut out,
+            );
+        }
+        if !event.categories.is_empty() {
+            let categories: Vec<String> = event
+                .categories
+                .iter()
+                .map(|category| escape_text(category))
+                .collect();
+            fold_line(&format!("CATEGORIES:{}", categories.join(",")), &mut out);
+        }
+        fold_line("TRANSP:TRANSPARENT", &mut out);
+        fold_line("END:VEVENT", &mut out);
+    }
+    fold_line("END:VCALENDAR", &mut out);
+    out
+}
+
+pub fn time_block_event(block: &TimeBlock) -> IcsEvent {
+    let mut apps: Vec<_> = block.apps.iter().collect();
+    apps.sort_by_key(|app| std::cmp::Reverse(app.total_duration));
+    let top_apps: Vec<String> = apps
+        .iter()
+        .take(MAX_TOP_APPS)
+        .map(|app| format!("{} ({})", app.app, format_duration(app.total_duration)))
+        .collect();
+    let mut description = format!("Category: {}", block.category);
+    if !top_apps.is_empty() {
+        description.push_str(&format!("\nTop apps: {}", top_apps.join(", ")));
+    }
+    IcsEvent {
+        uid: format!("block-{}-{}@{UID_DOMAIN}", block.start_time, block.end_time),
+        start: block.start_time,
+        end: block.end_time,
+        summary: block.category.clone(),
+        description: Some(description),
+        categories: vec![block.category.clone()],
+    }
+}
+
+pub fn manual_block_event(block: &ManualTimeBlock) -> IcsEvent {
+    IcsEvent {
+        uid: format!("manual-{}@{UID_DOMAIN}", block.id),
+        start: block.start_time,
+        end: block.end_time,
+        summary: block.title.clone(),
+        description: block.notes.clone(),
+        categories: std::iter::once("Manual time".to_string())
+            .chain(block.tags.0.iter().cloned())
+            .collect(),
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IcsExportOptions {
+    #[serde(default)]
+    pub device_uuids: Option<Vec<String>>,
+    #[serde(default = "default_true")]
+    pub include_time_blocks: bool,
+    #[serde(default = "default_true")]
+    pub include_manual_time_blocks: bool,
+}
+
+impl Default for IcsExportOptions {
+    fn default() -> Self {
+        Self {
+            device_uuids: None,
+            include_time_blocks: true,
+            include_manual_time_blocks: true,
+        }
+    }
+}
+
+/// Writes the range's time blocks and manual time blocks to `path` as VEVENTs and returns how
+/// many events were written.
+#[tauri::command]
+pub async fn export_ics(
+    path: String,
+    range_start: i64,
+    range_end: i64,
+    options: Option<IcsExportOptions>,
+) -> Result<usize, Error> {
+    if range_end <= range_start {
+        return Err(anyhow::anyhow!("Range end must be after range start").into());
+    }
+    let options = options.unwrap_or_default();
+    let mut events = Vec::new();
+    if options.include_time_blocks {
+        events.extend(
+            get_range(range_start, range_end, options.device_uuids)
+                .await?
+                .iter()
+                .map(time_block_event),
+        );
+    }
+    if options.include_manual_time_blocks {
+        events.extend(
+            get_manual_time_blocks(range_start, range_end)
+                .await?
+                .iter()
+                .map(manual_block_event),
+        );
+    }
+    events.sort_by_key(|event| (event.start, event.end));
+
+    let path = Path::new(path.trim());
+    std::fs::write(
+        path,
+        write_calendar(&events, chrono::Utc::now().timestamp()),
+    )
+    .with_context(|| format!("Failed to write calendar to {}", path.display()))?;
+    Ok(events.len())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ if line.is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Parses `NAME;PARAM=value;PARAM="quoted:value":VALUE`.
+fn parse_property(line: &str) -> Option<Property> {
+    let mut in_quotes = false;
+    let mut split = None;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => {
+                split = Some(index);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let (head, value) = line.split_at(split?);
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    in_quotes = false;
+    for c in head.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => parts.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    parts.push(current);
+    let mut parts = parts.into_iter();
+    let name = parts.next()?.to_ascii_uppercase();
+    let params = parts
+        .filter_map(|part| {
+            let (key, value) = part.split_once('=')?;
+            Some((key.to_ascii_uppercase(), value.to_string()))
+        })
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value: value[1..].to_string(),
+    })
+}
+
+/// A DTSTART-like value resolved to its wall-clock time and zone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct EventTime {
+    local: NaiveDateTime,
+    tz: Tz,
+    all_day: bool,
+}
+
+/// Local time to a timestamp, moving times inside a DST gap forward by an hour.
+fn to_timestamp(tz: Tz, local: NaiveDateTime) -> i64 {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|datetime| datetime.timestamp())
+        .unwrap_or_else(|| local.and_utc().timestamp())
+}
+
+impl EventTime {
+    fn timestamp(&self) -> i64 {
+        to_timestamp(self.tz, self.local)
+    }
+}
+
+/// UTC (`Z`) values, values with a known TZID and floating values in `default_tz`. Unknown
+/// TZIDs, such as Windows zone names, fall back to `default_tz`.
+fn parse_time_value(value: &str, tzid: Option<&str>, default_tz: Tz) -> Option<EventTime> {
+    let value = value.trim();
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some(EventTime {
+            local: date.and_time(NaiveTime::MIN),
+            tz: default_tz,
+            all_day: true,
+        });
+    }
+    if let Some(utc) = value.strip_suffix(['Z', 'z']) {
+        return Some(EventTime {
+            local: NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?,
+            tz: Tz::UTC,
+            all_day: false,
+        });
+    }
+    Some(EventTime {
+        local: NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?,
+        tz: tzid
+            .and_then(|name| name.trim_start_matches('/').parse::<Tz>().ok())
+            .unwrap_or(default_tz),
+        all_day: false,
+    })
+}
+
+fn parse_time(property: &Property, default_tz: Tz) -> Option<EventTime> {
+    parse_time_value(&property.value, property.param("TZID"), default_tz)
+}
+
+/// `P1DT2H30M`, `PT45M`, `P2W`; negative durations are rejected.
+fn parse_duration(value: &str) -> Option<i64> {
+    let rest = value.trim().strip_prefix('+').unwrap_or(value.trim());
+    let mut rest = rest.strip_prefix('P')?;
+    let mut seconds = 0;
+    let mut in_time = false;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('T') {
+            in_time = true;
+            rest = after;
+            continue;
+        }
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let amount: i64 = rest[..digits].parse().ok()?;
+        let unit = match (rest[digits..].chars().next()?, in_time) {
+            ('W', false) => 7 * 86_400,
+            ('D', false) => 86_400,
+            ('H', true) => 3_600,
+            ('M', true) => 60,
+            ('S', true) => 1,
+            _ => return None,
+        };
+        seconds += amount * unit;
+        rest = &rest[digits + 1..];
+    }
+    Some(seconds)
+}
+
+#[derive(Debug, Clone, Default)]
+struct RawEvent {
+    uid: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    location: Option<String>,
+    start: Option<EventTime>,
+    end: Option<EventTime>,
+    duration: Option<i64>,
+    rrule: Option<String>,
+    exdates: Vec<i64>,
+    recurrence_id: Option<i64>,
+    cancelled: bool,
+}
+
+fn parse_events(text: &str, default_tz: Tz) -> Vec<RawEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<RawEvent> = None;
+    let mut nested = 0; // Depth of components inside the VEVENT, e.g. VALARM
+    for line in unfold(text) {
+        let Some(property) = parse_property(&line) else {
+            continue;
+        };
+        let value = property.value.to_ascii_uppercase();
+        match (property.name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value == "VEVENT" => current = Some(RawEvent::default()),
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(_)) if value == "VEVENT" => events.extend(current.take()),
+            (_, Some(_)) if nested > 0 => {}
+            ("UID", Some(event)) => event.uid = Some(property.value.trim().to_string()),
+            ("SUMMARY", Some(event)) => event.summary = Some(unescape_text(&property.value)),
+            ("DESCRIPTION", Some(event)) => {
+                event.description = Some(unescape_text(&property.value))
+            }
+            ("LOCATION", Some(event)) => event.location = Some(unescape_text(&property.value)),
+            ("DTSTART", Some(event)) => event.start = parse_time(&property, default_tz),
+            ("DTEND", Some(event)) => event.end = parse_time(&property, default_tz),
+            ("DURATION", Some(event)) => event.duration = parse_duration(&property.value),
+            ("RRULE", Some(event)) => event.rrule = Some(property.value.clone()),
+            ("EXDATE", Some(event)) => event.exdates.extend(
+                property
+                    .value
+                    .split(',')
+                    .filter_map(|value| parse_time_value(value, property.param("TZID"), default_tz))
+                    .map(|time| time.timestamp()),
+            ),
+            ("RECURRENCE-ID", Some(event)) => {
+                event.recurrence_id = parse_time(&property, default_tz).map(|time| time.timestamp())
+            }
+            ("STATUS", Some(event)) => event.cancelled = value == "CANCELLED",
+            _ => {}
+        }
+    }
+    events
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct RecurrenceRule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<i64>,
+    by_day: Vec<(i32, Weekday)>, // 0 means every such weekday in the period
+    by_month_day: Vec<i32>,
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    Some(match value {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// Supports FREQ=DAILY/WEEKLY/MONTHLY/YEARLY with INTERVAL, COUNT, UNTIL, BYDAY (weekly and
+/// monthly) and BYMONTHDAY (monthly). Anything else is reported as unsupported.
+fn parse_rrule(value: &str, tz: Tz) -> Result<RecurrenceRule, String> {
+    let mut rule = RecurrenceRule {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+        by_month_day: Vec::new(),
+    };
+    let mut frequency = None;
+    for part in value.split(';').filter(|part| !part.is_empty()) {
+        let (key, val) = part
+            .split_once('=')
+            .ok_or_else(|| format!("malformed rule part {part}"))?;
+        let val = val.to_ascii_uppercase();
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                frequency = Some(match val.as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    other => return Err(format!("FREQ={other}")),
+                })
+            }
+            "INTERVAL" => {
+                rule.interval = val
+                    .parse()
+                    .ok()
+                    .filter(|interval| *interval > 0)
+                    .ok_or_else(|| format!("INTERVAL={val}"))?
+            }
+            "COUNT" => rule.count = Some(val.parse().map_err(|_| format!("COUNT={val}"))?),
+            "UNTIL" => {
+                let until =
+                    parse_time_value(&val, None, tz).ok_or_else(|| format!("UNTIL={val}"))?;
+                // A date-only UNTIL includes that whole day.
+                rule.until = Some(if until.all_day {
+                    until.timestamp() + 86_399
+                } else {
+                    until.timestamp()
+                });
+            }
+            "BYDAY" => {
+                for day in val.split(',') {
+                    // The weekday is the last two characters, which need not be ASCII.
+                    let split = day.char_indices().nth_back(1).map_or(0, |(index, _)| index);
+                    let (ordinal, weekday) = day.split_at(split);
+                    let weekday = parse_weekday(weekday).ok_or_else(|| format!("BYDAY={day}"))?;
+                    let ordinal = match ordinal {
+                        "" => 0,
+                        ordinal => ordinal.parse().map_err(|_| format!("BYDAY={day}"))?,
+                    };
+                    rule.by_day.push((ordinal, weekday));
+                }
+            }
+            "BYMONTHDAY" => {
+                for day in val.split(',') {
+                    rule.by_month_day
+                        .push(day.parse().map_err(|_| format!("BYMONTHDAY={day}"))?);
+                }
+            }
+            "WKST" => {}
+            other => return Err(other.to_string()),
+        }
+    }
+    rule.frequency = frequency.ok_or("a rule without FREQ")?;
+    let weekly_days =
+        rule.frequency == Frequency::Weekly && rule.by_day.iter().all(|(ordinal, _)| *ordinal == 0);
+    if !rule.by_day.is_empty() && !weekly_days && rule.frequency != Frequency::Monthly {
+        return Err("BYDAY outside weekly and monthly rules".into());
+    }
+    if !rule.by_month_day.is_empty() && rule.frequency != Frequency::Monthly {
+        return Err("BYMONTHDAY outside monthly rules".into());
+    }
+    Ok(rule)
+}
+
+fn month_days(month_start: NaiveDate, rule: &RecurrenceRule, anchor: NaiveDate) -> Vec<NaiveDate> {
+    let next_month = month_start + Months::new(1);
+    let length = (next_month - month_start).num_days() as i32;
+    let mut days: Vec<NaiveDate> = if !rule.by_month_day.is_empty() {
+        rule.by_month_day
+            .iter()
+            .filter_map(|&day| {
+                let day = if day < 0 { length + day + 1 } else { day };
+                (1..=length)
+                    .contains(&day)
+                    .then(|| month_start + Duration::days(i64::from(day - 1)))
+            })
+            .collect()
+    } else if !rule.by_day.is_empty() {
+        rule.by_day
+            .iter()
+            .flat_map(|&(ordinal, weekday)| {
+                let matching: Vec<NaiveDate> = month_start
+                    .iter_days()
+                    .take_while(|date| *date < next_month)
+                    .filter(|date| date.weekday() == weekday)
+                    .collect();
+                match ordinal {
+                    0 => matching,
+                    n if n > 0 => matching.get(n as usize - 1).copied().into_iter().collect(),
+                    n => matching
+                        .len()
+                        .checked_sub(n.unsigned_abs() as usize)
+                        .map(|index| matching[index])
+                        .into_iter()
+                        .collect(),
+                }
+            })
+            .collect()
+    } else {
+        NaiveDate::from_ymd_opt(month_start.year(), month_start.month(), anchor.day())
+            .into_iter()
+            .collect()
+    };
+    days.sort_unstable();
+    days.dedup();
+    days
+}
+
+/// Start times of a recurring event in its own zone, so a 09:00 meeting stays at 09:00 across
+/// DST changes. Stops at COUNT, UNTIL, the first occurrence after `range_end` or the first
+/// period past the last representable date.
+fn expand(rule: &RecurrenceRule, start: EventTime, range_end: i64) -> Vec<NaiveDateTime> {
+    let first = start.local.date();
+    let time = start.local.time();
+    let interval = i64::from(rule.interval);
+    let mut occurrences = Vec::new();
+    let mut generated = 0;
+
+    for period in 0..MAX_RECURRENCE_PERIODS {
+        let step = period * interval;
+        let dates: Vec<NaiveDate> = match rule.frequency {
+            Frequency::Daily => match first.checked_add_days(Days::new(step.unsigned_abs())) {
+                Some(date) => vec![date],
+                None => break,
+            },
+            Frequency::Weekly => {
+                let Some(week_start) = step.checked_mul(7).and_then(|days| {
+                    first
+                        .checked_sub_days(Days::new(u64::from(
+                            first.weekday().num_days_from_monday(),
+                        )))?
+                        .checked_add_days(Days::new(days.unsigned_abs()))
+                }) else {
+                    break;
+                };
+                let mut weekdays: Vec<Weekday> =
+                    rule.by_day.iter().map(|(_, weekday)| *weekday).collect();
+                if weekdays.is_empty() {
+                    weekdays.push(first.weekday());
+                }
+                let mut dates: Vec<NaiveDate> = weekdays
+                    .iter()
+                    .filter_map(|weekday| {
+                        week_start
+                            .checked_add_days(Days::new(u64::from(weekday.num_days_from_monday())))
+                    })
+                    .collect();
+                dates.sort_unstable();
+                dates.dedup();
+                dates
+            }
+            Frequency::Monthly => {
+                let Some(month_start) = u32::try_from(step)
+                    .ok()
+                    .and_then(|months| first.with_day(1)?.checked_add_months(Months::new(months)))
+                else {
+                    break;
+                };
+                month_days(month_start, rule, first)
+            }
+            Frequency::Yearly => {
+                let Some(year) = i32::try_from(step)
+                    .ok()
+                    .and_then(|step| first.year().checked_add(step))
+                else {
+                    break;
+                };
+                // A missing date, such as 29 February, only skips that year.
+                if year > NaiveDate::MAX.year() {
+                    break;
+                }
+                NaiveDate::from_ymd_opt(year, first.month(), first.day())
+                    .into_iter()
+                    .collect()
+            }
+        };
+
+        for date in dates {
+            let local = date.and_time(time);
+            if local < start.local {
+                continue;
+            }
+            let timestamp = to_timestamp(start.tz, local);
+            if rule.count.is_some_and(|count| generated >= count)
+                || rule.until.is_some_and(|until| timestamp > until)
+                || timestamp > range_end
+                || occurrences.len() >= MAX_OCCURRENCES
+            {
+                return occurrences;
+            }
+            generated += 1;
+            occurrences.push(local);
+        }
+    }
+    occurrences
+}
+
+/// One concrete block to import, keyed so that re-importing the same file updates it.
+#[derive(Debug, Clone, PartialEq)]
+struct Occurrence {
+    key: String,
+    title: String,
+    notes: Option<String>,
+    start: i64,
+    end: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IcsImportReport {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub skipped: usize, // All-day, cancelled, zero-length or exported by this app
+    pub warnings: Vec<String>,
+}
+
+fn event_title(event: &RawEvent) -> String {
+    let title = event.summary.as_deref().unwrap_or("").trim();
+    let title = if title.is_empty() {
+        "Untitled event"
+    } else {
+        title
+    };
+    title.chars().take(MAX_TITLE_CHARS).collect()
+}
+
+fn event_notes(event: &RawEvent) -> Option<String> {
+    let description = event.description.as_deref().map(str::trim).unwrap_or("");
+    let location = event.location.as_deref().map(str::trim).unwrap_or("");
+    let notes = match (description.is_empty(), location.is_empty()) {
+        (true, true) => return None,
+        (false, true) => description.to_string(),
+        (true, false) => format!("Location: {location}"),
+        (false, false) => format!("{description}\nLocation: {location}"),
+    };
+    Some(notes)
+}
+
+/// Expands recurring events, applies EXDATEs and RECURRENCE-ID overrides and keeps what
+/// overlaps the range.
+fn collect_occurrences(
+    events: Vec<RawEvent>,
+    range_start: i64,
+    range_end: i64,
+    report: &mut IcsImportReport,
+) -> Vec<Occurrence> {
+    let mut overrides: HashMap<(String, i64), RawEvent> = HashMap::new();
+    let mut masters = Vec::new();
+    for event in events {
+        match (&event.uid, event.recurrence_id) {
+            (Some(uid), Some(recurrence_id)) => {
+                overrides.insert((uid.clone(), recurrence_id), event);
+            }
+            _ => masters.push(event),
+        }
+    }
+
+    let mut occurrences = Vec::new();
+    let mut push = |event: &RawEvent, key: String, start: i64, report: &mut IcsImportReport| {
+        let Some(begin) = event.start else {
+            report.skipped += 1;
+            return;
+        };
+        let length = match (event.end, event.duration) {
+            (Some(end), _) => end.timestamp() - begin.timestamp(),
+            (None, Some(duration)) => duration,
+            (None, None) => 0,
+        };
+        if event.cancelled || begin.all_day || length <= 0 {
+            report.skipped += 1;
+            return;
+        }
+        if start < range_end && start + length > range_start {
+            occurrences.push(Occurrence {
+                key,
+                title: event_title(event),
+                notes: event_notes(event),
+                start,
+                end: start + length,
+            });
+        }
+    };
+
+    for event in masters {
+        let uid = event.uid.clone().unwrap_or_else(|| {
+            format!(
+                "{}@{}",
+                event.summary.as_deref().unwrap_or(""),
+                event.start.map(|time| time.timestamp()).unwrap_or(0)
+            )
+        });
+        if uid.ends_with(&format!("@{UID_DOMAIN}")) {
+            report.skipped += 1;
+            continue;
+        }
+        let Some(start) = event.start else {
+            report.skipped += 1;
+            continue;
+        };
+        let rule = match event
+            .rrule
+            .as_deref()
+            .map(|rule| parse_rrule(rule, start.tz))
+        {
+            None => {
+                push(&event, uid, start.timestamp(), report);
+                continue;
+            }
+            Some(Ok(rule)) => rule,
+            Some(Err(reason)) => {
+                report.warnings.push(format!(
+                    "{}: unsupported recurrence ({reason}), only the first occurrence was imported",
+                    event_title(&event)
+                ));
+                push(&event, uid, start.timestamp(), report);
+                continue;
+            }
+        };
+
+        let excluded: HashSet<i64> = event.exdates.iter().copied().collect();
+        for local in expand(&rule, start, range_end) {
+            let original = to_timestamp(start.tz, local);
+            if excluded.contains(&original) {
+                continue;
+            }
+            let key = format!("{uid}/{original}");
+            match overrides.remove(&(uid.clone(), original)) {
+                Some(changed) => {
+                    let moved = changed
+                        .start
+                        .map(|time| time.timestamp())
+                        .unwrap_or(original);
+                    push(&changed, key, moved, report);
+                }
+                None => push(&event, key, original, report),
+            }
+        }
+    }
+    occurrences
+}
+
+/// Imports a calendar file as manual time blocks. Events are matched to earlier imports by UID
+/// (and start, for recurring events), so importing the same file again updates instead of
+/// duplicating. Recurrences are expanded up to `range_end`.
+#[tauri::command]
+pub async fn import_ics(
+    path: String,
+    range_start: i64,
+    range_end: i64,
+) -> Result<IcsImportReport, Error> {
+    if range_end <= range_start {
+        return Err(anyhow::anyhow!("Range end must be after range start").into());
+    }
+    let path = Path::new(path.trim());
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read calendar file {}", path.display()))?;
+    let default_tz = load_time_zone().await?;
+
+    let mut report = IcsImportReport::default();
+    let occurrences = collect_occurrences(
+        parse_events(&text, default_tz),
+        range_start,
+        range_end,
+        &mut report,
+    );
+
+    let pool = get_pool().await?;
+    let mut tx = pool.begin().await?;
+    let now = chrono::Utc::now().timestamp();
+    for occurrence in occurrences {
+        let existing: Option<(i64, String, Option<String>, i64, i64)> = sqlx::query_as(
+            "SELECT id, title, notes, start_time, end_time
+             FROM manual_time_blocks
+             WHERE external_uid = ?1",
+        )
+        .bind(&occurrence.key)
+        .fetch_optional(&mut *tx)
+        .await?;
+        match existing {
+            None => {
+                sqlx::query(
+                    "INSERT INTO manual_time_blocks
+                     (title, notes, start_time, end_time, created_at, updated_at, external_uid)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?5, ?6)",
+                )
+                .bind(&occurrence.title)
+                .bind(&occurrence.notes)
+                .bind(occurrence.start)
+                .bind(occurrence.end)
+                .bind(now)
+                .bind(&occurrence.key)
+                .execute(&mut *tx)
+                .await?;
+                report.created += 1;
+            }
+            Some((_, title, notes, start, end))
+                if title == occurrence.title
+                    && notes == occurrence.notes
+                    && start == occurrence.start
+                    && end == occurrence.end =>
+            {
+                report.unchanged += 1;
+            }
+            Some((id, ..)) => {
+                sqlx::query(
+                    "UPDATE manual_time_blocks
+                     SET title = ?1, notes = ?2, start_time = ?3, end_time = ?4, updated_at = ?5
+                     WHERE id = ?6",
+                )
+                .bind(&occurrence.title)
+                .bind(&occurrence.notes)
+                .bind(occurrence.start)
+                .bind(occurrence.end)
+                .bind(now)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+                report.updated += 1;
+            }
+        }
+    }
+    tx.commit().await?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::queries::week::TimeBlockLogs;
+    use crate::db::tables::manual_time_block::Tags;
+
+    fn berlin(year: i32, month: u32, day: u32, hour: u32) -> i64 {
+        chrono_tz::Europe::Berlin
+            .with_ymd_and_hms(year, month, day, hour, 0, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    #[test]
+    fn writes_escaped_and_folded_events() {
+        let block = TimeBlock {
+            id: 1,
+            category: "Coding".into(),
+            apps: vec![
+                TimeBlockLogs {
+                    app: "Terminal".into(),
+                    app_names: vec![],
+                    total_duration: 600,
+                },
+                TimeBlockLogs {
+                    app: "Visual Studio Code".into(),
+                    app_names: vec![],
+                    total_duration: 4_500,
+                },
+            ],
+            start_time: 1_715_331_600,
+            end_time: 1_715_336_700,
+        };
+        let event = time_block_event(&block);
+        assert_eq!(
+            event.description.as_deref(),
+            Some("Category: Coding\nTop apps: Visual Studio Code (1h 15m), Terminal (10m)")
+        );
+
+        let mut manual = manual_block_event(&ManualTimeBlock {
+            id: 9,
+            title: "Planning; Q3, part 1".into(),
+            notes: Some("x".repeat(100)),
+            start_time: 0,
+            end_time: 3_600,
+            created_at: 0,
+            updated_at: 0,
+            project_id: None,
+            category_id: None,
+            tags: Tags::default(),
+        });
+        manual.categories.clear();
+        let ics = write_calendar(&[event, manual], 0);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.contains("DTSTART:20240510T090000Z\r\n"));
+        assert!(ics.contains("SUMMARY:Planning\\; Q3\\, part 1\r\n"));
+        assert!(ics.contains("CATEGORIES:Coding\r\n"));
+        assert!(ics.lines().all(|line| line.len() <= 75));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+
+        // What was written reads back the same.
+        let events = parse_events(&ics, Tz::UTC);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].summary.as_deref(), Some("Planning; Q3, part 1"));
+        assert_eq!(
+            events[1].description.as_deref(),
+            Some("x".repeat(100).as_str())
+        );
+    }
+
+    #[test]
+    fn parses_zones_durations_and_skips_nested_components() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:a@example.com\r\n\
+            DTSTART;TZID=\"Europe/Berlin\":20240510T090000\r\n\
+            DURATION:PT1H30M\r\n\
+            SUMMARY:Stand\r\n  up\r\n\
+            BEGIN:VALARM\r\n\
+            DESCRIPTION:Reminder\r\n\
+            END:VALARM\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:b@example.com\r\n\
+            DTSTART;TZID=W. Europe Standard Time:20240510T140000\r\n\
+            DTEND;TZID=W. Europe Standard Time:20240510T150000\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        let events = parse_events(ics, chrono_tz::Europe::Berlin);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].summary.as_deref(), Some("Stand up"));
+        assert_eq!(events[0].description, None);
+        assert_eq!(events[0].duration, Some(5_400));
+        assert_eq!(events[0].start.unwrap().timestamp(), berlin(2024, 5, 10, 9));
+        // Unknown zone names fall back to the configured zone.
+        assert_eq!(
+            events[1].start.unwrap().timestamp(),
+            berlin(2024, 5, 10, 14)
+        );
+
+        assert_eq!(parse_duration("P1W2DT3H"), Some(9 * 86_400 + 3 * 3_600));
+        assert_eq!(parse_duration("1H"), None);
+    }
+
+    #[test]
+    fn weekly_recurrences_keep_local_time_across_dst() {
+        let tz = chrono_tz::Europe::Berlin;
+        let ics = "BEGIN:VEVENT\n\
+            UID:weekly@example.com\n\
+            DTSTART;TZID=Europe/Berlin:20240321T090000\n\
+            DTEND;TZID=Europe/Berlin:20240321T093000\n\
+            RRULE:FREQ=WEEKLY;BYDAY=TU,TH;COUNT=5\n\
+            EXDATE;TZID=Europe/Berlin:20240326T090000\n\
+            SUMMARY:Sync\n\
+            END:VEVENT\n\
+            BEGIN:VEVENT\n\
+            UID:weekly@example.com\n\
+            RECURRENCE-ID;TZID=Europe/Berlin:20240402T090000\n\
+            DTSTART;TZID=Europe/Berlin:20240402T110000\n\
+            DTEND;TZID=Europe/Berlin:20240402T113000\n\
+            SUMMARY:Sync (moved)\n\
+            END:VEVENT\n\
+            BEGIN:VEVENT\n\
+            UID:block-1-2@time-tracker.hulks.ca\n\
+            DTSTART:20240321T090000Z\n\
+            DTEND:20240321T100000Z\n\
+            END:VEVENT\n";
+        let mut report = IcsImportReport::default();
+        let occurrences =
+            collect_occurrences(parse_events(ics, tz), 0, berlin(2025, 1, 1, 0), &mut report);
+
+        let starts: Vec<i64> = occurrences.iter().map(|o| o.start).collect();
+        assert_eq!(
+            starts,
+            vec![
+                berlin(2024, 3, 21, 9),
+                berlin(2024, 3, 28, 9),
+                berlin(2024, 4, 2, 11),
+                berlin(2024, 4, 4, 9),
+            ]
+        );
+        assert!(occurrences.iter().all(|o| o.end - o.start == 1_800));
+        assert_eq!(occurrences[2].title, "Sync (moved)");
+        assert_eq!(
+            occurrences[2].key,
+            format!("weekly@example.com/{}", berlin(2024, 4, 2, 9))
+        );
+        assert_eq!(report.skipped, 1);
+    }
+
+    #[test]
+    fn monthly_rules_pick_weekdays_and_skip_missing_days() {
+        let start = EventTime {
+            local: NaiveDate::from_ymd_opt(2024, 1, 31)
+                .unwrap()
+                .and_hms_opt(10, 0, 0)
+                .unwrap(),
+            tz: Tz::UTC,
+            all_day: false,
+        };
+        let dates = |rule: &str| -> Vec<String> {
+            expand(&parse_rrule(rule, Tz::UTC).unwrap(), start, i64::MAX)
+                .iter()
+                .map(|local| local.format("%m-%d").to_string())
+                .collect()
+        };
+        assert_eq!(
+            dates("FREQ=MONTHLY;COUNT=3"),
+            vec!["01-31", "03-31", "05-31"]
+        );
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYDAY=-1FR;UNTIL=20240430T235959Z"),
+            vec!["02-23", "03-29", "04-26"]
+        );
+        assert!(parse_rrule("FREQ=MONTHLY;BYSETPOS=-1", Tz::UTC).is_err());
+        assert!(parse_rrule("FREQ=WEEKLY;BYDAY=AéB", Tz::UTC).is_err());
+        assert!(parse_rrule("FREQ=WEEKLY;BYDAY=é", Tz::UTC).is_err());
+        assert!(parse_rrule("FREQ=WEEKLY;BYDAY=x1MO", Tz::UTC).is_err());
+    }
+
+    #[test]
+    fn huge_intervals_stop_at_the_last_representable_date() {
+        let start = EventTime {
+            local: NaiveDate::from_ymd_opt(2024, 1, 31)
+                .unwrap()
+                .and_hms_opt(10, 0, 0)
+                .unwrap(),
+            tz: Tz::UTC,
+            all_day: false,
+        };
+        for frequency in ["DAILY", "WEEKLY", "MONTHLY", "YEARLY"] {
+            let rule = parse_rrule(&format!("FREQ={frequency};INTERVAL=4000000000"), Tz::UTC);
+            assert_eq!(
+                expand(&rule.unwrap(), start, i64::MAX),
+                vec![start.local],
+                "{frequency}"
+            );
+        }
+    }
+}
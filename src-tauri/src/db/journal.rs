@@ -0,0 +1,364 @@
+use crate::db::queries::time_zone::{
+    at_local_hour, calendar_date, load_calendar_settings, load_time_zone, local_datetime,
+};
+use crate::db::queries::week::{get_range, TimeBlock};
+use crate::db::tables::app_metadata_kv::{
+    metadata_get, metadata_set, META_JOURNAL_EXPORT, META_JOURNAL_LAST_EXPORTED,
+};
+use crate::db::tables::manual_time_block::{get_manual_time_blocks, ManualTimeBlock};
+use crate::db::{format_duration, get_pool, Error};
+use anyhow::Context;
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The exporter only ever rewrites the text between these markers.
+const SECTION_START: &str = "<!-- time-tracker:start -->";
+const SECTION_END: &str = "<!-- time-tracker:end -->";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+/// Days the scheduler catches up on after the app was closed for a while.
+const MAX_CATCH_UP_DAYS: i64 = 7;
+
+fn default_top_apps() -> usize {
+    10
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalConfig {
+    #[serde(default)]
+    pub enabled: bool, // Export each day automatically once it has ended
+    #[serde(default)]
+    pub folder: Option<String>, // Daily notes folder of the vault
+    #[serde(default = "default_top_apps")]
+    pub top_apps: usize,
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            folder: None,
+            top_apps: default_top_apps(),
+        }
+    }
+}
+
+fn escape_cell(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
+
+fn time_span(tz: Tz, start: i64, end: i64) -> String {
+    format!(
+        "{}–{}",
+        local_datetime(tz, start).format("%H:%M"),
+        local_datetime(tz, end).format("%H:%M")
+    )
+}
+
+/// Renders the day's section, markers included.
+fn render_section(
+    date: NaiveDate,
+    tz: Tz,
+    blocks: &[TimeBlock],
+    manual: &[ManualTimeBlock],
+    top_apps: usize,
+) -> String {
+    let tracked: i64 = blocks
+        .iter()
+        .map(|block| block.end_time - block.start_time)
+        .sum();
+    let manual_total: i64 = manual
+        .iter()
+        .map(|block| block.end_time - block.start_time)
+        .sum();
+    let mut out = format!("{SECTION_START}\n## Time tracked\n\n");
+    out.push_str(&format!(
+        "**Tracked:** {} · **Manual:** {}\n",
+        format_duration(tracked),
+        format_duration(manual_total)
+    ));
+
+    if blocks.is_empty() && manual.is_empty() {
+        out.push_str(&format!(
+            "\nNothing was tracked on {}.\n",
+            date.format(DATE_FORMAT)
+        ));
+    }
+
+    if !blocks.is_empty() {
+        out.push_str("\n### Time blocks\n\n| Time | Category | Apps |\n| --- | --- | --- |\n");
+        for block in blocks {
+            let mut apps: Vec<_> = block.apps.iter().collect();
+            apps.sort_by_key(|app| std::cmp::Reverse(app.total_duration));
+            let apps: Vec<String> = apps
+                .iter()
+                .take(3)
+                .map(|app| format!("{} ({})", app.app, format_duration(app.total_duration)))
+                .collect();
+            out.push_str(&format!(
+                "| {} | {} | {} |\n",
+                time_span(tz, block.start_time, block.end_time),
+                escape_cell(&block.category),
+                escape_cell(&apps.join(", "))
+            ));
+        }
+
+        let mut totals: HashMap<&str, i64> = HashMap::new();
+        for app in blocks.iter().flat_map(|block| &block.apps) {
+            *totals.entry(app.app.as_str()).or_default() += app.total_duration;
+        }
+        let mut totals: Vec<(&str, i64)> = totals.into_iter().collect();
+        totals.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        if top_apps > 0 && !totals.is_empty() {
+            out.push_str("\n### Top apps\n\n");
+            for (index, (app, seconds)) in totals.iter().take(top_apps).enumerate() {
+                out.push_str(&format!(
+                    "{}. {} — {}\n",
+                    index + 1,
+                    app,
+                    format_duration(*seconds)
+                ));
+            }
+        }
+    }
+
+    if !manual.is_empty() {
+        out.push_str("\n### Manual time\n\n");
+        for block in manual {
+            out.push_str(&format!(
+                "- {} **{}** ({})\n",
+                time_span(tz, block.start_time, block.end_time),
+                block.title.trim(),
+                format_duration(block.end_time - block.start_time)
+            ));
+            let notes = block.notes.as_deref().map(str::trim).unwrap_or_default();
+            for line in notes.lines().filter(|line| !line.trim().is_empty()) {
+                out.push_str(&format!("    - {}\n", line.trim()));
+            }
+        }
+    }
+    out.push_str(SECTION_END);
+    out
+}
+
+/// Puts `section` in place of the last marked section, or appends it when the note has none.
+/// Everything outside the markers is kept as written.
+fn replace_section(note: &str, section: &str) -> String {
+    if let Some(start) = note.rfind(SECTION_START) {
+        if let Some(end) = note[start..].find(SECTION_END) {
+            let end = start + end + SECTION_END.len();
+            return format!("{}{section}{}", &note[..start], &note[end..]);
+        }
+    }
+    let note = note.trim_end();
+    if note.is_empty() {
+        format!("{section}\n")
+    } else {
+        format!("{note}\n\n{section}\n")
+    }
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, Error> {
+    Ok(NaiveDate::parse_from_str(date.trim(), DATE_FORMAT)
+        .with_context(|| format!("'{date}' is not a YYYY-MM-DD date"))?)
+}
+
+async fn load_config() -> Result<JournalConfig, Error> {
+    let pool = get_pool().await?;
+    let config = metadata_get(&pool, META_JOURNAL_EXPORT).await?;
+    Ok(config
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+/// Writes one calendar day's section into `<folder>/YYYY-MM-DD.md`.
+async fn write_journal(config: &JournalConfig, date: NaiveDate) -> Result<PathBuf, Error> {
+    let Some(folder) = config
+        .folder
+        .as_deref()
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+    else {
+        return Err(anyhow::anyhow!("Choose a daily notes folder first").into());
+    };
+    let tz = load_time_zone().await?;
+    let start_hour = load_calendar_settings().await?.start_hour;
+    let range_start = at_local_hour(tz, date, start_hour);
+    let range_end = at_local_hour(tz, date.succ_opt().unwrap_or(date), start_hour);
+    let blocks = get_range(range_start, range_end, None).await?;
+    let mut manual = get_manual_time_blocks(range_start, range_end).await?;
+    manual.sort_by_key(|block| block.start_time);
+    let section = render_section(date, tz, &blocks, &manual, config.top_apps);
+
+    let folder = Path::new(folder);
+    std::fs::create_dir_all(folder)
+        .with_context(|| format!("Failed to create {}", folder.display()))?;
+    let path = folder.join(format!("{}.md", date.format(DATE_FORMAT)));
+    let note = match std::fs::read_to_string(&path) {
+        Ok(note) => note,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(anyhow::Error::new(e)
+                .context(format!("Failed to read {}", path.display()))
+                .into())
+        }
+    };
+    std::fs::write(&path, replace_section(&note, &section))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Exports every day that ended since the last automatic export, at most a week back, when the
+/// journal export is enabled. Returns the notes written.
+pub async fn export_due_journals(now: i64) -> Result<Vec<PathBuf>, Error> {
+    let config = load_config().await?;
+    if !config.enabled || config.folder.is_none() {
+        return Ok(Vec::new());
+    }
+    let tz = load_time_zone().await?;
+    let start_hour = load_calendar_settings().await?.start_hour;
+    let Some(last_ended) = calendar_date(tz, now, start_hour).pred_opt() else {
+        return Ok(Vec::new());
+    };
+    let pool = get_pool().await?;
+    let last_exported = metadata_get(&pool, META_JOURNAL_LAST_EXPORTED)
+        .await?
+        .and_then(|date| parse_date(&date).ok());
+    let mut date = match last_exported {
+        Some(last) => last
+            .succ_opt()
+            .unwrap_or(last)
+            .max(last_ended - chrono::Duration::days(MAX_CATCH_UP_DAYS - 1)),
+        None => last_ended,
+    };
+
+    let mut written = Vec::new();
+    while date <= last_ended {
+        written.push(write_journal(&config, date).await?);
+        metadata_set(
+            &pool,
+            META_JOURNAL_LAST_EXPORTED,
+            &date.format(DATE_FORMAT).to_string(),
+        )
+        .await?;
+        date = match date.succ_opt() {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    Ok(written)
+}
+
+#[tauri::command]
+pub async fn get_journal_config() -> Result<JournalConfig, Error> {
+    load_config().await
+}
+
+#[tauri::command]
+pub async fn set_journal_config(config: JournalConfig) -> Result<(), Error> {
+    let pool = get_pool().await?;
+    let json = serde_json::to_string(&config).map_err(anyhow::Error::new)?;
+    metadata_set(&pool, META_JOURNAL_EXPORT, &json).await?;
+    Ok(())
+}
+
+/// Writes or refreshes the section for `date` (YYYY-MM-DD) right away, even for today, and
+/// returns the note's path.
+#[tauri::command]
+pub async fn export_journal(date: String) -> Result<String, Error> {
+    let config = load_config().await?;
+    let path = write_journal(&config, parse_date(&date)?).await?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::queries::week::TimeBlockLogs;
+    use crate::db::tables::manual_time_block::Tags;
+
+    fn app(name: &str, total_duration: i64) -> TimeBlockLogs {
+        TimeBlockLogs {
+            app: name.to_string(),
+            app_names: vec![name.to_string()],
+            total_duration,
+        }
+    }
+
+    #[test]
+    fn renders_blocks_apps_and_manual_notes() {
+        let tz = chrono_tz::UTC;
+        let date = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let base = at_local_hour(tz, date, 9);
+        let blocks = vec![
+            TimeBlock {
+                id: 0,
+                category: "Coding".into(),
+                apps: vec![app("Editor", 3_000), app("Terminal | zsh", 600)],
+                start_time: base,
+                end_time: base + 3_600,
+            },
+            TimeBlock {
+                id: 1,
+                category: "Browsing".into(),
+                apps: vec![app("Browser", 1_200), app("Editor", 600)],
+                start_time: base + 3_600,
+                end_time: base + 5_400,
+            },
+        ];
+        let manual = vec![ManualTimeBlock {
+            id: 1,
+            title: "Standup".into(),
+            notes: Some("Planning\n\nReview PRs".into()),
+            start_time: base + 7_200,
+            end_time: base + 8_100,
+            created_at: 0,
+            updated_at: 0,
+            project_id: None,
+            category_id: None,
+            tags: Tags::default(),
+        }];
+
+        let section = render_section(date, tz, &blocks, &manual, 2);
+        assert_eq!(
+            section,
+            "<!-- time-tracker:start -->\n## Time tracked\n\n\
+             **Tracked:** 1h 30m · **Manual:** 15m\n\n\
+             ### Time blocks\n\n| Time | Category | Apps |\n| --- | --- | --- |\n\
+             | 09:00–10:00 | Coding | Editor (50m), Terminal \\| zsh (10m) |\n\
+             | 10:00–10:30 | Browsing | Browser (20m), Editor (10m) |\n\n\
+             ### Top apps\n\n1. Editor — 1h 00m\n2. Browser — 20m\n\n\
+             ### Manual time\n\n- 11:00–11:15 **Standup** (15m)\n    - Planning\n    - Review PRs\n\
+             <!-- time-tracker:end -->"
+        );
+        assert!(
+            render_section(date, tz, &[], &[], 5).contains("Nothing was tracked on 2024-03-04.")
+        );
+    }
+
+    #[test]
+    fn only_the_marked_section_is_replaced() {
+        let section = format!("{SECTION_START}\nnew\n{SECTION_END}");
+        assert_eq!(replace_section("", &section), format!("{section}\n"));
+        assert_eq!(
+            replace_section("# Monday\n\nThoughts\n\n", &section),
+            format!("# Monday\n\nThoughts\n\n{section}\n")
+        );
+        let note = format!("# Monday\n{SECTION_START}\nold\n{SECTION_END}\n\nEvening notes\n");
+        assert_eq!(
+            replace_section(&note, &section),
+            format!("# Monday\n{section}\n\nEvening notes\n")
+        );
+        // An unterminated marker is left alone and a fresh section is appended.
+        let broken = format!("{SECTION_START}\nhand edits");
+        assert_eq!(
+            replace_section(&broken, &section),
+            format!("{broken}\n\n{section}\n")
+        );
+    }
+}
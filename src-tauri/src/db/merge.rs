@@ -0,0 +1,932 @@
+use crate::db::pool::get_db_path;
+use crate::db::tables::device::{insert_device, Device};
+use crate::db::tables::log::PENDING_LOCAL_DEVICE_UUID;
+use crate::db::validation::missing_column_default;
+use crate::db::{backup, get_pool, Error};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection, Row, SqliteConnection};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// The other database is attached to the connection under this schema name.
+const SOURCE: &str = "source";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DatabaseMergeOptions {
+    pub device_uuid: Option<String>, // Import every log under this device instead of its own
+    pub device_name: Option<String>, // Name for a device the merge has to create
+    /// Roll the merge back after counting it, leaving the attached file and this database as
+    /// they were.
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MergedDevice {
+    pub source_uuid: String,
+    pub device_uuid: String,
+    pub device_name: String,
+    pub created: bool,
+    pub logs_added: u64,
+    pub logs_updated: u64, // Same log with a longer duration in the other database
+    pub logs_duplicate: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MergeConflict {
+    pub kind: String, // "category" or "app_group"
+    pub name: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DatabaseMergeReport {
+    pub devices: Vec<MergedDevice>,
+    pub categories_added: u64,
+    pub categories_matched: u64,
+    pub regexes_added: u64,
+    pub app_groups_added: u64,
+    pub projects_added: u64,
+    pub clients_added: u64,
+    pub manual_blocks_added: u64,
+    pub manual_blocks_duplicate: u64,
+    pub conflicts: Vec<MergeConflict>, // This database's version was kept
+    pub dry_run: bool,
+    pub backup_created: Option<String>,
+}
+
+impl DatabaseMergeReport {
+    fn conflict(&mut self, kind: &str, name: &str, detail: String) {
+        self.conflicts.push(MergeConflict {
+            kind: kind.to_string(),
+            name: name.to_string(),
+            detail,
+        });
+    }
+}
+
+async fn source_table_exists(conn: &mut SqliteConnection, table: &str) -> Result<bool, Error> {
+    let count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM source.sqlite_master WHERE type = 'table' AND name = ?1",
+    )
+    .bind(table)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(count > 0)
+}
+
+async fn schema_version(conn: &mut SqliteConnection, schema: &str) -> Result<i64, Error> {
+    Ok(sqlx::query_scalar(sqlx::AssertSqlSafe(format!(
+        "SELECT COALESCE(MAX(version), 0) FROM {schema}._sqlx_migrations WHERE success = 1"
+    )))
+    .fetch_one(&mut *conn)
+    .await?)
+}
+
+/// The other database's `columns` of `table`, for a SELECT. Columns
+/// added since that database was last opened read as the default this version gives them.
+async fn source_columns(
+    conn: &mut SqliteConnection,
+    table: &str,
+    columns: &[&str],
+) -> Result<String, Error> {
+    let present: HashSet<String> = sqlx::query(sqlx::AssertSqlSafe(format!(
+        "PRAGMA {SOURCE}.table_info({table})"
+    )))
+    .fetch_all(&mut *conn)
+    .await?
+    .iter()
+    .map(|row| row.try_get("name"))
+    .collect::<Result<_, _>>()?;
+    let mut selected = Vec::with_capacity(columns.len());
+    for column in columns {
+        if present.contains(*column) {
+            selected.push(column.to_string());
+            continue;
+        }
+        let Some(default) = missing_column_default(table, column) else {
+            return Err(anyhow::anyhow!(
+                "The other database has no {table}.{column}, open it once with this version of the app first"
+            )
+            .into());
+        };
+        selected.push(format!("{default} AS {column}"));
+    }
+    Ok(selected.join(", "))
+}
+
+/// Both databases have to be on the same migration; the app upgrades a database when it opens it.
+/// Columns and tables added outside migrations are handled by each merge step.
+async fn check_source(conn: &mut SqliteConnection) -> Result<(), Error> {
+    if !source_table_exists(conn, "_sqlx_migrations").await?
+        || !source_table_exists(conn, "logs").await?
+    {
+        return Err(anyhow::anyhow!("The file is not a Time Tracker database").into());
+    }
+    let source = schema_version(conn, SOURCE).await?;
+    let target = schema_version(conn, "main").await?;
+    if source > target {
+        return Err(anyhow::anyhow!(
+            "The other database uses a newer schema ({source} > {target}), update this app first"
+        )
+        .into());
+    }
+    if source < target {
+        return Err(anyhow::anyhow!(
+            "The other database uses an older schema ({source} < {target}), open it once with this version of the app first"
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Picks the device each of the other database's log owners is merged into, creating devices
+/// this database does not know yet. Logs that were never registered for sync get a new device.
+async fn map_devices(
+    conn: &mut SqliteConnection,
+    options: &DatabaseMergeOptions,
+    fallback_name: &str,
+) -> Result<Vec<MergedDevice>, Error> {
+    let source_uuids: Vec<String> =
+        sqlx::query_scalar("SELECT DISTINCT device_uuid FROM source.logs ORDER BY device_uuid")
+            .fetch_all(&mut *conn)
+            .await?;
+    let source_names: HashMap<String, String> =
+        sqlx::query_as::<_, (String, String)>("SELECT uuid, name FROM source.devices")
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .collect();
+    let pending_uuid = uuid::Uuid::new_v4().to_string();
+
+    let mut devices = Vec::new();
+    for source_uuid in source_uuids {
+        let device_uuid = match &options.device_uuid {
+            Some(uuid) => uuid.clone(),
+            None if source_uuid == PENDING_LOCAL_DEVICE_UUID => pending_uuid.clone(),
+            None => source_uuid.clone(),
+        };
+        let existing: Option<String> =
+            sqlx::query_scalar("SELECT name FROM devices WHERE uuid = ?1")
+                .bind(&device_uuid)
+                .fetch_optional(&mut *conn)
+                .await?;
+        let created = existing.is_none() && device_uuid != PENDING_LOCAL_DEVICE_UUID;
+        let device_name = existing
+            .or_else(|| options.device_name.clone())
+            .or_else(|| source_names.get(&source_uuid).cloned())
+            .unwrap_or_else(|| fallback_name.to_string());
+        if created {
+            insert_device(
+                &mut *conn,
+                &Device::new(device_uuid.clone(), device_name.clone()),
+            )
+            .await?;
+        }
+        devices.push(MergedDevice {
+            source_uuid,
+            device_uuid,
+            device_name,
+            created,
+            ..Default::default()
+        });
+    }
+    Ok(devices)
+}
+
+/// Copies one device's logs, skipping logs already present at the same time for the same app.
+/// Ids are kept when free so synced devices stay aligned with the server.
+async fn merge_logs(conn: &mut SqliteConnection, device: &mut MergedDevice) -> Result<(), Error> {
+    let existing: Vec<(i64, i64, String, i64)> =
+        sqlx::query_as("SELECT id, timestamp, app, duration FROM logs WHERE device_uuid = ?1")
+            .bind(&device.device_uuid)
+            .fetch_all(&mut *conn)
+            .await?;
+    let mut used_ids: HashSet<i64> = existing.iter().map(|(id, ..)| *id).collect();
+    let mut next_id = used_ids.iter().max().copied().unwrap_or(0) + 1;
+    let mut existing: HashMap<(i64, String), (i64, i64)> = existing
+        .into_iter()
+        .map(|(id, timestamp, app, duration)| ((timestamp, app), (id, duration)))
+        .collect();
+
+    let logs: Vec<(i64, i64, String, i64, bool)> = sqlx::query_as(
+        "SELECT id, timestamp, app, duration, is_deleted FROM source.logs
+         WHERE device_uuid = ?1 ORDER BY id",
+    )
+    .bind(&device.source_uuid)
+    .fetch_all(&mut *conn)
+    .await?;
+    for (id, timestamp, app, duration, is_deleted) in logs {
+        match existing.get_mut(&(timestamp, app.clone())) {
+            Some((target_id, known)) if *known < duration => {
+                sqlx::query("UPDATE logs SET duration = ?1 WHERE id = ?2 AND device_uuid = ?3")
+                    .bind(duration)
+                    .bind(*target_id)
+                    .bind(&device.device_uuid)
+                    .execute(&mut *conn)
+                    .await?;
+                *known = duration;
+                device.logs_updated += 1;
+            }
+            Some(_) => device.logs_duplicate += 1,
+            None => {
+                let id = if used_ids.insert(id) {
+                    id
+                } else {
+                    while !used_ids.insert(next_id) {
+                        next_id += 1;
+                    }
+                    next_id
+                };
+                next_id = next_id.max(id + 1);
+                sqlx::query(
+                    "INSERT INTO logs (id, device_uuid, app, timestamp, duration, is_deleted)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                )
+                .bind(id)
+                .bind(&device.device_uuid)
+                .bind(&app)
+                .bind(timestamp)
+                .bind(duration)
+                .bind(is_deleted)
+                .execute(&mut *conn)
+                .await?;
+                existing.insert((timestamp, app), (id, duration));
+                device.logs_added += 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+type CategoryRow = (
+    i64,
+    String,
+    Option<i64>,
+    Option<String>,
+    bool,
+    bool,
+    bool,
+    bool,
+    i64,
+);
+
+/// Matches categories by name, case-insensitively, and returns the other database's category
+/// ids mapped to this one's.
+async fn merge_categories(
+    conn: &mut SqliteConnection,
+    report: &mut DatabaseMergeReport,
+) -> Result<HashMap<i64, i64>, Error> {
+    const COLUMNS: [&str; 9] = [
+        "id",
+        "name",
+        "priority",
+        "color",
+        "regex_enabled",
+        "is_visible",
+        "in_stats",
+        "is_collapsed",
+        "productivity_weight",
+    ];
+    let current: Vec<CategoryRow> = sqlx::query_as(sqlx::AssertSqlSafe(format!(
+        "SELECT {} FROM category",
+        COLUMNS.join(", ")
+    )))
+    .fetch_all(&mut *conn)
+    .await?;
+    let current: HashMap<String, CategoryRow> = current
+        .into_iter()
+        .map(|row| (row.1.to_lowercase(), row))
+        .collect();
+    let selected = source_columns(conn, "category", &COLUMNS).await?;
+    let incoming: Vec<CategoryRow> = sqlx::query_as(sqlx::AssertSqlSafe(format!(
+        "SELECT {selected} FROM source.category ORDER BY id"
+    )))
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut ids = HashMap::new();
+    for row in incoming {
+        if let Some(known) = current.get(&row.1.to_lowercase()) {
+            if known.3 != row.3 || known.2 != row.2 {
+                report.conflict(
+                    "category",
+                    &known.1,
+                    format!(
+                        "Kept colour {} and priority {}, the other database has {} and {}",
+                        known.3.as_deref().unwrap_or("none"),
+                        known.2.map_or("none".to_string(), |p| p.to_string()),
+                        row.3.as_deref().unwrap_or("none"),
+                        row.2.map_or("none".to_string(), |p| p.to_string()),
+                    ),
+                );
+            }
+            ids.insert(row.0, known.0);
+            report.categories_matched += 1;
+            continue;
+        }
+        let id = sqlx::query(
+            "INSERT INTO category (name, priority, color, regex_enabled, is_visible, in_stats,
+                                   is_collapsed, productivity_weight)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )
+        .bind(&row.1)
+        .bind(row.2)
+        .bind(&row.3)
+        .bind(row.4)
+        .bind(row.5)
+        .bind(row.6)
+        .bind(row.7)
+        .bind(row.8)
+        .execute(&mut *conn)
+        .await?
+        .last_insert_rowid();
+        ids.insert(row.0, id);
+        report.categories_added += 1;
+    }
+    Ok(ids)
+}
+
+async fn merge_regexes(
+    conn: &mut SqliteConnection,
+    categories: &HashMap<i64, i64>,
+    report: &mut DatabaseMergeReport,
+) -> Result<(), Error> {
+    type RegexRow = (i64, String, Option<i64>, Option<i64>);
+    let current: HashSet<RegexRow> = sqlx::query_as::<_, RegexRow>(
+        "SELECT cat_id, regex, effective_from, effective_until FROM category_regex",
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .collect();
+    let selected = source_columns(
+        conn,
+        "category_regex",
+        &["cat_id", "regex", "effective_from", "effective_until"],
+    )
+    .await?;
+    let incoming: Vec<RegexRow> = sqlx::query_as(sqlx::AssertSqlSafe(format!(
+        "SELECT {selected} FROM source.category_regex ORDER BY id"
+    )))
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for (cat_id, regex, from, until) in incoming {
+        // Regexes of categories that no longer exist in the other database are dropped.
+        let Some(cat_id) = categories.get(&cat_id).copied() else {
+            continue;
+        };
+        if current.contains(&(cat_id, regex.clone(), from, until)) {
+            continue;
+        }
+        sqlx::query(
+            "INSERT INTO category_regex (cat_id, regex, effective_from, effective_until)
+             VALUES (?1, ?2, ?3, ?4)",
+        )
+        .bind(cat_id)
+        .bind(&regex)
+        .bind(from)
+        .bind(until)
+        .execute(&mut *conn)
+        .await?;
+        report.regexes_added += 1;
+    }
+    Ok(())
+}
+
+async fn merge_app_groups(
+    conn: &mut SqliteConnection,
+    report: &mut DatabaseMergeReport,
+) -> Result<(), Error> {
+    let current: Vec<(String, String)> = sqlx::query_as("SELECT name, regex FROM app_groups")
+        .fetch_all(&mut *conn)
+        .await?;
+    let by_name: HashMap<String, (String, String)> = current
+        .iter()
+        .map(|(name, regex)| (name.to_lowercase(), (name.clone(), regex.clone())))
+        .collect();
+    let by_regex: HashMap<&str, &str> = current
+        .iter()
+        .map(|(name, regex)| (regex.as_str(), name.as_str()))
+        .collect();
+    let incoming: Vec<(String, String)> =
+        sqlx::query_as("SELECT name, regex FROM source.app_groups ORDER BY id")
+            .fetch_all(&mut *conn)
+            .await?;
+
+    let mut inserts = Vec::new();
+    for (name, regex) in incoming {
+        match (
+            by_name.get(&name.to_lowercase()),
+            by_regex.get(regex.as_str()),
+        ) {
+            (Some((_, known)), _) if *known == regex => {}
+            (Some((known_name, known)), _) => report.conflict(
+                "app_group",
+                known_name,
+                format!("Kept pattern {known}, the other database uses {regex}"),
+            ),
+            (None, Some(owner)) => report.conflict(
+                "app_group",
+                &name,
+                format!("Pattern {regex} already belongs to group {owner}"),
+            ),
+            (None, None) => inserts.push((name, regex)),
+        }
+    }
+    for (name, regex) in inserts {
+        let inserted =
+            sqlx::query("INSERT OR IGNORE INTO app_groups (name, regex) VALUES (?1, ?2)")
+                .bind(&name)
+                .bind(&regex)
+                .execute(&mut *conn)
+                .await?
+                .rows_affected();
+        report.app_groups_added += inserted;
+    }
+    Ok(())
+}
+
+/// A project with its client's name and rate.
+type ProjectRow = (
+    i64,
+    String,
+    bool,
+    Option<f64>,
+    Option<String>,
+    Option<String>,
+    Option<f64>,
+);
+
+/// Maps the other database's projects to this one's by name, creating missing projects and
+/// their clients so manual blocks keep their project.
+async fn merge_projects(
+    conn: &mut SqliteConnection,
+    report: &mut DatabaseMergeReport,
+) -> Result<HashMap<i64, i64>, Error> {
+    // Databases from before projects existed have none to merge.
+    if !source_table_exists(conn, "projects").await? {
+        return Ok(HashMap::new());
+    }
+    let names = |rows: Vec<(i64, String)>| -> HashMap<String, i64> {
+        rows.into_iter()
+            .map(|(id, name)| (name.to_lowercase(), id))
+            .collect()
+    };
+    let mut projects = names(
+        sqlx::query_as("SELECT id, name FROM projects")
+            .fetch_all(&mut *conn)
+            .await?,
+    );
+    let mut clients = names(
+        sqlx::query_as("SELECT id, name FROM clients")
+            .fetch_all(&mut *conn)
+            .await?,
+    );
+    let incoming: Vec<ProjectRow> = sqlx::query_as(
+        "SELECT p.id, p.name, p.billable, p.hourly_rate, p.regex, c.name, c.hourly_rate
+             FROM source.projects p LEFT JOIN source.clients c ON c.id = p.client_id
+             ORDER BY p.id",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut ids = HashMap::new();
+    for (source_id, name, billable, rate, regex, client, client_rate) in incoming {
+        if let Some(id) = projects.get(&name.to_lowercase()) {
+            ids.insert(source_id, *id);
+            continue;
+        }
+        let client_id = match client {
+            Some(client) => match clients.get(&client.to_lowercase()) {
+                Some(id) => Some(*id),
+                None => {
+                    let id = sqlx::query("INSERT INTO clients (name, hourly_rate) VALUES (?1, ?2)")
+                        .bind(&client)
+                        .bind(client_rate)
+                        .execute(&mut *conn)
+                        .await?
+                        .last_insert_rowid();
+                    clients.insert(client.to_lowercase(), id);
+                    report.clients_added += 1;
+                    Some(id)
+                }
+            },
+            None => None,
+        };
+        let id = sqlx::query(
+            "INSERT INTO projects (name, client_id, billable, hourly_rate, regex)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .bind(&name)
+        .bind(client_id)
+        .bind(billable)
+        .bind(rate)
+        .bind(&regex)
+        .execute(&mut *conn)
+        .await?
+        .last_insert_rowid();
+        projects.insert(name.to_lowercase(), id);
+        ids.insert(source_id, id);
+        report.projects_added += 1;
+    }
+    Ok(ids)
+}
+
+/// Blocks are the same when they share an external UID, or the title and times of blocks
+/// without one.
+async fn merge_manual_blocks(
+    conn: &mut SqliteConnection,
+    categories: &HashMap<i64, i64>,
+    projects: &HashMap<i64, i64>,
+    report: &mut DatabaseMergeReport,
+) -> Result<(), Error> {
+    type BlockRow = (
+        String,
+        Option<String>,
+        i64,
+        i64,
+        i64,
+        i64,
+        Option<i64>,
+        Option<String>,
+        Option<i64>,
+        String,
+    );
+    const COLUMNS: [&str; 10] = [
+        "title",
+        "notes",
+        "start_time",
+        "end_time",
+        "created_at",
+        "updated_at",
+        "project_id",
+        "external_uid",
+        "category_id",
+        "tags",
+    ];
+    let key = |block: &BlockRow| match &block.7 {
+        Some(uid) => format!("uid:{uid}"),
+        None => format!("{}-{}:{}", block.2, block.3, block.0),
+    };
+    let current: Vec<BlockRow> = sqlx::query_as(sqlx::AssertSqlSafe(format!(
+        "SELECT {} FROM manual_time_blocks",
+        COLUMNS.join(", ")
+    )))
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut known: HashSet<String> = current.iter().map(key).collect();
+    let selected = source_columns(conn, "manual_time_blocks", &COLUMNS).await?;
+    let incoming: Vec<BlockRow> = sqlx::query_as(sqlx::AssertSqlSafe(format!(
+        "SELECT {selected} FROM source.manual_time_blocks ORDER BY id"
+    )))
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for block in incoming {
+        if !known.insert(key(&block)) {
+            report.manual_blocks_duplicate += 1;
+            continue;
+        }
+        let project_id = block.6.and_then(|id| projects.get(&id).copied());
+        let category_id = block.8.and_then(|id| categories.get(&id).copied());
+        sqlx::query(
+            "INSERT INTO manual_time_blocks
+                (title, notes, start_time, end_time, created_at, updated_at, project_id, external_uid,
+                 category_id, tags)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        )
+        .bind(&block.0)
+        .bind(&block.1)
+        .bind(block.2)
+        .bind(block.3)
+        .bind(block.4)
+        .bind(block.5)
+        .bind(project_id)
+        .bind(&block.7)
+        .bind(category_id)
+        .bind(&block.9)
+        .execute(&mut *conn)
+        .await?;
+        report.manual_blocks_added += 1;
+    }
+    Ok(())
+}
+
+/// Merges the attached database into this one inside `conn`'s transaction. Settings, goals and
+/// other preferences of this database are left as they are.
+async fn merge_attached(
+    conn: &mut SqliteConnection,
+    options: &DatabaseMergeOptions,
+    fallback_name: &str,
+) -> Result<DatabaseMergeReport, Error> {
+    let mut report = DatabaseMergeReport {
+        dry_run: options.dry_run,
+        ..Default::default()
+    };
+    let mut devices = map_devices(conn, options, fallback_name).await?;
+    for device in &mut devices {
+        merge_logs(conn, device).await?;
+    }
+    report.devices = devices;
+
+    let categories = merge_categories(conn, &mut report).await?;
+    merge_regexes(conn, &categories, &mut report).await?;
+    merge_app_groups(conn, &mut report).await?;
+    let projects = merge_projects(conn, &mut report).await?;
+    merge_manual_blocks(conn, &categories, &projects, &mut report).await?;
+    Ok(report)
+}
+
+async fn merge_from(
+    conn: &mut SqliteConnection,
+    path: &Path,
+    options: &DatabaseMergeOptions,
+) -> Result<DatabaseMergeReport, Error> {
+    let fallback_name = path
+        .file_stem()
+        .map(|stem| format!("{} (merged)", stem.to_string_lossy()))
+        .unwrap_or_else(|| "Merged device".to_string());
+    // ATTACH cannot run inside a transaction, so the source stays attached around it.
+    sqlx::query(sqlx::AssertSqlSafe(format!(
+        "ATTACH DATABASE ?1 AS {SOURCE}"
+    )))
+    .bind(path.to_string_lossy().to_string())
+    .execute(&mut *conn)
+    .await?;
+    let result = async {
+        check_source(conn).await?;
+        let mut tx = conn.begin().await?;
+        let report = merge_attached(&mut tx, options, &fallback_name).await?;
+        if options.dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+        Ok::<_, Error>(report)
+    }
+    .await;
+    sqlx::query(sqlx::AssertSqlSafe(format!("DETACH DATABASE {SOURCE}")))
+        .execute(&mut *conn)
+        .await?;
+    result
+}
+
+/// Merges another Time Tracker database file, such as one from a laptop that was never
+/// registered for sync, into this database after a safety backup.
+#[tauri::command]
+pub async fn merge_database(
+    path: String,
+    options: Option<DatabaseMergeOptions>,
+) -> Result<DatabaseMergeReport, Error> {
+    let options = options.unwrap_or_default();
+    let path = Path::new(path.trim());
+    let source = path
+        .canonicalize()
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    if get_db_path()
+        .canonicalize()
+        .is_ok_and(|current| current == source)
+    {
+        return Err(anyhow::anyhow!("Choose a database other than the one in use").into());
+    }
+    let backup_created = if options.dry_run {
+        None
+    } else {
+        let backup = backup::create_safety_backup("pre_database_merge")
+            .context("Failed to create safety backup")?;
+        Some(backup.to_string_lossy().to_string())
+    };
+
+    let pool = get_pool().await?;
+    let mut conn = pool.acquire().await?;
+    let mut report = merge_from(&mut conn, &source, &options).await?;
+    report.backup_created = backup_created;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::tables::{
+        app_group, cat_regex, category, client, device, log, manual_time_block, project,
+    };
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use sqlx::SqlitePool;
+
+    async fn database(path: &Path) -> SqlitePool {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await
+            .unwrap();
+        log::create_table(&pool).await.unwrap();
+        device::create_table(&pool).await.unwrap();
+        category::create_table(&pool).await.unwrap();
+        cat_regex::create_table(&pool).await.unwrap();
+        app_group::create_table(&pool).await.unwrap();
+        client::create_table(&pool).await.unwrap();
+        project::create_table(&pool).await.unwrap();
+        manual_time_block::create_table(&pool).await.unwrap();
+        sqlx::query(
+            "CREATE TABLE _sqlx_migrations (version INTEGER PRIMARY KEY, success BOOLEAN NOT NULL);
+             INSERT INTO _sqlx_migrations VALUES (11, 1);",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool
+    }
+
+    async fn seed(pool: &SqlitePool, sql: &str) {
+        sqlx::raw_sql(sqlx::AssertSqlSafe(sql.to_string()))
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn merges_logs_and_reconciles_rules_by_name() {
+        // Databases attached to an in-memory database are in memory too, so both are files.
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("merge-source-{}.db", uuid::Uuid::new_v4()));
+        let target_path = dir.join(format!("merge-target-{}.db", uuid::Uuid::new_v4()));
+        let source = database(&path).await;
+        seed(
+            &source,
+            "DELETE FROM category;
+             INSERT INTO logs (id, device_uuid, app, timestamp, duration) VALUES
+                (1, '__pending_local__', 'Editor', 100, 30),
+                (2, '__pending_local__', 'Browser', 130, 10),
+                (1, 'phone', 'Chat', 100, 5);
+             INSERT INTO devices (uuid, name, kind) VALUES ('phone', 'Phone', 'remote');
+             INSERT INTO category (id, name, color) VALUES (7, 'coding', '#f00'), (8, 'Reading', '#0f0');
+             INSERT INTO category_regex (cat_id, regex) VALUES (7, 'Editor'), (8, 'Reader');
+             INSERT INTO app_groups (name, regex) VALUES ('Browsers', 'firefox'), ('Editors', 'vim');
+             INSERT INTO clients (id, name) VALUES (3, 'Acme');
+             INSERT INTO projects (id, name, client_id) VALUES (4, 'Website', 3);
+             INSERT INTO manual_time_blocks
+                (title, start_time, end_time, created_at, updated_at, project_id, category_id)
+             VALUES ('Meeting', 100, 200, 0, 0, 4, 7), ('Lunch', 300, 400, 0, 0, NULL, NULL);",
+        )
+        .await;
+        source.close().await;
+
+        let target = database(&target_path).await;
+        seed(
+            &target,
+            "DELETE FROM category;
+             INSERT INTO logs (id, device_uuid, app, timestamp, duration) VALUES
+                (1, 'phone', 'Chat', 100, 2), (2, 'phone', 'Mail', 200, 9);
+             INSERT INTO devices (uuid, name, kind) VALUES ('phone', 'My phone', 'remote');
+             INSERT INTO category (id, name, color) VALUES (1, 'Coding', '#00f');
+             INSERT INTO category_regex (cat_id, regex) VALUES (1, 'Editor');
+             INSERT INTO app_groups (name, regex) VALUES ('Browsers', 'chrome');
+             INSERT INTO manual_time_blocks (title, start_time, end_time, created_at, updated_at)
+             VALUES ('Lunch', 300, 400, 0, 0);",
+        )
+        .await;
+
+        let mut conn = target.acquire().await.unwrap();
+        let options = DatabaseMergeOptions {
+            device_name: Some("Old laptop".into()),
+            ..Default::default()
+        };
+        let report = merge_from(&mut conn, &path, &options).await.unwrap();
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_file(&target_path).ok();
+
+        let pending = &report.devices[0];
+        assert_eq!(pending.source_uuid, PENDING_LOCAL_DEVICE_UUID);
+        assert!(pending.created);
+        assert_eq!(pending.device_name, "Old laptop");
+        assert_eq!(pending.logs_added, 2);
+        let phone = &report.devices[1];
+        assert_eq!(
+            (
+                phone.device_name.as_str(),
+                phone.logs_updated,
+                phone.logs_added
+            ),
+            ("My phone", 1, 0)
+        );
+
+        assert_eq!((report.categories_matched, report.categories_added), (1, 1));
+        assert_eq!(report.regexes_added, 1);
+        assert_eq!(report.app_groups_added, 1);
+        assert_eq!(report.conflicts.len(), 2);
+        assert_eq!((report.projects_added, report.clients_added), (1, 1));
+        assert_eq!(
+            (report.manual_blocks_added, report.manual_blocks_duplicate),
+            (1, 1)
+        );
+
+        let logs: Vec<(String, i64, i64)> =
+            sqlx::query_as("SELECT device_uuid, id, duration FROM logs ORDER BY device_uuid, id")
+                .fetch_all(&mut *conn)
+                .await
+                .unwrap();
+        assert_eq!(
+            logs,
+            vec![
+                (pending.device_uuid.clone(), 1, 30),
+                (pending.device_uuid.clone(), 2, 10),
+                ("phone".into(), 1, 5),
+                ("phone".into(), 2, 9),
+            ]
+        );
+        let meeting: (String, String) = sqlx::query_as(
+            "SELECT p.name, c.name FROM manual_time_blocks m
+             JOIN projects p ON p.id = m.project_id
+             JOIN category c ON c.id = m.category_id
+             WHERE m.title = 'Meeting'",
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .unwrap();
+        assert_eq!(meeting, ("Website".into(), "Coding".into()));
+        let attached: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pragma_database_list")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(attached, 1);
+    }
+
+    #[tokio::test]
+    async fn merges_databases_not_yet_opened_by_this_version() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("merge-old-source-{}.db", uuid::Uuid::new_v4()));
+        let target_path = dir.join(format!("merge-old-target-{}.db", uuid::Uuid::new_v4()));
+        // The tables as they were before rule dates, projects, productivity and tags.
+        let source = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(
+                SqliteConnectOptions::new()
+                    .filename(&path)
+                    .create_if_missing(true),
+            )
+            .await
+            .unwrap();
+        log::create_table(&source).await.unwrap();
+        device::create_table(&source).await.unwrap();
+        app_group::create_table(&source).await.unwrap();
+        seed(
+            &source,
+            "CREATE TABLE _sqlx_migrations (version INTEGER PRIMARY KEY, success BOOLEAN NOT NULL);
+             INSERT INTO _sqlx_migrations VALUES (11, 1);
+             CREATE TABLE category (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL UNIQUE, priority INTEGER,
+                color TEXT, regex_enabled INTEGER NOT NULL DEFAULT 1,
+                is_visible INTEGER NOT NULL DEFAULT 1, in_stats INTEGER NOT NULL DEFAULT 1,
+                is_collapsed INTEGER NOT NULL DEFAULT 1);
+             CREATE TABLE category_regex (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, cat_id INTEGER NOT NULL, regex TEXT NOT NULL);
+             CREATE TABLE manual_time_blocks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, title TEXT NOT NULL, notes TEXT,
+                start_time INTEGER NOT NULL, end_time INTEGER NOT NULL,
+                created_at INTEGER NOT NULL, updated_at INTEGER NOT NULL);
+             INSERT INTO logs (id, device_uuid, app, timestamp, duration) VALUES
+                (1, 'desk', 'Editor', 100, 30);
+             INSERT INTO category (id, name, color) VALUES (5, 'Writing', '#0f0');
+             INSERT INTO category_regex (cat_id, regex) VALUES (5, 'Editor');
+             INSERT INTO manual_time_blocks (title, start_time, end_time, created_at, updated_at)
+             VALUES ('Standup', 100, 200, 0, 0);",
+        )
+        .await;
+        source.close().await;
+
+        let target = database(&target_path).await;
+        let mut conn = target.acquire().await.unwrap();
+        let report = merge_from(&mut conn, &path, &DatabaseMergeOptions::default())
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_file(&target_path).ok();
+
+        assert_eq!(report.devices[0].logs_added, 1);
+        assert_eq!(report.categories_added, 1);
+        assert_eq!(report.regexes_added, 1);
+        assert_eq!(report.projects_added, 0);
+        assert_eq!(report.manual_blocks_added, 1);
+        let rule: (String, i64, Option<i64>) = sqlx::query_as(
+            "SELECT c.name, c.productivity_weight, r.effective_from FROM category_regex r
+             JOIN category c ON c.id = r.cat_id WHERE r.regex = 'Editor'",
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .unwrap();
+        assert_eq!(rule, ("Writing".into(), 0, None));
+        let block: (Option<i64>, String) = sqlx::query_as(
+            "SELECT category_id, tags FROM manual_time_blocks WHERE title = 'Standup'",
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .unwrap();
+        assert_eq!(block, (None, String::new()));
+    }
+}
@@ -1,10 +1,20 @@
 #![cfg_attr(not(feature = "dev-warnings"), allow(dead_code, unused_imports))]
 
+pub mod activitywatch;
+pub mod archive;
 pub mod backup;
+pub mod charts;
+pub mod csv_import;
 pub mod error;
+pub mod export;
+pub mod ics;
+pub mod journal;
+pub mod merge;
 pub mod pool;
 pub mod queries;
+pub mod rule_pack;
 pub(crate) mod tables;
+pub mod timesheet;
 pub mod validation;
 
 use anyhow::Context;
@@ -22,6 +32,25 @@ pub use pool::{
 };
 pub use queries::get_week;
 
+/// A duration as "1h 05m", or "45m" under an hour, for reports and exports.
+pub fn format_duration(seconds: i64) -> String {
+    let minutes = seconds / 60;
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+/// Escapes text for HTML element content and double-quoted attributes.
+pub(crate) fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 #[derive(Serialize)]
 pub struct DbMigrationInfo {
     pub version: i64,
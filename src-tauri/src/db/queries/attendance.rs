@@ -0,0 +1,390 @@
+use crate::db::error::Error;
+use crate::db::export::csv_field;
+use crate::db::queries::statistics::labelled_logs;
+use crate::db::queries::time_zone::{
+    self, load_calendar_settings, load_time_zone, CalendarSettings,
+};
+use crate::db::tables::settings::get_settings;
+use anyhow::Context;
+use chrono::{Datelike, NaiveDate};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub const MAX_ATTENDANCE_DAYS: i64 = 366;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttendanceSettings {
+    pub daily_target: i64, // Seconds expected on each workday
+    pub break_gap: i64,    // Idle seconds that count as a break
+    pub workdays: i32,     // Bit 0 = Monday through bit 6 = Sunday
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AttendanceOptions {
+    pub device_uuids: Option<Vec<String>>,
+    pub daily_target: Option<i64>, // Overrides the dailyTargetMinutes setting, in seconds
+    pub break_gap: Option<i64>,    // Overrides the breakGapMinutes setting, in seconds
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AttendanceDay {
+    pub date: String, // YYYY-MM-DD
+    pub day_start: i64,
+    pub is_workday: bool,
+    pub first_activity: Option<i64>,
+    pub last_activity: Option<i64>, // End of the last log
+    pub worked: i64,                // Time from first to last activity minus breaks
+    pub break_time: i64,
+    pub breaks: i32,
+    pub target: i64,
+    pub overtime: i64, // worked - target; negative when short
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AttendanceWeek {
+    pub week_start: i64,
+    pub worked: i64,
+    pub target: i64,
+    pub overtime: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AttendanceReport {
+    pub daily_target: i64,
+    pub break_gap: i64,
+    pub days: Vec<AttendanceDay>,
+    pub weeks: Vec<AttendanceWeek>,
+    pub total_worked: i64,
+    pub total_overtime: i64,
+}
+
+async fn load_attendance_settings(
+    options: &AttendanceOptions,
+) -> Result<AttendanceSettings, Error> {
+    let settings = get_settings().await?;
+    let value = |key: &str, default: i32| {
+        settings
+            .iter()
+            .find(|setting| setting.key == key)
+            .map(|setting| setting.val)
+            .unwrap_or(default)
+    };
+    let daily_target = options
+        .daily_target
+        .unwrap_or(i64::from(value("dailyTargetMinutes", 480)) * 60);
+    let break_gap = options
+        .break_gap
+        .unwrap_or(i64::from(value("breakGapMinutes", 15)) * 60);
+    if daily_target < 0 {
+        return Err(anyhow::anyhow!("The daily target cannot be negative").into());
+    }
+    if break_gap <= 0 {
+        return Err(anyhow::anyhow!("The break threshold must be positive").into());
+    }
+    Ok(AttendanceSettings {
+        daily_target,
+        break_gap,
+        workdays: value("attendanceWorkdays", 0b001_1111),
+    })
+}
+
+/// Builds one day's row from its activity intervals, sorted by start.
+fn attendance_day(
+    date: NaiveDate,
+    day_start: i64,
+    intervals: &[(i64, i64)],
+    settings: AttendanceSettings,
+) -> AttendanceDay {
+    let is_workday = settings.workdays & (1 << date.weekday().num_days_from_monday()) != 0;
+    let target = if is_workday { settings.daily_target } else { 0 };
+
+    let mut first_activity = None;
+    let mut last_activity: Option<i64> = None;
+    let mut break_time = 0;
+    let mut breaks = 0;
+    for &(start, end) in intervals {
+        if let Some(last) = last_activity {
+            let gap = start - last;
+            if gap >= settings.break_gap {
+                break_time += gap;
+                breaks += 1;
+            }
+        }
+        first_activity.get_or_insert(start);
+        last_activity = Some(last_activity.map_or(end, |last| last.max(end)));
+    }
+    let worked = match (first_activity, last_activity) {
+        (Some(first), Some(last)) => last - first - break_time,
+        _ => 0,
+    };
+
+    AttendanceDay {
+        date: date.format("%Y-%m-%d").to_string(),
+        day_start,
+        is_workday,
+        first_activity,
+        last_activity,
+        worked,
+        break_time,
+        breaks,
+        target,
+        overtime: worked - target,
+    }
+}
+
+fn attendance_weeks(
+    tz: Tz,
+    days: &[AttendanceDay],
+    calendar: CalendarSettings,
+) -> Vec<AttendanceWeek> {
+    let mut weeks: BTreeMap<i64, AttendanceWeek> = BTreeMap::new();
+    for day in days {
+        let (week_start, _) = time_zone::week_bounds(
+            tz,
+            day.day_start,
+            calendar.start_hour,
+            calendar.week_start_day,
+        );
+        let week = weeks.entry(week_start).or_insert(AttendanceWeek {
+            week_start,
+            worked: 0,
+            target: 0,
+            overtime: 0,
+        });
+        week.worked += day.worked;
+        week.target += day.target;
+        week.overtime += day.overtime;
+    }
+    weeks.into_values().collect()
+}
+
+pub async fn build_attendance_report(
+    range_start: i64,
+    range_end: i64,
+    options: &AttendanceOptions,
+) -> Result<AttendanceReport, Error> {
+    if range_end < range_start {
+        return Err(anyhow::anyhow!("Range end must not be before range start").into());
+    }
+    let settings = load_attendance_settings(options).await?;
+    let tz = load_time_zone().await?;
+    let calendar = load_calendar_settings().await?;
+    let first_date = time_zone::calendar_date(tz, range_start, calendar.start_hour);
+    let last_date = time_zone::calendar_date(tz, range_end, calendar.start_hour);
+    if (last_date - first_date).num_days() >= MAX_ATTENDANCE_DAYS {
+        return Err(
+            anyhow::anyhow!("Attendance reports cover at most {MAX_ATTENDANCE_DAYS} days").into(),
+        );
+    }
+    let start = time_zone::at_local_hour(tz, first_date, calendar.start_hour);
+    let end = time_zone::at_local_hour(
+        tz,
+        last_date.succ_opt().unwrap_or(last_date),
+        calendar.start_hour,
+    ) - 1;
+
+    // Activity across devices is merged: overlapping logs count once.
+    let mut intervals: BTreeMap<NaiveDate, Vec<(i64, i64)>> = BTreeMap::new();
+    for log in labelled_logs(start, end, options.device_uuids.clone()).await? {
+        if log.duration <= 0 {
+            continue;
+        }
+        intervals
+            .entry(time_zone::calendar_date(
+                tz,
+                log.timestamp,
+                calendar.start_hour,
+            ))
+            .or_default()
+            .push((log.timestamp, log.timestamp + log.duration));
+    }
+
+    let days: Vec<AttendanceDay> = first_date
+        .iter_days()
+        .take_while(|date| *date <= last_date)
+        .map(|date| {
+            let mut day_intervals = intervals.remove(&date).unwrap_or_default();
+            day_intervals.sort_unstable();
+            let day_start = time_zone::at_local_hour(tz, date, calendar.start_hour);
+            attendance_day(date, day_start, &day_intervals, settings)
+        })
+        .collect();
+    let weeks = attendance_weeks(tz, &days, calendar);
+
+    Ok(AttendanceReport {
+        daily_target: settings.daily_target,
+        break_gap: settings.break_gap,
+        total_worked: days.iter().map(|day| day.worked).sum(),
+        total_overtime: days.iter().map(|day| day.overtime).sum(),
+        days,
+        weeks,
+    })
+}
+
+pub(crate) fn hours(seconds: i64) -> String {
+    format!("{:.2}", seconds as f64 / 3600.0)
+}
+
+fn local_time(tz: Tz, timestamp: Option<i64>) -> String {
+    timestamp
+        .map(|ts| {
+            time_zone::local_datetime(tz, ts)
+                .format("%H:%M")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+fn push_csv_row(out: &mut String, fields: &[String]) {
+    let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+    out.push_str(&fields.join(","));
+    out.push('\n');
+}
+
+/// One row per day followed by one row per week; durations are in hours.
+pub fn attendance_csv(tz: Tz, report: &AttendanceReport) -> String {
+    let mut out = String::from(
+        "type,date,start,end,worked_hours,break_hours,breaks,target_hours,overtime_hours\n",
+    );
+    for day in &report.days {
+        let fields = [
+            "day".to_string(),
+            day.date.clone(),
+            local_time(tz, day.first_activity),
+            local_time(tz, day.last_activity),
+            hours(day.worked),
+            hours(day.break_time),
+            day.breaks.to_string(),
+            hours(day.target),
+            hours(day.overtime),
+        ];
+        push_csv_row(&mut out, &fields);
+    }
+    for week in &report.weeks {
+        let fields = [
+            "week".to_string(),
+            time_zone::local_datetime(tz, week.week_start)
+                .format("%Y-%m-%d")
+                .to_string(),
+            String::new(),
+            String::new(),
+            hours(week.worked),
+            String::new(),
+            String::new(),
+            hours(week.target),
+            hours(week.overtime),
+        ];
+        push_csv_row(&mut out, &fields);
+    }
+    out
+}
+
+#[tauri::command]
+pub async fn get_attendance_report(
+    range_start: i64,
+    range_end: i64,
+    options: Option<AttendanceOptions>,
+) -> Result<AttendanceReport, Error> {
+    build_attendance_report(range_start, range_end, &options.unwrap_or_default()).await
+}
+
+/// Writes the report as CSV when the path ends in `.csv`, otherwise as JSON.
+#[tauri::command]
+pub async fn export_attendance_report(
+    path: String,
+    range_start: i64,
+    range_end: i64,
+    options: Option<AttendanceOptions>,
+) -> Result<(), Error> {
+    let path = Path::new(path.trim());
+    let report =
+        build_attendance_report(range_start, range_end, &options.unwrap_or_default()).await?;
+    let is_csv = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    let contents = if is_csv {
+        attendance_csv(load_time_zone().await?, &report)
+    } else {
+        serde_json::to_string_pretty(&report).map_err(anyhow::Error::new)?
+    };
+    std::fs::write(path, contents)
+        .with_context(|| format!("Failed to write attendance report to {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETTINGS: AttendanceSettings = AttendanceSettings {
+        daily_target: 8 * 3600,
+        break_gap: 15 * 60,
+        workdays: 0b001_1111,
+    };
+
+    #[test]
+    fn long_gaps_are_breaks_and_short_ones_are_work() {
+        // Monday 2024-05-06, 09:00 to 18:00 UTC with a one-hour lunch and a five-minute gap.
+        let date = NaiveDate::from_ymd_opt(2024, 5, 6).unwrap();
+        let nine = 1_714_986_000;
+        let intervals = [
+            (nine, nine + 3 * 3600),
+            (nine + 3 * 3600 + 300, nine + 4 * 3600),
+            (nine + 5 * 3600, nine + 9 * 3600),
+        ];
+        let day = attendance_day(date, nine - 9 * 3600, &intervals, SETTINGS);
+        assert!(day.is_workday);
+        assert_eq!(day.first_activity, Some(nine));
+        assert_eq!(day.last_activity, Some(nine + 9 * 3600));
+        assert_eq!(day.breaks, 1);
+        assert_eq!(day.break_time, 3600);
+        assert_eq!(day.worked, 8 * 3600);
+        assert_eq!(day.overtime, 0);
+    }
+
+    #[test]
+    fn weekend_work_is_all_overtime_and_weeks_add_up() {
+        let tz = Tz::UTC;
+        let saturday = NaiveDate::from_ymd_opt(2024, 5, 11).unwrap();
+        let friday = NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+        let start = |date: NaiveDate| time_zone::at_local_hour(tz, date, 0);
+
+        let weekend = attendance_day(
+            saturday,
+            start(saturday),
+            &[(start(saturday) + 36_000, start(saturday) + 39_600)],
+            SETTINGS,
+        );
+        assert!(!weekend.is_workday);
+        assert_eq!(weekend.overtime, 3600);
+
+        let absent = attendance_day(friday, start(friday), &[], SETTINGS);
+        assert_eq!(absent.overtime, -8 * 3600);
+
+        let calendar = CalendarSettings {
+            start_hour: 0,
+            week_start_day: 0,
+        };
+        let weeks = attendance_weeks(tz, &[absent.clone(), weekend.clone()], calendar);
+        assert_eq!(weeks.len(), 1);
+        assert_eq!(weeks[0].overtime, -7 * 3600);
+
+        let csv = attendance_csv(
+            tz,
+            &AttendanceReport {
+                daily_target: SETTINGS.daily_target,
+                break_gap: SETTINGS.break_gap,
+                total_worked: 3600,
+                total_overtime: -7 * 3600,
+                days: vec![absent, weekend],
+                weeks,
+            },
+        );
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[2], "day,2024-05-11,10:00,11:00,1.00,0.00,0,0.00,1.00");
+        assert_eq!(lines[3], "week,2024-05-06,,,1.00,,,8.00,-7.00");
+    }
+}
@@ -0,0 +1,300 @@
+use crate::db;
+use crate::db::error::Error;
+use crate::db::queries::statistics::labelled_logs_overlapping;
+use crate::db::tables::client::{fetch_clients, Client};
+use crate::db::tables::manual_time_block::{
+    covered_intervals, get_manual_time_blocks, uncovered_parts,
+};
+use crate::db::tables::project::{
+    fetch_project_assignments, fetch_projects, Project, ProjectResolver,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ProjectBilling {
+    pub project_id: i64,
+    pub project_name: String,
+    pub client_id: Option<i64>,
+    pub client_name: Option<String>,
+    pub billable: bool,
+    pub hourly_rate: Option<f64>, // Effective rate after falling back to the client's
+    pub tracked_seconds: i64,
+    pub manual_seconds: i64,
+    pub total_seconds: i64,
+    pub hours: f64,
+    pub amount: f64, // Zero for non-billable projects
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ClientBilling {
+    pub client_id: Option<i64>, // None groups projects without a client
+    pub client_name: Option<String>,
+    pub total_seconds: i64,
+    pub billable_seconds: i64,
+    pub hours: f64,
+    pub amount: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BillingReport {
+    pub range_start: i64,
+    pub range_end: i64,
+    pub projects: Vec<ProjectBilling>,
+    pub clients: Vec<ClientBilling>,
+    pub unassigned_seconds: i64,
+    pub total_seconds: i64,
+    pub billable_seconds: i64,
+    pub total_amount: f64,
+}
+
+fn hours(seconds: i64) -> f64 {
+    seconds as f64 / 3600.0
+}
+
+pub fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// Seconds per project, split into (tracked, manual).
+pub type ProjectSeconds = HashMap<i64, (i64, i64)>;
+
+pub fn build_billing_report(
+    range_start: i64,
+    range_end: i64,
+    projects: &[Project],
+    clients: &[Client],
+    seconds: &ProjectSeconds,
+    unassigned_seconds: i64,
+) -> BillingReport {
+    let clients_by_id: HashMap<i64, &Client> =
+        clients.iter().map(|client| (client.id, client)).collect();
+
+    let mut project_rows: Vec<ProjectBilling> = projects
+        .iter()
+        .filter_map(|project| {
+            let &(tracked_seconds, manual_seconds) = seconds.get(&project.id)?;
+            let client = project
+                .client_id
+                .and_then(|id| clients_by_id.get(&id).copied());
+            let hourly_rate = project
+                .hourly_rate
+                .or_else(|| client.and_then(|client| client.hourly_rate));
+            let total_seconds = tracked_seconds + manual_seconds;
+            let amount = if project.billable {
+                round_cents(hours(total_seconds) * hourly_rate.unwrap_or(0.0))
+            } else {
+                0.0
+            };
+            Some(ProjectBilling {
+                project_id: project.id,
+                project_name: project.name.clone(),
+                client_id: client.map(|client| client.id),
+                client_name: client.map(|client| client.name.clone()),
+                billable: project.billable,
+                hourly_rate,
+                tracked_seconds,
+                manual_seconds,
+                total_seconds,
+                hours: hours(total_seconds),
+                amount,
+            })
+        })
+        .collect();
+    project_rows.sort_by(|left, right| {
+        left.client_name
+            .is_none()
+            .cmp(&right.client_name.is_none())
+            .then_with(|| left.client_name.cmp(&right.client_name))
+            .then_with(|| right.total_seconds.cmp(&left.total_seconds))
+            .then_with(|| left.project_name.cmp(&right.project_name))
+    });
+
+    let mut client_rows: Vec<ClientBilling> = Vec::new();
+    for row in &project_rows {
+        let index = match client_rows
+            .iter()
+            .position(|client| client.client_id == row.client_id)
+        {
+            Some(index) => index,
+            None => {
+                client_rows.push(ClientBilling {
+                    client_id: row.client_id,
+                    client_name: row.client_name.clone(),
+                    total_seconds: 0,
+                    billable_seconds: 0,
+                    hours: 0.0,
+                    amount: 0.0,
+                });
+                client_rows.len() - 1
+            }
+        };
+        let client = &mut client_rows[index];
+        client.total_seconds += row.total_seconds;
+        if row.billable {
+            client.billable_seconds += row.total_seconds;
+        }
+        client.hours = hours(client.total_seconds);
+        client.amount = round_cents(client.amount + row.amount);
+    }
+
+    BillingReport {
+        range_start,
+        range_end,
+        total_seconds: project_rows.iter().map(|row| row.total_seconds).sum(),
+        billable_seconds: client_rows.iter().map(|row| row.billable_seconds).sum(),
+        total_amount: round_cents(project_rows.iter().map(|row| row.amount).sum()),
+        unassigned_seconds,
+        projects: project_rows,
+        clients: client_rows,
+    }
+}
+
+/// Parts of a tracked log to bill, as (start, duration) pairs: the log clipped to the range,
+/// minus any time a manual block already bills.
+pub fn billable_parts(
+    timestamp: i64,
+    duration: i64,
+    range_start: i64,
+    range_end: i64,
+    covered: &[(i64, i64)],
+) -> Vec<(i64, i64)> {
+    let start = timestamp.max(range_start);
+    let end = (timestamp + duration).min(range_end);
+    if end <= start {
+        return Vec::new();
+    }
+    uncovered_parts(start, end - start, covered)
+}
+
+/// Hours and amounts per project and client. Tracked time is assigned through manual
+/// assignments and project rules; manual time blocks count towards their own project and
+/// replace any tracked time they overlap. Both are clipped to the range.
+#[tauri::command]
+pub async fn get_billing_report(
+    range_start: i64,
+    range_end: i64,
+    device_uuids: Option<Vec<String>>,
+) -> Result<BillingReport, Error> {
+    if range_end <= range_start {
+        return Err(anyhow::anyhow!("Range end must be after range start").into());
+    }
+    let pool = db::get_pool().await?;
+    let projects = fetch_projects(&pool).await?;
+    let clients = fetch_clients(&pool).await?;
+    let assignments = fetch_project_assignments(&pool, range_start, range_end).await?;
+    let resolver = ProjectResolver::new(&projects, assignments)?;
+
+    let blocks = get_manual_time_blocks(range_start, range_end).await?;
+    let covered = covered_intervals(&blocks);
+
+    let mut seconds = ProjectSeconds::new();
+    let mut unassigned_seconds = 0;
+    for log in labelled_logs_overlapping(range_start, range_end, device_uuids).await? {
+        let parts = billable_parts(
+            log.timestamp,
+            log.duration,
+            range_start,
+            range_end,
+            &covered,
+        );
+        for (start, duration) in parts {
+            for (project_id, share) in resolver.attribute(&log.title, start, duration) {
+                match project_id {
+                    Some(id) => seconds.entry(id).or_insert((0, 0)).0 += share,
+                    None => unassigned_seconds += share,
+                }
+            }
+        }
+    }
+    for block in blocks {
+        let share = block.end_time.min(range_end) - block.start_time.max(range_start);
+        match block.project_id {
+            Some(id) => seconds.entry(id).or_insert((0, 0)).1 += share,
+            None => unassigned_seconds += share,
+        }
+    }
+
+    Ok(build_billing_report(
+        range_start,
+        range_end,
+        &projects,
+        &clients,
+        &seconds,
+        unassigned_seconds,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(id: i64, client_id: Option<i64>, billable: bool, rate: Option<f64>) -> Project {
+        Project {
+            id,
+            name: format!("Project {id}"),
+            client_id,
+            billable,
+            hourly_rate: rate,
+            regex: None,
+        }
+    }
+
+    #[test]
+    fn amounts_use_the_project_or_client_rate() {
+        let clients = [Client {
+            id: 7,
+            name: "Acme".into(),
+            hourly_rate: Some(100.0),
+        }];
+        let projects = [
+            project(1, Some(7), true, None),
+            project(2, Some(7), true, Some(150.0)),
+            project(3, Some(7), false, None),
+            project(4, None, true, Some(80.0)),
+            project(5, None, true, Some(80.0)),
+        ];
+        let seconds = ProjectSeconds::from([
+            (1, (3600, 1800)),
+            (2, (1200, 0)),
+            (3, (7200, 0)),
+            (4, (0, 900)),
+        ]);
+
+        let report = build_billing_report(0, 86_400, &projects, &clients, &seconds, 60);
+        // Projects without time are left out.
+        assert_eq!(report.projects.len(), 4);
+        let first = &report.projects[0];
+        assert_eq!(first.project_id, 3);
+        assert_eq!(first.amount, 0.0);
+        let by_client_rate = report.projects.iter().find(|p| p.project_id == 1).unwrap();
+        assert_eq!(by_client_rate.hourly_rate, Some(100.0));
+        assert_eq!(by_client_rate.amount, 150.0);
+
+        assert_eq!(report.clients.len(), 2);
+        assert_eq!(report.clients[0].client_name.as_deref(), Some("Acme"));
+        assert_eq!(report.clients[0].total_seconds, 13_800);
+        assert_eq!(report.clients[0].billable_seconds, 6_600);
+        assert_eq!(report.clients[0].amount, 200.0);
+        assert_eq!(report.clients[1].client_id, None);
+        assert_eq!(report.clients[1].amount, 20.0);
+
+        assert_eq!(report.total_amount, 220.0);
+        assert_eq!(report.billable_seconds, 7_500);
+        assert_eq!(report.unassigned_seconds, 60);
+    }
+
+    #[test]
+    fn billable_parts_clip_to_the_range_and_skip_manual_time() {
+        let covered = [(150, 200)];
+        assert_eq!(billable_parts(100, 100, 0, 1_000, &[]), vec![(100, 100)]);
+        assert_eq!(billable_parts(900, 300, 0, 1_000, &[]), vec![(900, 100)]);
+        assert_eq!(billable_parts(-50, 100, 0, 1_000, &[]), vec![(0, 50)]);
+        assert_eq!(
+            billable_parts(100, 200, 0, 1_000, &covered),
+            vec![(100, 50), (200, 100)]
+        );
+        assert_eq!(billable_parts(160, 20, 0, 1_000, &covered), vec![]);
+        assert_eq!(billable_parts(1_000, 60, 0, 1_000, &[]), vec![]);
+    }
+}
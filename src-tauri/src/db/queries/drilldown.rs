@@ -0,0 +1,190 @@
+use crate::db::error::Error;
+use crate::db::queries::focus::MAX_SESSION_GAP;
+use crate::db::queries::statistics::{labelled_logs, LabelledLog};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Separators browsers and editors put between the document and the app name.
+const TITLE_SEPARATORS: [&str; 4] = [" - ", " — ", " – ", " | "];
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TitleStat {
+    pub title: String,
+    pub total_duration: i64,
+    pub first_seen: i64,
+    pub last_seen: i64, // End of the last log
+    pub sessions: i32,
+    pub raw_titles: Vec<String>, // Logged titles folded into this one, most used first
+}
+
+/// Drops the parts of a window title that change without the document changing: unread
+/// counters, unsaved-change markers and the trailing app name.
+pub fn normalize_title(title: &str) -> String {
+    let mut title = title.trim();
+    if let Some(rest) = title.strip_prefix('(') {
+        if let Some((count, rest)) = rest.split_once(')') {
+            if !count.is_empty() && count.chars().all(|c| c.is_ascii_digit()) {
+                title = rest.trim_start();
+            }
+        }
+    }
+    title = title
+        .trim_start_matches(['●', '•', '*'])
+        .trim_end_matches(['●', '•', '*'])
+        .trim();
+
+    if let Some(index) = TITLE_SEPARATORS
+        .iter()
+        .filter_map(|separator| title.rfind(separator))
+        .max()
+    {
+        if index > 0 {
+            title = title[..index]
+                .trim_end()
+                .trim_end_matches(['●', '•', '*'])
+                .trim();
+        }
+    }
+    title.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Totals per title, most time first. A session is a run of logs with the same title and no
+/// gap longer than [`MAX_SESSION_GAP`] between them.
+pub fn build_title_stats(logs: &[&LabelledLog], normalize: bool) -> Vec<TitleStat> {
+    let mut ordered = logs.to_vec();
+    ordered.sort_by_key(|log| log.timestamp);
+
+    let mut stats: HashMap<String, (TitleStat, HashMap<&str, i64>)> = HashMap::new();
+    for log in ordered {
+        let key = if normalize {
+            normalize_title(&log.title)
+        } else {
+            log.title.clone()
+        };
+        let end = log.timestamp + log.duration;
+        let (stat, raw) = stats.entry(key.clone()).or_insert_with(|| {
+            (
+                TitleStat {
+                    title: key,
+                    total_duration: 0,
+                    first_seen: log.timestamp,
+                    last_seen: end,
+                    sessions: 1,
+                    raw_titles: Vec::new(),
+                },
+                HashMap::new(),
+            )
+        });
+        if log.timestamp - stat.last_seen > MAX_SESSION_GAP {
+            stat.sessions += 1;
+        }
+        stat.total_duration += log.duration;
+        stat.last_seen = stat.last_seen.max(end);
+        *raw.entry(log.title.as_str()).or_insert(0) += log.duration;
+    }
+
+    let mut result: Vec<TitleStat> = stats
+        .into_values()
+        .map(|(mut stat, raw)| {
+            let mut raw: Vec<(&str, i64)> = raw.into_iter().collect();
+            raw.sort_by(|left, right| right.1.cmp(&left.1).then(left.0.cmp(right.0)));
+            stat.raw_titles = raw
+                .into_iter()
+                .map(|(title, _)| title.to_string())
+                .collect();
+            stat
+        })
+        .collect();
+    result.sort_by(|left, right| {
+        right
+            .total_duration
+            .cmp(&left.total_duration)
+            .then_with(|| left.title.cmp(&right.title))
+    });
+    result
+}
+
+/// Time per window title within one app group or category. With `normalize`, titles that only
+/// differ by counters, markers or the app suffix are combined.
+#[tauri::command]
+pub async fn get_title_drilldown(
+    range_start: i64,
+    range_end: i64,
+    category: Option<String>,
+    app_group: Option<String>,
+    normalize: bool,
+    device_uuids: Option<Vec<String>>,
+) -> Result<Vec<TitleStat>, Error> {
+    if category.is_some() == app_group.is_some() {
+        return Err(anyhow::anyhow!("Pick either a category or an app group").into());
+    }
+    if range_end <= range_start {
+        return Err(anyhow::anyhow!("Range end must be after range start").into());
+    }
+    let logs = labelled_logs(range_start, range_end, device_uuids).await?;
+    let matching: Vec<&LabelledLog> = logs
+        .iter()
+        .filter(|log| {
+            category.as_deref().is_none_or(|name| log.category == name)
+                && app_group.as_deref().is_none_or(|name| log.app == name)
+        })
+        .collect();
+    Ok(build_title_stats(&matching, normalize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(timestamp: i64, duration: i64, title: &str) -> LabelledLog {
+        LabelledLog {
+            timestamp,
+            duration,
+            category: "Work".to_string(),
+            app: "Firefox".to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    #[test]
+    fn normalizing_strips_counters_markers_and_app_names() {
+        assert_eq!(
+            normalize_title("(3) Fix login redirect · Pull Request #42 - Mozilla Firefox"),
+            "Fix login redirect · Pull Request #42"
+        );
+        assert_eq!(
+            normalize_title("● main.rs - crate - Visual Studio Code"),
+            "main.rs - crate"
+        );
+        assert_eq!(normalize_title("report.docx*  |  Word"), "report.docx");
+        assert_eq!(normalize_title("Terminal"), "Terminal");
+        assert_eq!(normalize_title("- leading dash"), "- leading dash");
+    }
+
+    #[test]
+    fn titles_are_totalled_with_sessions() {
+        let logs = [
+            log(0, 60, "PROJ-1 - Jira"),
+            log(60, 60, "(2) PROJ-1 - Jira"),
+            log(120, 60, "PROJ-2 - Jira"),
+            log(180, 60, "PROJ-1 - Jira"),
+            log(1_000, 120, "PROJ-1 - Jira"),
+        ];
+        let refs: Vec<&LabelledLog> = logs.iter().collect();
+
+        let stats = build_title_stats(&refs, true);
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].title, "PROJ-1");
+        assert_eq!(stats[0].total_duration, 300);
+        assert_eq!(stats[0].first_seen, 0);
+        assert_eq!(stats[0].last_seen, 1_120);
+        // The brief switch to PROJ-2 keeps the first session going; the idle gap ends it.
+        assert_eq!(stats[0].sessions, 2);
+        assert_eq!(
+            stats[0].raw_titles,
+            vec!["PROJ-1 - Jira", "(2) PROJ-1 - Jira"]
+        );
+
+        assert_eq!(build_title_stats(&refs, false).len(), 3);
+    }
+}
@@ -0,0 +1,312 @@
+use crate::db::queries::time_zone;
+use crate::db::tables::log::Log;
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Idle time allowed between two logs before they stop counting as one session.
+pub const MAX_SESSION_GAP: i64 = 120;
+
+/// Upper bounds (exclusive, in seconds) of the focus-session histogram buckets; the last bucket
+/// is open-ended.
+const SESSION_HISTOGRAM_BOUNDS: [i64; 5] = [5 * 60, 15 * 60, 30 * 60, 60 * 60, 120 * 60];
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HourlySwitchStat {
+    pub hour: i32, // 0-23
+    pub switches: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CategorySessionStat {
+    pub category: String,
+    pub longest_session: i64,
+    pub started_at: i64, // Unix timestamp of the longest session's first log
+    pub sessions: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FocusSessionBucket {
+    pub min_duration: i64,
+    pub max_duration: Option<i64>, // Exclusive; None for the open-ended last bucket
+    pub sessions: i64,
+    pub total_duration: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DayFragmentation {
+    pub day_start: i64, // Unix timestamp
+    pub switches: i64,
+    pub sessions: i64,
+    pub total_duration: i64,
+    pub switches_per_hour: f64,
+    pub fragmentation_index: f64, // 0 = one unbroken session, approaching 1 = many short ones
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct FocusStatistics {
+    pub total_switches: i64,
+    pub switches_per_hour: Vec<HourlySwitchStat>,
+    pub longest_sessions: Vec<CategorySessionStat>,
+    pub session_histogram: Vec<FocusSessionBucket>,
+    pub daily_fragmentation: Vec<DayFragmentation>,
+    pub fragmentation_index: f64, // Daily indexes averaged by tracked time
+}
+
+struct Session {
+    category: String,
+    start: i64,
+    duration: i64,
+}
+
+/// 1 - sum(s^2) / (sum s)^2 over session lengths: 0 when all the time was one session.
+fn fragmentation_index(session_durations: &[i64]) -> f64 {
+    let total: i64 = session_durations.iter().sum();
+    if total <= 0 {
+        return 0.0;
+    }
+    let total = total as f64;
+    let concentration: f64 = session_durations
+        .iter()
+        .map(|&duration| (duration as f64 / total).powi(2))
+        .sum();
+    1.0 - concentration
+}
+
+fn session_histogram(sessions: &[Session]) -> Vec<FocusSessionBucket> {
+    let mut lower = 0;
+    let mut buckets: Vec<FocusSessionBucket> = SESSION_HISTOGRAM_BOUNDS
+        .iter()
+        .map(|&upper| {
+            let bucket = FocusSessionBucket {
+                min_duration: lower,
+                max_duration: Some(upper),
+                sessions: 0,
+                total_duration: 0,
+            };
+            lower = upper;
+            bucket
+        })
+        .collect();
+    buckets.push(FocusSessionBucket {
+        min_duration: lower,
+        max_duration: None,
+        sessions: 0,
+        total_duration: 0,
+    });
+
+    for session in sessions {
+        if let Some(bucket) = buckets
+            .iter_mut()
+            .find(|bucket| bucket.max_duration.is_none_or(|max| session.duration < max))
+        {
+            bucket.sessions += 1;
+            bucket.total_duration += session.duration;
+        }
+    }
+    buckets
+}
+
+/// Walks each device's logs in order. A context switch is a change of app with no idle gap
+/// longer than [`MAX_SESSION_GAP`]; a session is an unbroken run of logs in one category.
+pub fn build_focus_statistics(
+    logs: &[Log],
+    mut category_of: impl FnMut(&Log) -> String,
+    tz: Tz,
+) -> FocusStatistics {
+    let mut ordered: Vec<&Log> = logs.iter().filter(|log| log.duration > 0).collect();
+    ordered.sort_by(|left, right| {
+        left.device_uuid
+            .cmp(&right.device_uuid)
+            .then(left.timestamp.cmp(&right.timestamp))
+            .then(left.id.cmp(&right.id))
+    });
+
+    let mut hourly_switches: HashMap<i32, i64> = HashMap::new();
+    let mut day_switches: HashMap<i64, i64> = HashMap::new();
+    let mut sessions: Vec<Session> = Vec::new();
+    let mut previous: Option<(&Log, String)> = None;
+
+    for log in ordered {
+        let category = category_of(log);
+        let continuous = previous.as_ref().is_some_and(|(prev, _)| {
+            prev.device_uuid == log.device_uuid
+                && log.timestamp - (prev.timestamp + prev.duration) <= MAX_SESSION_GAP
+        });
+        let same_category = previous
+            .as_ref()
+            .is_some_and(|(_, prev_category)| *prev_category == category);
+        let switched_app = previous
+            .as_ref()
+            .is_some_and(|(prev, _)| prev.app != log.app);
+
+        if continuous && switched_app {
+            *hourly_switches
+                .entry(time_zone::hour_of_day(tz, log.timestamp))
+                .or_insert(0) += 1;
+            *day_switches
+                .entry(time_zone::day_start(tz, log.timestamp, 0))
+                .or_insert(0) += 1;
+        }
+
+        match sessions.last_mut() {
+            Some(session) if continuous && same_category => session.duration += log.duration,
+            _ => sessions.push(Session {
+                category: category.clone(),
+                start: log.timestamp,
+                duration: log.duration,
+            }),
+        }
+        previous = Some((log, category));
+    }
+
+    let mut longest: HashMap<&str, CategorySessionStat> = HashMap::new();
+    for session in &sessions {
+        let stat = longest
+            .entry(&session.category)
+            .or_insert_with(|| CategorySessionStat {
+                category: session.category.clone(),
+                longest_session: 0,
+                started_at: session.start,
+                sessions: 0,
+            });
+        stat.sessions += 1;
+        if session.duration > stat.longest_session {
+            stat.longest_session = session.duration;
+            stat.started_at = session.start;
+        }
+    }
+    let mut longest_sessions: Vec<CategorySessionStat> = longest.into_values().collect();
+    longest_sessions.sort_by(|left, right| {
+        right
+            .longest_session
+            .cmp(&left.longest_session)
+            .then_with(|| left.category.cmp(&right.category))
+    });
+
+    let mut day_sessions: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+    for session in &sessions {
+        day_sessions
+            .entry(time_zone::day_start(tz, session.start, 0))
+            .or_default()
+            .push(session.duration);
+    }
+    let daily_fragmentation: Vec<DayFragmentation> = day_sessions
+        .into_iter()
+        .map(|(day_start, durations)| {
+            let total_duration: i64 = durations.iter().sum();
+            let switches = day_switches.get(&day_start).copied().unwrap_or(0);
+            DayFragmentation {
+                day_start,
+                switches,
+                sessions: durations.len() as i64,
+                total_duration,
+                switches_per_hour: if total_duration > 0 {
+                    switches as f64 / (total_duration as f64 / 3600.0)
+                } else {
+                    0.0
+                },
+                fragmentation_index: fragmentation_index(&durations),
+            }
+        })
+        .collect();
+
+    let tracked: i64 = daily_fragmentation
+        .iter()
+        .map(|day| day.total_duration)
+        .sum();
+    let overall_index = if tracked > 0 {
+        daily_fragmentation
+            .iter()
+            .map(|day| day.fragmentation_index * day.total_duration as f64)
+            .sum::<f64>()
+            / tracked as f64
+    } else {
+        0.0
+    };
+
+    FocusStatistics {
+        total_switches: hourly_switches.values().sum(),
+        switches_per_hour: (0..24)
+            .map(|hour| HourlySwitchStat {
+                hour,
+                switches: hourly_switches.get(&hour).copied().unwrap_or(0),
+            })
+            .collect(),
+        longest_sessions,
+        session_histogram: session_histogram(&sessions),
+        daily_fragmentation,
+        fragmentation_index: overall_index,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(id: i64, app: &str, timestamp: i64, duration: i64) -> Log {
+        Log {
+            id,
+            device_uuid: Some("desktop".into()),
+            app: app.into(),
+            timestamp,
+            duration,
+            is_deleted: false,
+        }
+    }
+
+    fn category(log: &Log) -> String {
+        if log.app.contains("Code") || log.app.contains("Terminal") {
+            "Coding".into()
+        } else {
+            "Social".into()
+        }
+    }
+
+    #[test]
+    fn switches_within_a_category_keep_the_session_going() {
+        let logs = vec![
+            log(1, "Visual Studio Code", 0, 1200),
+            log(2, "Terminal", 1200, 600),
+            log(3, "Discord", 1800, 60),
+            log(4, "Visual Studio Code", 1860, 300),
+            // Back after a long break: not a switch, and a new session.
+            log(5, "Visual Studio Code", 10_000, 2400),
+        ];
+        let focus = build_focus_statistics(&logs, category, Tz::UTC);
+
+        assert_eq!(focus.total_switches, 3);
+        assert_eq!(focus.switches_per_hour[0].switches, 3);
+        let coding = &focus.longest_sessions[0];
+        assert_eq!(coding.category, "Coding");
+        assert_eq!(coding.longest_session, 2400);
+        assert_eq!(coding.started_at, 10_000);
+        assert_eq!(coding.sessions, 3);
+
+        let sessions: Vec<i64> = focus.session_histogram.iter().map(|b| b.sessions).collect();
+        assert_eq!(sessions, vec![1, 1, 0, 2, 0, 0]);
+    }
+
+    #[test]
+    fn one_long_session_is_not_fragmented() {
+        let logs = vec![log(1, "Visual Studio Code", 0, 3600)];
+        let focus = build_focus_statistics(&logs, category, Tz::UTC);
+        assert_eq!(focus.daily_fragmentation[0].fragmentation_index, 0.0);
+
+        let choppy: Vec<Log> = (0..4)
+            .map(|i| {
+                let app = if i % 2 == 0 {
+                    "Visual Studio Code"
+                } else {
+                    "Discord"
+                };
+                log(i, app, i * 900, 900)
+            })
+            .collect();
+        let focus = build_focus_statistics(&choppy, category, Tz::UTC);
+        let day = &focus.daily_fragmentation[0];
+        assert_eq!(day.switches, 3);
+        assert!((day.fragmentation_index - 0.75).abs() < 1e-9);
+        assert!((day.switches_per_hour - 3.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,443 @@
+use crate::db::error::Error;
+use crate::db::queries::statistics::{labelled_logs, LabelledLog};
+use crate::db::queries::time_zone::{self, load_calendar_settings, load_time_zone};
+use crate::db::tables::category::get_categories;
+use crate::db::tables::google_calendar_sync::{
+    get_all_google_calendar_events, GetAllGoogleCalendarEventsParams, GoogleCalendarEvent,
+};
+use crate::db::tables::manual_time_block::{
+    get_manual_time_blocks, insert_manual_time_block, ManualTimeBlock, NewManualTimeBlock,
+};
+use crate::db::tables::settings::get_settings;
+use chrono::{Datelike, Duration, Timelike};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How far back manual blocks are searched for a recurring pattern.
+const RECURRING_LOOKBACK_DAYS: i64 = 28;
+/// Matching manual blocks needed before a title counts as recurring.
+const MIN_RECURRING_OCCURRENCES: usize = 2;
+/// How far from a gap the previous or next activity may be and still be suggested.
+const NEIGHBOUR_WINDOW: i64 = 2 * 3600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkingHours {
+    pub start_hour: u32,
+    pub end_hour: u32, // Exclusive; 24 means midnight
+    pub workdays: i32, // Bit 0 = Monday through bit 6 = Sunday
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GapSuggestionKind {
+    CalendarEvent,
+    PreviousBlock,
+    NextBlock,
+    Recurring,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GapSuggestion {
+    pub kind: GapSuggestionKind,
+    pub title: String,
+    pub start: i64,
+    pub end: i64,
+    pub detail: Option<String>, // Where the suggestion came from, stored as the block's notes
+    pub category_id: Option<i32>, // Category the accepted block is filed under
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct UntrackedGap {
+    pub start: i64,
+    pub end: i64,
+    pub duration: i64,
+    pub suggestions: Vec<GapSuggestion>,
+}
+
+async fn load_working_hours() -> Result<(WorkingHours, i64), Error> {
+    let settings = get_settings().await?;
+    let value = |key: &str, default: i32| {
+        settings
+            .iter()
+            .find(|setting| setting.key == key)
+            .map(|setting| setting.val)
+            .unwrap_or(default)
+    };
+    let hours = WorkingHours {
+        start_hour: value("workdayStartHour", 9).clamp(0, 23) as u32,
+        end_hour: value("workdayEndHour", 18).clamp(1, 24) as u32,
+        workdays: value("attendanceWorkdays", 0b001_1111),
+    };
+    Ok((hours, i64::from(value("minGapMinutes", 30).max(1)) * 60))
+}
+
+pub fn merge_intervals(mut intervals: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    intervals.retain(|(start, end)| end > start);
+    intervals.sort_unstable();
+    let mut merged: Vec<(i64, i64)> = Vec::with_capacity(intervals.len());
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Parts of `window` not covered by the merged `tracked` intervals, at least `min_gap` long.
+pub fn find_gaps(window: (i64, i64), tracked: &[(i64, i64)], min_gap: i64) -> Vec<(i64, i64)> {
+    let mut gaps = Vec::new();
+    let mut cursor = window.0;
+    for &(start, end) in tracked {
+        if end <= cursor {
+            continue;
+        }
+        if start >= window.1 {
+            break;
+        }
+        if start > cursor {
+            gaps.push((cursor, start));
+        }
+        cursor = cursor.max(end);
+    }
+    if cursor < window.1 {
+        gaps.push((cursor, window.1));
+    }
+    gaps.retain(|(start, end)| end - start >= min_gap);
+    gaps
+}
+
+/// Working-hour windows of every workday that overlaps the range, clipped to it.
+pub fn working_windows(
+    tz: Tz,
+    range_start: i64,
+    range_end: i64,
+    hours: WorkingHours,
+) -> Vec<(i64, i64)> {
+    let first = time_zone::local_datetime(tz, range_start).date_naive();
+    let last = time_zone::local_datetime(tz, range_end).date_naive();
+    first
+        .iter_days()
+        .take_while(|date| *date <= last)
+        .filter(|date| hours.workdays & (1 << date.weekday().num_days_from_monday()) != 0)
+        .filter_map(|date| {
+            let start = time_zone::at_local_hour(tz, date, hours.start_hour);
+            let end = if hours.end_hour >= 24 {
+                time_zone::at_local_hour(tz, date + Duration::days(1), 0)
+            } else {
+                time_zone::at_local_hour(tz, date, hours.end_hour)
+            };
+            let window = (start.max(range_start), end.min(range_end));
+            (window.0 < window.1).then_some(window)
+        })
+        .collect()
+}
+
+fn calendar_suggestions(gap: (i64, i64), events: &[GoogleCalendarEvent]) -> Vec<GapSuggestion> {
+    events
+        .iter()
+        .filter(|event| event.start < gap.1 && event.end > gap.0)
+        .map(|event| GapSuggestion {
+            kind: GapSuggestionKind::CalendarEvent,
+            title: event.title.clone(),
+            start: event.start.max(gap.0),
+            end: event.end.min(gap.1),
+            detail: Some("Google Calendar event".to_string()),
+            category_id: None,
+        })
+        .collect()
+}
+
+/// Suggests the category of the activity right before or after the gap, filed under that
+/// category so the block is not recategorized by its title.
+fn neighbour_suggestions(
+    gap: (i64, i64),
+    logs: &[LabelledLog],
+    category_ids: &HashMap<String, i32>,
+) -> Vec<GapSuggestion> {
+    let previous = logs
+        .iter()
+        .filter(|log| log.timestamp + log.duration <= gap.0)
+        .filter(|log| gap.0 - (log.timestamp + log.duration) <= NEIGHBOUR_WINDOW)
+        .max_by_key(|log| log.timestamp);
+    let next = logs
+        .iter()
+        .filter(|log| log.timestamp >= gap.1 && log.timestamp - gap.1 <= NEIGHBOUR_WINDOW)
+        .min_by_key(|log| log.timestamp);
+
+    let mut suggestions = Vec::new();
+    if let Some(log) = previous {
+        suggestions.push(GapSuggestion {
+            kind: GapSuggestionKind::PreviousBlock,
+            title: log.category.clone(),
+            start: gap.0,
+            end: gap.1,
+            detail: Some(format!("Continues {} before the gap", log.app)),
+            category_id: category_ids.get(&log.category).copied(),
+        });
+    }
+    if let Some(log) = next {
+        if previous.is_none_or(|previous| previous.category != log.category) {
+            suggestions.push(GapSuggestion {
+                kind: GapSuggestionKind::NextBlock,
+                title: log.category.clone(),
+                start: gap.0,
+                end: gap.1,
+                detail: Some(format!("Leads into {} after the gap", log.app)),
+                category_id: category_ids.get(&log.category).copied(),
+            });
+        }
+    }
+    suggestions
+}
+
+/// Titles of manual blocks that covered the same time of day on the same weekday in earlier
+/// weeks, most frequent first, with the category of the latest such block.
+fn recurring_suggestions(
+    tz: Tz,
+    gap: (i64, i64),
+    history: &[ManualTimeBlock],
+) -> Vec<GapSuggestion> {
+    let gap_start = time_zone::local_datetime(tz, gap.0);
+    let minute_of_day = |ts: i64| {
+        let dt = time_zone::local_datetime(tz, ts);
+        dt.hour() * 60 + dt.minute()
+    };
+    let (gap_from, gap_to) = (minute_of_day(gap.0), minute_of_day(gap.1 - 1));
+
+    let mut counts: HashMap<&str, (usize, &ManualTimeBlock)> = HashMap::new();
+    for block in history {
+        let block_start = time_zone::local_datetime(tz, block.start_time);
+        let same_weekday = block_start.weekday() == gap_start.weekday();
+        let earlier_day = block_start.date_naive() < gap_start.date_naive();
+        let same_day = block_start.date_naive()
+            == time_zone::local_datetime(tz, block.end_time - 1).date_naive();
+        if !(same_weekday && earlier_day && same_day) {
+            continue;
+        }
+        let (block_from, block_to) = (
+            minute_of_day(block.start_time),
+            minute_of_day(block.end_time - 1),
+        );
+        if block_from <= gap_to && block_to >= gap_from {
+            let entry = counts.entry(block.title.as_str()).or_insert((0, block));
+            entry.0 += 1;
+            if block.start_time > entry.1.start_time {
+                entry.1 = block;
+            }
+        }
+    }
+
+    let mut recurring: Vec<(&str, (usize, &ManualTimeBlock))> = counts
+        .into_iter()
+        .filter(|(_, (count, _))| *count >= MIN_RECURRING_OCCURRENCES)
+        .collect();
+    recurring.sort_by(|left, right| (right.1).0.cmp(&(left.1).0).then(left.0.cmp(right.0)));
+    recurring
+        .into_iter()
+        .map(|(title, (count, latest))| GapSuggestion {
+            kind: GapSuggestionKind::Recurring,
+            title: title.to_string(),
+            start: gap.0,
+            end: gap.1,
+            detail: Some(format!("Logged at this time on {count} earlier weeks")),
+            category_id: latest.category_id,
+        })
+        .collect()
+}
+
+/// Untracked stretches inside working hours, each with suggestions for what filled them.
+/// Manual time blocks count as tracked. Calendar events are skipped when Google Calendar
+/// is not connected or cannot be reached.
+#[tauri::command]
+pub async fn get_untracked_gaps(
+    range_start: i64,
+    range_end: i64,
+    min_gap: Option<i64>,
+    device_uuids: Option<Vec<String>>,
+) -> Result<Vec<UntrackedGap>, Error> {
+    if range_end <= range_start {
+        return Err(anyhow::anyhow!("Range end must be after range start").into());
+    }
+    let (hours, default_min_gap) = load_working_hours().await?;
+    let min_gap = min_gap.unwrap_or(default_min_gap).max(1);
+    let tz = load_time_zone().await?;
+    let start_hour = load_calendar_settings().await?.start_hour;
+    let range_end = range_end.min(chrono::Utc::now().timestamp());
+    if range_end <= range_start {
+        return Ok(Vec::new());
+    }
+
+    let logs = labelled_logs(
+        range_start - NEIGHBOUR_WINDOW,
+        range_end + NEIGHBOUR_WINDOW,
+        device_uuids,
+    )
+    .await?;
+    let manual_blocks = get_manual_time_blocks(range_start, range_end).await?;
+    let history_start = time_zone::add_days(
+        tz,
+        time_zone::day_start(tz, range_start, start_hour),
+        -RECURRING_LOOKBACK_DAYS,
+    );
+    let history = get_manual_time_blocks(history_start, range_end).await?;
+    let events = get_all_google_calendar_events(GetAllGoogleCalendarEventsParams {
+        start_time: range_start,
+        end_time: range_end,
+    })
+    .await
+    .unwrap_or_default();
+    let category_ids: HashMap<String, i32> = get_categories()
+        .await?
+        .into_iter()
+        .map(|category| (category.name, category.id))
+        .collect();
+
+    let tracked = merge_intervals(
+        logs.iter()
+            .map(|log| (log.timestamp, log.timestamp + log.duration))
+            .chain(
+                manual_blocks
+                    .iter()
+                    .map(|block| (block.start_time, block.end_time)),
+            )
+            .collect(),
+    );
+
+    Ok(working_windows(tz, range_start, range_end, hours)
+        .into_iter()
+        .flat_map(|window| find_gaps(window, &tracked, min_gap))
+        .map(|gap| {
+            let mut suggestions = calendar_suggestions(gap, &events);
+            suggestions.extend(neighbour_suggestions(gap, &logs, &category_ids));
+            suggestions.extend(recurring_suggestions(tz, gap, &history));
+            UntrackedGap {
+                start: gap.0,
+                end: gap.1,
+                duration: gap.1 - gap.0,
+                suggestions,
+            }
+        })
+        .collect())
+}
+
+/// Records a suggestion as a manual time block and returns the new block's id.
+#[tauri::command]
+pub async fn accept_gap_suggestion(suggestion: GapSuggestion) -> Result<i64, Error> {
+    insert_manual_time_block(NewManualTimeBlock {
+        title: suggestion.title,
+        notes: suggestion.detail,
+        start_time: suggestion.start,
+        end_time: suggestion.end,
+        project_id: None,
+        category_id: suggestion.category_id,
+        tags: vec![],
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::tables::manual_time_block::Tags;
+    use chrono::TimeZone;
+
+    fn utc(day: u32, hour: u32, minute: u32) -> i64 {
+        chrono::Utc
+            .with_ymd_and_hms(2024, 5, day, hour, minute, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    #[test]
+    fn gaps_are_the_uncovered_parts_of_working_hours() {
+        let hours = WorkingHours {
+            start_hour: 9,
+            end_hour: 17,
+            workdays: 0b001_1111,
+        };
+        // Friday 2024-05-10 through Saturday; only Friday is a workday.
+        let windows = working_windows(Tz::UTC, utc(10, 0, 0), utc(12, 0, 0), hours);
+        assert_eq!(windows, vec![(utc(10, 9, 0), utc(10, 17, 0))]);
+
+        let tracked = merge_intervals(vec![
+            (utc(10, 8, 0), utc(10, 10, 0)),
+            (utc(10, 9, 30), utc(10, 11, 0)),
+            (utc(10, 11, 10), utc(10, 12, 0)),
+            (utc(10, 14, 0), utc(10, 16, 0)),
+        ]);
+        assert_eq!(tracked.len(), 3);
+        let gaps = find_gaps(windows[0], &tracked, 30 * 60);
+        assert_eq!(
+            gaps,
+            vec![
+                (utc(10, 12, 0), utc(10, 14, 0)),
+                (utc(10, 16, 0), utc(10, 17, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn suggestions_come_from_neighbours_calendar_and_history() {
+        let gap = (utc(10, 12, 0), utc(10, 14, 0));
+        let logs = vec![
+            LabelledLog {
+                timestamp: utc(10, 11, 0),
+                duration: 3600,
+                category: "Coding".into(),
+                app: "Visual Studio Code".into(),
+                title: "gaps.rs - crate - Visual Studio Code".into(),
+            },
+            LabelledLog {
+                timestamp: utc(10, 14, 0),
+                duration: 600,
+                category: "Coding".into(),
+                app: "Terminal".into(),
+                title: "Terminal".into(),
+            },
+        ];
+        let category_ids = HashMap::from([("Coding".to_string(), 3)]);
+        let neighbours = neighbour_suggestions(gap, &logs, &category_ids);
+        assert_eq!(neighbours.len(), 1);
+        assert_eq!(neighbours[0].kind, GapSuggestionKind::PreviousBlock);
+        assert_eq!(neighbours[0].title, "Coding");
+        assert_eq!(neighbours[0].category_id, Some(3));
+
+        let events = vec![GoogleCalendarEvent {
+            calendar_id: 1,
+            event_id: "abc".into(),
+            title: "Design review".into(),
+            start: utc(10, 13, 0),
+            end: utc(10, 15, 0),
+            description: None,
+            location: None,
+        }];
+        let from_calendar = calendar_suggestions(gap, &events);
+        assert_eq!(from_calendar[0].start, utc(10, 13, 0));
+        assert_eq!(from_calendar[0].end, gap.1);
+
+        let block = |days_before: i64, title: &str, category_id| ManualTimeBlock {
+            id: days_before,
+            title: title.into(),
+            notes: None,
+            start_time: utc(10, 12, 30) - days_before * 86_400,
+            end_time: utc(10, 13, 30) - days_before * 86_400,
+            created_at: 0,
+            updated_at: 0,
+            project_id: None,
+            category_id,
+            tags: Tags::default(),
+        };
+        // Two earlier Fridays, plus a one-off on Thursday.
+        let history = vec![
+            block(14, "Lunch", None),
+            block(7, "Lunch", Some(5)),
+            block(1, "Gym", None),
+        ];
+        let recurring = recurring_suggestions(Tz::UTC, gap, &history);
+        assert_eq!(recurring.len(), 1);
+        assert_eq!(recurring[0].title, "Lunch");
+        assert_eq!(recurring[0].category_id, Some(5));
+    }
+}
@@ -0,0 +1,353 @@
+use crate::db;
+use crate::db::error::Error;
+use crate::db::queries::statistics::{labelled_logs, LabelledLog};
+use crate::db::queries::time_zone::{
+    self, load_calendar_settings, load_time_zone, CalendarSettings,
+};
+use crate::db::tables::goal::{fetch_goals, Goal, GoalDirection, GoalPeriod, GoalTargetKind};
+use chrono::Datelike;
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GoalStatus {
+    InProgress,  // At-least goal not reached yet
+    Met,         // At-least goal reached
+    Missed,      // At-least goal whose period ended short of the target
+    WithinLimit, // At-most goal not crossed
+    Exceeded,    // At-most goal crossed
+    Inactive,    // The goal does not apply to the current day
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct GoalProgress {
+    pub goal: Goal,
+    pub target_name: String,
+    pub period_start: i64,
+    pub period_end: i64, // Inclusive
+    pub tracked_seconds: i64,
+    pub remaining_seconds: i64, // Still needed for at-least goals, still allowed for at-most goals
+    pub progress: f64,          // tracked / target; 1.0 means the target was reached
+    pub status: GoalStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoalEvent {
+    Met,
+    LimitCrossed,
+}
+
+pub fn goal_period_bounds(
+    tz: Tz,
+    period: GoalPeriod,
+    anchor: i64,
+    calendar: CalendarSettings,
+) -> (i64, i64) {
+    match period {
+        GoalPeriod::Day => {
+            let start = time_zone::day_start(tz, anchor, calendar.start_hour);
+            (start, time_zone::add_days(tz, start, 1) - 1)
+        }
+        GoalPeriod::Week => {
+            time_zone::week_bounds(tz, anchor, calendar.start_hour, calendar.week_start_day)
+        }
+    }
+}
+
+fn matches_target(goal: &Goal, target_name: &str, log: &LabelledLog) -> bool {
+    match goal.target_kind {
+        GoalTargetKind::Category => log.category == target_name,
+        GoalTargetKind::AppGroup => log.app == target_name,
+    }
+}
+
+pub fn goal_progress(
+    goal: &Goal,
+    target_name: &str,
+    logs: &[LabelledLog],
+    tz: Tz,
+    calendar: CalendarSettings,
+    anchor: i64,
+    now: i64,
+) -> GoalProgress {
+    let (period_start, period_end) = goal_period_bounds(tz, goal.period, anchor, calendar);
+    let weekday_of = |timestamp: i64| {
+        time_zone::calendar_date(tz, timestamp, calendar.start_hour)
+            .weekday()
+            .num_days_from_monday()
+    };
+
+    let tracked_seconds: i64 = logs
+        .iter()
+        .filter(|log| log.timestamp >= period_start && log.timestamp <= period_end)
+        .filter(|log| matches_target(goal, target_name, log))
+        .filter(|log| goal.applies_on(weekday_of(log.timestamp)))
+        .filter(|log| goal.counts_hour(time_zone::hour_of_day(tz, log.timestamp)))
+        .map(|log| log.duration)
+        .sum();
+
+    let status = if goal.period == GoalPeriod::Day && !goal.applies_on(weekday_of(period_start)) {
+        GoalStatus::Inactive
+    } else {
+        match goal.direction {
+            GoalDirection::AtLeast if tracked_seconds >= goal.target_seconds => GoalStatus::Met,
+            GoalDirection::AtLeast if now > period_end => GoalStatus::Missed,
+            GoalDirection::AtLeast => GoalStatus::InProgress,
+            GoalDirection::AtMost if tracked_seconds > goal.target_seconds => GoalStatus::Exceeded,
+            GoalDirection::AtMost => GoalStatus::WithinLimit,
+        }
+    };
+
+    let progress = if goal.target_seconds > 0 {
+        tracked_seconds as f64 / goal.target_seconds as f64
+    } else if tracked_seconds > 0 || goal.direction == GoalDirection::AtLeast {
+        1.0
+    } else {
+        0.0
+    };
+
+    GoalProgress {
+        goal: goal.clone(),
+        target_name: target_name.to_string(),
+        period_start,
+        period_end,
+        tracked_seconds,
+        remaining_seconds: (goal.target_seconds - tracked_seconds).max(0),
+        progress,
+        status,
+    }
+}
+
+/// The notification a status change should raise, if any. A status seen for the first time
+/// in a period only raises one when `notify_new` is set, so a restart does not repeat them.
+pub fn goal_event(
+    previous: Option<GoalStatus>,
+    current: GoalStatus,
+    notify_new: bool,
+) -> Option<GoalEvent> {
+    if previous == Some(current) || (previous.is_none() && !notify_new) {
+        return None;
+    }
+    match current {
+        GoalStatus::Met => Some(GoalEvent::Met),
+        GoalStatus::Exceeded => Some(GoalEvent::LimitCrossed),
+        _ => None,
+    }
+}
+
+async fn target_names() -> Result<HashMap<(GoalTargetKind, i64), String>, Error> {
+    let pool = db::get_pool().await?;
+    let categories: Vec<(i64, String)> = sqlx::query_as("SELECT id, name FROM category")
+        .fetch_all(&pool)
+        .await?;
+    let app_groups: Vec<(i64, String)> = sqlx::query_as("SELECT id, name FROM app_groups")
+        .fetch_all(&pool)
+        .await?;
+    Ok(categories
+        .into_iter()
+        .map(|(id, name)| ((GoalTargetKind::Category, id), name))
+        .chain(
+            app_groups
+                .into_iter()
+                .map(|(id, name)| ((GoalTargetKind::AppGroup, id), name)),
+        )
+        .collect())
+}
+
+/// Progress of every enabled goal for the periods containing `anchor`.
+pub async fn evaluate_goals(
+    anchor: i64,
+    now: i64,
+    device_uuids: Option<Vec<String>>,
+) -> Result<Vec<GoalProgress>, Error> {
+    let pool = db::get_pool().await?;
+    let goals: Vec<Goal> = fetch_goals(&pool)
+        .await?
+        .into_iter()
+        .filter(|goal| goal.is_enabled)
+        .collect();
+    if goals.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let tz = load_time_zone().await?;
+    let calendar = load_calendar_settings().await?;
+    let names = target_names().await?;
+    let (range_start, range_end) = goals
+        .iter()
+        .map(|goal| goal_period_bounds(tz, goal.period, anchor, calendar))
+        .fold(
+            (i64::MAX, i64::MIN),
+            |(start, end), (period_start, period_end)| {
+                (start.min(period_start), end.max(period_end))
+            },
+        );
+    let logs = labelled_logs(range_start, range_end, device_uuids).await?;
+
+    Ok(goals
+        .iter()
+        .filter_map(|goal| {
+            let name = names.get(&(goal.target_kind, goal.target_id))?;
+            Some(goal_progress(goal, name, &logs, tz, calendar, anchor, now))
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_goal_progress(
+    anchor: Option<i64>,
+    device_uuids: Option<Vec<String>>,
+) -> Result<Vec<GoalProgress>, Error> {
+    let now = chrono::Utc::now().timestamp();
+    evaluate_goals(anchor.unwrap_or(now), now, device_uuids).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::tables::goal::ALL_WEEKDAYS;
+    use chrono::TimeZone;
+
+    const CALENDAR: CalendarSettings = CalendarSettings {
+        start_hour: 0,
+        week_start_day: 0,
+    };
+
+    fn utc(day: u32, hour: u32) -> i64 {
+        chrono::Utc
+            .with_ymd_and_hms(2024, 5, day, hour, 0, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    fn log(timestamp: i64, duration: i64, category: &str) -> LabelledLog {
+        LabelledLog {
+            timestamp,
+            duration,
+            category: category.to_string(),
+            app: "Steam".to_string(),
+            title: "Steam".to_string(),
+        }
+    }
+
+    fn goal(period: GoalPeriod, direction: GoalDirection, target_seconds: i64) -> Goal {
+        Goal {
+            id: 1,
+            name: "Test".to_string(),
+            target_kind: GoalTargetKind::Category,
+            target_id: 1,
+            period,
+            direction,
+            target_seconds,
+            window_start_hour: None,
+            window_end_hour: None,
+            weekdays: ALL_WEEKDAYS,
+            is_enabled: true,
+        }
+    }
+
+    #[test]
+    fn weekly_target_counts_only_the_target_category() {
+        let logs = vec![
+            log(utc(6, 9), 3600, "Coding"),
+            log(utc(8, 9), 3600, "Coding"),
+            log(utc(8, 10), 3600, "Social"),
+            log(utc(13, 9), 3600, "Coding"), // Following week
+        ];
+        let target = goal(GoalPeriod::Week, GoalDirection::AtLeast, 2 * 3600);
+        let progress = goal_progress(
+            &target,
+            "Coding",
+            &logs,
+            Tz::UTC,
+            CALENDAR,
+            utc(8, 12),
+            utc(8, 12),
+        );
+        assert_eq!(progress.period_start, utc(6, 0));
+        assert_eq!(progress.tracked_seconds, 2 * 3600);
+        assert_eq!(progress.status, GoalStatus::Met);
+
+        let later = goal_progress(
+            &target,
+            "Coding",
+            &logs[..1],
+            Tz::UTC,
+            CALENDAR,
+            utc(8, 12),
+            utc(14, 0),
+        );
+        assert_eq!(later.status, GoalStatus::Missed);
+        assert_eq!(later.remaining_seconds, 3600);
+    }
+
+    #[test]
+    fn weekday_window_limit_ignores_evenings_and_weekends() {
+        // No Gaming before 18:00 on weekdays.
+        let mut limit = goal(GoalPeriod::Day, GoalDirection::AtMost, 0);
+        limit.window_end_hour = Some(18);
+        limit.weekdays = 0b001_1111;
+
+        // Friday 2024-05-10: evening gaming is fine, afternoon gaming is not.
+        let evening = vec![log(utc(10, 19), 1800, "Gaming")];
+        let progress = goal_progress(
+            &limit,
+            "Gaming",
+            &evening,
+            Tz::UTC,
+            CALENDAR,
+            utc(10, 20),
+            utc(10, 20),
+        );
+        assert_eq!(progress.status, GoalStatus::WithinLimit);
+
+        let afternoon = vec![log(utc(10, 15), 60, "Gaming")];
+        let progress = goal_progress(
+            &limit,
+            "Gaming",
+            &afternoon,
+            Tz::UTC,
+            CALENDAR,
+            utc(10, 20),
+            utc(10, 20),
+        );
+        assert_eq!(progress.status, GoalStatus::Exceeded);
+
+        // Saturday 2024-05-11 is outside the goal's days.
+        let saturday = vec![log(utc(11, 15), 600, "Gaming")];
+        let progress = goal_progress(
+            &limit,
+            "Gaming",
+            &saturday,
+            Tz::UTC,
+            CALENDAR,
+            utc(11, 20),
+            utc(11, 20),
+        );
+        assert_eq!(progress.status, GoalStatus::Inactive);
+        assert_eq!(progress.tracked_seconds, 0);
+    }
+
+    #[test]
+    fn events_fire_once_per_crossing() {
+        assert_eq!(
+            goal_event(Some(GoalStatus::WithinLimit), GoalStatus::Exceeded, false),
+            Some(GoalEvent::LimitCrossed)
+        );
+        assert_eq!(
+            goal_event(Some(GoalStatus::Exceeded), GoalStatus::Exceeded, true),
+            None
+        );
+        assert_eq!(
+            goal_event(Some(GoalStatus::InProgress), GoalStatus::Met, false),
+            Some(GoalEvent::Met)
+        );
+        assert_eq!(goal_event(None, GoalStatus::Met, false), None);
+        assert_eq!(
+            goal_event(None, GoalStatus::Met, true),
+            Some(GoalEvent::Met)
+        );
+    }
+}
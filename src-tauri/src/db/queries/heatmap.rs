@@ -0,0 +1,134 @@
+use crate::db::error::Error;
+use crate::db::queries::statistics::labelled_logs;
+use crate::db::queries::time_zone::{self, load_calendar_settings, load_time_zone};
+use chrono::NaiveDate;
+use serde::Serialize;
+
+/// Quantiles of active-day totals that separate heatmap levels 1-4; level 0 means no activity.
+const LEVEL_QUANTILES: [f64; 3] = [0.25, 0.5, 0.75];
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HeatmapDay {
+    pub date: String,   // YYYY-MM-DD
+    pub day_start: i64, // Unix timestamp
+    pub total_duration: i64,
+    pub level: u8, // 0 = no activity, 4 = busiest quarter of active days
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct YearHeatmap {
+    pub year: i32,
+    pub days: Vec<HeatmapDay>,
+    pub thresholds: Vec<i64>, // Highest total in levels 1, 2 and 3
+    pub total_duration: i64,
+    pub active_days: i32,
+    pub max_duration: i64,
+}
+
+/// Nearest-rank quantile thresholds over the non-zero totals.
+pub fn quantile_thresholds(totals: &[i64]) -> Vec<i64> {
+    let mut active: Vec<i64> = totals.iter().copied().filter(|&total| total > 0).collect();
+    if active.is_empty() {
+        return vec![0; LEVEL_QUANTILES.len()];
+    }
+    active.sort_unstable();
+    LEVEL_QUANTILES
+        .iter()
+        .map(|quantile| {
+            let rank = (quantile * active.len() as f64).ceil() as usize;
+            active[rank.clamp(1, active.len()) - 1]
+        })
+        .collect()
+}
+
+pub fn heatmap_level(total: i64, thresholds: &[i64]) -> u8 {
+    if total <= 0 {
+        return 0;
+    }
+    1 + thresholds
+        .iter()
+        .take_while(|&&threshold| total > threshold)
+        .count() as u8
+}
+
+#[tauri::command]
+pub async fn get_year_heatmap(
+    year: i32,
+    category: Option<String>,
+    app_group: Option<String>,
+    device_uuids: Option<Vec<String>>,
+) -> Result<YearHeatmap, Error> {
+    if category.is_some() && app_group.is_some() {
+        return Err(anyhow::anyhow!("Filter by a category or an app group, not both").into());
+    }
+    let first_date = NaiveDate::from_ymd_opt(year, 1, 1)
+        .ok_or_else(|| anyhow::anyhow!("Year {year} is out of range"))?;
+    let next_first_date = NaiveDate::from_ymd_opt(year + 1, 1, 1)
+        .ok_or_else(|| anyhow::anyhow!("Year {year} is out of range"))?;
+
+    let tz = load_time_zone().await?;
+    let start_hour = load_calendar_settings().await?.start_hour;
+    let range_start = time_zone::at_local_hour(tz, first_date, start_hour);
+    let range_end = time_zone::at_local_hour(tz, next_first_date, start_hour) - 1;
+    let day_count = (next_first_date - first_date).num_days() as usize;
+
+    let mut totals = vec![0i64; day_count];
+    for log in labelled_logs(range_start, range_end, device_uuids).await? {
+        if category.as_deref().is_some_and(|name| log.category != name)
+            || app_group.as_deref().is_some_and(|name| log.app != name)
+        {
+            continue;
+        }
+        let index = (time_zone::calendar_date(tz, log.timestamp, start_hour) - first_date)
+            .num_days() as usize;
+        if let Some(total) = totals.get_mut(index) {
+            *total += log.duration;
+        }
+    }
+
+    let thresholds = quantile_thresholds(&totals);
+    let days = first_date
+        .iter_days()
+        .zip(&totals)
+        .map(|(date, &total_duration)| HeatmapDay {
+            date: date.format("%Y-%m-%d").to_string(),
+            day_start: time_zone::at_local_hour(tz, date, start_hour),
+            total_duration,
+            level: heatmap_level(total_duration, &thresholds),
+        })
+        .collect();
+
+    Ok(YearHeatmap {
+        year,
+        days,
+        total_duration: totals.iter().sum(),
+        active_days: totals.iter().filter(|&&total| total > 0).count() as i32,
+        max_duration: totals.iter().copied().max().unwrap_or(0),
+        thresholds,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_split_active_days_into_quarters() {
+        let totals = [0, 100, 200, 300, 400, 0, 500, 600, 700, 800];
+        let thresholds = quantile_thresholds(&totals);
+        assert_eq!(thresholds, vec![200, 400, 600]);
+
+        let levels: Vec<u8> = totals
+            .iter()
+            .map(|&total| heatmap_level(total, &thresholds))
+            .collect();
+        assert_eq!(levels, vec![0, 1, 1, 2, 2, 0, 3, 3, 4, 4]);
+    }
+
+    #[test]
+    fn a_year_without_activity_is_all_level_zero() {
+        let thresholds = quantile_thresholds(&[0; 365]);
+        assert_eq!(thresholds, vec![0, 0, 0]);
+        assert_eq!(heatmap_level(0, &thresholds), 0);
+    }
+}
@@ -0,0 +1,147 @@
+use crate::db::error::Error;
+use crate::db::queries::statistics::{labelled_logs, LabelledLog};
+use crate::db::tables::key_extractor::{
+    build_key_extractors, get_key_extractors, CachedKeyExtractor,
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct KeyStat {
+    pub key: String,
+    pub total_duration: i64,
+    pub first_seen: i64,
+    pub last_seen: i64,   // End of the last log
+    pub title_count: i32, // Distinct window titles the key was found in
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct KeyReport {
+    pub extractor_id: i32,
+    pub extractor_name: String,
+    pub keys: Vec<KeyStat>, // Most time first
+    pub matched_duration: i64,
+}
+
+/// Totals per key for one rule. Each log counts towards at most one key per rule.
+pub fn build_key_report(extractor: &CachedKeyExtractor, logs: &[LabelledLog]) -> KeyReport {
+    let mut stats: HashMap<String, (KeyStat, HashSet<&str>)> = HashMap::new();
+    for log in logs {
+        let Some(key) = extractor.extract(&log.title) else {
+            continue;
+        };
+        let end = log.timestamp + log.duration;
+        let (stat, titles) = stats.entry(key.clone()).or_insert_with(|| {
+            (
+                KeyStat {
+                    key,
+                    total_duration: 0,
+                    first_seen: log.timestamp,
+                    last_seen: end,
+                    title_count: 0,
+                },
+                HashSet::new(),
+            )
+        });
+        stat.total_duration += log.duration;
+        stat.first_seen = stat.first_seen.min(log.timestamp);
+        stat.last_seen = stat.last_seen.max(end);
+        titles.insert(log.title.as_str());
+    }
+
+    let mut keys: Vec<KeyStat> = stats
+        .into_values()
+        .map(|(mut stat, titles)| {
+            stat.title_count = titles.len() as i32;
+            stat
+        })
+        .collect();
+    keys.sort_by(|left, right| {
+        right
+            .total_duration
+            .cmp(&left.total_duration)
+            .then_with(|| left.key.cmp(&right.key))
+    });
+    KeyReport {
+        extractor_id: extractor.id,
+        extractor_name: extractor.name.clone(),
+        matched_duration: keys.iter().map(|stat| stat.total_duration).sum(),
+        keys,
+    }
+}
+
+/// Time per extracted key in the range, one report per extraction rule, or only for
+/// `extractor_id` when given.
+#[tauri::command]
+pub async fn get_key_report(
+    range_start: i64,
+    range_end: i64,
+    extractor_id: Option<i32>,
+    device_uuids: Option<Vec<String>>,
+) -> Result<Vec<KeyReport>, Error> {
+    if range_end <= range_start {
+        return Err(anyhow::anyhow!("Range end must be after range start").into());
+    }
+    let mut rules = get_key_extractors().await?;
+    if let Some(id) = extractor_id {
+        rules.retain(|rule| rule.id == id);
+        if rules.is_empty() {
+            return Err(anyhow::anyhow!("Extraction rule {id} does not exist").into());
+        }
+    }
+    let extractors = build_key_extractors(&rules)?;
+    let logs = labelled_logs(range_start, range_end, device_uuids).await?;
+    Ok(extractors
+        .iter()
+        .map(|extractor| build_key_report(extractor, &logs))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::tables::key_extractor::KeyExtractor;
+
+    fn log(timestamp: i64, duration: i64, title: &str) -> LabelledLog {
+        LabelledLog {
+            timestamp,
+            duration,
+            category: "Work".to_string(),
+            app: "Jira".to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    #[test]
+    fn time_is_totalled_per_key() {
+        let extractors = build_key_extractors(&[KeyExtractor {
+            id: 3,
+            name: "Jira".into(),
+            regex: r"\b(?P<key>[A-Z]+-\d+)\b".into(),
+            template: None,
+        }])
+        .unwrap();
+        let logs = [
+            log(100, 60, "PROJ-1 Login fails - Jira"),
+            log(200, 120, "PROJ-2 Slow search - Jira"),
+            log(400, 30, "Edit PROJ-1 - Jira"),
+            log(500, 600, "Dashboard - Jira"),
+        ];
+
+        let report = build_key_report(&extractors[0], &logs);
+        assert_eq!(report.extractor_id, 3);
+        assert_eq!(report.matched_duration, 210);
+        assert_eq!(report.keys.len(), 2);
+        assert_eq!(report.keys[0].key, "PROJ-2");
+        assert_eq!(
+            report.keys[1],
+            KeyStat {
+                key: "PROJ-1".into(),
+                total_duration: 90,
+                first_seen: 100,
+                last_seen: 430,
+                title_count: 2,
+            }
+        );
+    }
+}
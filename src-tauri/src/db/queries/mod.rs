@@ -1,5 +1,26 @@
+pub mod attendance;
+pub mod billing;
+pub mod drilldown;
+pub mod focus;
+pub mod gaps;
+pub mod goals;
+pub mod heatmap;
+pub mod keys;
 pub mod week;
 pub mod statistics;
+pub mod time_zone;
+pub mod trends;
 
-pub use week::{get_week, get_week_for_app_filter};
-pub use statistics::{get_week_statistics, get_day_statistics, get_total_statistics};
+pub use week::{get_range, get_week, get_week_for_app_filter};
+pub use statistics::{
+    get_day_statistics, get_range_statistics, get_total_statistics, get_week_statistics,
+};
+pub use attendance::{export_attendance_report, get_attendance_report};
+pub use billing::get_billing_report;
+pub use drilldown::get_title_drilldown;
+pub use gaps::{accept_gap_suggestion, get_untracked_gaps};
+pub use goals::get_goal_progress;
+pub use heatmap::get_year_heatmap;
+pub use keys::get_key_report;
+pub use time_zone::{get_time_zone, set_time_zone};
+pub use trends::get_category_trends;
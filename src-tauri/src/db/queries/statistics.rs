@@ -2,15 +2,21 @@ use crate::db;
 use crate::db::tables::app_group::{
     build_app_group_matchers, get_app_groups, resolve_app_group, CachedAppGroup,
 };
-use cat_regex::{get_cat_regex, CategoryRegex};
-use category::{get_categories, Category};
+use cat_regex::{get_cat_regex_history, is_effective_at, CategoryRegex};
+use category::{get_categories, Category, MAX_PRODUCTIVITY_WEIGHT};
+use crate::db::queries::focus::{build_focus_statistics, FocusStatistics};
+use crate::db::queries::time_zone::{self, load_calendar_settings, load_time_zone, CalendarSettings};
 use chrono::{Datelike, Timelike};
+use chrono_tz::Tz;
 use db::error::Error;
+use db::tables::manual_time_block::{
+    covered_intervals, get_manual_time_blocks, uncovered_parts, ManualTimeBlock, ManualTimeMode,
+};
 use db::tables::{cat_regex, category, log, skipped_app};
-use log::get_logs;
+use log::{get_logs, get_logs_overlapping};
 use log::Log;
 use regex::Regex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use skipped_app::get_skipped_apps;
 use std::collections::{BTreeSet, HashMap};
 
@@ -21,6 +27,7 @@ pub struct CategoryStat {
     pub percentage: f64,
     pub percentage_change: Option<f64>,
     pub color: Option<String>,
+    pub productivity_weight: i32,
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -39,8 +46,34 @@ pub struct HourlyStat {
 
 #[derive(Serialize, Debug, Clone)]
 pub struct DayCategoryStat {
-    pub day: i32, // 0=Monday, 6=Sunday
+    pub day: i32,     // Position in the week, 0 = the configured first day
+    pub weekday: i32, // 0=Monday, 6=Sunday
+    pub category: String,
+    pub total_duration: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ProductivityContributor {
     pub category: String,
+    pub weight: i32,
+    pub total_duration: i64,
+    pub weighted_duration: i64, // total_duration * weight
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ProductivityScore {
+    pub score: f64, // -100 when all time has weight -2, 100 when all time has weight +2
+    pub weighted_duration: i64,
+    pub previous_score: Option<f64>,
+    pub score_change: Option<f64>, // Points vs previous period
+    pub top_positive: Vec<ProductivityContributor>,
+    pub top_negative: Vec<ProductivityContributor>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DayProductivity {
+    pub day_start: i64, // Unix timestamp
+    pub score: f64,
     pub total_duration: i64,
 }
 
@@ -61,6 +94,37 @@ pub struct WeekStatistics {
     pub average_time_active_days: f64,
     pub most_active_day: Option<(i64, i64)>, // (timestamp, duration)
     pub most_inactive_day: Option<(i64, i64)>, // (timestamp, duration)
+    pub productivity: ProductivityScore,
+    pub daily_productivity: Vec<DayProductivity>,
+    pub focus: FocusStatistics,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RangeBucket {
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BucketStat {
+    pub start: i64,
+    pub end: i64, // Inclusive
+    pub total_time: i64,
+    pub categories: Vec<CategoryStat>,
+    pub productivity_score: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RangeStatistics {
+    pub range_start: i64,
+    pub range_end: i64,
+    pub bucket: RangeBucket,
+    pub previous_start: i64, // Previous equivalent period used for the *_change fields
+    pub previous_end: i64,
+    pub statistics: WeekStatistics,
+    pub buckets: Vec<BucketStat>,
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -69,12 +133,19 @@ pub struct DayStatistics {
     pub categories: Vec<CategoryStat>,
     pub top_apps: Vec<AppStat>,
     pub hourly_distribution: Vec<HourlyStat>,
+    pub productivity: ProductivityScore,
+    pub focus: FocusStatistics,
 }
 
+const TOP_PRODUCTIVITY_CONTRIBUTORS: usize = 3;
+const MAX_RANGE_BUCKETS: usize = 5000;
+
 struct CachedCategoryRegex {
     regex: Regex,
     category: String,
     priority: i32,
+    effective_from: Option<i64>,
+    effective_until: Option<i64>,
 }
 
 fn build_app_stats(logs: &[Log], app_groups: &[CachedAppGroup]) -> Vec<AppStat> {
@@ -113,31 +184,247 @@ fn app_duration_map(logs: &[Log], app_groups: &[CachedAppGroup]) -> HashMap<Stri
     durations
 }
 
-fn derive_category(app: &str, regexes: &[CachedCategoryRegex]) -> String {
+fn derive_category(app: &str, timestamp: i64, regexes: &[CachedCategoryRegex]) -> String {
     if regexes.is_empty() {
         return "Miscellaneous".to_string();
     }
 
     regexes
         .iter()
+        .filter(|regex| is_effective_at(regex.effective_from, regex.effective_until, timestamp))
         .find(|regex| regex.regex.is_match(app))
         .map(|regex| regex.category.clone())
         .unwrap_or_else(|| "Miscellaneous".to_string())
 }
 
+/// Derived categories per app, kept separately for each stretch of time between the moments a
+/// dated rule starts or stops applying, since the same rules are in effect throughout one.
+struct CategoryCache {
+    boundaries: Vec<i64>,
+    windows: HashMap<usize, HashMap<String, String>>,
+}
+
+impl CategoryCache {
+    fn new(regexes: &[CachedCategoryRegex]) -> Self {
+        let mut boundaries: Vec<i64> = regexes
+            .iter()
+            .flat_map(|regex| [regex.effective_from, regex.effective_until])
+            .flatten()
+            .collect();
+        boundaries.sort_unstable();
+        boundaries.dedup();
+        Self {
+            boundaries,
+            windows: HashMap::new(),
+        }
+    }
+}
+
 fn derive_category_cached(
     app: &str,
+    timestamp: i64,
     regexes: &[CachedCategoryRegex],
-    cache: &mut HashMap<String, String>,
+    cache: &mut CategoryCache,
 ) -> String {
-    if let Some(category) = cache.get(app) {
+    let window = cache
+        .boundaries
+        .partition_point(|boundary| *boundary <= timestamp);
+    let categories = cache.windows.entry(window).or_default();
+    if let Some(category) = categories.get(app) {
         return category.clone();
     }
-    let category = derive_category(app, regexes);
-    cache.insert(app.to_string(), category.clone());
+    let category = derive_category(app, timestamp, regexes);
+    categories.insert(app.to_string(), category.clone());
     category
 }
 
+fn productivity_weights(categories: &[Category]) -> HashMap<String, i32> {
+    categories
+        .iter()
+        .map(|cat| (cat.name.clone(), cat.productivity_weight))
+        .collect()
+}
+
+/// Returns the score and the weighted duration for a set of per-category durations.
+fn productivity_score_value(
+    category_durations: &HashMap<String, i64>,
+    weights: &HashMap<String, i32>,
+) -> (f64, i64) {
+    let total: i64 = category_durations.values().sum();
+    let weighted: i64 = category_durations
+        .iter()
+        .map(|(category, duration)| duration * i64::from(weights.get(category).copied().unwrap_or(0)))
+        .sum();
+    if total == 0 {
+        return (0.0, weighted);
+    }
+    let score = weighted as f64 / (total as f64 * f64::from(MAX_PRODUCTIVITY_WEIGHT)) * 100.0;
+    (score, weighted)
+}
+
+fn build_productivity_score(
+    category_durations: &HashMap<String, i64>,
+    previous_durations: Option<&HashMap<String, i64>>,
+    weights: &HashMap<String, i32>,
+) -> ProductivityScore {
+    let (score, weighted_duration) = productivity_score_value(category_durations, weights);
+    let previous_score = previous_durations
+        .filter(|durations| durations.values().sum::<i64>() > 0)
+        .map(|durations| productivity_score_value(durations, weights).0);
+
+    let mut contributors: Vec<ProductivityContributor> = category_durations
+        .iter()
+        .map(|(category, &total_duration)| {
+            let weight = weights.get(category).copied().unwrap_or(0);
+            ProductivityContributor {
+                category: category.clone(),
+                weight,
+                total_duration,
+                weighted_duration: total_duration * i64::from(weight),
+            }
+        })
+        .filter(|contributor| contributor.weighted_duration != 0)
+        .collect();
+    contributors.sort_by(|left, right| {
+        right
+            .weighted_duration
+            .cmp(&left.weighted_duration)
+            .then_with(|| left.category.cmp(&right.category))
+    });
+
+    let top_positive = contributors
+        .iter()
+        .filter(|contributor| contributor.weighted_duration > 0)
+        .take(TOP_PRODUCTIVITY_CONTRIBUTORS)
+        .cloned()
+        .collect();
+    let top_negative = contributors
+        .iter()
+        .rev()
+        .filter(|contributor| contributor.weighted_duration < 0)
+        .take(TOP_PRODUCTIVITY_CONTRIBUTORS)
+        .cloned()
+        .collect();
+
+    ProductivityScore {
+        score,
+        weighted_duration,
+        previous_score,
+        score_change: previous_score.map(|previous| score - previous),
+        top_positive,
+        top_negative,
+    }
+}
+
+fn build_daily_productivity(
+    day_category_durations: &HashMap<i64, HashMap<String, i64>>,
+    weights: &HashMap<String, i32>,
+) -> Vec<DayProductivity> {
+    let mut days: Vec<DayProductivity> = day_category_durations
+        .iter()
+        .map(|(&day_start, durations)| DayProductivity {
+            day_start,
+            score: productivity_score_value(durations, weights).0,
+            total_duration: durations.values().sum(),
+        })
+        .collect();
+    days.sort_by_key(|day| day.day_start);
+    days
+}
+
+fn build_bucket_stat(
+    start: i64,
+    end: i64,
+    category_durations: &HashMap<String, i64>,
+    category_colors: &HashMap<String, Option<String>>,
+    weights: &HashMap<String, i32>,
+) -> BucketStat {
+    let total_time: i64 = category_durations.values().sum();
+    let mut categories: Vec<CategoryStat> = category_durations
+        .iter()
+        .map(|(category, &total_duration)| CategoryStat {
+            category: category.clone(),
+            total_duration,
+            percentage: if total_time > 0 {
+                (total_duration as f64 / total_time as f64) * 100.0
+            } else {
+                0.0
+            },
+            percentage_change: None,
+            color: category_colors.get(category).cloned().flatten(),
+            productivity_weight: weights.get(category).copied().unwrap_or(0),
+        })
+        .collect();
+    categories.sort_by_key(|stat| std::cmp::Reverse(stat.total_duration));
+    BucketStat {
+        start,
+        end,
+        total_time,
+        categories,
+        productivity_score: productivity_score_value(category_durations, weights).0,
+    }
+}
+
+/// Splits `[range_start, range_end]` at calendar day, week or month boundaries. The first and
+/// last buckets are clipped to the range.
+fn range_buckets(
+    tz: Tz,
+    range_start: i64,
+    range_end: i64,
+    bucket: RangeBucket,
+    calendar: CalendarSettings,
+) -> Result<Vec<(i64, i64)>, Error> {
+    let mut bounds = Vec::new();
+    let mut cursor = range_start;
+    while cursor <= range_end {
+        let next = match bucket {
+            RangeBucket::Day => {
+                time_zone::add_days(tz, time_zone::day_start(tz, cursor, calendar.start_hour), 1)
+            }
+            RangeBucket::Week => {
+                time_zone::week_bounds(tz, cursor, calendar.start_hour, calendar.week_start_day).1
+                    + 1
+            }
+            RangeBucket::Month => time_zone::add_months(
+                tz,
+                time_zone::month_start(tz, cursor, calendar.start_hour),
+                1,
+            ),
+        }
+        .max(cursor + 1);
+        bounds.push((cursor, (next - 1).min(range_end)));
+        if bounds.len() > MAX_RANGE_BUCKETS {
+            return Err(anyhow::anyhow!("Range is too long for {bucket:?} buckets").into());
+        }
+        cursor = next;
+    }
+    Ok(bounds)
+}
+
+/// How far back the previous equivalent period starts: the same number of months for monthly
+/// buckets and for weekly buckets over whole months, otherwise the same number of days.
+fn previous_period_shift(
+    tz: Tz,
+    range_start: i64,
+    range_end: i64,
+    bucket: RangeBucket,
+    start_hour: u32,
+) -> (PeriodShift, i32) {
+    let first = time_zone::calendar_date(tz, range_start, start_hour);
+    let last = time_zone::calendar_date(tz, range_end, start_hour);
+    let days = (last - first).num_days() + 1;
+    let months =
+        (last.year() - first.year()) * 12 + last.month() as i32 - first.month() as i32 + 1;
+    let whole_months = first.day() == 1 && last.succ_opt().is_some_and(|next| next.day() == 1);
+    let shift = match bucket {
+        RangeBucket::Day => PeriodShift::Days(days),
+        RangeBucket::Week if whole_months => PeriodShift::Months(months),
+        RangeBucket::Week => PeriodShift::Days(days),
+        RangeBucket::Month => PeriodShift::Months(months),
+    };
+    (shift, days as i32)
+}
+
 fn build_regex_table(
     categories: &[Category],
     cat_regex: &[CategoryRegex],
@@ -158,6 +445,8 @@ fn build_regex_table(
                 category: cat.name.clone(),
                 priority: cat.priority,
                 regex: compiled_regex,
+                effective_from: reg.effective_from,
+                effective_until: reg.effective_until,
             })
         })
         .collect::<Result<Vec<_>, Error>>()?;
@@ -166,57 +455,131 @@ fn build_regex_table(
     Ok(regex)
 }
 
-fn get_week_start(timestamp: i64) -> i64 {
-    use chrono::{Datelike, Local, TimeZone, Weekday};
-    let dt = Local.timestamp_opt(timestamp, 0).unwrap();
+/// Device marker for the logs that stand in for manual time blocks.
+const MANUAL_DEVICE_UUID: &str = "manual-time";
 
-    let days_from_monday = match dt.weekday() {
-        Weekday::Mon => 0,
-        Weekday::Tue => 1,
-        Weekday::Wed => 2,
-        Weekday::Thu => 3,
-        Weekday::Fri => 4,
-        Weekday::Sat => 5,
-        Weekday::Sun => 6,
-    };
+fn category_names(categories: &[Category]) -> HashMap<i32, String> {
+    categories
+        .iter()
+        .map(|category| (category.id, category.name.clone()))
+        .collect()
+}
 
-    let week_start = dt.date_naive().and_hms_opt(0, 0, 0).unwrap()
-        - chrono::Duration::days(days_from_monday as i64);
+/// The block's own category, or else the one its title matches when it starts, like a tracked app.
+fn manual_block_category(
+    block: &ManualTimeBlock,
+    names: &HashMap<i32, String>,
+    regexes: &[CachedCategoryRegex],
+) -> String {
+    block
+        .category_id
+        .and_then(|id| names.get(&id).cloned())
+        .unwrap_or_else(|| derive_category(&block.title, block.start_time, regexes))
+}
 
-    week_start.and_local_timezone(Local).unwrap().timestamp()
+/// Manual time blocks folded into statistics as logs named after the block.
+struct ManualTime {
+    blocks: Vec<ManualTimeBlock>,
+    categories: HashMap<i64, String>, // Block id to the name of its category
+    covered: Vec<(i64, i64)>,         // Tracked time to leave out, when blocks override it
 }
 
-fn get_day_start(timestamp: i64) -> i64 {
-    use chrono::{Local, TimeZone};
-    let dt = Local.timestamp_opt(timestamp, 0).unwrap();
+impl ManualTime {
+    async fn load(
+        categories: &[Category],
+        regexes: &[CachedCategoryRegex],
+        mode: ManualTimeMode,
+    ) -> Result<Self, Error> {
+        let blocks = match mode {
+            ManualTimeMode::Exclude => Vec::new(),
+            ManualTimeMode::Add | ManualTimeMode::Override => {
+                get_manual_time_blocks(i64::MIN, i64::MAX).await?
+            }
+        };
+        Ok(Self::new(blocks, categories, regexes, mode))
+    }
 
-    dt.date_naive()
-        .and_hms_opt(0, 0, 0)
-        .unwrap()
-        .and_local_timezone(Local)
-        .unwrap()
-        .timestamp()
-}
+    fn new(
+        blocks: Vec<ManualTimeBlock>,
+        categories: &[Category],
+        regexes: &[CachedCategoryRegex],
+        mode: ManualTimeMode,
+    ) -> Self {
+        let names = category_names(categories);
+        let categories = blocks
+            .iter()
+            .map(|block| (block.id, manual_block_category(block, &names, regexes)))
+            .collect();
+        let covered = match mode {
+            ManualTimeMode::Override => covered_intervals(&blocks),
+            ManualTimeMode::Exclude | ManualTimeMode::Add => Vec::new(),
+        };
+        Self {
+            blocks,
+            categories,
+            covered,
+        }
+    }
 
-fn get_day_of_week(timestamp: i64) -> i32 {
-    use chrono::{Datelike, Local, TimeZone, Weekday};
-    let dt = Local.timestamp_opt(timestamp, 0).unwrap();
+    /// `logs` with the blocks added, split at local hours so hourly and daily breakdowns see
+    /// where the time was spent. Overridden tracked time is cut out first.
+    fn apply(&self, logs: Vec<Log>, tz: Tz) -> Vec<Log> {
+        let mut result = Vec::with_capacity(logs.len() + self.blocks.len());
+        for log in logs {
+            if self.covered.is_empty() {
+                result.push(log);
+                continue;
+            }
+            for (timestamp, duration) in uncovered_parts(log.timestamp, log.duration, &self.covered)
+            {
+                result.push(Log {
+                    timestamp,
+                    duration,
+                    ..log.clone()
+                });
+            }
+        }
 
-    match dt.weekday() {
-        Weekday::Mon => 0,
-        Weekday::Tue => 1,
-        Weekday::Wed => 2,
-        Weekday::Thu => 3,
-        Weekday::Fri => 4,
-        Weekday::Sat => 5,
-        Weekday::Sun => 6,
+        for block in &self.blocks {
+            let mut start = block.start_time;
+            while start < block.end_time {
+                let local = time_zone::local_datetime(tz, start);
+                let into_hour = i64::from(local.minute() * 60 + local.second());
+                let end = (start + 3600 - into_hour).min(block.end_time);
+                result.push(Log {
+                    id: block.id,
+                    device_uuid: Some(MANUAL_DEVICE_UUID.to_string()),
+                    app: block.title.clone(),
+                    timestamp: start,
+                    duration: end - start,
+                    is_deleted: false,
+                });
+                start = end;
+            }
+        }
+        result.sort_by_key(|log| log.timestamp);
+        result
+    }
+
+    fn category(&self, log: &Log) -> Option<&str> {
+        if log.device_uuid.as_deref() != Some(MANUAL_DEVICE_UUID) {
+            return None;
+        }
+        self.categories.get(&log.id).map(String::as_str)
     }
 }
 
-fn get_hour(timestamp: i64) -> i32 {
-    use chrono::{Local, TimeZone, Timelike};
-    let dt = Local.timestamp_opt(timestamp, 0).unwrap();
-    dt.hour() as i32
+/// Logs of the selected devices, with manual time folded in. Manual blocks belong to no device,
+/// so they count whichever devices are selected.
+fn device_logs_with_manual_time(
+    logs: Vec<Log>,
+    device_uuids: Option<Vec<String>>,
+    local_uuid: Option<String>,
+    manual: &ManualTime,
+    tz: Tz,
+) -> Vec<Log> {
+    let logs = crate::db::tables::device::filter_logs_by_devices(logs, device_uuids, local_uuid);
+    manual.apply(logs, tz)
 }
 
 #[tauri::command]
@@ -224,8 +587,66 @@ pub async fn get_week_statistics(
     week_start: i64,
     week_end: i64,
     device_uuids: Option<Vec<String>>,
+    manual_time: Option<ManualTimeMode>,
 ) -> Result<WeekStatistics, Error> {
-    use chrono::{Local, TimeZone};
+    let period = StatsPeriod {
+        start: week_start,
+        end: week_end,
+        shift: PeriodShift::Days(7),
+        total_number_of_days: 7,
+    };
+    Ok(
+        period_statistics(&period, &[], device_uuids, manual_time.unwrap_or_default())
+            .await?
+            .statistics,
+    )
+}
+
+/// Distance back to the previous equivalent period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PeriodShift {
+    Days(i64),
+    Months(i32),
+}
+
+impl PeriodShift {
+    fn back(self, tz: Tz, timestamp: i64) -> i64 {
+        match self {
+            PeriodShift::Days(days) => time_zone::add_days(tz, timestamp, -days),
+            PeriodShift::Months(months) => time_zone::add_months(tz, timestamp, -months),
+        }
+    }
+}
+
+struct StatsPeriod {
+    start: i64,
+    end: i64, // Inclusive
+    shift: PeriodShift,
+    total_number_of_days: i32,
+}
+
+impl StatsPeriod {
+    fn previous_start(&self, tz: Tz) -> i64 {
+        self.shift.back(tz, self.start)
+    }
+
+    fn previous_end(&self, tz: Tz) -> i64 {
+        self.shift.back(tz, self.end + 1) - 1
+    }
+}
+
+struct PeriodStatistics {
+    statistics: WeekStatistics,
+    buckets: Vec<BucketStat>,
+}
+
+async fn period_statistics(
+    period: &StatsPeriod,
+    bucket_bounds: &[(i64, i64)],
+    device_uuids: Option<Vec<String>>,
+    manual_time: ManualTimeMode,
+) -> Result<PeriodStatistics, Error> {
+    use chrono::Local;
 
     let local_uuid = crate::db::tables::device::get_local_log_device_uuid().await?;
     let mut logs = get_logs().await?;
@@ -241,22 +662,31 @@ pub async fn get_week_statistics(
 
     logs.retain(|log| !is_skipped(&log.app));
 
-    logs = crate::db::tables::device::filter_logs_by_devices(logs, device_uuids, local_uuid);
-
-    let cat_regex = get_cat_regex().await?;
+    let cat_regex = get_cat_regex_history().await?;
     let categories = get_categories().await?;
     let regex = build_regex_table(&categories, &cat_regex)?;
     let app_groups = build_app_group_matchers(&get_app_groups().await?)?;
+    let weights = productivity_weights(&categories);
+    let tz = load_time_zone().await?;
+    let manual = ManualTime::load(&categories, &regex, manual_time).await?;
+    logs = device_logs_with_manual_time(logs, device_uuids, local_uuid, &manual, tz);
+    let category_of = |log: &Log| {
+        manual
+            .category(log)
+            .map(str::to_string)
+            .unwrap_or_else(|| derive_category(&log.app, log.timestamp, &regex))
+    };
+    let week_start_day = load_calendar_settings().await?.week_start_day;
 
     let now = Local::now().timestamp();
-    let compare_end = week_end.min(now);
+    let compare_end = period.end.min(now);
 
-    let week_logs: Vec<Log> = logs
+    let range_logs: Vec<Log> = logs
         .into_iter()
-        .filter(|log| log.timestamp >= week_start && log.timestamp <= week_end)
+        .filter(|log| log.timestamp >= period.start && log.timestamp <= period.end)
         .collect();
 
-    let period_logs: Vec<Log> = week_logs
+    let period_logs: Vec<Log> = range_logs
         .iter()
         .filter(|log| log.timestamp <= compare_end)
         .cloned()
@@ -270,13 +700,14 @@ pub async fn get_week_statistics(
     }
 
     for log in &period_logs {
-        let category = derive_category(&log.app, &regex);
+        let category = category_of(log);
         *category_durations.entry(category).or_insert(0) += log.duration;
     }
 
     let total_time: i64 = category_durations.values().sum();
 
     let mut category_stats: Vec<CategoryStat> = category_durations
+        .clone()
         .into_iter()
         .map(|(category, total_duration)| {
             let percentage = if total_time > 0 {
@@ -290,6 +721,7 @@ pub async fn get_week_statistics(
                 percentage,
                 percentage_change: None, // Will calculate later if needed
                 color: category_colors.get(&category).cloned().flatten(),
+                productivity_weight: weights.get(&category).copied().unwrap_or(0),
             }
         })
         .collect();
@@ -302,7 +734,7 @@ pub async fn get_week_statistics(
 
     let mut hourly_durations: HashMap<i32, i64> = HashMap::new();
     for log in &period_logs {
-        let hour = get_hour(log.timestamp);
+        let hour = time_zone::hour_of_day(tz, log.timestamp);
         *hourly_durations.entry(hour).or_insert(0) += log.duration;
     }
 
@@ -315,24 +747,37 @@ pub async fn get_week_statistics(
 
     let mut day_category_durations: HashMap<(i32, String), i64> = HashMap::new();
     for log in &period_logs {
-        let day = get_day_of_week(log.timestamp);
-        let category = derive_category(&log.app, &regex);
+        let day = time_zone::day_of_week(tz, log.timestamp);
+        let category = category_of(log);
         *day_category_durations.entry((day, category)).or_insert(0) += log.duration;
     }
 
-    let day_category_breakdown: Vec<DayCategoryStat> = day_category_durations
+    let mut day_category_breakdown: Vec<DayCategoryStat> = day_category_durations
         .into_iter()
-        .map(|((day, category), total_duration)| DayCategoryStat {
-            day,
+        .map(|((weekday, category), total_duration)| DayCategoryStat {
+            day: (weekday - week_start_day as i32).rem_euclid(7),
+            weekday,
             category,
             total_duration,
         })
         .collect();
+    day_category_breakdown.sort_by(|left, right| {
+        left.day
+            .cmp(&right.day)
+            .then_with(|| left.category.cmp(&right.category))
+    });
 
     let mut day_totals: HashMap<i64, i64> = HashMap::new();
+    let mut day_start_category_durations: HashMap<i64, HashMap<String, i64>> = HashMap::new();
     for log in &period_logs {
-        let day_start = get_day_start(log.timestamp);
+        let day_start = time_zone::day_start(tz, log.timestamp, 0);
         *day_totals.entry(day_start).or_insert(0) += log.duration;
+        let category = category_of(log);
+        *day_start_category_durations
+            .entry(day_start)
+            .or_default()
+            .entry(category)
+            .or_insert(0) += log.duration;
     }
 
     let active_days: Vec<i64> = day_totals.keys().copied().collect();
@@ -349,15 +794,18 @@ pub async fn get_week_statistics(
         .map(|(&timestamp, &duration)| (timestamp, duration));
 
     let all_logs = get_logs().await?;
-    let all_logs_filtered: Vec<Log> = all_logs
-        .into_iter()
-        .filter(|log| !is_skipped(&log.app))
-        .collect();
+    let all_logs_filtered: Vec<Log> = manual.apply(
+        all_logs
+            .into_iter()
+            .filter(|log| !is_skipped(&log.app))
+            .collect(),
+        tz,
+    );
 
     let total_time_all_time: i64 = all_logs_filtered.iter().map(|log| log.duration).sum();
 
-    let today_start = get_day_start(Local::now().timestamp());
-    let today_end = today_start + 86400; // 24 hours
+    let today_start = time_zone::day_start(tz, Local::now().timestamp(), 0);
+    let today_end = time_zone::add_days(tz, today_start, 1);
     let all_time_today: i64 = all_logs_filtered
         .iter()
         .filter(|log| log.timestamp >= today_start && log.timestamp < today_end)
@@ -370,16 +818,20 @@ pub async fn get_week_statistics(
         0.0
     };
 
-    let prev_week_start = week_start - 7 * 86400;
-    let prev_compare_end = prev_week_start + (compare_end - week_start);
-    let prev_week_logs: Vec<Log> = all_logs_filtered
+    let prev_start = period.previous_start(tz);
+    let prev_compare_end = if compare_end >= period.end {
+        period.previous_end(tz)
+    } else {
+        period.shift.back(tz, compare_end)
+    };
+    let prev_logs: Vec<Log> = all_logs_filtered
         .into_iter()
-        .filter(|log| log.timestamp >= prev_week_start && log.timestamp <= prev_compare_end)
+        .filter(|log| log.timestamp >= prev_start && log.timestamp <= prev_compare_end)
         .collect();
 
-    let prev_week_total: i64 = prev_week_logs.iter().map(|log| log.duration).sum();
-    let total_time_change = if prev_week_total > 0 {
-        Some(((total_time as f64 - prev_week_total as f64) / prev_week_total as f64) * 100.0)
+    let prev_total: i64 = prev_logs.iter().map(|log| log.duration).sum();
+    let total_time_change = if prev_total > 0 {
+        Some(((total_time as f64 - prev_total as f64) / prev_total as f64) * 100.0)
     } else if total_time > 0 {
         Some(100.0)
     } else {
@@ -387,8 +839,8 @@ pub async fn get_week_statistics(
     };
 
     let mut prev_category_durations: HashMap<String, i64> = HashMap::new();
-    for log in &prev_week_logs {
-        let category = derive_category(&log.app, &regex);
+    for log in &prev_logs {
+        let category = category_of(log);
         *prev_category_durations.entry(category).or_insert(0) += log.duration;
     }
 
@@ -407,7 +859,14 @@ pub async fn get_week_statistics(
         }
     }
 
-    let prev_app_durations = app_duration_map(&prev_week_logs, &app_groups);
+    let productivity = build_productivity_score(
+        &category_durations,
+        Some(&prev_category_durations),
+        &weights,
+    );
+    let daily_productivity = build_daily_productivity(&day_start_category_durations, &weights);
+
+    let prev_app_durations = app_duration_map(&prev_logs, &app_groups);
 
     for app_stat in &mut top_apps {
         let prev_duration = *prev_app_durations.get(&app_stat.app).unwrap_or(&0i64);
@@ -433,7 +892,22 @@ pub async fn get_week_statistics(
         }
     }
 
-    Ok(WeekStatistics {
+    let buckets = bucket_bounds
+        .iter()
+        .map(|&(start, end)| {
+            let mut durations: HashMap<String, i64> = HashMap::new();
+            for log in period_logs
+                .iter()
+                .filter(|log| log.timestamp >= start && log.timestamp <= end)
+            {
+                let category = category_of(log);
+                *durations.entry(category).or_insert(0) += log.duration;
+            }
+            build_bucket_stat(start, end, &durations, &category_colors, &weights)
+        })
+        .collect();
+
+    let statistics = WeekStatistics {
         total_time,
         total_time_change,
         categories: category_stats,
@@ -443,18 +917,154 @@ pub async fn get_week_statistics(
         day_category_breakdown,
         first_active_day,
         number_of_active_days,
-        total_number_of_days: 7,
+        total_number_of_days: period.total_number_of_days,
         all_time_today,
         total_time_all_time,
         average_time_active_days,
         most_active_day,
         most_inactive_day,
+        productivity,
+        daily_productivity,
+        focus: build_focus_statistics(&period_logs, category_of, tz),
+    };
+
+    Ok(PeriodStatistics { statistics, buckets })
+}
+
+/// A tracked log resolved to its category and app group, for queries layered on statistics.
+#[derive(Debug, Clone)]
+pub(crate) struct LabelledLog {
+    pub timestamp: i64,
+    pub duration: i64,
+    pub category: String,
+    pub app: String,   // App group name, or the raw app when no group matches
+    pub title: String, // Raw window title as logged
+}
+
+/// Resolves logs to their category and app group with the rules in effect, caching per app.
+pub(crate) struct LogLabeller {
+    skipped_regexes: Vec<Regex>,
+    category_regexes: Vec<CachedCategoryRegex>,
+    app_groups: Vec<CachedAppGroup>,
+    category_cache: CategoryCache,
+    category_names: HashMap<i32, String>,
+}
+
+impl LogLabeller {
+    pub async fn load() -> Result<Self, Error> {
+        let categories = get_categories().await?;
+        let category_regexes = build_regex_table(&categories, &get_cat_regex_history().await?)?;
+        Ok(Self {
+            skipped_regexes: get_skipped_apps()
+                .await?
+                .iter()
+                .filter_map(|app| Regex::new(&app.regex).ok())
+                .collect(),
+            category_cache: CategoryCache::new(&category_regexes),
+            category_regexes,
+            app_groups: build_app_group_matchers(&get_app_groups().await?)?,
+            category_names: category_names(&categories),
+        })
+    }
+
+    pub fn is_skipped(&self, app: &str) -> bool {
+        self.skipped_regexes.iter().any(|regex| regex.is_match(app))
+    }
+
+    pub fn label(&mut self, log: &Log) -> LabelledLog {
+        LabelledLog {
+            category: derive_category_cached(
+                &log.app,
+                log.timestamp,
+                &self.category_regexes,
+                &mut self.category_cache,
+            ),
+            app: resolve_app_group(&log.app, &self.app_groups).to_string(),
+            title: log.app.clone(),
+            timestamp: log.timestamp,
+            duration: log.duration,
+        }
+    }
+
+    /// Category a manual time block counts under, the same way statistics count it.
+    pub fn manual_category(&self, block: &ManualTimeBlock) -> String {
+        manual_block_category(block, &self.category_names, &self.category_regexes)
+    }
+}
+
+/// Non-skipped logs between `range_start` and `range_end` (inclusive), labelled the same way the
+/// statistics screens label them.
+pub(crate) async fn labelled_logs(
+    range_start: i64,
+    range_end: i64,
+    device_uuids: Option<Vec<String>>,
+) -> Result<Vec<LabelledLog>, Error> {
+    let mut logs = labelled_logs_overlapping(range_start, range_end, device_uuids).await?;
+    logs.retain(|log| log.timestamp >= range_start);
+    Ok(logs)
+}
+
+/// Like [`labelled_logs`], but also keeps logs that start before `range_start` and run into it,
+/// for callers that clip each log to the range.
+pub(crate) async fn labelled_logs_overlapping(
+    range_start: i64,
+    range_end: i64,
+    device_uuids: Option<Vec<String>>,
+) -> Result<Vec<LabelledLog>, Error> {
+    let local_uuid = crate::db::tables::device::get_local_log_device_uuid().await?;
+    let mut logs = get_logs_overlapping(range_start, range_end).await?;
+    let mut labeller = LogLabeller::load().await?;
+
+    logs.retain(|log| !labeller.is_skipped(&log.app));
+    logs = crate::db::tables::device::filter_logs_by_devices(logs, device_uuids, local_uuid);
+
+    Ok(logs.iter().map(|log| labeller.label(log)).collect())
+}
+
+#[tauri::command]
+pub async fn get_range_statistics(
+    range_start: i64,
+    range_end: i64,
+    bucket: RangeBucket,
+    device_uuids: Option<Vec<String>>,
+    manual_time: Option<ManualTimeMode>,
+) -> Result<RangeStatistics, Error> {
+    if range_end < range_start {
+        return Err(anyhow::anyhow!("Range end must not be before range start").into());
+    }
+    let tz = load_time_zone().await?;
+    let calendar = load_calendar_settings().await?;
+    let bucket_bounds = range_buckets(tz, range_start, range_end, bucket, calendar)?;
+    let (shift, total_number_of_days) =
+        previous_period_shift(tz, range_start, range_end, bucket, calendar.start_hour);
+    let period = StatsPeriod {
+        start: range_start,
+        end: range_end,
+        shift,
+        total_number_of_days,
+    };
+    let result = period_statistics(
+        &period,
+        &bucket_bounds,
+        device_uuids,
+        manual_time.unwrap_or_default(),
+    )
+    .await?;
+
+    Ok(RangeStatistics {
+        range_start,
+        range_end,
+        bucket,
+        previous_start: period.previous_start(tz),
+        previous_end: period.previous_end(tz),
+        statistics: result.statistics,
+        buckets: result.buckets,
     })
 }
 
 #[tauri::command]
 pub async fn get_total_statistics() -> Result<WeekStatistics, Error> {
-    use chrono::{Local, TimeZone};
+    use chrono::Local;
 
     let mut logs = get_logs().await?;
     let skipped_apps = get_skipped_apps().await?;
@@ -468,10 +1078,13 @@ pub async fn get_total_statistics() -> Result<WeekStatistics, Error> {
 
     logs.retain(|log| !is_skipped(&log.app));
 
-    let cat_regex = get_cat_regex().await?;
+    let cat_regex = get_cat_regex_history().await?;
     let categories = get_categories().await?;
     let regex = build_regex_table(&categories, &cat_regex)?;
     let app_groups = build_app_group_matchers(&get_app_groups().await?)?;
+    let weights = productivity_weights(&categories);
+    let tz = load_time_zone().await?;
+    let week_start_day = load_calendar_settings().await?.week_start_day;
 
     let mut category_durations: HashMap<String, i64> = HashMap::new();
     let mut category_colors: HashMap<String, Option<String>> = HashMap::new();
@@ -483,26 +1096,33 @@ pub async fn get_total_statistics() -> Result<WeekStatistics, Error> {
     let mut hourly_durations: HashMap<i32, i64> = HashMap::new();
     let mut day_category_durations: HashMap<(i32, String), i64> = HashMap::new();
     let mut day_totals: HashMap<i64, i64> = HashMap::new();
-    let mut app_category_cache: HashMap<String, String> = HashMap::new();
+    let mut day_start_category_durations: HashMap<i64, HashMap<String, i64>> = HashMap::new();
+    let mut app_category_cache = CategoryCache::new(&regex);
 
     for log in &logs {
-        let category = derive_category_cached(&log.app, &regex, &mut app_category_cache);
+        let category = derive_category_cached(&log.app, log.timestamp, &regex, &mut app_category_cache);
         *category_durations.entry(category.clone()).or_insert(0) += log.duration;
-        let hour = get_hour(log.timestamp);
+        let hour = time_zone::hour_of_day(tz, log.timestamp);
         *hourly_durations.entry(hour).or_insert(0) += log.duration;
 
-        let day = get_day_of_week(log.timestamp);
+        let day = time_zone::day_of_week(tz, log.timestamp);
         *day_category_durations
             .entry((day, category.clone()))
             .or_insert(0) += log.duration;
 
-        let day_start = get_day_start(log.timestamp);
+        let day_start = time_zone::day_start(tz, log.timestamp, 0);
         *day_totals.entry(day_start).or_insert(0) += log.duration;
+        *day_start_category_durations
+            .entry(day_start)
+            .or_default()
+            .entry(category)
+            .or_insert(0) += log.duration;
     }
 
     let total_time: i64 = category_durations.values().sum();
 
     let mut category_stats: Vec<CategoryStat> = category_durations
+        .clone()
         .into_iter()
         .map(|(category, total_duration)| {
             let percentage = if total_time > 0 {
@@ -516,6 +1136,7 @@ pub async fn get_total_statistics() -> Result<WeekStatistics, Error> {
                 percentage,
                 percentage_change: None,
                 color: category_colors.get(&category).cloned().flatten(),
+                productivity_weight: weights.get(&category).copied().unwrap_or(0),
             }
         })
         .collect();
@@ -533,14 +1154,20 @@ pub async fn get_total_statistics() -> Result<WeekStatistics, Error> {
         })
         .collect();
 
-    let day_category_breakdown: Vec<DayCategoryStat> = day_category_durations
+    let mut day_category_breakdown: Vec<DayCategoryStat> = day_category_durations
         .into_iter()
-        .map(|((day, category), total_duration)| DayCategoryStat {
-            day,
+        .map(|((weekday, category), total_duration)| DayCategoryStat {
+            day: (weekday - week_start_day as i32).rem_euclid(7),
+            weekday,
             category,
             total_duration,
         })
         .collect();
+    day_category_breakdown.sort_by(|left, right| {
+        left.day
+            .cmp(&right.day)
+            .then_with(|| left.category.cmp(&right.category))
+    });
 
     let first_active_day = day_totals.keys().min().copied();
     let most_active_day = day_totals
@@ -554,12 +1181,16 @@ pub async fn get_total_statistics() -> Result<WeekStatistics, Error> {
 
     let number_of_active_days = day_totals.len() as i32;
     let total_number_of_days = match (day_totals.keys().min().copied(), day_totals.keys().max().copied()) {
-        (Some(min_ts), Some(max_ts)) => ((max_ts - min_ts) / 86400 + 1) as i32,
+        (Some(min_ts), Some(max_ts)) => {
+            let first = time_zone::calendar_date(tz, min_ts, 0);
+            let last = time_zone::calendar_date(tz, max_ts, 0);
+            ((last - first).num_days() + 1) as i32
+        }
         _ => 0,
     };
 
-    let today_start = get_day_start(Local::now().timestamp());
-    let today_end = today_start + 86400;
+    let today_start = time_zone::day_start(tz, Local::now().timestamp(), 0);
+    let today_end = time_zone::add_days(tz, today_start, 1);
     let all_time_today: i64 = logs
         .iter()
         .filter(|log| log.timestamp >= today_start && log.timestamp < today_end)
@@ -588,6 +1219,13 @@ pub async fn get_total_statistics() -> Result<WeekStatistics, Error> {
         average_time_active_days,
         most_active_day,
         most_inactive_day,
+        productivity: build_productivity_score(&category_durations, None, &weights),
+        daily_productivity: build_daily_productivity(&day_start_category_durations, &weights),
+        focus: build_focus_statistics(
+            &logs,
+            |log| derive_category_cached(&log.app, log.timestamp, &regex, &mut app_category_cache),
+            tz,
+        ),
     })
 }
 
@@ -596,6 +1234,7 @@ pub async fn get_day_statistics(
     day_start: i64,
     day_end: i64,
     device_uuids: Option<Vec<String>>,
+    manual_time: Option<ManualTimeMode>,
 ) -> Result<DayStatistics, Error> {
     let local_uuid = crate::db::tables::device::get_local_log_device_uuid().await?;
     let mut logs = get_logs().await?;
@@ -611,12 +1250,31 @@ pub async fn get_day_statistics(
 
     logs.retain(|log| !is_skipped(&log.app));
 
-    logs = crate::db::tables::device::filter_logs_by_devices(logs, device_uuids, local_uuid);
-
-    let cat_regex = get_cat_regex().await?;
+    let cat_regex = get_cat_regex_history().await?;
     let categories = get_categories().await?;
     let regex = build_regex_table(&categories, &cat_regex)?;
     let app_groups = build_app_group_matchers(&get_app_groups().await?)?;
+    let weights = productivity_weights(&categories);
+    let tz = load_time_zone().await?;
+    let manual = ManualTime::load(&categories, &regex, manual_time.unwrap_or_default()).await?;
+    logs = device_logs_with_manual_time(logs, device_uuids, local_uuid, &manual, tz);
+    let category_of = |log: &Log| {
+        manual
+            .category(log)
+            .map(str::to_string)
+            .unwrap_or_else(|| derive_category(&log.app, log.timestamp, &regex))
+    };
+
+    let prev_day_start = time_zone::add_days(tz, day_start, -1);
+    let prev_day_end = time_zone::add_days(tz, day_end, -1);
+    let mut prev_category_durations: HashMap<String, i64> = HashMap::new();
+    for log in logs
+        .iter()
+        .filter(|log| log.timestamp >= prev_day_start && log.timestamp <= prev_day_end)
+    {
+        let category = category_of(log);
+        *prev_category_durations.entry(category).or_insert(0) += log.duration;
+    }
 
     let day_logs: Vec<Log> = logs
         .into_iter()
@@ -631,13 +1289,14 @@ pub async fn get_day_statistics(
     }
 
     for log in &day_logs {
-        let category = derive_category(&log.app, &regex);
+        let category = category_of(log);
         *category_durations.entry(category).or_insert(0) += log.duration;
     }
 
     let total_time: i64 = category_durations.values().sum();
 
     let mut category_stats: Vec<CategoryStat> = category_durations
+        .clone()
         .into_iter()
         .map(|(category, total_duration)| {
             let percentage = if total_time > 0 {
@@ -651,6 +1310,7 @@ pub async fn get_day_statistics(
                 percentage,
                 percentage_change: None,
                 color: category_colors.get(&category).cloned().flatten(),
+                productivity_weight: weights.get(&category).copied().unwrap_or(0),
             }
         })
         .collect();
@@ -662,7 +1322,7 @@ pub async fn get_day_statistics(
 
     let mut hourly_durations: HashMap<i32, i64> = HashMap::new();
     for log in &day_logs {
-        let hour = get_hour(log.timestamp);
+        let hour = time_zone::hour_of_day(tz, log.timestamp);
         *hourly_durations.entry(hour).or_insert(0) += log.duration;
     }
 
@@ -673,11 +1333,19 @@ pub async fn get_day_statistics(
         })
         .collect();
 
+    let productivity = build_productivity_score(
+        &category_durations,
+        Some(&prev_category_durations),
+        &weights,
+    );
+
     Ok(DayStatistics {
         total_time,
         categories: category_stats,
         top_apps,
         hourly_distribution,
+        productivity,
+        focus: build_focus_statistics(&day_logs, category_of, tz),
     })
 }
 
@@ -720,3 +1388,308 @@ mod app_group_statistics_tests {
         assert_eq!(stats[0].app_names.len(), 6);
     }
 }
+
+#[cfg(test)]
+mod category_cache_tests {
+    use super::*;
+
+    #[test]
+    fn cached_categories_follow_dated_rules() {
+        let rule = |pattern: &str, category: &str, from: Option<i64>, until: Option<i64>| {
+            CachedCategoryRegex {
+                regex: Regex::new(pattern).unwrap(),
+                category: category.into(),
+                priority: 0,
+                effective_from: from,
+                effective_until: until,
+            }
+        };
+        let regexes = vec![
+            rule("Editor", "Writing", None, Some(1_000)),
+            rule("Editor", "Coding", Some(1_000), None),
+        ];
+        let mut cache = CategoryCache::new(&regexes);
+        let categories: Vec<String> = [500, 999, 1_000, 5_000, 10]
+            .into_iter()
+            .map(|timestamp| derive_category_cached("Editor", timestamp, &regexes, &mut cache))
+            .collect();
+        assert_eq!(
+            categories,
+            vec!["Writing", "Writing", "Coding", "Coding", "Writing"]
+        );
+        assert_eq!(cache.windows.len(), 2);
+    }
+}
+
+#[cfg(test)]
+mod productivity_tests {
+    use super::*;
+
+    fn durations(entries: &[(&str, i64)]) -> HashMap<String, i64> {
+        entries
+            .iter()
+            .map(|(category, duration)| (category.to_string(), *duration))
+            .collect()
+    }
+
+    #[test]
+    fn score_trend_and_contributors_follow_category_weights() {
+        let weights: HashMap<String, i32> = [("Coding", 2), ("Reading", 1), ("Social", -2)]
+            .into_iter()
+            .map(|(name, weight)| (name.to_string(), weight))
+            .collect();
+        let current = durations(&[
+            ("Coding", 3600),
+            ("Reading", 1800),
+            ("Social", 1800),
+            ("Miscellaneous", 0),
+        ]);
+        let previous = durations(&[("Social", 3600)]);
+
+        let score = build_productivity_score(&current, Some(&previous), &weights);
+
+        // (2*3600 + 1800 - 2*1800) / (2 * 7200)
+        assert!((score.score - 37.5).abs() < 1e-9);
+        assert_eq!(score.weighted_duration, 5400);
+        assert_eq!(score.previous_score, Some(-100.0));
+        assert!((score.score_change.unwrap() - 137.5).abs() < 1e-9);
+        let positive: Vec<&str> = score
+            .top_positive
+            .iter()
+            .map(|contributor| contributor.category.as_str())
+            .collect();
+        assert_eq!(positive, vec!["Coding", "Reading"]);
+        assert_eq!(score.top_negative.len(), 1);
+        assert_eq!(score.top_negative[0].weighted_duration, -3600);
+    }
+
+    #[test]
+    fn empty_periods_have_no_trend() {
+        let score = build_productivity_score(&HashMap::new(), Some(&HashMap::new()), &HashMap::new());
+        assert_eq!(score.score, 0.0);
+        assert_eq!(score.previous_score, None);
+        assert_eq!(score.score_change, None);
+    }
+}
+
+#[cfg(test)]
+mod range_tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const CALENDAR: CalendarSettings = CalendarSettings {
+        start_hour: 0,
+        week_start_day: 0,
+    };
+
+    fn local(tz: Tz, year: i32, month: u32, day: u32) -> i64 {
+        tz.with_ymd_and_hms(year, month, day, 0, 0, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    #[test]
+    fn month_buckets_follow_calendar_months_across_dst() {
+        let tz = chrono_tz::Europe::London;
+        let start = local(tz, 2024, 2, 15);
+        let end = local(tz, 2024, 4, 10) - 1;
+        let buckets = range_buckets(tz, start, end, RangeBucket::Month, CALENDAR).unwrap();
+        assert_eq!(
+            buckets,
+            vec![
+                (start, local(tz, 2024, 3, 1) - 1),
+                (local(tz, 2024, 3, 1), local(tz, 2024, 4, 1) - 1),
+                (local(tz, 2024, 4, 1), end),
+            ]
+        );
+        // March 2024 lost an hour when the UK moved to BST.
+        assert_eq!(buckets[1].1 + 1 - buckets[1].0, 31 * 86400 - 3600);
+    }
+
+    #[test]
+    fn week_buckets_start_on_the_configured_day() {
+        let tz = chrono_tz::UTC;
+        let calendar = CalendarSettings {
+            start_hour: 0,
+            week_start_day: 6,
+        };
+        // Wednesday 2024-05-01 through Tuesday 2024-05-14.
+        let start = local(tz, 2024, 5, 1);
+        let end = local(tz, 2024, 5, 15) - 1;
+        let buckets = range_buckets(tz, start, end, RangeBucket::Week, calendar).unwrap();
+        assert_eq!(
+            buckets,
+            vec![
+                (start, local(tz, 2024, 5, 5) - 1),
+                (local(tz, 2024, 5, 5), local(tz, 2024, 5, 12) - 1),
+                (local(tz, 2024, 5, 12), end),
+            ]
+        );
+    }
+
+    #[test]
+    fn previous_period_matches_the_bucket_size() {
+        let tz = chrono_tz::UTC;
+        let march = (local(tz, 2024, 3, 1), local(tz, 2024, 4, 1) - 1);
+        let (shift, days) = previous_period_shift(tz, march.0, march.1, RangeBucket::Month, 0);
+        assert_eq!(shift, PeriodShift::Months(1));
+        assert_eq!(days, 31);
+        let period = StatsPeriod {
+            start: march.0,
+            end: march.1,
+            shift,
+            total_number_of_days: days,
+        };
+        assert_eq!(period.previous_start(tz), local(tz, 2024, 2, 1));
+        assert_eq!(period.previous_end(tz), local(tz, 2024, 3, 1) - 1);
+
+        let (shift, _) = previous_period_shift(tz, march.0, march.1, RangeBucket::Week, 0);
+        assert_eq!(shift, PeriodShift::Months(1));
+        let (shift, _) = previous_period_shift(tz, march.0, march.1, RangeBucket::Day, 0);
+        assert_eq!(shift, PeriodShift::Days(31));
+
+        let ten_days = (local(tz, 2024, 3, 4), local(tz, 2024, 3, 14) - 1);
+        let (shift, _) = previous_period_shift(tz, ten_days.0, ten_days.1, RangeBucket::Week, 0);
+        assert_eq!(shift, PeriodShift::Days(10));
+    }
+}
+
+#[cfg(test)]
+mod manual_time_tests {
+    use super::*;
+    use crate::db::tables::manual_time_block::Tags;
+    use chrono::TimeZone;
+
+    #[test]
+    fn manual_blocks_are_split_by_hour_and_can_replace_tracked_time() {
+        let tz = chrono_tz::Asia::Kolkata; // Half-hour offset, so hours are not UTC hours
+        let at = |hour: u32, minute: u32| {
+            tz.with_ymd_and_hms(2024, 5, 10, hour, minute, 0)
+                .unwrap()
+                .timestamp()
+        };
+        let block = |id: i64, start_time: i64, end_time: i64| ManualTimeBlock {
+            id,
+            title: "Client call".into(),
+            notes: None,
+            start_time,
+            end_time,
+            created_at: 0,
+            updated_at: 0,
+            project_id: None,
+            category_id: (id == 1).then_some(3),
+            tags: Tags::default(),
+        };
+        let category = Category {
+            id: 3,
+            name: "Meetings".into(),
+            priority: 0,
+            color: None,
+            regex_enabled: true,
+            is_visible: true,
+            in_stats: true,
+            is_collapsed: false,
+            productivity_weight: 0,
+        };
+        let regexes = vec![CachedCategoryRegex {
+            regex: Regex::new("call").unwrap(),
+            category: "Calls".into(),
+            priority: 0,
+            effective_from: None,
+            effective_until: None,
+        }];
+        let manual = |mode: ManualTimeMode| {
+            ManualTime::new(
+                vec![
+                    block(1, at(9, 30), at(11, 15)),
+                    block(2, at(12, 0), at(12, 30)),
+                ],
+                std::slice::from_ref(&category),
+                &regexes,
+                mode,
+            )
+        };
+        let tracked = Log {
+            id: 7,
+            device_uuid: Some("desktop".into()),
+            app: "Editor".into(),
+            timestamp: at(9, 0),
+            duration: 3_600,
+            is_deleted: false,
+        };
+        let spans = |logs: &[Log]| -> Vec<(String, i64, i64)> {
+            logs.iter()
+                .map(|log| (log.app.clone(), log.timestamp, log.duration))
+                .collect()
+        };
+
+        let overriding = manual(ManualTimeMode::Override);
+        let logs = overriding.apply(vec![tracked.clone()], tz);
+        assert_eq!(
+            spans(&logs),
+            vec![
+                ("Editor".into(), at(9, 0), 1_800),
+                ("Client call".into(), at(9, 30), 1_800),
+                ("Client call".into(), at(10, 0), 3_600),
+                ("Client call".into(), at(11, 0), 900),
+                ("Client call".into(), at(12, 0), 1_800),
+            ]
+        );
+        assert_eq!(overriding.category(&logs[1]), Some("Meetings"));
+        assert_eq!(overriding.category(&logs[4]), Some("Calls")); // No category, so the title decides
+        assert_eq!(overriding.category(&logs[0]), None);
+
+        let logs = manual(ManualTimeMode::Add).apply(vec![tracked.clone()], tz);
+        assert_eq!(
+            logs.iter().map(|log| log.duration).sum::<i64>(),
+            3_600 + 8_100
+        );
+        assert_eq!(spans(&logs)[0], ("Editor".into(), at(9, 0), 3_600));
+    }
+
+    #[test]
+    fn manual_time_counts_when_devices_are_filtered() {
+        let tz = chrono_tz::UTC;
+        let manual = ManualTime::new(
+            vec![ManualTimeBlock {
+                id: 1,
+                title: "Workshop".into(),
+                notes: None,
+                start_time: 0,
+                end_time: 1_800,
+                created_at: 0,
+                updated_at: 0,
+                project_id: None,
+                category_id: None,
+                tags: Tags::default(),
+            }],
+            &[],
+            &[],
+            ManualTimeMode::Add,
+        );
+        let log = |id: i64, device: &str| Log {
+            id,
+            device_uuid: Some(device.into()),
+            app: "Editor".into(),
+            timestamp: 3_600,
+            duration: 60,
+            is_deleted: false,
+        };
+
+        let logs = device_logs_with_manual_time(
+            vec![log(1, "laptop"), log(2, "phone")],
+            Some(vec!["laptop".into()]),
+            Some("laptop".into()),
+            &manual,
+            tz,
+        );
+        let kept: Vec<(i64, Option<&str>)> = logs
+            .iter()
+            .map(|log| (log.id, log.device_uuid.as_deref()))
+            .collect();
+        assert_eq!(
+            kept,
+            vec![(1, Some(MANUAL_DEVICE_UUID)), (1, Some("laptop"))]
+        );
+    }
+}
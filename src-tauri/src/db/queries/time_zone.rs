@@ -0,0 +1,249 @@
+use crate::db;
+use crate::db::error::Error;
+use crate::db::tables::app_metadata_kv::{
+    metadata_delete, metadata_get, metadata_set, META_TIME_ZONE,
+};
+use crate::db::tables::settings::get_settings;
+
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TimeZoneSetting {
+    pub configured: Option<String>, // None means follow the system zone
+    pub effective: String,
+    pub system: String,
+}
+
+pub fn system_time_zone() -> Tz {
+    iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|name| name.parse().ok())
+        .unwrap_or(Tz::UTC)
+}
+
+fn parse_time_zone(name: &str) -> Result<Tz, Error> {
+    name.trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Unknown time zone {name}").into())
+}
+
+/// The zone used for every day, week and hour bucket; falls back to the system zone.
+pub async fn load_time_zone() -> Result<Tz, Error> {
+    let pool = db::get_pool().await?;
+    let configured = metadata_get(&pool, META_TIME_ZONE).await?;
+    Ok(configured
+        .and_then(|name| name.parse().ok())
+        .unwrap_or_else(system_time_zone))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CalendarSettings {
+    pub start_hour: u32,     // Hour each calendar day begins at
+    pub week_start_day: u32, // 0 = Monday through 6 = Sunday
+}
+
+pub async fn load_calendar_settings() -> Result<CalendarSettings, Error> {
+    let settings = get_settings().await?;
+    let value = |key: &str, default: i32| {
+        settings
+            .iter()
+            .find(|setting| setting.key == key)
+            .map(|setting| setting.val)
+            .unwrap_or(default)
+    };
+    Ok(CalendarSettings {
+        start_hour: value("calendarStartHour", 8).clamp(0, 23) as u32,
+        week_start_day: value("weekStartDay", 0).clamp(0, 6) as u32,
+    })
+}
+
+#[tauri::command]
+pub async fn get_time_zone() -> Result<TimeZoneSetting, Error> {
+    let pool = db::get_pool().await?;
+    let configured = metadata_get(&pool, META_TIME_ZONE).await?;
+    let effective = load_time_zone().await?;
+    Ok(TimeZoneSetting {
+        configured,
+        effective: effective.name().to_string(),
+        system: system_time_zone().name().to_string(),
+    })
+}
+
+#[tauri::command]
+pub async fn set_time_zone(time_zone: Option<String>) -> Result<(), Error> {
+    let pool = db::get_pool().await?;
+    match time_zone.filter(|name| !name.trim().is_empty()) {
+        Some(name) => {
+            let tz = parse_time_zone(&name)?;
+            metadata_set(&pool, META_TIME_ZONE, tz.name()).await?;
+        }
+        None => metadata_delete(&pool, META_TIME_ZONE).await?,
+    }
+    Ok(())
+}
+
+pub fn local_datetime(tz: Tz, timestamp: i64) -> DateTime<Tz> {
+    DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .with_timezone(&tz)
+}
+
+/// Resolves a wall-clock time to a timestamp. Repeated times take the first occurrence and
+/// times skipped by a DST jump move forward to the first minute that exists.
+pub fn resolve_local(tz: Tz, naive: NaiveDateTime) -> i64 {
+    if let Some(dt) = tz.from_local_datetime(&naive).earliest() {
+        return dt.timestamp();
+    }
+    (1..=24 * 60)
+        .filter_map(|minutes| {
+            tz.from_local_datetime(&(naive + Duration::minutes(minutes)))
+                .earliest()
+        })
+        .map(|dt| dt.timestamp())
+        .next()
+        .unwrap_or_else(|| naive.and_utc().timestamp())
+}
+
+pub fn at_local_hour(tz: Tz, date: NaiveDate, hour: u32) -> i64 {
+    resolve_local(tz, date.and_hms_opt(hour.min(23), 0, 0).unwrap_or_default())
+}
+
+/// The calendar date a timestamp belongs to when days begin at `start_hour`.
+pub fn calendar_date(tz: Tz, timestamp: i64, start_hour: u32) -> NaiveDate {
+    let dt = local_datetime(tz, timestamp);
+    let date = dt.date_naive();
+    if dt.hour() >= start_hour {
+        date
+    } else {
+        date.pred_opt().unwrap_or(date)
+    }
+}
+
+pub fn day_start(tz: Tz, timestamp: i64, start_hour: u32) -> i64 {
+    at_local_hour(tz, calendar_date(tz, timestamp, start_hour), start_hour)
+}
+
+/// Moves a timestamp by whole local days, keeping its wall-clock time across DST changes.
+pub fn add_days(tz: Tz, timestamp: i64, days: i64) -> i64 {
+    let naive = local_datetime(tz, timestamp).naive_local();
+    resolve_local(tz, naive + Duration::days(days))
+}
+
+/// Moves a timestamp by whole local months, clamping to the end of shorter months.
+pub fn add_months(tz: Tz, timestamp: i64, months: i32) -> i64 {
+    let naive = local_datetime(tz, timestamp).naive_local();
+    let shifted = if months >= 0 {
+        naive.checked_add_months(Months::new(months as u32))
+    } else {
+        naive.checked_sub_months(Months::new(months.unsigned_abs()))
+    };
+    resolve_local(tz, shifted.unwrap_or(naive))
+}
+
+/// Start of the calendar month containing `timestamp`, with days beginning at `start_hour`.
+pub fn month_start(tz: Tz, timestamp: i64, start_hour: u32) -> i64 {
+    let date = calendar_date(tz, timestamp, start_hour);
+    at_local_hour(tz, date.with_day(1).unwrap_or(date), start_hour)
+}
+
+pub fn day_of_week(tz: Tz, timestamp: i64) -> i32 {
+    local_datetime(tz, timestamp)
+        .weekday()
+        .num_days_from_monday() as i32
+}
+
+/// Position of the timestamp's day within a week starting on `week_start_day` (0 = Monday).
+pub fn day_of_week_from(tz: Tz, timestamp: i64, week_start_day: u32) -> i32 {
+    (day_of_week(tz, timestamp) - week_start_day as i32).rem_euclid(7)
+}
+
+pub fn hour_of_day(tz: Tz, timestamp: i64) -> i32 {
+    local_datetime(tz, timestamp).hour() as i32
+}
+
+/// Week containing `anchor` that begins on `week_start_day` (0 = Monday), with days beginning
+/// at `start_hour`. The end is inclusive, one second before the next week starts.
+pub fn week_bounds(tz: Tz, anchor: i64, start_hour: u32, week_start_day: u32) -> (i64, i64) {
+    let date = calendar_date(tz, anchor, start_hour);
+    let days_into_week =
+        (date.weekday().num_days_from_monday() as i64 - week_start_day as i64).rem_euclid(7);
+    let first_day = date - Duration::days(days_into_week);
+    let week_start = at_local_hour(tz, first_day, start_hour);
+    let next_week_start = at_local_hour(tz, first_day + Duration::days(7), start_hour);
+    (week_start, next_week_start - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> i64 {
+        chrono::Utc
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    #[test]
+    fn spring_forward_week_is_an_hour_short() {
+        let tz = chrono_tz::America::New_York;
+        // Wednesday 2024-03-13; clocks went forward on Sunday 2024-03-10.
+        let (start, end) = week_bounds(tz, utc(2024, 3, 13, 16, 0), 5, 0);
+        assert_eq!(start, utc(2024, 3, 11, 9, 0));
+        assert_eq!(end, utc(2024, 3, 18, 9, 0) - 1);
+
+        let (start, end) = week_bounds(tz, utc(2024, 3, 8, 16, 0), 5, 0);
+        assert_eq!(start, utc(2024, 3, 4, 10, 0));
+        assert_eq!(end + 1 - start, 7 * 86400 - 3600);
+    }
+
+    #[test]
+    fn fall_back_day_is_an_hour_longer_and_repeats_an_hour() {
+        let tz = chrono_tz::America::New_York;
+        let sunday = NaiveDate::from_ymd_opt(2024, 11, 3).unwrap();
+        let start = at_local_hour(tz, sunday, 0);
+        let next = add_days(tz, start, 1);
+        assert_eq!(next - start, 25 * 3600);
+
+        // 01:30 happens twice that morning, once in EDT and once in EST.
+        assert_eq!(hour_of_day(tz, utc(2024, 11, 3, 5, 30)), 1);
+        assert_eq!(hour_of_day(tz, utc(2024, 11, 3, 6, 30)), 1);
+        assert_eq!(day_start(tz, utc(2024, 11, 3, 6, 30), 0), start);
+    }
+
+    #[test]
+    fn start_hour_inside_a_dst_gap_moves_forward() {
+        let tz = chrono_tz::America::New_York;
+        // 02:00 does not exist on 2024-03-10; the day begins at 03:00 EDT instead.
+        let start = day_start(tz, utc(2024, 3, 10, 12, 0), 2);
+        assert_eq!(start, utc(2024, 3, 10, 7, 0));
+    }
+
+    #[test]
+    fn start_hour_is_applied_in_the_configured_zone() {
+        let tz = chrono_tz::Europe::Berlin;
+        // 03:30 local on Monday 2024-07-08 still belongs to Sunday when days start at 05:00.
+        let anchor = utc(2024, 7, 8, 1, 30);
+        assert_eq!(day_of_week(tz, anchor), 0);
+        assert_eq!(
+            calendar_date(tz, anchor, 5),
+            NaiveDate::from_ymd_opt(2024, 7, 7).unwrap()
+        );
+        let (start, _) = week_bounds(tz, anchor, 5, 0);
+        assert_eq!(start, utc(2024, 7, 1, 3, 0));
+    }
+
+    #[test]
+    fn weeks_can_start_on_sunday() {
+        let tz = chrono_tz::Asia::Jerusalem;
+        // Thursday 2024-05-16 12:00 local (UTC+3); a Sunday-start week began on 2024-05-12.
+        let anchor = utc(2024, 5, 16, 9, 0);
+        let (start, end) = week_bounds(tz, anchor, 0, 6);
+        assert_eq!(start, utc(2024, 5, 11, 21, 0));
+        assert_eq!(end, utc(2024, 5, 18, 21, 0) - 1);
+        assert_eq!(day_of_week_from(tz, anchor, 6), 4);
+        assert_eq!(day_of_week_from(tz, start, 6), 0);
+    }
+}
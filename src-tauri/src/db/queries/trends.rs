@@ -0,0 +1,265 @@
+use crate::db::error::Error;
+use crate::db::queries::statistics::labelled_logs;
+use crate::db::queries::time_zone::{self, load_calendar_settings, load_time_zone};
+use crate::db::tables::category::get_categories;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+pub const MAX_TREND_DAYS: u32 = 731;
+const SHORT_ROLLING_WINDOW: usize = 7;
+const LONG_ROLLING_WINDOW: usize = 28;
+
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct TrendLine {
+    pub slope: f64,     // Seconds per day
+    pub intercept: f64, // Fitted seconds on the first day
+}
+
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Streaks {
+    pub current: i32,
+    pub longest: i32,
+    pub longest_start: Option<usize>, // Index into the series' days
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CategorySeries {
+    pub category: String,
+    pub color: Option<String>,
+    pub daily_totals: Vec<i64>,
+    pub rolling_7: Vec<f64>,
+    pub rolling_28: Vec<f64>,
+    pub streaks: Streaks,
+    pub trend: TrendLine,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TrendSeries {
+    pub days: Vec<i64>, // Start of each day, oldest first
+    pub min_daily_duration: i64,
+    pub total: CategorySeries, // Every category counted in stats
+    pub categories: Vec<CategorySeries>,
+}
+
+/// Averages over the trailing `window` days. `values` starts `lookback` days before the first
+/// day reported, so the earliest averages still cover a full window.
+pub fn rolling_average(values: &[i64], lookback: usize, window: usize) -> Vec<f64> {
+    (lookback..values.len())
+        .map(|index| {
+            let start = (index + 1).saturating_sub(window);
+            let slice = &values[start..=index];
+            slice.iter().sum::<i64>() as f64 / slice.len() as f64
+        })
+        .collect()
+}
+
+/// Runs of consecutive days with at least `min_daily_duration`. A last day that is still in
+/// progress does not break the current streak.
+pub fn streaks(
+    daily_totals: &[i64],
+    min_daily_duration: i64,
+    last_day_in_progress: bool,
+) -> Streaks {
+    let met = |total: i64| total >= min_daily_duration.max(1);
+    let mut result = Streaks::default();
+    let mut run = 0;
+    for (index, &total) in daily_totals.iter().enumerate() {
+        if met(total) {
+            run += 1;
+            if run > result.longest {
+                result.longest = run;
+                result.longest_start = Some(index + 1 - run as usize);
+            }
+        } else {
+            run = 0;
+        }
+    }
+
+    let mut days = daily_totals.iter().rev().peekable();
+    if last_day_in_progress && days.peek().is_some_and(|&&total| !met(total)) {
+        days.next();
+    }
+    result.current = days.take_while(|&&total| met(total)).count() as i32;
+    result
+}
+
+/// Least-squares line through the daily totals.
+pub fn linear_trend(daily_totals: &[i64]) -> TrendLine {
+    let count = daily_totals.len() as f64;
+    if daily_totals.is_empty() {
+        return TrendLine::default();
+    }
+    let mean_x = (count - 1.0) / 2.0;
+    let mean_y = daily_totals.iter().sum::<i64>() as f64 / count;
+    let (covariance, variance) = daily_totals.iter().enumerate().fold(
+        (0.0, 0.0),
+        |(covariance, variance), (index, &total)| {
+            let dx = index as f64 - mean_x;
+            (
+                covariance + dx * (total as f64 - mean_y),
+                variance + dx * dx,
+            )
+        },
+    );
+    let slope = if variance > 0.0 {
+        covariance / variance
+    } else {
+        0.0
+    };
+    TrendLine {
+        slope,
+        intercept: mean_y - slope * mean_x,
+    }
+}
+
+fn build_series(
+    category: String,
+    color: Option<String>,
+    totals_with_lookback: &[i64],
+    min_daily_duration: i64,
+    last_day_in_progress: bool,
+) -> CategorySeries {
+    let lookback = LONG_ROLLING_WINDOW - 1;
+    let daily_totals = totals_with_lookback[lookback..].to_vec();
+    CategorySeries {
+        category,
+        color,
+        rolling_7: rolling_average(totals_with_lookback, lookback, SHORT_ROLLING_WINDOW),
+        rolling_28: rolling_average(totals_with_lookback, lookback, LONG_ROLLING_WINDOW),
+        streaks: streaks(&daily_totals, min_daily_duration, last_day_in_progress),
+        trend: linear_trend(&daily_totals),
+        daily_totals,
+    }
+}
+
+/// Daily category totals for the `days` days ending on the day containing `range_end`, with
+/// rolling averages, streaks of days reaching `min_daily_duration` and a linear trend.
+#[tauri::command]
+pub async fn get_category_trends(
+    range_end: i64,
+    days: u32,
+    min_daily_duration: i64,
+    device_uuids: Option<Vec<String>>,
+) -> Result<TrendSeries, Error> {
+    if days == 0 || days > MAX_TREND_DAYS {
+        return Err(anyhow::anyhow!("Pick between 1 and {MAX_TREND_DAYS} days").into());
+    }
+    if min_daily_duration < 0 {
+        return Err(anyhow::anyhow!("The daily minimum cannot be negative").into());
+    }
+
+    let tz = load_time_zone().await?;
+    let start_hour = load_calendar_settings().await?.start_hour;
+    let lookback = LONG_ROLLING_WINDOW - 1;
+    let span = days as usize + lookback;
+    let last_day = time_zone::day_start(tz, range_end, start_hour);
+    let first_day = time_zone::add_days(tz, last_day, -(span as i64 - 1));
+    let next_day = time_zone::add_days(tz, last_day, 1);
+    let first_date = time_zone::calendar_date(tz, first_day, start_hour);
+
+    let categories = get_categories().await?;
+    let hidden: HashSet<&str> = categories
+        .iter()
+        .filter(|cat| !cat.in_stats)
+        .map(|cat| cat.name.as_str())
+        .collect();
+    let colors: HashMap<&str, Option<String>> = categories
+        .iter()
+        .map(|cat| (cat.name.as_str(), cat.color.clone()))
+        .collect();
+
+    let mut totals: HashMap<String, Vec<i64>> = HashMap::new();
+    for log in labelled_logs(first_day, next_day - 1, device_uuids).await? {
+        if hidden.contains(log.category.as_str()) {
+            continue;
+        }
+        let index = (time_zone::calendar_date(tz, log.timestamp, start_hour) - first_date)
+            .num_days() as usize;
+        if let Some(total) = totals
+            .entry(log.category)
+            .or_insert_with(|| vec![0; span])
+            .get_mut(index)
+        {
+            *total += log.duration;
+        }
+    }
+
+    let day_starts: Vec<i64> = (lookback..span)
+        .map(|offset| time_zone::add_days(tz, first_day, offset as i64))
+        .collect();
+    let last_day_in_progress = chrono::Utc::now().timestamp() < next_day;
+
+    let mut overall = vec![0; span];
+    for values in totals.values() {
+        for (sum, value) in overall.iter_mut().zip(values) {
+            *sum += value;
+        }
+    }
+
+    let mut series: Vec<CategorySeries> = totals
+        .into_iter()
+        .map(|(category, values)| {
+            let color = colors.get(category.as_str()).cloned().flatten();
+            build_series(
+                category,
+                color,
+                &values,
+                min_daily_duration,
+                last_day_in_progress,
+            )
+        })
+        .collect();
+    series.sort_by(|left, right| {
+        right
+            .daily_totals
+            .iter()
+            .sum::<i64>()
+            .cmp(&left.daily_totals.iter().sum::<i64>())
+            .then_with(|| left.category.cmp(&right.category))
+    });
+
+    Ok(TrendSeries {
+        days: day_starts,
+        min_daily_duration,
+        total: build_series(
+            "Total".to_string(),
+            None,
+            &overall,
+            min_daily_duration,
+            last_day_in_progress,
+        ),
+        categories: series,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolling_average_uses_the_lookback_days() {
+        let values = [10, 20, 30, 40];
+        assert_eq!(rolling_average(&values, 2, 3), vec![20.0, 30.0]);
+        // Without lookback the first windows are shorter.
+        assert_eq!(rolling_average(&values, 0, 2), vec![10.0, 15.0, 25.0, 35.0]);
+    }
+
+    #[test]
+    fn streaks_skip_an_unfinished_today() {
+        let totals = [600, 600, 0, 600, 600, 600, 100];
+        let result = streaks(&totals, 300, true);
+        assert_eq!(result.longest, 3);
+        assert_eq!(result.longest_start, Some(3));
+        assert_eq!(result.current, 3);
+
+        assert_eq!(streaks(&totals, 300, false).current, 0);
+    }
+
+    #[test]
+    fn trend_fits_a_straight_line() {
+        let trend = linear_trend(&[100, 200, 300, 400]);
+        assert!((trend.slope - 100.0).abs() < 1e-9);
+        assert!((trend.intercept - 100.0).abs() < 1e-9);
+        assert_eq!(linear_trend(&[500]).slope, 0.0);
+    }
+}
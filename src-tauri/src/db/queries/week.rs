@@ -3,13 +3,14 @@ use crate::db::error::Error;
 use crate::db::tables::app_group::{
     build_app_group_matchers, get_app_groups, resolve_app_group, CachedAppGroup,
 };
-use crate::db::tables::cat_regex::{get_cat_regex, CategoryRegex};
+use crate::db::tables::cat_regex::{get_cat_regex_history, is_effective_at, CategoryRegex};
 use crate::db::tables::category::{get_categories, Category};
 use crate::db::tables::log::{get_logs, mark_log_deleted, Log};
 use crate::db::tables::skipped_app::get_skipped_apps;
 use crate::db::tables::settings::get_settings;
+use crate::db::queries::time_zone::{self, load_time_zone};
 
-use chrono::{Datelike, Duration, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -104,15 +105,22 @@ struct CachedCategoryRegex {
     regex: Regex,
     category: String,
     priority: i32,
+    effective_from: Option<i64>,
+    effective_until: Option<i64>,
 }
 
-fn derive_category(app: &str, regexes: &[CachedCategoryRegex]) -> Result<String, Error> {
+fn derive_category(
+    app: &str,
+    timestamp: i64,
+    regexes: &[CachedCategoryRegex],
+) -> Result<String, Error> {
     if regexes.is_empty() {
         return Ok("Miscellaneous".to_string());
     }
 
     regexes
         .iter()
+        .filter(|regex| is_effective_at(regex.effective_from, regex.effective_until, timestamp))
         .find(|regex| regex.regex.is_match(app))
         .map(|regex| regex.category.clone())
         .ok_or_else(|| anyhow::anyhow!("Derive Category Error").into())
@@ -138,6 +146,8 @@ fn build_regex_table(
                 category: cat.name.clone(),
                 priority: cat.priority,
                 regex: compiled_regex,
+                effective_from: reg.effective_from,
+                effective_until: reg.effective_until,
             })
         })
         .collect::<Result<Vec<_>, Error>>()?;
@@ -181,13 +191,13 @@ fn get_time_blocks(
     time_blocks.push(TimeBlock::new(
         first,
         0,
-        derive_category(&first.app, regex)?,
+        derive_category(&first.app, first.timestamp, regex)?,
         app_groups,
     ));
 
     let mut time_block_index = 0;
     for log in &long_logs[1..] {
-        let log_cat = derive_category(&log.app, regex)?;
+        let log_cat = derive_category(&log.app, log.timestamp, regex)?;
         let log_end_time = log.timestamp + log.duration;
 
         if let Some(current_time_block) = time_blocks.get_mut(time_block_index) {
@@ -240,7 +250,7 @@ fn get_time_blocks(
             break;
         }
 
-        let short_log_cat = derive_category(&short_log.app, regex)?;
+        let short_log_cat = derive_category(&short_log.app, short_log.timestamp, regex)?;
 
         let mut best_match: Option<usize> = None;
         let mut min_distance = i64::MAX;
@@ -290,30 +300,27 @@ fn setting_val(rows: &HashMap<String, i32>, key: &str, default: i32) -> i64 {
     rows.get(key).copied().unwrap_or(default) as i64
 }
 
-async fn load_runtime_settings() -> Result<(i64, TimeBlockSettings), Error> {
+async fn load_runtime_settings() -> Result<(i64, u32, TimeBlockSettings), Error> {
     let settings = get_settings().await?;
     let rows: HashMap<String, i32> = settings.into_iter().map(|s| (s.key, s.val)).collect();
     let calendar_start_hour = setting_val(&rows, "calendarStartHour", 8).clamp(0, 23);
+    let week_start_day = setting_val(&rows, "weekStartDay", 0).clamp(0, 6) as u32;
     let time_block_settings = TimeBlockSettings {
         min_log_duration: setting_val(&rows, "minLogDuration", 1).max(1),
         max_attach_distance: setting_val(&rows, "maxAttachDistance", 400).max(0),
         lookahead_window: setting_val(&rows, "lookaheadWindow", 500).max(0),
         min_duration: setting_val(&rows, "minDuration", 300).max(1),
     };
-    Ok((calendar_start_hour, time_block_settings))
+    Ok((calendar_start_hour, week_start_day, time_block_settings))
 }
 
-fn week_bounds_from_anchor(anchor_unix: i64, calendar_start_hour: i64) -> (i64, i64) {
-    let dt = Utc.timestamp_opt(anchor_unix, 0).single().unwrap_or_else(|| Utc::now());
-    let start_of_day = if dt.hour() as i64 >= calendar_start_hour {
-        dt.date_naive().and_hms_opt(calendar_start_hour as u32, 0, 0).unwrap()
-    } else {
-        (dt.date_naive() - Duration::days(1)).and_hms_opt(calendar_start_hour as u32, 0, 0).unwrap()
-    };
-    let weekday = start_of_day.weekday().num_days_from_monday() as i64;
-    let week_start = start_of_day - Duration::days(weekday);
-    let week_end = week_start + Duration::days(7) - Duration::seconds(1);
-    (week_start.and_utc().timestamp(), week_end.and_utc().timestamp())
+fn week_bounds_from_anchor(
+    tz: Tz,
+    anchor_unix: i64,
+    calendar_start_hour: i64,
+    week_start_day: u32,
+) -> (i64, i64) {
+    time_zone::week_bounds(tz, anchor_unix, calendar_start_hour as u32, week_start_day)
 }
 
 #[tauri::command]
@@ -321,8 +328,33 @@ pub async fn get_week(
     week_anchor: i64,
     device_uuids: Option<Vec<String>>,
 ) -> Result<Vec<TimeBlock>, Error> {
-    let (calendar_start_hour, time_block_settings) = load_runtime_settings().await?;
-    let (week_start, week_end) = week_bounds_from_anchor(week_anchor, calendar_start_hour);
+    let (calendar_start_hour, week_start_day, time_block_settings) = load_runtime_settings().await?;
+    let tz = load_time_zone().await?;
+    let (week_start, week_end) =
+        week_bounds_from_anchor(tz, week_anchor, calendar_start_hour, week_start_day);
+    time_blocks_in_range(week_start, week_end, device_uuids, &time_block_settings).await
+}
+
+/// Time blocks for any span, e.g. a month or quarter; both ends are inclusive.
+#[tauri::command]
+pub async fn get_range(
+    range_start: i64,
+    range_end: i64,
+    device_uuids: Option<Vec<String>>,
+) -> Result<Vec<TimeBlock>, Error> {
+    if range_end < range_start {
+        return Err(anyhow::anyhow!("Range end must not be before range start").into());
+    }
+    let (_, _, time_block_settings) = load_runtime_settings().await?;
+    time_blocks_in_range(range_start, range_end, device_uuids, &time_block_settings).await
+}
+
+async fn time_blocks_in_range(
+    range_start: i64,
+    range_end: i64,
+    device_uuids: Option<Vec<String>>,
+    time_block_settings: &TimeBlockSettings,
+) -> Result<Vec<TimeBlock>, Error> {
     let local_uuid = crate::db::tables::device::get_local_log_device_uuid().await?;
     let mut logs = get_logs().await?;
     let skipped_apps = get_skipped_apps().await?;
@@ -349,14 +381,14 @@ pub async fn get_week(
 
     logs = crate::db::tables::device::filter_logs_by_devices(logs, device_uuids, local_uuid);
 
-    let cat_regex = get_cat_regex().await?;
+    let cat_regex = get_cat_regex_history().await?;
     let categories = get_categories().await?;
     let regex = build_regex_table(&categories, &cat_regex)?;
     let app_groups = build_app_group_matchers(&get_app_groups().await?)?;
 
     let logs: Vec<Log> = logs
         .into_iter()
-        .filter(|log| log.timestamp >= week_start && log.timestamp <= week_end)
+        .filter(|log| log.timestamp >= range_start && log.timestamp <= range_end)
         .collect();
 
     if logs.is_empty() {
@@ -364,8 +396,8 @@ pub async fn get_week(
     }
 
     transform_time_blocks(
-        get_time_blocks(&logs, &regex, &app_groups, &time_block_settings)?,
-        &time_block_settings,
+        get_time_blocks(&logs, &regex, &app_groups, time_block_settings)?,
+        time_block_settings,
     )
 }
 
@@ -375,8 +407,10 @@ pub async fn get_week_for_app_filter(
     app_name: String,
     device_uuids: Option<Vec<String>>,
 ) -> Result<Vec<TimeBlock>, Error> {
-    let (calendar_start_hour, time_block_settings) = load_runtime_settings().await?;
-    let (week_start, week_end) = week_bounds_from_anchor(week_anchor, calendar_start_hour);
+    let (calendar_start_hour, week_start_day, time_block_settings) = load_runtime_settings().await?;
+    let tz = load_time_zone().await?;
+    let (week_start, week_end) =
+        week_bounds_from_anchor(tz, week_anchor, calendar_start_hour, week_start_day);
     let local_uuid = crate::db::tables::device::get_local_log_device_uuid().await?;
     let mut logs = get_logs().await?;
     let skipped_apps = get_skipped_apps().await?;
@@ -393,7 +427,7 @@ pub async fn get_week_for_app_filter(
 
     logs = crate::db::tables::device::filter_logs_by_devices(logs, device_uuids, local_uuid);
 
-    let cat_regex = get_cat_regex().await?;
+    let cat_regex = get_cat_regex_history().await?;
     let categories = get_categories().await?;
     let regex = build_regex_table(&categories, &cat_regex)?;
     let app_groups = build_app_group_matchers(&get_app_groups().await?)?;
@@ -0,0 +1,795 @@
+use crate::db::tables::cat_regex::preserve_rule_history;
+use crate::db::tables::category::{
+    delete_category_with_conn, MAX_PRODUCTIVITY_WEIGHT, MIN_PRODUCTIVITY_WEIGHT,
+};
+use crate::db::{backup, get_pool, Error};
+use anyhow::Context;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+use std::collections::HashSet;
+use std::path::Path;
+
+pub const RULE_PACK_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RulePack {
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub categories: Vec<RulePackCategory>,
+    #[serde(default)]
+    pub app_groups: Vec<RulePackAppGroup>,
+    #[serde(default)]
+    pub skipped_apps: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RulePackCategory {
+    pub name: String,
+    pub priority: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default = "default_true")]
+    pub regex_enabled: bool,
+    #[serde(default = "default_true")]
+    pub is_visible: bool,
+    #[serde(default = "default_true")]
+    pub in_stats: bool,
+    #[serde(default)]
+    pub productivity_weight: i32,
+    #[serde(default)]
+    pub regexes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RulePackAppGroup {
+    pub name: String,
+    pub regex: String,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RulePackFormat {
+    Json,
+    Toml,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RulePackImportMode {
+    Merge,
+    Replace,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RulePackExportOptions {
+    pub categories: Option<Vec<String>>,
+    #[serde(default = "default_true")]
+    pub include_app_groups: bool,
+    #[serde(default = "default_true")]
+    pub include_skipped_apps: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RulePackConflict {
+    pub kind: String,
+    pub name: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct RulePackImportReport {
+    pub categories_added: Vec<String>,
+    pub categories_updated: Vec<String>,
+    pub categories_removed: Vec<String>,
+    pub regexes_added: i64,
+    pub app_groups_added: Vec<String>,
+    pub skipped_apps_added: i64,
+    pub conflicts: Vec<RulePackConflict>,
+    pub backup_created: Option<String>,
+    pub applied: bool,
+}
+
+impl RulePackImportReport {
+    fn conflict(&mut self, kind: &str, name: &str, detail: String) {
+        self.conflicts.push(RulePackConflict {
+            kind: kind.to_string(),
+            name: name.to_string(),
+            detail,
+        });
+    }
+}
+
+fn on_off(flag: bool) -> &'static str {
+    if flag {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+pub fn format_for_path(path: &Path) -> RulePackFormat {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("toml") => RulePackFormat::Toml,
+        _ => RulePackFormat::Json,
+    }
+}
+
+pub fn parse_rule_pack(contents: &str, format: RulePackFormat) -> Result<RulePack, Error> {
+    let pack: RulePack = match format {
+        RulePackFormat::Json => {
+            serde_json::from_str(contents).context("Rule pack is not valid JSON")?
+        }
+        RulePackFormat::Toml => toml::from_str(contents).context("Rule pack is not valid TOML")?,
+    };
+    validate_rule_pack(&pack)?;
+    Ok(pack)
+}
+
+pub fn serialize_rule_pack(pack: &RulePack, format: RulePackFormat) -> Result<String, Error> {
+    Ok(match format {
+        RulePackFormat::Json => serde_json::to_string_pretty(pack).map_err(anyhow::Error::new)?,
+        RulePackFormat::Toml => toml::to_string_pretty(pack).map_err(anyhow::Error::new)?,
+    })
+}
+
+fn validate_rule_pack(pack: &RulePack) -> Result<(), Error> {
+    if pack.version == 0 || pack.version > RULE_PACK_VERSION {
+        return Err(anyhow::anyhow!(
+            "Unsupported rule pack version {} (this app reads up to version {})",
+            pack.version,
+            RULE_PACK_VERSION
+        )
+        .into());
+    }
+
+    let mut seen = HashSet::new();
+    for category in &pack.categories {
+        if category.name.trim().is_empty() {
+            return Err(anyhow::anyhow!("Category names cannot be empty").into());
+        }
+        if !seen.insert(category.name.trim()) {
+            return Err(
+                anyhow::anyhow!("Category {} appears more than once", category.name).into(),
+            );
+        }
+        if !(MIN_PRODUCTIVITY_WEIGHT..=MAX_PRODUCTIVITY_WEIGHT)
+            .contains(&category.productivity_weight)
+        {
+            return Err(anyhow::anyhow!(
+                "Productivity weight for {} must be between {MIN_PRODUCTIVITY_WEIGHT} and {MAX_PRODUCTIVITY_WEIGHT}",
+                category.name
+            )
+            .into());
+        }
+        for pattern in &category.regexes {
+            Regex::new(pattern)
+                .map_err(|error| anyhow::anyhow!("Invalid regex in {}: {error}", category.name))?;
+        }
+    }
+    for group in &pack.app_groups {
+        if group.name.trim().is_empty() || group.regex.trim().is_empty() {
+            return Err(anyhow::anyhow!("App groups need a name and a regex").into());
+        }
+        Regex::new(&group.regex).map_err(|error| {
+            anyhow::anyhow!("Invalid regex in app group {}: {error}", group.name)
+        })?;
+    }
+    for pattern in &pack.skipped_apps {
+        Regex::new(pattern)
+            .map_err(|error| anyhow::anyhow!("Invalid skipped app regex: {error}"))?;
+    }
+    Ok(())
+}
+
+pub async fn build_rule_pack(
+    conn: &mut SqliteConnection,
+    options: &RulePackExportOptions,
+) -> Result<RulePack, Error> {
+    let rows = sqlx::query_as::<_, (i32, String, i32, Option<String>, bool, bool, bool, i32)>(
+        "SELECT id, name, priority, color, regex_enabled, is_visible, in_stats, productivity_weight
+         FROM category
+         ORDER BY priority DESC, name",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let selected: Option<HashSet<&str>> = options
+        .categories
+        .as_ref()
+        .map(|names| names.iter().map(|name| name.as_str()).collect());
+
+    let mut categories = Vec::new();
+    for (id, name, priority, color, regex_enabled, is_visible, in_stats, productivity_weight) in
+        rows
+    {
+        if let Some(selected) = &selected {
+            if !selected.contains(name.as_str()) {
+                continue;
+            }
+        }
+        let regexes: Vec<String> =
+            sqlx::query_scalar("SELECT regex FROM category_regex WHERE cat_id = ?1 AND effective_until IS NULL ORDER BY id")
+                .bind(id)
+                .fetch_all(&mut *conn)
+                .await?;
+        categories.push(RulePackCategory {
+            name,
+            priority,
+            color,
+            regex_enabled,
+            is_visible,
+            in_stats,
+            productivity_weight,
+            regexes,
+        });
+    }
+
+    if let Some(selected) = &selected {
+        let missing: Vec<&str> = selected
+            .iter()
+            .copied()
+            .filter(|name| !categories.iter().any(|cat| cat.name == *name))
+            .collect();
+        if !missing.is_empty() {
+            return Err(anyhow::anyhow!("Unknown categories: {}", missing.join(", ")).into());
+        }
+    }
+
+    let app_groups = if options.include_app_groups {
+        sqlx::query_as::<_, (String, String)>("SELECT name, regex FROM app_groups ORDER BY id")
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .map(|(name, regex)| RulePackAppGroup { name, regex })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let skipped_apps = if options.include_skipped_apps {
+        sqlx::query_scalar("SELECT regex FROM skipped_apps ORDER BY regex")
+            .fetch_all(&mut *conn)
+            .await?
+    } else {
+        Vec::new()
+    };
+
+    Ok(RulePack {
+        version: RULE_PACK_VERSION,
+        name: None,
+        categories,
+        app_groups,
+        skipped_apps,
+    })
+}
+
+pub async fn apply_rule_pack(
+    conn: &mut SqliteConnection,
+    pack: &RulePack,
+    replace: bool,
+) -> Result<RulePackImportReport, Error> {
+    let mut report = RulePackImportReport::default();
+    // With rule history kept, replaced rules are retired rather than deleted and the pack's
+    // rules start now, so earlier logs keep their categories.
+    let now = chrono::Utc::now().timestamp();
+    let rules_from = if replace && preserve_rule_history(&mut *conn).await? {
+        Some(now)
+    } else {
+        None
+    };
+
+    if replace {
+        let pack_names: HashSet<&str> = pack.categories.iter().map(|c| c.name.trim()).collect();
+        let existing: Vec<(i32, String)> = sqlx::query_as("SELECT id, name FROM category")
+            .fetch_all(&mut *conn)
+            .await?;
+        for (id, name) in existing {
+            if name == "Miscellaneous" || pack_names.contains(name.as_str()) {
+                continue;
+            }
+            delete_category_with_conn(&mut *conn, id, true).await?;
+            report.categories_removed.push(name);
+        }
+        const KEEP_CATCH_ALL: &str = "NOT EXISTS (
+                SELECT 1 FROM category c
+                WHERE c.id = category_regex.cat_id AND c.name = 'Miscellaneous' AND category_regex.regex = '.*'
+            )";
+        if rules_from.is_some() {
+            sqlx::query(sqlx::AssertSqlSafe(format!(
+                "UPDATE category_regex SET effective_until = ?1
+                 WHERE (effective_until IS NULL OR effective_until > ?1) AND {KEEP_CATCH_ALL}"
+            )))
+            .bind(now)
+            .execute(&mut *conn)
+            .await?;
+            sqlx::query(sqlx::AssertSqlSafe(format!(
+                "DELETE FROM category_regex
+                 WHERE effective_from IS NOT NULL AND effective_from >= ?1 AND {KEEP_CATCH_ALL}"
+            )))
+            .bind(now)
+            .execute(&mut *conn)
+            .await?;
+        } else {
+            sqlx::query(sqlx::AssertSqlSafe(format!(
+                "DELETE FROM category_regex WHERE {KEEP_CATCH_ALL}"
+            )))
+            .execute(&mut *conn)
+            .await?;
+        }
+        sqlx::query("DELETE FROM app_groups")
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query("DELETE FROM goals WHERE target_kind = 'app_group'")
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query("DELETE FROM skipped_apps")
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    for category in &pack.categories {
+        let name = category.name.trim();
+        let existing = sqlx::query_as::<_, (i32, i32, Option<String>, i32, bool, bool, bool)>(
+            "SELECT id, priority, color, productivity_weight, regex_enabled, is_visible, in_stats
+             FROM category WHERE name = ?1",
+        )
+        .bind(name)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let cat_id = match existing {
+            Some((id, priority, color, weight, regex_enabled, is_visible, in_stats)) if replace => {
+                let new_priority = if name == "Miscellaneous" {
+                    0
+                } else {
+                    category.priority
+                };
+                sqlx::query(
+                    "UPDATE category
+                     SET priority = ?1, color = ?2, regex_enabled = ?3, is_visible = ?4, in_stats = ?5,
+                         productivity_weight = ?6
+                     WHERE id = ?7",
+                )
+                .bind(new_priority)
+                .bind(&category.color)
+                .bind(category.regex_enabled)
+                .bind(category.is_visible)
+                .bind(category.in_stats)
+                .bind(category.productivity_weight)
+                .bind(id)
+                .execute(&mut *conn)
+                .await?;
+                if priority != new_priority
+                    || color != category.color
+                    || weight != category.productivity_weight
+                    || regex_enabled != category.regex_enabled
+                    || is_visible != category.is_visible
+                    || in_stats != category.in_stats
+                {
+                    report.categories_updated.push(name.to_string());
+                }
+                id
+            }
+            Some((id, priority, color, weight, regex_enabled, is_visible, in_stats)) => {
+                if weight != category.productivity_weight {
+                    report.conflict(
+                        "category",
+                        name,
+                        format!(
+                            "keeps productivity weight {weight} (pack has {})",
+                            category.productivity_weight
+                        ),
+                    );
+                }
+                if priority != category.priority {
+                    report.conflict(
+                        "category",
+                        name,
+                        format!("keeps priority {priority} (pack has {})", category.priority),
+                    );
+                }
+                if color != category.color {
+                    report.conflict(
+                        "category",
+                        name,
+                        format!(
+                            "keeps color {} (pack has {})",
+                            color.as_deref().unwrap_or("none"),
+                            category.color.as_deref().unwrap_or("none")
+                        ),
+                    );
+                }
+                for (setting, kept, packed) in [
+                    ("regex matching", regex_enabled, category.regex_enabled),
+                    ("visibility", is_visible, category.is_visible),
+                    ("statistics inclusion", in_stats, category.in_stats),
+                ] {
+                    if kept != packed {
+                        report.conflict(
+                            "category",
+                            name,
+                            format!(
+                                "keeps {setting} {} (pack has {})",
+                                on_off(kept),
+                                on_off(packed)
+                            ),
+                        );
+                    }
+                }
+                id
+            }
+            None => {
+                let result = sqlx::query(
+                    "INSERT INTO category (name, priority, color, regex_enabled, is_visible, in_stats, is_collapsed, productivity_weight)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, ?7)",
+                )
+                .bind(name)
+                .bind(category.priority)
+                .bind(&category.color)
+                .bind(category.regex_enabled)
+                .bind(category.is_visible)
+                .bind(category.in_stats)
+                .bind(category.productivity_weight)
+                .execute(&mut *conn)
+                .await?;
+                report.categories_added.push(name.to_string());
+                result.last_insert_rowid() as i32
+            }
+        };
+
+        for pattern in &category.regexes {
+            let owner: Option<String> = sqlx::query_scalar(
+                "SELECT c.name FROM category_regex cr
+                 JOIN category c ON c.id = cr.cat_id
+                 WHERE cr.regex = ?1 AND cr.cat_id != ?2 AND cr.effective_until IS NULL
+                 LIMIT 1",
+            )
+            .bind(pattern)
+            .bind(cat_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+            if let Some(owner) = owner {
+                report.conflict(
+                    "category_regex",
+                    name,
+                    format!("pattern {pattern} is also used by {owner}"),
+                );
+            }
+
+            let result = sqlx::query(
+                "INSERT INTO category_regex (cat_id, regex, effective_from)
+                 SELECT ?1, ?2, ?3
+                 WHERE NOT EXISTS (
+                     SELECT 1 FROM category_regex
+                     WHERE cat_id = ?1 AND regex = ?2 AND effective_until IS NULL
+                 )",
+            )
+            .bind(cat_id)
+            .bind(pattern)
+            .bind(rules_from)
+            .execute(&mut *conn)
+            .await?;
+            report.regexes_added += result.rows_affected() as i64;
+        }
+    }
+
+    for group in &pack.app_groups {
+        let name = group.name.trim();
+        let regex = group.regex.trim();
+        let existing: Option<String> =
+            sqlx::query_scalar("SELECT name FROM app_groups WHERE regex = ?1")
+                .bind(regex)
+                .fetch_optional(&mut *conn)
+                .await?;
+        match existing {
+            Some(existing_name) if existing_name != name => {
+                report.conflict(
+                    "app_group",
+                    name,
+                    format!("pattern {regex} already belongs to {existing_name}"),
+                );
+            }
+            Some(_) => {}
+            None => {
+                sqlx::query("INSERT INTO app_groups (name, regex) VALUES (?1, ?2)")
+                    .bind(name)
+                    .bind(regex)
+                    .execute(&mut *conn)
+                    .await?;
+                report.app_groups_added.push(name.to_string());
+            }
+        }
+    }
+
+    for pattern in &pack.skipped_apps {
+        let result = sqlx::query("INSERT OR IGNORE INTO skipped_apps (regex) VALUES (?1)")
+            .bind(pattern)
+            .execute(&mut *conn)
+            .await?;
+        report.skipped_apps_added += result.rows_affected() as i64;
+    }
+
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn export_rule_pack(path: String, options: RulePackExportOptions) -> Result<(), Error> {
+    let path = Path::new(path.trim());
+    let pool = get_pool().await?;
+    let mut conn = pool.acquire().await?;
+    let pack = build_rule_pack(&mut conn, &options).await?;
+    let contents = serialize_rule_pack(&pack, format_for_path(path))?;
+    std::fs::write(path, contents)
+        .with_context(|| format!("Failed to write rule pack to {}", path.display()))?;
+    Ok(())
+}
+
+/// Imports a rule pack in one transaction. With `dry_run` the transaction is rolled back, so
+/// the report previews a merge or replace without touching the rules or taking a backup.
+#[tauri::command]
+pub async fn import_rule_pack(
+    path: String,
+    mode: RulePackImportMode,
+    dry_run: bool,
+) -> Result<RulePackImportReport, Error> {
+    let path = Path::new(path.trim());
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read rule pack from {}", path.display()))?;
+    let pack = parse_rule_pack(&contents, format_for_path(path))?;
+
+    let backup_created = if mode == RulePackImportMode::Replace && !dry_run {
+        let path = backup::create_safety_backup("pre_rule_pack_replace")
+            .context("Failed to create safety backup")?;
+        Some(path.to_string_lossy().to_string())
+    } else {
+        None
+    };
+
+    let pool = get_pool().await?;
+    let mut tx = pool.begin().await?;
+    let mut report = apply_rule_pack(&mut tx, &pack, mode == RulePackImportMode::Replace).await?;
+    report.backup_created = backup_created;
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+        report.applied = true;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::tables::{
+        app_group, cat_regex, category, goal, manual_time_block, settings, skipped_app,
+    };
+    use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::SqlitePool;
+
+    async fn rule_tables() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        category::create_table(&pool).await.unwrap();
+        cat_regex::create_table(&pool).await.unwrap();
+        app_group::create_table(&pool).await.unwrap();
+        goal::create_table(&pool).await.unwrap();
+        skipped_app::create_table(&pool).await.unwrap();
+        settings::create_table(&pool).await.unwrap();
+        manual_time_block::create_table(&pool).await.unwrap();
+        pool
+    }
+
+    fn sample_pack() -> RulePack {
+        RulePack {
+            version: RULE_PACK_VERSION,
+            name: Some("Team".into()),
+            categories: vec![
+                RulePackCategory {
+                    name: "Coding".into(),
+                    priority: 450,
+                    color: Some("#1100ff".into()),
+                    regex_enabled: true,
+                    is_visible: true,
+                    in_stats: true,
+                    productivity_weight: 0,
+                    regexes: vec!["Zed".into()],
+                },
+                RulePackCategory {
+                    name: "Meetings".into(),
+                    priority: 800,
+                    color: Some("#0ea5e9".into()),
+                    regex_enabled: true,
+                    is_visible: true,
+                    in_stats: true,
+                    productivity_weight: 0,
+                    regexes: vec!["(?i)zoom meeting".into()],
+                },
+            ],
+            app_groups: vec![RulePackAppGroup {
+                name: "YouTube".into(),
+                regex: "(?i)youtube".into(),
+            }],
+            skipped_apps: vec!["^Program Manager$".into()],
+        }
+    }
+
+    #[test]
+    fn round_trips_through_json_and_toml() {
+        let pack = sample_pack();
+        for format in [RulePackFormat::Json, RulePackFormat::Toml] {
+            let text = serialize_rule_pack(&pack, format).unwrap();
+            assert_eq!(parse_rule_pack(&text, format).unwrap(), pack);
+        }
+    }
+
+    #[test]
+    fn rejects_newer_versions_and_bad_patterns() {
+        let mut pack = sample_pack();
+        pack.version = RULE_PACK_VERSION + 1;
+        let text = serialize_rule_pack(&pack, RulePackFormat::Json).unwrap();
+        assert!(parse_rule_pack(&text, RulePackFormat::Json).is_err());
+
+        let mut pack = sample_pack();
+        pack.categories[0].regexes.push("(".into());
+        let text = serialize_rule_pack(&pack, RulePackFormat::Json).unwrap();
+        assert!(parse_rule_pack(&text, RulePackFormat::Json).is_err());
+    }
+
+    #[tokio::test]
+    async fn merge_adds_missing_rules_and_reports_conflicts_by_name() {
+        let pool = rule_tables().await;
+        sqlx::query("INSERT INTO app_groups (name, regex) VALUES ('Videos', '(?i)youtube')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        let report = apply_rule_pack(&mut conn, &sample_pack(), false)
+            .await
+            .unwrap();
+
+        assert_eq!(report.categories_added, vec!["Meetings".to_string()]);
+        assert_eq!(report.regexes_added, 2);
+        assert!(report.app_groups_added.is_empty());
+        assert!(report
+            .conflicts
+            .iter()
+            .any(|c| c.kind == "category" && c.name == "Coding"));
+        assert!(report
+            .conflicts
+            .iter()
+            .any(|c| c.kind == "app_group" && c.name == "YouTube"));
+
+        let priority: i32 =
+            sqlx::query_scalar("SELECT priority FROM category WHERE name = 'Coding'")
+                .fetch_one(&mut *conn)
+                .await
+                .unwrap();
+        assert_eq!(priority, 400);
+    }
+
+    #[tokio::test]
+    async fn replace_drops_rules_missing_from_the_pack() {
+        let pool = rule_tables().await;
+        let mut conn = pool.acquire().await.unwrap();
+        let report = apply_rule_pack(&mut conn, &sample_pack(), true)
+            .await
+            .unwrap();
+
+        assert!(report.categories_removed.contains(&"Gaming".to_string()));
+        assert!(!report
+            .categories_removed
+            .contains(&"Miscellaneous".to_string()));
+        let names: Vec<String> = sqlx::query_scalar("SELECT name FROM category ORDER BY name")
+            .fetch_all(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(names, vec!["Coding", "Meetings", "Miscellaneous"]);
+
+        let exported = build_rule_pack(
+            &mut conn,
+            &RulePackExportOptions {
+                categories: Some(vec!["Meetings".into()]),
+                include_app_groups: false,
+                include_skipped_apps: false,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(exported.categories.len(), 1);
+        assert_eq!(exported.categories[0].regexes, vec!["(?i)zoom meeting"]);
+    }
+
+    #[tokio::test]
+    async fn flag_only_differences_are_reported() {
+        // Only visibility differs from the seeded Coding category.
+        let mut pack = sample_pack();
+        pack.categories.truncate(1);
+        pack.categories[0].priority = 400;
+        pack.categories[0].is_visible = false;
+
+        for replace in [false, true] {
+            let pool = rule_tables().await;
+            let mut conn = pool.acquire().await.unwrap();
+            let report = apply_rule_pack(&mut conn, &pack, replace).await.unwrap();
+            if replace {
+                assert_eq!(report.categories_updated, vec!["Coding".to_string()]);
+            } else {
+                let details: Vec<&str> = report
+                    .conflicts
+                    .iter()
+                    .filter(|c| c.kind == "category" && c.name == "Coding")
+                    .map(|c| c.detail.as_str())
+                    .collect();
+                assert_eq!(details, vec!["keeps visibility on (pack has off)"]);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn replace_retires_rules_and_unlinks_manual_time() {
+        let pool = rule_tables().await;
+        sqlx::query(
+            "INSERT INTO manual_time_blocks (title, start_time, end_time, created_at, updated_at, category_id)
+             SELECT 'Match', 0, 60, 0, 0, id FROM category WHERE name = 'Gaming'",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO category_regex (cat_id, regex)
+             SELECT id, CASE name WHEN 'Coding' THEN 'Code' ELSE '.*' END FROM category
+             WHERE name IN ('Coding', 'Miscellaneous')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        apply_rule_pack(&mut conn, &sample_pack(), true)
+            .await
+            .unwrap();
+
+        let category_id: Option<i32> =
+            sqlx::query_scalar("SELECT category_id FROM manual_time_blocks")
+                .fetch_one(&mut *conn)
+                .await
+                .unwrap();
+        assert_eq!(category_id, None);
+
+        // Coding's earlier rules are kept for history; the pack's rule starts now.
+        let coding: Vec<(String, Option<i64>, Option<i64>)> = sqlx::query_as(
+            "SELECT cr.regex, cr.effective_from, cr.effective_until FROM category_regex cr
+             JOIN category c ON c.id = cr.cat_id WHERE c.name = 'Coding'",
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .unwrap();
+        assert_eq!(coding.len(), 2);
+        for (regex, from, until) in coding {
+            if regex == "Zed" {
+                assert!(from.is_some() && until.is_none());
+            } else {
+                assert!(until.is_some());
+            }
+        }
+        let live_catch_all: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM category_regex WHERE regex = '.*' AND effective_until IS NULL",
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .unwrap();
+        assert_eq!(live_catch_all, 1);
+    }
+}
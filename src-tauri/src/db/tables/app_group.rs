@@ -1,4 +1,5 @@
 use crate::db;
+use crate::db::tables::goal::{delete_goals_for_target, GoalTargetKind};
 use crate::db::Error;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -38,7 +39,7 @@ fn validate(name: &str, pattern: &str) -> Result<(String, String), Error> {
     Ok((name.to_string(), pattern.to_string()))
 }
 
-fn regex_specificity(pattern: &str) -> usize {
+pub fn regex_specificity(pattern: &str) -> usize {
     pattern
         .chars()
         .filter(|character| character.is_alphanumeric())
@@ -129,10 +130,13 @@ pub async fn update_app_group(app_group: AppGroup) -> Result<(), Error> {
 #[tauri::command]
 pub async fn delete_app_group(id: i32) -> Result<(), Error> {
     let pool = db::get_pool().await?;
+    let mut tx = pool.begin().await?;
     sqlx::query("DELETE FROM app_groups WHERE id = ?1")
         .bind(id)
-        .execute(&pool)
+        .execute(&mut *tx)
         .await?;
+    delete_goals_for_target(&mut *tx, GoalTargetKind::AppGroup, i64::from(id)).await?;
+    tx.commit().await?;
     Ok(())
 }
 
@@ -5,6 +5,9 @@ pub const META_GOOGLE_CLIENT_ID: &str = "google_oauth_client_id";
 pub const META_GOOGLE_CLIENT_SECRET: &str = "google_oauth_client_secret";
 pub const META_CALENDAR_VIEW_PREFS: &str = "calendar_view_prefs_v1";
 pub const META_LOCAL_DEVICE_UUID: &str = "local_device_uuid_v1";
+pub const META_TIME_ZONE: &str = "time_zone_v1";
+pub const META_JOURNAL_EXPORT: &str = "journal_export_v1";
+pub const META_JOURNAL_LAST_EXPORTED: &str = "journal_last_exported_v1";
 pub const SERVER_IP: &str = "server_ip";
 pub const DEFAULT_SERVER_IP: &str = "100.75.95.90";
 
@@ -30,6 +33,14 @@ pub async fn metadata_set(pool: &SqlitePool, key: &str, value: &str) -> Result<(
     Ok(())
 }
 
+pub async fn metadata_delete(pool: &SqlitePool, key: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM app_metadata WHERE key = ?1")
+        .bind(key)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn get_server_ip() -> Result<Option<String>, Error> {
     let pool = get_pool().await?;
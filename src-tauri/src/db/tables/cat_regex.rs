@@ -8,11 +8,36 @@ pub struct CategoryRegex {
     pub id: i32,
     pub cat_id: i32,
     pub regex: String,
+    #[serde(default)]
+    pub effective_from: Option<i64>,
+    #[serde(default)]
+    pub effective_until: Option<i64>,
 }
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NewCategoryRegex {
     cat_id: i32,
     regex: String,
+    #[serde(default)]
+    effective_from: Option<i64>,
+    #[serde(default)]
+    effective_until: Option<i64>,
+}
+
+impl CategoryRegex {
+    pub fn is_effective_at(&self, timestamp: i64) -> bool {
+        is_effective_at(self.effective_from, self.effective_until, timestamp)
+    }
+}
+
+/// A rule applies from `effective_from` (inclusive) up to `effective_until` (exclusive);
+/// a missing bound is open-ended.
+pub fn is_effective_at(
+    effective_from: Option<i64>,
+    effective_until: Option<i64>,
+    timestamp: i64,
+) -> bool {
+    effective_from.is_none_or(|from| timestamp >= from)
+        && effective_until.is_none_or(|until| timestamp < until)
 }
 
 pub async fn create_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
@@ -21,7 +46,9 @@ pub async fn create_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     id       INTEGER
         primary key autoincrement,
     cat_id   INTEGER not null,
-    regex    TEXT    not null
+    regex    TEXT    not null,
+    effective_from  INTEGER,
+    effective_until INTEGER
 );",
     )
     .execute(pool)
@@ -30,6 +57,31 @@ pub async fn create_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     Ok(())
 }
 
+const PRESERVE_RULE_HISTORY_KEY: &str = "preserveRuleHistory";
+
+pub async fn preserve_rule_history<'e, E>(executor: E) -> Result<bool, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    let val: Option<i32> = sqlx::query_scalar("SELECT val FROM settings WHERE key = ?1")
+        .bind(PRESERVE_RULE_HISTORY_KEY)
+        .fetch_optional(executor)
+        .await?;
+    Ok(val.unwrap_or(1) != 0)
+}
+
+fn validate_effective_range(
+    effective_from: Option<i64>,
+    effective_until: Option<i64>,
+) -> Result<(), Error> {
+    if let (Some(from), Some(until)) = (effective_from, effective_until) {
+        if until <= from {
+            return Err(anyhow::anyhow!("A rule must end after it starts").into());
+        }
+    }
+    Ok(())
+}
+
 const DEFAULT_REGEX_SEED_VERSION: &str = "default_regex_seed_initialized_v2";
 const DEFAULT_REGEXES: &[(&str, &str, &str)] = &[
     ("social_anydesk", "Social", "AnyDesk"),
@@ -321,28 +373,62 @@ fn find_seed_key(category_name: &str, regex: &str) -> Option<&'static str> {
 }
 #[tauri::command]
 pub async fn insert_cat_regex(new_category_regex: NewCategoryRegex) -> Result<i64, Error> {
+    validate_effective_range(
+        new_category_regex.effective_from,
+        new_category_regex.effective_until,
+    )?;
     let pool = db::get_pool().await?;
     let result = sqlx::query!(
-        "INSERT INTO category_regex (cat_id, regex) VALUES (?1, ?2)",
+        "INSERT INTO category_regex (cat_id, regex, effective_from, effective_until)
+         VALUES (?1, ?2, ?3, ?4)",
         new_category_regex.cat_id,
-        new_category_regex.regex
+        new_category_regex.regex,
+        new_category_regex.effective_from,
+        new_category_regex.effective_until
     )
     .execute(&pool)
     .await?;
     Ok(result.last_insert_rowid())
 }
 
+/// Starts `edited` at `now` in place of `row`, retiring `row` then if it is still in force so
+/// earlier logs keep their category. A rule that is already retired is left as it is.
+async fn replace_rule_with_conn(
+    conn: &mut sqlx::SqliteConnection,
+    row: &CategoryRegex,
+    edited: &CategoryRegex,
+    now: i64,
+) -> Result<(), Error> {
+    let effective_from = edited.effective_from.map_or(now, |from| from.max(now));
+    validate_effective_range(Some(effective_from), edited.effective_until)?;
+    if row.effective_until.is_none_or(|until| until > now) {
+        sqlx::query!(
+            "UPDATE category_regex SET effective_until = ?1 WHERE id = ?2",
+            now,
+            row.id
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    sqlx::query!(
+        "INSERT INTO category_regex (cat_id, regex, effective_from, effective_until)
+         VALUES (?1, ?2, ?3, ?4)",
+        edited.cat_id,
+        edited.regex,
+        effective_from,
+        edited.effective_until
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn update_cat_regex_by_id(cat_regex: CategoryRegex) -> Result<(), Error> {
+    validate_effective_range(cat_regex.effective_from, cat_regex.effective_until)?;
     let pool = db::get_pool().await?;
     
-    let current = sqlx::query_as!(
-        CategoryRegex,
-        r#"SELECT id as "id!: i32", cat_id as "cat_id!: i32", regex FROM category_regex WHERE id = ?1"#,
-        cat_regex.id
-    )
-    .fetch_optional(&pool)
-    .await?;
+    let current = fetch_cat_regex(&pool, cat_regex.id).await?;
     if let Some(ref row) = current {
         let cat_name: Option<String> = sqlx::query_scalar("SELECT name FROM category WHERE id = ?1")
             .bind(row.cat_id)
@@ -371,60 +457,88 @@ pub async fn update_cat_regex_by_id(cat_regex: CategoryRegex) -> Result<(), Erro
         return Err(anyhow::anyhow!("Category with id {} does not exist", cat_regex.cat_id).into());
     }
     
-    let regex_exists: i64 = sqlx::query_scalar!(
-        "SELECT COUNT(*) FROM category_regex WHERE id = ?1",
-        cat_regex.id
-    )
-    .fetch_one(&pool)
-    .await?;
-    
-    if regex_exists == 0 {
+    let Some(row) = current else {
         return Err(anyhow::anyhow!("Regex pattern with id {} does not exist", cat_regex.id).into());
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    let rule_changed = row.cat_id != cat_regex.cat_id || row.regex != cat_regex.regex;
+    let already_started = row.effective_from.is_none_or(|from| from < now);
+    if rule_changed && already_started && preserve_rule_history(&pool).await? {
+        let mut tx = pool.begin().await?;
+        replace_rule_with_conn(&mut tx, &row, &cat_regex, now).await?;
+        tx.commit().await?;
+        return Ok(());
     }
-    
+
     sqlx::query!(
-        "UPDATE category_regex SET cat_id = ?1, regex = ?2 WHERE id = ?3",
+        "UPDATE category_regex
+         SET cat_id = ?1, regex = ?2, effective_from = ?3, effective_until = ?4
+         WHERE id = ?5",
         cat_regex.cat_id,
         cat_regex.regex,
+        cat_regex.effective_from,
+        cat_regex.effective_until,
         cat_regex.id
     )
     .execute(&pool)
     .await?;
     Ok(())
 }
+
+async fn fetch_cat_regex(pool: &SqlitePool, id: i32) -> Result<Option<CategoryRegex>, sqlx::Error> {
+    sqlx::query_as!(
+        CategoryRegex,
+        r#"SELECT id as "id!: i32", cat_id as "cat_id!: i32", regex, effective_from, effective_until
+           FROM category_regex WHERE id = ?1"#,
+        id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
 #[tauri::command]
 pub async fn get_cat_regex_by_id(id: i32) -> Result<CategoryRegex, Error> {
     let pool = db::get_pool().await?;
+    fetch_cat_regex(&pool, id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Regex pattern with id {id} does not exist").into())
+}
+
+/// Rules that are in force now; retired rules are kept only for categorizing history.
+#[tauri::command]
+pub async fn get_cat_regex() -> Result<Vec<CategoryRegex>, Error> {
+    let pool = db::get_pool().await?;
+    let now = chrono::Utc::now().timestamp();
     let regex = sqlx::query_as!(
         CategoryRegex,
-        r#"SELECT id as "id!: i32", cat_id as "cat_id!: i32", regex FROM category_regex WHERE id = ?1"#,
-        id
+        r#"SELECT id as "id!: i32", cat_id as "cat_id!: i32", regex, effective_from, effective_until
+           FROM category_regex WHERE effective_until IS NULL OR effective_until > ?1"#,
+        now
     )
-    .fetch_one(&pool)
+    .fetch_all(&pool)
     .await?;
     Ok(regex)
 }
+
+/// Every rule including retired and future-dated ones, for categorizing logs at their own time.
 #[tauri::command]
-pub async fn get_cat_regex() -> Result<Vec<CategoryRegex>, Error> {
+pub async fn get_cat_regex_history() -> Result<Vec<CategoryRegex>, Error> {
     let pool = db::get_pool().await?;
     let regex = sqlx::query_as!(
         CategoryRegex,
-        r#"SELECT id as "id!: i32", cat_id as "cat_id!: i32", regex FROM category_regex"#
+        r#"SELECT id as "id!: i32", cat_id as "cat_id!: i32", regex, effective_from, effective_until
+           FROM category_regex"#
     )
     .fetch_all(&pool)
     .await?;
     Ok(regex)
 }
+
 #[tauri::command]
 pub async fn delete_cat_regex_by_id(id: i32) -> Result<(), Error> {
     let pool = db::get_pool().await?;
-    let row = sqlx::query_as!(
-        CategoryRegex,
-        r#"SELECT id as "id!: i32", cat_id as "cat_id!: i32", regex FROM category_regex WHERE id = ?1"#,
-        id
-    )
-    .fetch_optional(&pool)
-    .await?;
+    let row = fetch_cat_regex(&pool, id).await?;
     if let Some(ref r) = row {
         let cat_name: Option<String> = sqlx::query_scalar("SELECT name FROM category WHERE id = ?1")
             .bind(r.cat_id)
@@ -439,8 +553,264 @@ pub async fn delete_cat_regex_by_id(id: i32) -> Result<(), Error> {
             }
         }
     }
+    if preserve_rule_history(&pool).await? {
+        let now = chrono::Utc::now().timestamp();
+        sqlx::query!(
+            "UPDATE category_regex SET effective_until = ?1
+             WHERE id = ?2 AND (effective_until IS NULL OR effective_until > ?1)",
+            now,
+            id
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM category_regex
+             WHERE id = ?1 AND effective_from IS NOT NULL AND effective_from >= ?2",
+            id,
+            now
+        )
+        .execute(&pool)
+        .await?;
+        return Ok(());
+    }
     sqlx::query!("DELETE FROM category_regex WHERE id = ?1", id)
         .execute(&pool)
         .await?;
     Ok(())
 }
+
+/// Makes the rules in force now also govern `[range_start, range_end)`, trimming retired
+/// rules out of that range so history inside it is recategorized on the next query. Rules
+/// scheduled to start after `now` are left as they are.
+pub async fn reapply_current_rules_with_conn(
+    conn: &mut sqlx::SqliteConnection,
+    range_start: i64,
+    range_end: i64,
+    now: i64,
+) -> Result<(), sqlx::Error> {
+    let rows = sqlx::query_as::<_, CategoryRegex>(
+        "SELECT id, cat_id, regex, effective_from, effective_until FROM category_regex",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for row in rows {
+        let from = row.effective_from.unwrap_or(i64::MIN);
+        let until = row.effective_until.unwrap_or(i64::MAX);
+        if from > now {
+            continue;
+        }
+        let is_current = row.is_effective_at(now);
+
+        if !is_current {
+            if from >= range_end || until <= range_start {
+                continue;
+            }
+            if until > range_end {
+                sqlx::query(
+                    "INSERT INTO category_regex (cat_id, regex, effective_from, effective_until)
+                     VALUES (?1, ?2, ?3, ?4)",
+                )
+                .bind(row.cat_id)
+                .bind(&row.regex)
+                .bind(range_end)
+                .bind(row.effective_until)
+                .execute(&mut *conn)
+                .await?;
+            }
+            if from < range_start {
+                sqlx::query("UPDATE category_regex SET effective_until = ?1 WHERE id = ?2")
+                    .bind(range_start)
+                    .bind(row.id)
+                    .execute(&mut *conn)
+                    .await?;
+            } else {
+                sqlx::query("DELETE FROM category_regex WHERE id = ?1")
+                    .bind(row.id)
+                    .execute(&mut *conn)
+                    .await?;
+            }
+        } else if from > range_start {
+            if from <= range_end {
+                sqlx::query("UPDATE category_regex SET effective_from = ?1 WHERE id = ?2")
+                    .bind(range_start)
+                    .bind(row.id)
+                    .execute(&mut *conn)
+                    .await?;
+            } else {
+                sqlx::query(
+                    "INSERT INTO category_regex (cat_id, regex, effective_from, effective_until)
+                     VALUES (?1, ?2, ?3, ?4)",
+                )
+                .bind(row.cat_id)
+                .bind(&row.regex)
+                .bind(range_start)
+                .bind(range_end)
+                .execute(&mut *conn)
+                .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn reapply_current_rules(range_start: i64, range_end: i64) -> Result<(), Error> {
+    if range_end <= range_start {
+        return Err(anyhow::anyhow!("Range end must be after range start").into());
+    }
+    crate::db::backup::create_safety_backup("pre_reapply_rules")?;
+    let pool = db::get_pool().await?;
+    let mut tx = pool.begin().await?;
+    reapply_current_rules_with_conn(&mut tx, range_start, range_end, chrono::Utc::now().timestamp())
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn rule_windows(pool: &SqlitePool) -> Vec<(String, Option<i64>, Option<i64>)> {
+        sqlx::query_as(
+            "SELECT regex, effective_from, effective_until FROM category_regex
+             ORDER BY regex, effective_from",
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn effective_windows_are_half_open() {
+        assert!(is_effective_at(None, None, 0));
+        assert!(is_effective_at(Some(100), Some(200), 100));
+        assert!(!is_effective_at(Some(100), Some(200), 200));
+        assert!(!is_effective_at(Some(100), None, 99));
+    }
+
+    #[tokio::test]
+    async fn reapplying_trims_retired_rules_and_extends_current_ones() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        create_table(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO category_regex (cat_id, regex, effective_from, effective_until) VALUES
+             (1, 'old', NULL, 500),
+             (1, 'new', 500, NULL),
+             (1, 'always', NULL, NULL),
+             (1, 'scheduled', 2000, NULL)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        reapply_current_rules_with_conn(&mut conn, 200, 300, 1000)
+            .await
+            .unwrap();
+        drop(conn);
+
+        assert_eq!(
+            rule_windows(&pool).await,
+            vec![
+                ("always".to_string(), None, None),
+                ("new".to_string(), Some(200), Some(300)),
+                ("new".to_string(), Some(500), None),
+                ("old".to_string(), None, Some(200)),
+                ("old".to_string(), Some(300), Some(500)),
+                ("scheduled".to_string(), Some(2000), None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn editing_a_retired_rule_keeps_its_window() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        create_table(&pool).await.unwrap();
+        let rule = |id, regex: &str, effective_until| CategoryRegex {
+            id,
+            cat_id: 1,
+            regex: regex.to_string(),
+            effective_from: None,
+            effective_until,
+        };
+        let retired = rule(1, "retired", Some(500));
+        let live = rule(2, "live", None);
+        for row in [&retired, &live] {
+            sqlx::query(
+                "INSERT INTO category_regex (id, cat_id, regex, effective_from, effective_until)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )
+            .bind(row.id)
+            .bind(row.cat_id)
+            .bind(&row.regex)
+            .bind(row.effective_from)
+            .bind(row.effective_until)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let mut conn = pool.acquire().await.unwrap();
+        replace_rule_with_conn(&mut conn, &retired, &rule(1, "retired edit", None), 1000)
+            .await
+            .unwrap();
+        replace_rule_with_conn(&mut conn, &live, &rule(2, "live edit", None), 1000)
+            .await
+            .unwrap();
+        drop(conn);
+
+        assert_eq!(
+            rule_windows(&pool).await,
+            vec![
+                ("live".to_string(), None, Some(1000)),
+                ("live edit".to_string(), Some(1000), None),
+                ("retired".to_string(), None, Some(500)),
+                ("retired edit".to_string(), Some(1000), None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn editing_a_rule_to_end_before_now_is_rejected() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        create_table(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO category_regex (id, cat_id, regex, effective_from, effective_until)
+             VALUES (1, 1, 'live', NULL, NULL)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let rule = |regex: &str, effective_until| CategoryRegex {
+            id: 1,
+            cat_id: 1,
+            regex: regex.to_string(),
+            effective_from: None,
+            effective_until,
+        };
+
+        let mut conn = pool.acquire().await.unwrap();
+        let result =
+            replace_rule_with_conn(&mut conn, &rule("live", None), &rule("edit", Some(900)), 1000)
+                .await;
+        drop(conn);
+
+        assert!(result.is_err());
+        assert_eq!(rule_windows(&pool).await, vec![("live".to_string(), None, None)]);
+    }
+}
@@ -1,7 +1,8 @@
 use crate::db;
+use crate::db::tables::goal::{delete_goals_for_target, GoalTargetKind};
 use crate::db::Error;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
 
 #[derive(Debug, Serialize, FromRow, Deserialize)]
 pub struct Category {
@@ -14,6 +15,9 @@ pub struct Category {
     pub is_visible: bool,
     pub in_stats: bool,
     pub is_collapsed: bool,
+    /// How productive time in this category counts, from -2 to +2.
+    #[serde(default)]
+    pub productivity_weight: i32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -25,6 +29,21 @@ pub struct NewCategory {
     is_visible: bool,
     in_stats: bool,
     is_collapsed: bool,
+    #[serde(default)]
+    productivity_weight: i32,
+}
+
+pub const MIN_PRODUCTIVITY_WEIGHT: i32 = -2;
+pub const MAX_PRODUCTIVITY_WEIGHT: i32 = 2;
+
+fn validate_productivity_weight(weight: i32) -> Result<(), Error> {
+    if !(MIN_PRODUCTIVITY_WEIGHT..=MAX_PRODUCTIVITY_WEIGHT).contains(&weight) {
+        return Err(anyhow::anyhow!(
+            "Productivity weight must be between {MIN_PRODUCTIVITY_WEIGHT} and {MAX_PRODUCTIVITY_WEIGHT}"
+        )
+        .into());
+    }
+    Ok(())
 }
 
 pub async fn create_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
@@ -37,7 +56,8 @@ pub async fn create_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             regex_enabled INTEGER NOT NULL DEFAULT 1,
             is_visible INTEGER NOT NULL DEFAULT 1,
             in_stats INTEGER NOT NULL DEFAULT 1,
-            is_collapsed INTEGER NOT NULL DEFAULT 1
+            is_collapsed INTEGER NOT NULL DEFAULT 1,
+            productivity_weight INTEGER NOT NULL DEFAULT 0
         );",
     )
     .execute(pool)
@@ -89,6 +109,7 @@ pub async fn create_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
 
 #[tauri::command]
 pub async fn insert_category(new_category: NewCategory) -> Result<i64, Error> {
+    validate_productivity_weight(new_category.productivity_weight)?;
     let pool = db::get_pool().await?;
     let result = sqlx::query(
         "INSERT INTO category (
@@ -98,8 +119,9 @@ pub async fn insert_category(new_category: NewCategory) -> Result<i64, Error> {
             regex_enabled,
             is_visible,
             in_stats,
-            is_collapsed
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            is_collapsed,
+            productivity_weight
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
     )
     .bind(&new_category.name)
     .bind(new_category.priority)
@@ -108,6 +130,7 @@ pub async fn insert_category(new_category: NewCategory) -> Result<i64, Error> {
     .bind(new_category.is_visible)
     .bind(new_category.in_stats)
     .bind(new_category.is_collapsed)
+    .bind(new_category.productivity_weight)
     .execute(&pool)
     .await?;
 
@@ -127,7 +150,8 @@ pub async fn get_category_by_id(id: i32) -> Result<Category, Error> {
             regex_enabled,
             is_visible,
             in_stats,
-            is_collapsed
+            is_collapsed,
+            productivity_weight
         FROM category
         WHERE id = ?1
         "#,
@@ -139,6 +163,17 @@ pub async fn get_category_by_id(id: i32) -> Result<Category, Error> {
     Ok(cat)
 }
 
+pub async fn ensure_category_exists(pool: &SqlitePool, category_id: i32) -> Result<(), Error> {
+    let exists: Option<i32> = sqlx::query_scalar("SELECT id FROM category WHERE id = ?1")
+        .bind(category_id)
+        .fetch_optional(pool)
+        .await?;
+    if exists.is_none() {
+        return Err(anyhow::anyhow!("Category {category_id} does not exist").into());
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn get_categories() -> Result<Vec<Category>, Error> {
     let pool = db::get_pool().await?;
@@ -152,7 +187,8 @@ pub async fn get_categories() -> Result<Vec<Category>, Error> {
             regex_enabled,
             is_visible,
             in_stats,
-            is_collapsed
+            is_collapsed,
+            productivity_weight
         FROM category
         ORDER BY priority DESC
         "#,
@@ -220,6 +256,22 @@ pub async fn update_category_by_id(cat: Category) -> Result<(), Error> {
     Ok(())
 }
 
+/// Weights are edited on their own so existing category updates never reset them.
+#[tauri::command]
+pub async fn set_category_productivity_weight(id: i32, weight: i32) -> Result<(), Error> {
+    validate_productivity_weight(weight)?;
+    let pool = db::get_pool().await?;
+    let result = sqlx::query("UPDATE category SET productivity_weight = ?1 WHERE id = ?2")
+        .bind(weight)
+        .bind(id)
+        .execute(&pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(anyhow::anyhow!("Category with id {id} does not exist").into());
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn delete_category_by_id(id: i32, cascade: bool) -> Result<(), Error> {
     let pool = db::get_pool().await?;
@@ -235,32 +287,47 @@ pub async fn delete_category_by_id(id: i32, cascade: bool) -> Result<(), Error>
         }
     }
 
+    delete_category_with_conn(&mut tx, id, cascade).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Deletes a category and its goals and unlinks its manual time blocks. Its rules are deleted
+/// with `cascade`, otherwise they move to Miscellaneous.
+pub async fn delete_category_with_conn(
+    conn: &mut SqliteConnection,
+    id: i32,
+    cascade: bool,
+) -> Result<(), sqlx::Error> {
     if cascade {
         sqlx::query("DELETE FROM category_regex WHERE cat_id = ?1")
             .bind(id)
-            .execute(&mut *tx)
+            .execute(&mut *conn)
             .await?;
     } else {
         let misc_id: Option<i32> =
             sqlx::query_scalar("SELECT id FROM category WHERE name = 'Miscellaneous'")
-                .fetch_optional(&mut *tx)
+                .fetch_optional(&mut *conn)
                 .await?;
 
         if let Some(misc_id) = misc_id {
             sqlx::query("UPDATE category_regex SET cat_id = ?1 WHERE cat_id = ?2")
                 .bind(misc_id)
                 .bind(id)
-                .execute(&mut *tx)
+                .execute(&mut *conn)
                 .await?;
         }
     }
 
     sqlx::query("DELETE FROM category WHERE id = ?1")
         .bind(id)
-        .execute(&mut *tx)
+        .execute(&mut *conn)
+        .await?;
+    delete_goals_for_target(&mut *conn, GoalTargetKind::Category, i64::from(id)).await?;
+    sqlx::query("UPDATE manual_time_blocks SET category_id = NULL WHERE category_id = ?1")
+        .bind(id)
+        .execute(&mut *conn)
         .await?;
-
-    tx.commit().await?;
-
     Ok(())
 }
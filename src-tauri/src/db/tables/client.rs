@@ -0,0 +1,107 @@
+use crate::db;
+use crate::db::Error;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Client {
+    pub id: i64,
+    pub name: String,
+    pub hourly_rate: Option<f64>, // Used by the client's projects that have no rate of their own
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewClient {
+    pub name: String,
+    #[serde(default)]
+    pub hourly_rate: Option<f64>,
+}
+
+pub async fn create_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS clients (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            hourly_rate REAL
+        )",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Shared by clients and projects: rates must be finite and not negative.
+pub fn validate_rate(hourly_rate: Option<f64>) -> Result<(), Error> {
+    if hourly_rate.is_some_and(|rate| !rate.is_finite() || rate < 0.0) {
+        return Err(anyhow::anyhow!("Hourly rate must be zero or more").into());
+    }
+    Ok(())
+}
+
+fn validate(name: &str, hourly_rate: Option<f64>) -> Result<String, Error> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(anyhow::anyhow!("Client name cannot be empty").into());
+    }
+    validate_rate(hourly_rate)?;
+    Ok(name.to_string())
+}
+
+pub async fn fetch_clients(pool: &SqlitePool) -> Result<Vec<Client>, Error> {
+    Ok(sqlx::query_as::<_, Client>(
+        "SELECT id, name, hourly_rate FROM clients ORDER BY name COLLATE NOCASE",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+#[tauri::command]
+pub async fn get_clients() -> Result<Vec<Client>, Error> {
+    let pool = db::get_pool().await?;
+    fetch_clients(&pool).await
+}
+
+#[tauri::command]
+pub async fn insert_client(new_client: NewClient) -> Result<i64, Error> {
+    let name = validate(&new_client.name, new_client.hourly_rate)?;
+    let pool = db::get_pool().await?;
+    let result = sqlx::query("INSERT INTO clients (name, hourly_rate) VALUES (?1, ?2)")
+        .bind(name)
+        .bind(new_client.hourly_rate)
+        .execute(&pool)
+        .await?;
+    Ok(result.last_insert_rowid())
+}
+
+#[tauri::command]
+pub async fn update_client(client: Client) -> Result<(), Error> {
+    let name = validate(&client.name, client.hourly_rate)?;
+    let pool = db::get_pool().await?;
+    let result = sqlx::query("UPDATE clients SET name = ?1, hourly_rate = ?2 WHERE id = ?3")
+        .bind(name)
+        .bind(client.hourly_rate)
+        .bind(client.id)
+        .execute(&pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(anyhow::anyhow!("Client {} does not exist", client.id).into());
+    }
+    Ok(())
+}
+
+/// Deletes the client; its projects are kept without a client.
+#[tauri::command]
+pub async fn delete_client(id: i64) -> Result<(), Error> {
+    let pool = db::get_pool().await?;
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM clients WHERE id = ?1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE projects SET client_id = NULL WHERE client_id = ?1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
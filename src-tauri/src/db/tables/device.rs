@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::{Executor, FromRow, Row, Sqlite, SqlitePool};
 
 const KIND_LOCAL: &str = "local";
-const KIND_REMOTE: &str = "remote";
+pub(crate) const KIND_REMOTE: &str = "remote";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum DeviceState {
@@ -0,0 +1,258 @@
+use crate::db;
+use crate::db::Error;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+pub const ALL_WEEKDAYS: i32 = 0b111_1111; // Bit 0 = Monday through bit 6 = Sunday
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum GoalTargetKind {
+    Category,
+    AppGroup,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum GoalPeriod {
+    Day,
+    Week,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum GoalDirection {
+    AtLeast, // A target to reach, e.g. 20h of Coding per week
+    AtMost,  // A limit not to cross, e.g. 1h of Social per day
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Goal {
+    pub id: i64,
+    pub name: String,
+    pub target_kind: GoalTargetKind,
+    pub target_id: i64, // category.id or app_groups.id depending on target_kind
+    pub period: GoalPeriod,
+    pub direction: GoalDirection,
+    pub target_seconds: i64,
+    pub window_start_hour: Option<i32>, // Only time from this local hour counts
+    pub window_end_hour: Option<i32>,   // Only time before this local hour counts
+    pub weekdays: i32,
+    pub is_enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewGoal {
+    pub name: String,
+    pub target_kind: GoalTargetKind,
+    pub target_id: i64,
+    pub period: GoalPeriod,
+    pub direction: GoalDirection,
+    pub target_seconds: i64,
+    #[serde(default)]
+    pub window_start_hour: Option<i32>,
+    #[serde(default)]
+    pub window_end_hour: Option<i32>,
+    #[serde(default = "all_weekdays")]
+    pub weekdays: i32,
+}
+
+fn all_weekdays() -> i32 {
+    ALL_WEEKDAYS
+}
+
+impl Goal {
+    pub fn applies_on(&self, weekday: u32) -> bool {
+        self.weekdays & (1 << weekday) != 0
+    }
+
+    /// Whether time logged at `hour` (local, 0-23) falls inside the goal's window.
+    pub fn counts_hour(&self, hour: i32) -> bool {
+        self.window_start_hour.is_none_or(|start| hour >= start)
+            && self.window_end_hour.is_none_or(|end| hour < end)
+    }
+}
+
+pub async fn create_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS goals (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            target_kind TEXT NOT NULL,
+            target_id INTEGER NOT NULL,
+            period TEXT NOT NULL,
+            direction TEXT NOT NULL,
+            target_seconds INTEGER NOT NULL,
+            window_start_hour INTEGER,
+            window_end_hour INTEGER,
+            weekdays INTEGER NOT NULL DEFAULT 127,
+            is_enabled INTEGER NOT NULL DEFAULT 1
+        )",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn validate(
+    name: &str,
+    target_seconds: i64,
+    window_start_hour: Option<i32>,
+    window_end_hour: Option<i32>,
+    weekdays: i32,
+) -> Result<String, Error> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(anyhow::anyhow!("Goal name cannot be empty").into());
+    }
+    if target_seconds < 0 {
+        return Err(anyhow::anyhow!("Goal target cannot be negative").into());
+    }
+    if window_start_hour.is_some_and(|hour| !(0..=23).contains(&hour)) {
+        return Err(anyhow::anyhow!("Window start must be an hour between 0 and 23").into());
+    }
+    if window_end_hour.is_some_and(|hour| !(1..=24).contains(&hour)) {
+        return Err(anyhow::anyhow!("Window end must be an hour between 1 and 24").into());
+    }
+    if let (Some(start), Some(end)) = (window_start_hour, window_end_hour) {
+        if end <= start {
+            return Err(anyhow::anyhow!("Window end must be after window start").into());
+        }
+    }
+    if weekdays & ALL_WEEKDAYS == 0 || weekdays & !ALL_WEEKDAYS != 0 {
+        return Err(anyhow::anyhow!("Pick at least one weekday for the goal").into());
+    }
+    Ok(name.to_string())
+}
+
+async fn ensure_target_exists(
+    pool: &SqlitePool,
+    target_kind: GoalTargetKind,
+    target_id: i64,
+) -> Result<(), Error> {
+    let exists: Option<i64> = match target_kind {
+        GoalTargetKind::Category => sqlx::query_scalar("SELECT id FROM category WHERE id = ?1"),
+        GoalTargetKind::AppGroup => sqlx::query_scalar("SELECT id FROM app_groups WHERE id = ?1"),
+    }
+    .bind(target_id)
+    .fetch_optional(pool)
+    .await?;
+    if exists.is_none() {
+        return Err(anyhow::anyhow!("Goal target {target_id} does not exist").into());
+    }
+    Ok(())
+}
+
+pub async fn fetch_goals(pool: &SqlitePool) -> Result<Vec<Goal>, Error> {
+    Ok(sqlx::query_as::<_, Goal>(
+        "SELECT id, name, target_kind, target_id, period, direction, target_seconds,
+                window_start_hour, window_end_hour, weekdays, is_enabled
+         FROM goals
+         ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Removes goals pointing at a category or app group that is being deleted.
+pub async fn delete_goals_for_target<'e, E>(
+    executor: E,
+    target_kind: GoalTargetKind,
+    target_id: i64,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    sqlx::query("DELETE FROM goals WHERE target_kind = ?1 AND target_id = ?2")
+        .bind(target_kind)
+        .bind(target_id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_goals() -> Result<Vec<Goal>, Error> {
+    let pool = db::get_pool().await?;
+    fetch_goals(&pool).await
+}
+
+#[tauri::command]
+pub async fn insert_goal(new_goal: NewGoal) -> Result<i64, Error> {
+    let name = validate(
+        &new_goal.name,
+        new_goal.target_seconds,
+        new_goal.window_start_hour,
+        new_goal.window_end_hour,
+        new_goal.weekdays,
+    )?;
+    let pool = db::get_pool().await?;
+    ensure_target_exists(&pool, new_goal.target_kind, new_goal.target_id).await?;
+    let result = sqlx::query(
+        "INSERT INTO goals (name, target_kind, target_id, period, direction, target_seconds,
+                            window_start_hour, window_end_hour, weekdays)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+    )
+    .bind(name)
+    .bind(new_goal.target_kind)
+    .bind(new_goal.target_id)
+    .bind(new_goal.period)
+    .bind(new_goal.direction)
+    .bind(new_goal.target_seconds)
+    .bind(new_goal.window_start_hour)
+    .bind(new_goal.window_end_hour)
+    .bind(new_goal.weekdays)
+    .execute(&pool)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+#[tauri::command]
+pub async fn update_goal(goal: Goal) -> Result<(), Error> {
+    let name = validate(
+        &goal.name,
+        goal.target_seconds,
+        goal.window_start_hour,
+        goal.window_end_hour,
+        goal.weekdays,
+    )?;
+    let pool = db::get_pool().await?;
+    ensure_target_exists(&pool, goal.target_kind, goal.target_id).await?;
+    let result = sqlx::query(
+        "UPDATE goals
+         SET name = ?1, target_kind = ?2, target_id = ?3, period = ?4, direction = ?5,
+             target_seconds = ?6, window_start_hour = ?7, window_end_hour = ?8, weekdays = ?9,
+             is_enabled = ?10
+         WHERE id = ?11",
+    )
+    .bind(name)
+    .bind(goal.target_kind)
+    .bind(goal.target_id)
+    .bind(goal.period)
+    .bind(goal.direction)
+    .bind(goal.target_seconds)
+    .bind(goal.window_start_hour)
+    .bind(goal.window_end_hour)
+    .bind(goal.weekdays)
+    .bind(goal.is_enabled)
+    .bind(goal.id)
+    .execute(&pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(anyhow::anyhow!("Goal {} does not exist", goal.id).into());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_goal(id: i64) -> Result<(), Error> {
+    let pool = db::get_pool().await?;
+    sqlx::query("DELETE FROM goals WHERE id = ?1")
+        .bind(id)
+        .execute(&pool)
+        .await?;
+    Ok(())
+}
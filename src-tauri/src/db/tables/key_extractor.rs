@@ -0,0 +1,207 @@
+use crate::db;
+use crate::db::Error;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+/// Capture group used as the key when a rule has no template.
+pub const KEY_GROUP: &str = "key";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct KeyExtractor {
+    pub id: i32,
+    pub name: String,
+    pub regex: String,
+    pub template: Option<String>, // e.g. "$owner/$repo#$number"; defaults to the `key` group
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewKeyExtractor {
+    pub name: String,
+    pub regex: String,
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CachedKeyExtractor {
+    pub id: i32,
+    pub name: String,
+    regex: Regex,
+    template: String,
+}
+
+impl CachedKeyExtractor {
+    /// The key for a window title, or `None` when the rule does not match or the key is empty.
+    pub fn extract(&self, title: &str) -> Option<String> {
+        let captures = self.regex.captures(title)?;
+        let mut key = String::new();
+        captures.expand(&self.template, &mut key);
+        let key = key.trim();
+        (!key.is_empty()).then(|| key.to_string())
+    }
+}
+
+fn validate(
+    name: &str,
+    pattern: &str,
+    template: Option<&str>,
+) -> Result<(String, String, Option<String>), Error> {
+    let name = name.trim();
+    let pattern = pattern.trim();
+    let template = template.map(str::trim).filter(|value| !value.is_empty());
+    if name.is_empty() {
+        return Err(anyhow::anyhow!("Rule name cannot be empty").into());
+    }
+    if pattern.is_empty() {
+        return Err(anyhow::anyhow!("Regex pattern cannot be empty").into());
+    }
+    let regex = Regex::new(pattern).map_err(|error| anyhow::anyhow!("Invalid regex: {error}"))?;
+    let groups: Vec<&str> = regex.capture_names().flatten().collect();
+    if groups.is_empty() {
+        return Err(anyhow::anyhow!("The regex needs at least one named capture group").into());
+    }
+    if template.is_none() && !groups.contains(&KEY_GROUP) {
+        return Err(anyhow::anyhow!(
+            "Add a (?P<{KEY_GROUP}>...) capture group or a template built from named groups"
+        )
+        .into());
+    }
+    Ok((
+        name.to_string(),
+        pattern.to_string(),
+        template.map(str::to_string),
+    ))
+}
+
+pub fn build_key_extractors(rules: &[KeyExtractor]) -> Result<Vec<CachedKeyExtractor>, Error> {
+    Ok(rules
+        .iter()
+        .map(|rule| {
+            Ok(CachedKeyExtractor {
+                id: rule.id,
+                name: rule.name.clone(),
+                regex: Regex::new(&rule.regex)?,
+                template: rule
+                    .template
+                    .clone()
+                    .unwrap_or_else(|| format!("${{{KEY_GROUP}}}")),
+            })
+        })
+        .collect::<Result<Vec<_>, regex::Error>>()?)
+}
+
+pub async fn create_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS key_extractors (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            regex TEXT NOT NULL UNIQUE,
+            template TEXT
+        )",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_key_extractors() -> Result<Vec<KeyExtractor>, Error> {
+    let pool = db::get_pool().await?;
+    Ok(sqlx::query_as::<_, KeyExtractor>(
+        "SELECT id, name, regex, template FROM key_extractors ORDER BY id",
+    )
+    .fetch_all(&pool)
+    .await?)
+}
+
+#[tauri::command]
+pub async fn insert_key_extractor(new_key_extractor: NewKeyExtractor) -> Result<i64, Error> {
+    let (name, regex, template) = validate(
+        &new_key_extractor.name,
+        &new_key_extractor.regex,
+        new_key_extractor.template.as_deref(),
+    )?;
+    let pool = db::get_pool().await?;
+    let result =
+        sqlx::query("INSERT INTO key_extractors (name, regex, template) VALUES (?1, ?2, ?3)")
+            .bind(name)
+            .bind(regex)
+            .bind(template)
+            .execute(&pool)
+            .await?;
+    Ok(result.last_insert_rowid())
+}
+
+#[tauri::command]
+pub async fn update_key_extractor(key_extractor: KeyExtractor) -> Result<(), Error> {
+    let (name, regex, template) = validate(
+        &key_extractor.name,
+        &key_extractor.regex,
+        key_extractor.template.as_deref(),
+    )?;
+    let pool = db::get_pool().await?;
+    let result =
+        sqlx::query("UPDATE key_extractors SET name = ?1, regex = ?2, template = ?3 WHERE id = ?4")
+            .bind(name)
+            .bind(regex)
+            .bind(template)
+            .bind(key_extractor.id)
+            .execute(&pool)
+            .await?;
+    if result.rows_affected() == 0 {
+        return Err(anyhow::anyhow!("Extraction rule {} does not exist", key_extractor.id).into());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_key_extractor(id: i32) -> Result<(), Error> {
+    let pool = db::get_pool().await?;
+    sqlx::query("DELETE FROM key_extractors WHERE id = ?1")
+        .bind(id)
+        .execute(&pool)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(regex: &str, template: Option<&str>) -> KeyExtractor {
+        KeyExtractor {
+            id: 1,
+            name: "Rule".into(),
+            regex: regex.into(),
+            template: template.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn extracts_the_key_group_or_a_template() {
+        let rules = vec![
+            rule(r"\b(?P<key>[A-Z][A-Z0-9]+-\d+)\b", None),
+            rule(
+                r"(?P<owner>[\w.-]+)/(?P<repo>[\w.-]+)#(?P<number>\d+)",
+                Some("$owner/$repo#$number"),
+            ),
+        ];
+        let extractors = build_key_extractors(&rules).unwrap();
+        assert_eq!(
+            extractors[0].extract("PROJ-1234 Fix login - Jira"),
+            Some("PROJ-1234".to_string())
+        );
+        assert_eq!(extractors[0].extract("Inbox - Mail"), None);
+        assert_eq!(
+            extractors[1].extract("Fix login by ana · Pull Request acme/web#5678 · GitHub"),
+            Some("acme/web#5678".to_string())
+        );
+    }
+
+    #[test]
+    fn rules_need_a_named_group() {
+        assert!(validate("Jira", r"[A-Z]+-\d+", None).is_err());
+        assert!(validate("Jira", r"[A-Z]+-\d+", Some("$0")).is_err());
+        assert!(validate("Jira", r"(?P<key>[A-Z]+-\d+)", Some("  ")).is_ok());
+    }
+}
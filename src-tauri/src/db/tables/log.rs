@@ -229,6 +229,32 @@ pub async fn get_logs() -> Result<Vec<Log>, Error> {
     Ok(logs)
 }
 
+/// Logs that overlap `[range_start, range_end]`, including ones that start before
+/// `range_start` and run into it.
+pub async fn get_logs_overlapping(range_start: i64, range_end: i64) -> Result<Vec<Log>, Error> {
+    let pool = db::get_pool().await?;
+    Ok(get_logs_overlapping_with_executor(&pool, range_start, range_end).await?)
+}
+
+pub(crate) async fn get_logs_overlapping_with_executor<'e, E>(
+    executor: E,
+    range_start: i64,
+    range_end: i64,
+) -> Result<Vec<Log>, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    sqlx::query_as::<_, Log>(
+        "SELECT id, device_uuid, app, timestamp, duration, is_deleted FROM logs
+         WHERE timestamp <= ?2 AND (timestamp >= ?1 OR timestamp + duration > ?1)
+           AND is_deleted = 0",
+    )
+    .bind(range_start)
+    .bind(range_end)
+    .fetch_all(executor)
+    .await
+}
+
 #[tauri::command]
 pub async fn get_log_by_id(id: i64) -> Result<Log, Error> {
     let pool = db::get_pool().await?;
@@ -467,6 +493,42 @@ mod merge_logs_tests {
     }
 }
 
+#[cfg(test)]
+mod range_tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[tokio::test]
+    async fn overlapping_logs_include_ones_that_straddle_the_range_start() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        create_table(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO logs (id, device_uuid, app, timestamp, duration) VALUES
+             (1, 'd', 'ended before', 0, 50),
+             (2, 'd', 'straddles start', 80, 40),
+             (3, 'd', 'at start', 100, 0),
+             (4, 'd', 'inside', 150, 10),
+             (5, 'd', 'after', 300, 10)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let mut apps: Vec<String> = get_logs_overlapping_with_executor(&pool, 100, 200)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|log| log.app)
+            .collect();
+        apps.sort();
+        assert_eq!(apps, vec!["at start", "inside", "straddles start"]);
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GetLogsByCategoryRequest {
     pub category: String,
@@ -480,7 +542,7 @@ pub async fn get_logs_by_category(
     request: GetLogsByCategoryRequest,
 ) -> Result<Vec<MergedLog>, Error> {
     use crate::db::tables::{cat_regex, category, skipped_app};
-    use cat_regex::get_cat_regex;
+    use cat_regex::{get_cat_regex_history, is_effective_at};
     use category::get_categories;
     use regex::Regex;
     use skipped_app::get_skipped_apps;
@@ -510,19 +572,24 @@ pub async fn get_logs_by_category(
     logs.retain(|log| !is_skipped(&log.app));
 
     let categories = get_categories().await?;
-    let cat_regex_list = get_cat_regex().await?;
+    let cat_regex_list = get_cat_regex_history().await?;
 
     let category_map: HashMap<i32, &category::Category> =
         categories.iter().map(|cat| (cat.id, cat)).collect();
 
-    let mut regex_list: Vec<(Regex, String)> = Vec::new();
+    let mut regex_list: Vec<(Regex, String, Option<i64>, Option<i64>)> = Vec::new();
     for reg in cat_regex_list {
         if let Some(cat) = category_map.get(&reg.cat_id) {
-            regex_list.push((Regex::new(&reg.regex)?, cat.name.clone()));
+            regex_list.push((
+                Regex::new(&reg.regex)?,
+                cat.name.clone(),
+                reg.effective_from,
+                reg.effective_until,
+            ));
         }
     }
 
-    regex_list.sort_by_key(|(_, cat_name)| {
+    regex_list.sort_by_key(|(_, cat_name, _, _)| {
         categories
             .iter()
             .find(|c| c.name == *cat_name)
@@ -535,8 +602,9 @@ pub async fn get_logs_by_category(
         .filter(|log| {
             let matched_category = regex_list
                 .iter()
-                .find(|(regex, _)| regex.is_match(&log.app))
-                .map(|(_, cat_name)| cat_name.clone())
+                .filter(|(_, _, from, until)| is_effective_at(*from, *until, log.timestamp))
+                .find(|(regex, _, _, _)| regex.is_match(&log.app))
+                .map(|(_, cat_name, _, _)| cat_name.clone())
                 .unwrap_or_else(|| "Miscellaneous".to_string());
 
             matched_category == request.category
@@ -1,9 +1,36 @@
 use crate::db;
+use crate::db::tables::category::ensure_category_exists;
+use crate::db::tables::project::ensure_project_exists;
 use crate::db::Error;
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool};
 
 const RUNNING_TIMER_KEY: &str = "running_manual_time_timer_v1";
+const MAX_TAGS: usize = 20;
+
+/// Tags of a manual time block, stored comma-separated.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Tags(pub Vec<String>);
+
+impl From<String> for Tags {
+    fn from(value: String) -> Self {
+        Tags(
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_string)
+                .collect(),
+        )
+    }
+}
+
+impl Tags {
+    pub fn to_column(&self) -> String {
+        self.0.join(",")
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct ManualTimeBlock {
@@ -14,6 +41,23 @@ pub struct ManualTimeBlock {
     pub end_time: i64,
     pub created_at: i64,
     pub updated_at: i64,
+    pub project_id: Option<i64>,
+    pub category_id: Option<i32>,
+    #[sqlx(try_from = "String")]
+    pub tags: Tags,
+}
+
+/// How statistics and reports count manual time blocks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ManualTimeMode {
+    /// Left out of the totals.
+    Exclude,
+    /// Counted on top of tracked time, even where the two overlap.
+    #[default]
+    Add,
+    /// Replaces the tracked time it overlaps.
+    Override,
 }
 
 #[derive(Debug, Deserialize)]
@@ -22,6 +66,12 @@ pub struct NewManualTimeBlock {
     pub notes: Option<String>,
     pub start_time: i64,
     pub end_time: i64,
+    #[serde(default)]
+    pub project_id: Option<i64>,
+    #[serde(default)]
+    pub category_id: Option<i32>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,6 +81,12 @@ pub struct UpdateManualTimeBlock {
     pub notes: Option<String>,
     pub start_time: i64,
     pub end_time: i64,
+    #[serde(default)]
+    pub project_id: Option<i64>,
+    #[serde(default)]
+    pub category_id: Option<i32>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,7 +107,11 @@ pub async fn create_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             start_time INTEGER NOT NULL,
             end_time INTEGER NOT NULL,
             created_at INTEGER NOT NULL,
-            updated_at INTEGER NOT NULL
+            updated_at INTEGER NOT NULL,
+            project_id INTEGER,
+            external_uid TEXT,
+            category_id INTEGER,
+            tags TEXT NOT NULL DEFAULT ''
         )",
     )
     .execute(pool)
@@ -64,6 +124,13 @@ pub async fn create_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     .execute(pool)
     .await?;
 
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_manual_time_blocks_external_uid
+         ON manual_time_blocks(external_uid)",
+    )
+    .execute(pool)
+    .await?;
+
     Ok(())
 }
 
@@ -91,6 +158,75 @@ fn validate(
     Ok((title.to_string(), notes))
 }
 
+/// Trims tags and drops empty ones and repeats, keeping the first spelling.
+pub(crate) fn normalize_tags(tags: &[String]) -> Result<Tags, Error> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags
+        .iter()
+        .map(|tag| tag.trim())
+        .filter(|tag| !tag.is_empty())
+    {
+        if tag.contains(',') {
+            return Err(anyhow::anyhow!("Tags cannot contain commas").into());
+        }
+        if tag.chars().count() > 50 {
+            return Err(anyhow::anyhow!("Tags must be 50 characters or fewer").into());
+        }
+        if !normalized
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(tag))
+        {
+            normalized.push(tag.to_string());
+        }
+    }
+    if normalized.len() > MAX_TAGS {
+        return Err(anyhow::anyhow!("A block can have at most {MAX_TAGS} tags").into());
+    }
+    Ok(Tags(normalized))
+}
+
+/// Sorted, non-overlapping time covered by `blocks`.
+pub(crate) fn covered_intervals(blocks: &[ManualTimeBlock]) -> Vec<(i64, i64)> {
+    let mut intervals: Vec<(i64, i64)> = blocks
+        .iter()
+        .map(|block| (block.start_time, block.end_time))
+        .collect();
+    intervals.sort_unstable();
+    let mut merged: Vec<(i64, i64)> = Vec::with_capacity(intervals.len());
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Parts of `[start, start + duration)` outside `covered`, as (start, duration) pairs.
+pub(crate) fn uncovered_parts(
+    start: i64,
+    duration: i64,
+    covered: &[(i64, i64)],
+) -> Vec<(i64, i64)> {
+    let end = start + duration;
+    let mut parts = Vec::new();
+    let mut cursor = start;
+    let first = covered.partition_point(|&(_, covered_end)| covered_end <= start);
+    for &(covered_start, covered_end) in &covered[first..] {
+        if covered_start >= end {
+            break;
+        }
+        if covered_start > cursor {
+            parts.push((cursor, covered_start - cursor));
+        }
+        cursor = cursor.max(covered_end);
+    }
+    if cursor < end {
+        parts.push((cursor, end - cursor));
+    }
+    parts
+}
+
 #[tauri::command]
 pub async fn get_manual_time_blocks(
     range_start: i64,
@@ -101,7 +237,8 @@ pub async fn get_manual_time_blocks(
     }
     let pool = db::get_pool().await?;
     Ok(sqlx::query_as::<_, ManualTimeBlock>(
-        "SELECT id, title, notes, start_time, end_time, created_at, updated_at
+        "SELECT id, title, notes, start_time, end_time, created_at, updated_at, project_id,
+                category_id, tags
          FROM manual_time_blocks
          WHERE end_time > ?1 AND start_time < ?2
          ORDER BY start_time, id",
@@ -122,18 +259,29 @@ pub async fn insert_manual_time_block(
         new_manual_time_block.start_time,
         new_manual_time_block.end_time,
     )?;
+    let tags = normalize_tags(&new_manual_time_block.tags)?;
     let pool = db::get_pool().await?;
+    if let Some(project_id) = new_manual_time_block.project_id {
+        ensure_project_exists(&pool, project_id).await?;
+    }
+    if let Some(category_id) = new_manual_time_block.category_id {
+        ensure_category_exists(&pool, category_id).await?;
+    }
     let now = chrono::Utc::now().timestamp();
     let result = sqlx::query(
         "INSERT INTO manual_time_blocks
-         (title, notes, start_time, end_time, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+         (title, notes, start_time, end_time, created_at, updated_at, project_id, category_id,
+          tags)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?5, ?6, ?7, ?8)",
     )
     .bind(title)
     .bind(notes)
     .bind(new_manual_time_block.start_time)
     .bind(new_manual_time_block.end_time)
     .bind(now)
+    .bind(new_manual_time_block.project_id)
+    .bind(new_manual_time_block.category_id)
+    .bind(tags.to_column())
     .execute(&pool)
     .await?;
     Ok(result.last_insert_rowid())
@@ -272,17 +420,28 @@ pub async fn update_manual_time_block(
         manual_time_block.start_time,
         manual_time_block.end_time,
     )?;
+    let tags = normalize_tags(&manual_time_block.tags)?;
     let pool = db::get_pool().await?;
+    if let Some(project_id) = manual_time_block.project_id {
+        ensure_project_exists(&pool, project_id).await?;
+    }
+    if let Some(category_id) = manual_time_block.category_id {
+        ensure_category_exists(&pool, category_id).await?;
+    }
     let result = sqlx::query(
         "UPDATE manual_time_blocks
-         SET title = ?1, notes = ?2, start_time = ?3, end_time = ?4, updated_at = ?5
-         WHERE id = ?6",
+         SET title = ?1, notes = ?2, start_time = ?3, end_time = ?4, updated_at = ?5,
+             project_id = ?6, category_id = ?7, tags = ?8
+         WHERE id = ?9",
     )
     .bind(title)
     .bind(notes)
     .bind(manual_time_block.start_time)
     .bind(manual_time_block.end_time)
     .bind(chrono::Utc::now().timestamp())
+    .bind(manual_time_block.project_id)
+    .bind(manual_time_block.category_id)
+    .bind(tags.to_column())
     .bind(manual_time_block.id)
     .execute(&pool)
     .await?;
@@ -310,7 +469,10 @@ pub async fn delete_manual_time_block(id: i64) -> Result<(), Error> {
 
 #[cfg(test)]
 mod tests {
-    use super::{create_table, validate, ManualTimeBlock};
+    use super::{
+        covered_intervals, create_table, normalize_tags, uncovered_parts, validate,
+        ManualTimeBlock, Tags,
+    };
     use sqlx::SqlitePool;
 
     #[test]
@@ -328,15 +490,16 @@ mod tests {
         create_table(&pool).await.unwrap();
         sqlx::query(
             "INSERT INTO manual_time_blocks
-             (title, notes, start_time, end_time, created_at, updated_at)
-             VALUES ('Planning', 'Weekly plan', 100, 200, 1, 1)",
+             (title, notes, start_time, end_time, created_at, updated_at, tags)
+             VALUES ('Planning', 'Weekly plan', 100, 200, 1, 1, 'planning,team')",
         )
         .execute(&pool)
         .await
         .unwrap();
 
         let rows = sqlx::query_as::<_, ManualTimeBlock>(
-            "SELECT id, title, notes, start_time, end_time, created_at, updated_at
+            "SELECT id, title, notes, start_time, end_time, created_at, updated_at, project_id,
+                    category_id, tags
              FROM manual_time_blocks
              WHERE end_time > ?1 AND start_time < ?2",
         )
@@ -349,5 +512,39 @@ mod tests {
         assert_eq!(rows.len(), 1);
         assert_eq!(rows[0].title, "Planning");
         assert_eq!(rows[0].notes.as_deref(), Some("Weekly plan"));
+        assert_eq!(rows[0].tags, Tags(vec!["planning".into(), "team".into()]));
+    }
+
+    #[test]
+    fn normalizes_tags() {
+        let tags =
+            normalize_tags(&[" Client ".into(), "".into(), "client".into(), "ops".into()]).unwrap();
+        assert_eq!(tags.to_column(), "Client,ops");
+        assert!(normalize_tags(&["a,b".into()]).is_err());
+    }
+
+    #[test]
+    fn cuts_blocks_out_of_tracked_time() {
+        let block = |start_time: i64, end_time: i64| ManualTimeBlock {
+            id: start_time,
+            title: "Meeting".into(),
+            notes: None,
+            start_time,
+            end_time,
+            created_at: 0,
+            updated_at: 0,
+            project_id: None,
+            category_id: None,
+            tags: Tags::default(),
+        };
+        let covered = covered_intervals(&[block(400, 500), block(100, 200), block(150, 250)]);
+        assert_eq!(covered, vec![(100, 250), (400, 500)]);
+
+        assert_eq!(
+            uncovered_parts(50, 250, &covered),
+            vec![(50, 50), (250, 50)]
+        );
+        assert_eq!(uncovered_parts(120, 60, &covered), vec![]);
+        assert_eq!(uncovered_parts(380, 100, &covered), vec![(380, 20)]);
     }
 }
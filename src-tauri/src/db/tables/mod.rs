@@ -2,10 +2,14 @@ pub mod app_group;
 pub mod app_metadata_kv;
 pub mod cat_regex;
 pub mod category;
+pub mod client;
 pub mod device;
+pub mod goal;
 pub mod google_calendar;
 pub mod google_calendar_sync;
+pub mod key_extractor;
 pub mod log;
 pub mod manual_time_block;
+pub mod project;
 pub mod settings;
 pub mod skipped_app;
@@ -0,0 +1,412 @@
+use crate::db;
+use crate::db::tables::app_group::regex_specificity;
+use crate::db::tables::client::validate_rate;
+use crate::db::Error;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Project {
+    pub id: i64,
+    pub name: String,
+    pub client_id: Option<i64>,
+    pub billable: bool,
+    pub hourly_rate: Option<f64>, // Falls back to the client's rate
+    pub regex: Option<String>,    // Window titles assigned to the project automatically
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewProject {
+    pub name: String,
+    #[serde(default)]
+    pub client_id: Option<i64>,
+    #[serde(default = "default_billable")]
+    pub billable: bool,
+    #[serde(default)]
+    pub hourly_rate: Option<f64>,
+    #[serde(default)]
+    pub regex: Option<String>,
+}
+
+fn default_billable() -> bool {
+    true
+}
+
+/// Tracked time between `start_time` and `end_time` assigned to a project by hand.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ProjectAssignment {
+    pub id: i64,
+    pub project_id: i64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewProjectAssignment {
+    pub project_id: i64,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+/// Decides which project tracked time belongs to. Manual assignments win over rules, and a
+/// later assignment wins over an earlier one it overlaps.
+#[derive(Debug, Clone)]
+pub struct ProjectResolver {
+    rules: Vec<(Regex, i64)>,
+    assignments: Vec<ProjectAssignment>,
+}
+
+impl ProjectResolver {
+    pub fn new(
+        projects: &[Project],
+        mut assignments: Vec<ProjectAssignment>,
+    ) -> Result<Self, Error> {
+        let mut rules = projects
+            .iter()
+            .filter_map(|project| {
+                let pattern = project.regex.as_deref()?;
+                Some(
+                    Regex::new(pattern)
+                        .map(|regex| (regex, regex_specificity(pattern), project.id)),
+                )
+            })
+            .collect::<Result<Vec<_>, regex::Error>>()?;
+        rules.sort_by(|left, right| right.1.cmp(&left.1).then(left.2.cmp(&right.2)));
+        assignments.sort_by_key(|assignment| std::cmp::Reverse(assignment.id));
+        Ok(Self {
+            rules: rules
+                .into_iter()
+                .map(|(regex, _, project_id)| (regex, project_id))
+                .collect(),
+            assignments,
+        })
+    }
+
+    pub fn rule_project(&self, title: &str) -> Option<i64> {
+        self.rules
+            .iter()
+            .find(|(regex, _)| regex.is_match(title))
+            .map(|(_, project_id)| *project_id)
+    }
+
+    /// Splits a log across projects, returning seconds per project (`None` = unassigned).
+    pub fn attribute(&self, title: &str, start: i64, duration: i64) -> Vec<(Option<i64>, i64)> {
+        let end = start + duration;
+        let overlapping: Vec<&ProjectAssignment> = self
+            .assignments
+            .iter()
+            .filter(|assignment| assignment.start_time < end && assignment.end_time > start)
+            .collect();
+        let fallback = self.rule_project(title);
+        if overlapping.is_empty() {
+            return vec![(fallback, duration)];
+        }
+
+        let mut points: Vec<i64> = vec![start, end];
+        for assignment in &overlapping {
+            points.extend([assignment.start_time, assignment.end_time]);
+        }
+        points.retain(|point| (start..=end).contains(point));
+        points.sort_unstable();
+        points.dedup();
+
+        let mut shares: Vec<(Option<i64>, i64)> = Vec::new();
+        for segment in points.windows(2) {
+            let project_id = overlapping
+                .iter()
+                .find(|assignment| {
+                    assignment.start_time <= segment[0] && assignment.end_time >= segment[1]
+                })
+                .map(|assignment| assignment.project_id)
+                .or(fallback);
+            match shares.iter_mut().find(|(id, _)| *id == project_id) {
+                Some((_, seconds)) => *seconds += segment[1] - segment[0],
+                None => shares.push((project_id, segment[1] - segment[0])),
+            }
+        }
+        shares
+    }
+}
+
+pub async fn create_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS projects (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            client_id INTEGER,
+            billable INTEGER NOT NULL DEFAULT 1,
+            hourly_rate REAL,
+            regex TEXT
+        )",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn create_assignments_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS project_assignments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            start_time INTEGER NOT NULL,
+            end_time INTEGER NOT NULL,
+            created_at INTEGER NOT NULL
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_project_assignments_range
+         ON project_assignments(start_time, end_time)",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn validate(
+    name: &str,
+    hourly_rate: Option<f64>,
+    regex: Option<&str>,
+) -> Result<(String, Option<String>), Error> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(anyhow::anyhow!("Project name cannot be empty").into());
+    }
+    validate_rate(hourly_rate)?;
+    let regex = regex.map(str::trim).filter(|pattern| !pattern.is_empty());
+    if let Some(pattern) = regex {
+        Regex::new(pattern).map_err(|error| anyhow::anyhow!("Invalid regex: {error}"))?;
+    }
+    Ok((name.to_string(), regex.map(str::to_string)))
+}
+
+pub async fn ensure_project_exists(pool: &SqlitePool, project_id: i64) -> Result<(), Error> {
+    let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM projects WHERE id = ?1")
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await?;
+    if exists.is_none() {
+        return Err(anyhow::anyhow!("Project {project_id} does not exist").into());
+    }
+    Ok(())
+}
+
+async fn ensure_client_exists(pool: &SqlitePool, client_id: Option<i64>) -> Result<(), Error> {
+    let Some(client_id) = client_id else {
+        return Ok(());
+    };
+    let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM clients WHERE id = ?1")
+        .bind(client_id)
+        .fetch_optional(pool)
+        .await?;
+    if exists.is_none() {
+        return Err(anyhow::anyhow!("Client {client_id} does not exist").into());
+    }
+    Ok(())
+}
+
+pub async fn fetch_projects(pool: &SqlitePool) -> Result<Vec<Project>, Error> {
+    Ok(sqlx::query_as::<_, Project>(
+        "SELECT id, name, client_id, billable, hourly_rate, regex
+         FROM projects
+         ORDER BY name COLLATE NOCASE",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn fetch_project_assignments(
+    pool: &SqlitePool,
+    range_start: i64,
+    range_end: i64,
+) -> Result<Vec<ProjectAssignment>, Error> {
+    Ok(sqlx::query_as::<_, ProjectAssignment>(
+        "SELECT id, project_id, start_time, end_time, created_at
+         FROM project_assignments
+         WHERE end_time > ?1 AND start_time < ?2
+         ORDER BY start_time, id",
+    )
+    .bind(range_start)
+    .bind(range_end)
+    .fetch_all(pool)
+    .await?)
+}
+
+#[tauri::command]
+pub async fn get_projects() -> Result<Vec<Project>, Error> {
+    let pool = db::get_pool().await?;
+    fetch_projects(&pool).await
+}
+
+#[tauri::command]
+pub async fn insert_project(new_project: NewProject) -> Result<i64, Error> {
+    let (name, regex) = validate(
+        &new_project.name,
+        new_project.hourly_rate,
+        new_project.regex.as_deref(),
+    )?;
+    let pool = db::get_pool().await?;
+    ensure_client_exists(&pool, new_project.client_id).await?;
+    let result = sqlx::query(
+        "INSERT INTO projects (name, client_id, billable, hourly_rate, regex)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )
+    .bind(name)
+    .bind(new_project.client_id)
+    .bind(new_project.billable)
+    .bind(new_project.hourly_rate)
+    .bind(regex)
+    .execute(&pool)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+#[tauri::command]
+pub async fn update_project(project: Project) -> Result<(), Error> {
+    let (name, regex) = validate(&project.name, project.hourly_rate, project.regex.as_deref())?;
+    let pool = db::get_pool().await?;
+    ensure_client_exists(&pool, project.client_id).await?;
+    let result = sqlx::query(
+        "UPDATE projects
+         SET name = ?1, client_id = ?2, billable = ?3, hourly_rate = ?4, regex = ?5
+         WHERE id = ?6",
+    )
+    .bind(name)
+    .bind(project.client_id)
+    .bind(project.billable)
+    .bind(project.hourly_rate)
+    .bind(regex)
+    .bind(project.id)
+    .execute(&pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(anyhow::anyhow!("Project {} does not exist", project.id).into());
+    }
+    Ok(())
+}
+
+/// Deletes the project and its assignments; its manual time blocks are kept unassigned.
+#[tauri::command]
+pub async fn delete_project(id: i64) -> Result<(), Error> {
+    let pool = db::get_pool().await?;
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM projects WHERE id = ?1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM project_assignments WHERE project_id = ?1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE manual_time_blocks SET project_id = NULL WHERE project_id = ?1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_project_assignments(
+    range_start: i64,
+    range_end: i64,
+) -> Result<Vec<ProjectAssignment>, Error> {
+    if range_end <= range_start {
+        return Err(anyhow::anyhow!("Range end must be after range start").into());
+    }
+    let pool = db::get_pool().await?;
+    fetch_project_assignments(&pool, range_start, range_end).await
+}
+
+#[tauri::command]
+pub async fn insert_project_assignment(
+    new_project_assignment: NewProjectAssignment,
+) -> Result<i64, Error> {
+    if new_project_assignment.end_time <= new_project_assignment.start_time {
+        return Err(anyhow::anyhow!("End time must be after start time").into());
+    }
+    let pool = db::get_pool().await?;
+    ensure_project_exists(&pool, new_project_assignment.project_id).await?;
+    let result = sqlx::query(
+        "INSERT INTO project_assignments (project_id, start_time, end_time, created_at)
+         VALUES (?1, ?2, ?3, ?4)",
+    )
+    .bind(new_project_assignment.project_id)
+    .bind(new_project_assignment.start_time)
+    .bind(new_project_assignment.end_time)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(&pool)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+#[tauri::command]
+pub async fn delete_project_assignment(id: i64) -> Result<(), Error> {
+    let pool = db::get_pool().await?;
+    sqlx::query("DELETE FROM project_assignments WHERE id = ?1")
+        .bind(id)
+        .execute(&pool)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(id: i64, regex: Option<&str>) -> Project {
+        Project {
+            id,
+            name: format!("Project {id}"),
+            client_id: None,
+            billable: true,
+            hourly_rate: None,
+            regex: regex.map(str::to_string),
+        }
+    }
+
+    fn assignment(id: i64, project_id: i64, start_time: i64, end_time: i64) -> ProjectAssignment {
+        ProjectAssignment {
+            id,
+            project_id,
+            start_time,
+            end_time,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn the_most_specific_rule_assigns_a_title() {
+        let resolver = ProjectResolver::new(
+            &[
+                project(1, Some("(?i)acme")),
+                project(2, Some("(?i)acme/web")),
+            ],
+            Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(resolver.rule_project("acme/web#12 - GitHub"), Some(2));
+        assert_eq!(resolver.rule_project("acme/api - GitHub"), Some(1));
+        assert_eq!(resolver.attribute("Inbox", 0, 60), vec![(None, 60)]);
+    }
+
+    #[test]
+    fn assignments_split_a_log_and_override_rules() {
+        let resolver = ProjectResolver::new(
+            &[
+                project(1, Some("Editor")),
+                project(2, None),
+                project(3, None),
+            ],
+            vec![assignment(1, 2, 100, 200), assignment(2, 3, 150, 160)],
+        )
+        .unwrap();
+        let shares = resolver.attribute("Editor", 50, 200);
+        assert_eq!(shares, vec![(Some(1), 100), (Some(2), 90), (Some(3), 10)]);
+    }
+}
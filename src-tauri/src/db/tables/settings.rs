@@ -35,6 +35,7 @@ pub async fn create_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
 pub async fn seed_defaults(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     let default_settings: &[(&str, i32, bool, i32, Option<i32>, Option<i32>)] = &[
         ("calendarStartHour", 5, false, 5, Some(0), Some(23)),
+        ("weekStartDay", 0, false, 0, Some(0), Some(6)),
         ("calendarHeight", 100, false, 100, Some(50), Some(200)),
         ("rightSidebarWidth", 480, false, 480, Some(280), Some(800)),
         ("minLogDuration", 1, false, 1, Some(1), None),
@@ -43,6 +44,14 @@ pub async fn seed_defaults(pool: &SqlitePool) -> Result<(), sqlx::Error> {
         ("minDuration", 300, false, 300, Some(1), None),
         ("uiMinAppDuration", 30, false, 30, Some(1), None),
         ("categorySidebarCount", 5, false, 5, Some(1), Some(30)),
+        ("preserveRuleHistory", 1, false, 1, Some(0), Some(1)),
+        ("goalNotifications", 1, false, 1, Some(0), Some(1)),
+        ("dailyTargetMinutes", 480, false, 480, Some(0), Some(1440)),
+        ("breakGapMinutes", 15, false, 15, Some(1), Some(240)),
+        ("attendanceWorkdays", 31, false, 31, Some(0), Some(127)),
+        ("workdayStartHour", 9, false, 9, Some(0), Some(23)),
+        ("workdayEndHour", 18, false, 18, Some(1), Some(24)),
+        ("minGapMinutes", 30, false, 30, Some(1), Some(480)),
     ];
 
     for (key, val, is_locked, default_val, min_val, max_val) in default_settings {
@@ -0,0 +1,869 @@
+use crate::db::charts::{category_colors, donut_chart, ChartSlice};
+use crate::db::queries::attendance::hours;
+use crate::db::queries::billing::{billable_parts, round_cents};
+use crate::db::queries::statistics::{labelled_logs_overlapping, LabelledLog, LogLabeller};
+use crate::db::queries::time_zone::{
+    calendar_date, load_calendar_settings, load_time_zone, local_datetime,
+};
+use crate::db::tables::client::{fetch_clients, validate_rate};
+use crate::db::tables::manual_time_block::{
+    covered_intervals, get_manual_time_blocks, ManualTimeMode,
+};
+use crate::db::tables::project::{fetch_project_assignments, fetch_projects, ProjectResolver};
+use crate::db::{escape_html, get_pool, Error};
+use anyhow::Context;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+const UNASSIGNED_LABEL: &str = "No project";
+const TEMPLATE_DIR: &str = "report_templates";
+
+const DEFAULT_HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{{title}}</title>
+<style>
+  body { font-family: -apple-system, "Segoe UI", Roboto, sans-serif; color: #1f2933; margin: 2rem; }
+  h1 { margin-bottom: 0.25rem; }
+  .meta { color: #616e7c; margin-bottom: 1.5rem; }
+  table { border-collapse: collapse; width: 100%; margin-bottom: 2rem; }
+  th, td { border-bottom: 1px solid #d9e2ec; padding: 0.4rem 0.6rem; text-align: left; }
+  td.number, th.number { text-align: right; font-variant-numeric: tabular-nums; }
+  tr.total td { font-weight: 600; border-top: 2px solid #1f2933; }
+  @media print { body { margin: 0; } }
+</style>
+</head>
+<body>
+<h1>{{title}}</h1>
+<div class="meta">{{period}} &middot; {{rounding}} &middot; generated {{generated_at}}</div>
+<h2>By day</h2>
+{{daily_table}}
+<h2>Summary</h2>
+{{summary_table}}
+{{summary_chart}}
+</body>
+</html>
+"#;
+
+const DEFAULT_MARKDOWN_TEMPLATE: &str = "# {{title}}
+
+{{period}} · {{rounding}} · generated {{generated_at}}
+
+## By day
+
+{{daily_table}}
+
+## Summary
+
+{{summary_table}}
+
+{{summary_chart}}
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimesheetGrouping {
+    #[default]
+    Category,
+    Project,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    #[default]
+    Up,
+    Down,
+    Nearest,
+}
+
+/// Each day's time per category or project is rounded to a multiple of `minutes`; zero keeps
+/// exact times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RoundingRule {
+    pub minutes: u32,
+    #[serde(default)]
+    pub mode: RoundingMode,
+}
+
+impl RoundingRule {
+    pub fn apply(&self, seconds: i64) -> i64 {
+        let step = i64::from(self.minutes) * 60;
+        if step == 0 {
+            return seconds;
+        }
+        let steps = match self.mode {
+            RoundingMode::Up => (seconds + step - 1).div_euclid(step),
+            RoundingMode::Down => seconds.div_euclid(step),
+            RoundingMode::Nearest => (seconds + step / 2).div_euclid(step),
+        };
+        steps * step
+    }
+
+    fn describe(&self) -> String {
+        match (self.minutes, self.mode) {
+            (0, _) => "exact times".to_string(),
+            (minutes, RoundingMode::Up) => format!("rounded up to {minutes} min"),
+            (minutes, RoundingMode::Down) => format!("rounded down to {minutes} min"),
+            (minutes, RoundingMode::Nearest) => format!("rounded to the nearest {minutes} min"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TimesheetOptions {
+    pub title: Option<String>,
+    pub grouping: TimesheetGrouping,
+    pub rounding: RoundingRule,
+    pub hourly_rate: Option<f64>, // For category timesheets, or billable projects without a rate
+    pub include_rates: bool,      // Project timesheets use project and client rates
+    pub currency: Option<String>,
+    pub device_uuids: Option<Vec<String>>,
+    pub manual_time: ManualTimeMode,
+    pub template_path: Option<String>, // Overrides the built-in or user template
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimesheetEntry {
+    pub date: String, // YYYY-MM-DD
+    pub label: String,
+    pub seconds: i64,
+    pub rounded_seconds: i64,
+    pub hourly_rate: Option<f64>,
+    pub amount: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimesheetTotal {
+    pub label: String,
+    pub seconds: i64,
+    pub rounded_seconds: i64,
+    pub amount: Option<f64>,
+    pub color: Option<String>, // Category colour, for category timesheets
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Timesheet {
+    pub title: String,
+    pub range_start: i64,
+    pub range_end: i64,
+    pub first_date: Option<String>,
+    pub last_date: Option<String>,
+    pub rounding: RoundingRule,
+    pub currency: Option<String>,
+    pub entries: Vec<TimesheetEntry>,
+    pub totals: Vec<TimesheetTotal>,
+    pub total_seconds: i64,
+    pub total_rounded_seconds: i64,
+    pub total_amount: Option<f64>, // None when no entry has a rate
+}
+
+/// Seconds per day and label.
+pub type TimesheetCells = BTreeMap<(NaiveDate, String), i64>;
+
+/// Billable projects by label, with their own or their client's rate when rates are included.
+pub type BillableRates = HashMap<String, Option<f64>>;
+
+pub fn build_timesheet(
+    title: String,
+    range_start: i64,
+    range_end: i64,
+    cells: &TimesheetCells,
+    rates: &BillableRates,
+    options: &TimesheetOptions,
+) -> Timesheet {
+    // Non-billable projects and unassigned time are never billed, not even at the flat rate.
+    let rate_for = |label: &str| match options.grouping {
+        TimesheetGrouping::Category => options.hourly_rate,
+        TimesheetGrouping::Project => rates
+            .get(label)
+            .and_then(|&rate| rate.or(options.hourly_rate)),
+    };
+    let entries: Vec<TimesheetEntry> = cells
+        .iter()
+        .filter(|(_, seconds)| **seconds > 0)
+        .map(|((date, label), &seconds)| {
+            let rounded_seconds = options.rounding.apply(seconds);
+            let hourly_rate = rate_for(label);
+            TimesheetEntry {
+                date: date.format("%Y-%m-%d").to_string(),
+                label: label.clone(),
+                seconds,
+                rounded_seconds,
+                hourly_rate,
+                amount: hourly_rate.map(|rate| round_cents(rounded_seconds as f64 / 3600.0 * rate)),
+            }
+        })
+        .collect();
+
+    let mut totals: Vec<TimesheetTotal> = Vec::new();
+    for entry in &entries {
+        let index = match totals.iter().position(|total| total.label == entry.label) {
+            Some(index) => index,
+            None => {
+                totals.push(TimesheetTotal {
+                    label: entry.label.clone(),
+                    seconds: 0,
+                    rounded_seconds: 0,
+                    amount: None,
+                    color: None,
+                });
+                totals.len() - 1
+            }
+        };
+        let total = &mut totals[index];
+        total.seconds += entry.seconds;
+        total.rounded_seconds += entry.rounded_seconds;
+        if let Some(amount) = entry.amount {
+            total.amount = Some(round_cents(total.amount.unwrap_or(0.0) + amount));
+        }
+    }
+    totals.sort_by(|left, right| {
+        right
+            .rounded_seconds
+            .cmp(&left.rounded_seconds)
+            .then_with(|| left.label.cmp(&right.label))
+    });
+
+    let amounts: Vec<f64> = entries.iter().filter_map(|entry| entry.amount).collect();
+    Timesheet {
+        title,
+        range_start,
+        range_end,
+        first_date: entries.first().map(|entry| entry.date.clone()),
+        last_date: entries.last().map(|entry| entry.date.clone()),
+        rounding: options.rounding,
+        currency: options.currency.clone(),
+        total_seconds: entries.iter().map(|entry| entry.seconds).sum(),
+        total_rounded_seconds: entries.iter().map(|entry| entry.rounded_seconds).sum(),
+        total_amount: (!amounts.is_empty()).then(|| round_cents(amounts.iter().sum())),
+        entries,
+        totals,
+    }
+}
+
+fn money(amount: Option<f64>, currency: Option<&str>) -> String {
+    match (amount, currency) {
+        (None, _) => String::new(),
+        (Some(amount), Some(currency)) => format!("{amount:.2} {currency}"),
+        (Some(amount), None) => format!("{amount:.2}"),
+    }
+}
+
+fn escape_markdown(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+/// Table cells for both formats: a header, the body rows and a total row, plus which columns
+/// hold numbers.
+struct Table {
+    headers: Vec<&'static str>,
+    numeric: Vec<bool>,
+    rows: Vec<Vec<String>>,
+    total: Vec<String>,
+}
+
+fn daily_table(sheet: &Timesheet) -> Table {
+    let with_rates = sheet.total_amount.is_some();
+    let currency = sheet.currency.as_deref();
+    let mut headers = vec!["Date", "Item", "Hours"];
+    if with_rates {
+        headers.extend(["Rate", "Amount"]);
+    }
+    let rows = sheet
+        .entries
+        .iter()
+        .map(|entry| {
+            let mut row = vec![
+                entry.date.clone(),
+                entry.label.clone(),
+                hours(entry.rounded_seconds),
+            ];
+            if with_rates {
+                row.push(money(entry.hourly_rate, None));
+                row.push(money(entry.amount, currency));
+            }
+            row
+        })
+        .collect();
+    let mut total = vec![
+        "Total".to_string(),
+        String::new(),
+        hours(sheet.total_rounded_seconds),
+    ];
+    if with_rates {
+        total.push(String::new());
+        total.push(money(sheet.total_amount, currency));
+    }
+    Table {
+        numeric: headers
+            .iter()
+            .map(|header| *header != "Date" && *header != "Item")
+            .collect(),
+        headers,
+        rows,
+        total,
+    }
+}
+
+fn summary_table(sheet: &Timesheet) -> Table {
+    let with_rates = sheet.total_amount.is_some();
+    let currency = sheet.currency.as_deref();
+    let mut headers = vec!["Item", "Tracked hours", "Billed hours"];
+    if with_rates {
+        headers.push("Amount");
+    }
+    let rows = sheet
+        .totals
+        .iter()
+        .map(|total| {
+            let mut row = vec![
+                total.label.clone(),
+                hours(total.seconds),
+                hours(total.rounded_seconds),
+            ];
+            if with_rates {
+                row.push(money(total.amount, currency));
+            }
+            row
+        })
+        .collect();
+    let mut total = vec![
+        "Total".to_string(),
+        hours(sheet.total_seconds),
+        hours(sheet.total_rounded_seconds),
+    ];
+    if with_rates {
+        total.push(money(sheet.total_amount, currency));
+    }
+    Table {
+        numeric: headers.iter().map(|header| *header != "Item").collect(),
+        headers,
+        rows,
+        total,
+    }
+}
+
+fn markdown_table(table: &Table) -> String {
+    let line = |cells: &[String]| {
+        let cells: Vec<String> = cells.iter().map(|cell| escape_markdown(cell)).collect();
+        format!("| {} |\n", cells.join(" | "))
+    };
+    let mut out = format!("| {} |\n", table.headers.join(" | "));
+    let separators: Vec<&str> = table
+        .numeric
+        .iter()
+        .map(|numeric| if *numeric { "---:" } else { "---" })
+        .collect();
+    out.push_str(&format!("| {} |\n", separators.join(" | ")));
+    for row in &table.rows {
+        out.push_str(&line(row));
+    }
+    let total: Vec<String> = table
+        .total
+        .iter()
+        .map(|cell| {
+            if cell.is_empty() {
+                String::new()
+            } else {
+                format!("**{cell}**")
+            }
+        })
+        .collect();
+    out.push_str(&line(&total));
+    out.trim_end().to_string()
+}
+
+fn html_table(table: &Table) -> String {
+    let class = |column: usize| {
+        if table.numeric[column] {
+            " class=\"number\""
+        } else {
+            ""
+        }
+    };
+    let row = |cells: &[String], tag: &str| {
+        let cells: String = cells
+            .iter()
+            .enumerate()
+            .map(|(column, cell)| format!("<{tag}{}>{}</{tag}>", class(column), escape_html(cell)))
+            .collect();
+        cells
+    };
+    let headers: Vec<String> = table
+        .headers
+        .iter()
+        .map(|header| header.to_string())
+        .collect();
+    let mut out = format!(
+        "<table>\n<thead><tr>{}</tr></thead>\n<tbody>\n",
+        row(&headers, "th")
+    );
+    for cells in &table.rows {
+        out.push_str(&format!("<tr>{}</tr>\n", row(cells, "td")));
+    }
+    out.push_str(&format!(
+        "<tr class=\"total\">{}</tr>\n</tbody>\n</table>",
+        row(&table.total, "td")
+    ));
+    out
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Html,
+    Markdown,
+}
+
+impl ReportFormat {
+    /// Markdown for `.md` and `.markdown`, HTML otherwise.
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("md" | "markdown") => Self::Markdown,
+            _ => Self::Html,
+        }
+    }
+
+    fn template_file(self) -> &'static str {
+        match self {
+            Self::Html => "timesheet.html",
+            Self::Markdown => "timesheet.md",
+        }
+    }
+}
+
+/// Replaces `{{name}}` placeholders in one pass, so placeholders inside substituted values stay
+/// as they are; unknown placeholders are left as they are too.
+fn fill_template(template: &str, values: &[(&str, String)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 2..];
+        let value = after.find("}}").and_then(|close| {
+            let name = &after[..close];
+            let value = values.iter().find(|(key, _)| *key == name)?;
+            Some((&value.1, close))
+        });
+        match value {
+            Some((value, close)) => {
+                out.push_str(value);
+                rest = &after[close + 2..];
+            }
+            None => {
+                out.push('{');
+                rest = &rest[open + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+pub fn render_timesheet(
+    sheet: &Timesheet,
+    format: ReportFormat,
+    template: Option<&str>,
+    generated_at: &str,
+) -> String {
+    let period = match (&sheet.first_date, &sheet.last_date) {
+        (Some(first), Some(last)) if first != last => format!("{first} to {last}"),
+        (Some(first), _) => first.clone(),
+        _ => "No time recorded".to_string(),
+    };
+    let (daily, summary) = (daily_table(sheet), summary_table(sheet));
+    let slices: Vec<ChartSlice> = sheet
+        .totals
+        .iter()
+        .map(|total| ChartSlice {
+            label: total.label.clone(),
+            seconds: total.rounded_seconds,
+            color: total.color.clone(),
+        })
+        .collect();
+    let escape = |value: &str| match format {
+        ReportFormat::Html => escape_html(value),
+        ReportFormat::Markdown => value.to_string(),
+    };
+    let (template, daily_table, summary_table) = match format {
+        ReportFormat::Html => (
+            template.unwrap_or(DEFAULT_HTML_TEMPLATE),
+            html_table(&daily),
+            html_table(&summary),
+        ),
+        ReportFormat::Markdown => (
+            template.unwrap_or(DEFAULT_MARKDOWN_TEMPLATE),
+            markdown_table(&daily),
+            markdown_table(&summary),
+        ),
+    };
+    let currency = sheet.currency.as_deref();
+    fill_template(
+        template,
+        &[
+            ("title", escape(&sheet.title)),
+            ("period", escape(&period)),
+            ("rounding", escape(&sheet.rounding.describe())),
+            ("generated_at", escape(generated_at)),
+            ("daily_table", daily_table),
+            ("summary_table", summary_table),
+            ("summary_chart", donut_chart(&sheet.title, &slices)),
+            ("total_hours", hours(sheet.total_rounded_seconds)),
+            ("total_amount", escape(&money(sheet.total_amount, currency))),
+        ],
+    )
+}
+
+/// An explicit template path, else `report_templates/timesheet.{html,md}` in the data
+/// directory, else the built-in template.
+fn load_template(
+    format: ReportFormat,
+    template_path: Option<&str>,
+) -> Result<Option<String>, Error> {
+    let path = match template_path.map(str::trim).filter(|path| !path.is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let path = crate::instance::data_dir()
+                .join(TEMPLATE_DIR)
+                .join(format.template_file());
+            if !path.exists() {
+                return Ok(None);
+            }
+            path
+        }
+    };
+    let template = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read template {}", path.display()))?;
+    Ok(Some(template))
+}
+
+/// Seconds per day and category or project, from tracked logs and manual time blocks, both
+/// clipped to the range. Tracked time counts towards the day it started on, minus any part
+/// under a block when manual time overrides it.
+async fn collect_cells(
+    range_start: i64,
+    range_end: i64,
+    options: &TimesheetOptions,
+) -> Result<(TimesheetCells, BillableRates), Error> {
+    let tz = load_time_zone().await?;
+    let start_hour = load_calendar_settings().await?.start_hour;
+    let day = |timestamp: i64| calendar_date(tz, timestamp, start_hour);
+    let blocks = match options.manual_time {
+        ManualTimeMode::Exclude => Vec::new(),
+        ManualTimeMode::Add | ManualTimeMode::Override => {
+            get_manual_time_blocks(range_start, range_end).await?
+        }
+    };
+    let covered = match options.manual_time {
+        ManualTimeMode::Override => covered_intervals(&blocks),
+        ManualTimeMode::Exclude | ManualTimeMode::Add => Vec::new(),
+    };
+    let logs: Vec<LabelledLog> =
+        labelled_logs_overlapping(range_start, range_end, options.device_uuids.clone())
+            .await?
+            .into_iter()
+            .flat_map(|log| {
+                billable_parts(
+                    log.timestamp,
+                    log.duration,
+                    range_start,
+                    range_end,
+                    &covered,
+                )
+                .into_iter()
+                .map(move |(timestamp, duration)| LabelledLog {
+                    timestamp,
+                    duration,
+                    ..log.clone()
+                })
+            })
+            .collect();
+    let mut cells = TimesheetCells::new();
+    let mut rates = BillableRates::new();
+
+    match options.grouping {
+        TimesheetGrouping::Category => {
+            let labeller = LogLabeller::load().await?;
+            for log in logs {
+                *cells.entry((day(log.timestamp), log.category)).or_insert(0) += log.duration;
+            }
+            for block in blocks {
+                let start = block.start_time.max(range_start);
+                let label = labeller.manual_category(&block);
+                *cells.entry((day(start), label)).or_insert(0) +=
+                    block.end_time.min(range_end) - start;
+            }
+        }
+        TimesheetGrouping::Project => {
+            let pool = get_pool().await?;
+            let projects = fetch_projects(&pool).await?;
+            let clients = fetch_clients(&pool).await?;
+            let assignments = fetch_project_assignments(&pool, range_start, range_end).await?;
+            let resolver = ProjectResolver::new(&projects, assignments)?;
+            let names: HashMap<i64, String> = projects
+                .iter()
+                .map(|project| (project.id, project.name.clone()))
+                .collect();
+            let label = |project_id: Option<i64>| {
+                project_id
+                    .and_then(|id| names.get(&id).cloned())
+                    .unwrap_or_else(|| UNASSIGNED_LABEL.to_string())
+            };
+
+            for log in logs {
+                for (project_id, share) in
+                    resolver.attribute(&log.title, log.timestamp, log.duration)
+                {
+                    *cells
+                        .entry((day(log.timestamp), label(project_id)))
+                        .or_insert(0) += share;
+                }
+            }
+            for block in blocks {
+                let start = block.start_time.max(range_start);
+                *cells
+                    .entry((day(start), label(block.project_id)))
+                    .or_insert(0) += block.end_time.min(range_end) - start;
+            }
+
+            for project in projects.iter().filter(|project| project.billable) {
+                let client_rate = project.client_id.and_then(|id| {
+                    clients
+                        .iter()
+                        .find(|client| client.id == id)
+                        .and_then(|client| client.hourly_rate)
+                });
+                let rate = if options.include_rates {
+                    project.hourly_rate.or(client_rate)
+                } else {
+                    None
+                };
+                rates.insert(project.name.clone(), rate);
+            }
+        }
+    }
+    Ok((cells, rates))
+}
+
+async fn timesheet(
+    range_start: i64,
+    range_end: i64,
+    options: &TimesheetOptions,
+) -> Result<Timesheet, Error> {
+    if range_end <= range_start {
+        return Err(anyhow::anyhow!("Range end must be after range start").into());
+    }
+    validate_rate(options.hourly_rate)?;
+    let (cells, rates) = collect_cells(range_start, range_end, options).await?;
+    let title = options
+        .title
+        .as_deref()
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .unwrap_or("Timesheet")
+        .to_string();
+    let mut sheet = build_timesheet(title, range_start, range_end, &cells, &rates, options);
+    if options.grouping == TimesheetGrouping::Category {
+        let colors = category_colors().await?;
+        for total in &mut sheet.totals {
+            total.color = colors.get(&total.label).cloned();
+        }
+    }
+    Ok(sheet)
+}
+
+#[tauri::command]
+pub async fn get_timesheet(
+    range_start: i64,
+    range_end: i64,
+    options: Option<TimesheetOptions>,
+) -> Result<Timesheet, Error> {
+    timesheet(range_start, range_end, &options.unwrap_or_default()).await
+}
+
+/// Renders the timesheet to `path` as HTML, or Markdown for `.md` files, and returns it.
+#[tauri::command]
+pub async fn export_timesheet(
+    path: String,
+    range_start: i64,
+    range_end: i64,
+    options: Option<TimesheetOptions>,
+) -> Result<Timesheet, Error> {
+    let options = options.unwrap_or_default();
+    let sheet = timesheet(range_start, range_end, &options).await?;
+    let path = Path::new(path.trim());
+    let format = ReportFormat::from_path(path);
+    let template = load_template(format, options.template_path.as_deref())?;
+    let generated_at = local_datetime(load_time_zone().await?, chrono::Utc::now().timestamp())
+        .format("%Y-%m-%d %H:%M")
+        .to_string();
+    std::fs::write(
+        path,
+        render_timesheet(&sheet, format, template.as_deref(), &generated_at),
+    )
+    .with_context(|| format!("Failed to write timesheet to {}", path.display()))?;
+    Ok(sheet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+    }
+
+    fn sample(options: &TimesheetOptions) -> Timesheet {
+        let cells = TimesheetCells::from([
+            ((date(6), "Website".to_string()), 3_000),
+            ((date(6), "Support".to_string()), 600),
+            ((date(7), "Website".to_string()), 5_400),
+            ((date(7), "Empty".to_string()), 0),
+        ]);
+        let rates = BillableRates::from([
+            ("Website".to_string(), Some(100.0)),
+            ("Support".to_string(), None),
+        ]);
+        build_timesheet("May".into(), 0, 1, &cells, &rates, options)
+    }
+
+    #[test]
+    fn rounding_rules() {
+        let rule = |minutes, mode| RoundingRule { minutes, mode };
+        assert_eq!(rule(15, RoundingMode::Up).apply(60), 900);
+        assert_eq!(rule(15, RoundingMode::Up).apply(900), 900);
+        assert_eq!(rule(15, RoundingMode::Down).apply(1_799), 900);
+        assert_eq!(rule(15, RoundingMode::Nearest).apply(1_349), 900);
+        assert_eq!(rule(15, RoundingMode::Nearest).apply(1_350), 1_800);
+        assert_eq!(rule(0, RoundingMode::Up).apply(61), 61);
+    }
+
+    #[test]
+    fn totals_use_rounded_time_and_rates() {
+        let options = TimesheetOptions {
+            grouping: TimesheetGrouping::Project,
+            rounding: RoundingRule {
+                minutes: 15,
+                mode: RoundingMode::Up,
+            },
+            ..Default::default()
+        };
+        let sheet = sample(&options);
+        assert_eq!(sheet.entries.len(), 3);
+        assert_eq!(sheet.first_date.as_deref(), Some("2024-05-06"));
+        // 50 min -> 60 min, 10 min -> 15 min, 90 min stays.
+        assert_eq!(
+            sheet
+                .entries
+                .iter()
+                .map(|entry| entry.rounded_seconds)
+                .collect::<Vec<_>>(),
+            vec![900, 3_600, 5_400]
+        );
+        assert_eq!(sheet.totals[0].label, "Website");
+        assert_eq!(sheet.totals[0].amount, Some(250.0));
+        assert_eq!(sheet.totals[1].amount, None);
+        assert_eq!(sheet.total_rounded_seconds, 9_900);
+        assert_eq!(sheet.total_amount, Some(250.0));
+
+        let flat = sample(&TimesheetOptions {
+            hourly_rate: Some(60.0),
+            ..options.clone()
+        });
+        assert_eq!(flat.totals[1].amount, Some(15.0));
+        assert_eq!(flat.total_amount, Some(265.0));
+    }
+
+    #[test]
+    fn only_billable_projects_fall_back_to_the_flat_rate() {
+        let cells = TimesheetCells::from([
+            ((date(6), "Website".to_string()), 3_600),
+            ((date(6), "Internal".to_string()), 3_600),
+            ((date(6), UNASSIGNED_LABEL.to_string()), 3_600),
+        ]);
+        let rates = BillableRates::from([("Website".to_string(), None)]);
+        let options = TimesheetOptions {
+            grouping: TimesheetGrouping::Project,
+            hourly_rate: Some(50.0),
+            ..Default::default()
+        };
+        let sheet = build_timesheet("May".into(), 0, 1, &cells, &rates, &options);
+        let rate = |label: &str| {
+            let entry = sheet.entries.iter().find(|entry| entry.label == label);
+            entry.unwrap().hourly_rate
+        };
+        assert_eq!(rate("Website"), Some(50.0));
+        assert_eq!(rate("Internal"), None);
+        assert_eq!(rate(UNASSIGNED_LABEL), None);
+        assert_eq!(sheet.total_amount, Some(50.0));
+
+        let by_category = TimesheetOptions {
+            grouping: TimesheetGrouping::Category,
+            ..options
+        };
+        let sheet = build_timesheet("May".into(), 0, 1, &cells, &rates, &by_category);
+        assert_eq!(sheet.total_amount, Some(150.0));
+    }
+
+    #[test]
+    fn renders_markdown_and_html() {
+        let mut sheet = sample(&TimesheetOptions {
+            grouping: TimesheetGrouping::Project,
+            currency: Some("EUR".into()),
+            ..Default::default()
+        });
+        sheet.title = "Acme | <May>".into();
+
+        let markdown = render_timesheet(&sheet, ReportFormat::Markdown, None, "now");
+        assert!(markdown.starts_with("# Acme | <May>\n"));
+        assert!(markdown.contains("2024-05-06 to 2024-05-07 · exact times"));
+        assert!(markdown.contains(
+            "| Date | Item | Hours | Rate | Amount |\n| --- | --- | ---: | ---: | ---: |"
+        ));
+        assert!(markdown.contains("| 2024-05-07 | Website | 1.50 | 100.00 | 150.00 EUR |"));
+        assert!(markdown.contains("| **Total** | **2.50** | **2.50** | **233.33 EUR** |"));
+        // The chart is one line, so Markdown keeps it as a single inline HTML block.
+        let chart = markdown
+            .lines()
+            .find(|line| line.starts_with("<svg"))
+            .unwrap();
+        assert!(chart.ends_with("</svg>"));
+        assert!(chart.contains("<title>Acme | &lt;May&gt;</title>"));
+        assert!(!markdown.contains("{{"));
+
+        let html = render_timesheet(&sheet, ReportFormat::Html, None, "now");
+        assert!(html.contains("<title>Acme | &lt;May&gt;</title>"));
+        assert!(html.contains("<td class=\"number\">1.50</td>"));
+        assert!(!html.contains("{{"));
+
+        let custom = render_timesheet(
+            &sheet,
+            ReportFormat::Markdown,
+            Some("{{title}}: {{total_hours}} h, {{total_amount}} {{unknown}}"),
+            "now",
+        );
+        assert_eq!(custom, "Acme | <May>: 2.50 h, 233.33 EUR {{unknown}}");
+
+        sheet.title = "{{daily_table}}".into();
+        let title_only = render_timesheet(
+            &sheet,
+            ReportFormat::Markdown,
+            Some("{{title}} {{{title}}}"),
+            "now",
+        );
+        assert_eq!(title_only, "{{daily_table}} {{{daily_table}}}");
+        assert_eq!(
+            ReportFormat::from_path(Path::new("sheet.MD")),
+            ReportFormat::Markdown
+        );
+    }
+}
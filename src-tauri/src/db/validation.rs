@@ -178,6 +178,12 @@ fn get_expected_tables() -> Vec<ExpectedTable> {
                     not_null: true,
                     default_value: Some("1"),
                 },
+                ExpectedColumn {
+                    name: "productivity_weight",
+                    sql_type: "INTEGER",
+                    not_null: true,
+                    default_value: Some("0"),
+                },
             ],
         },
         ExpectedTable {
@@ -201,6 +207,18 @@ fn get_expected_tables() -> Vec<ExpectedTable> {
                     not_null: true,
                     default_value: None,
                 },
+                ExpectedColumn {
+                    name: "effective_from",
+                    sql_type: "INTEGER",
+                    not_null: false,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "effective_until",
+                    sql_type: "INTEGER",
+                    not_null: false,
+                    default_value: None,
+                },
             ],
         },
         ExpectedTable {
@@ -226,6 +244,106 @@ fn get_expected_tables() -> Vec<ExpectedTable> {
                 },
             ],
         },
+        ExpectedTable {
+            name: "key_extractors",
+            columns: vec![
+                ExpectedColumn {
+                    name: "id",
+                    sql_type: "INTEGER",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "name",
+                    sql_type: "TEXT",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "regex",
+                    sql_type: "TEXT",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "template",
+                    sql_type: "TEXT",
+                    not_null: false,
+                    default_value: None,
+                },
+            ],
+        },
+        ExpectedTable {
+            name: "goals",
+            columns: vec![
+                ExpectedColumn {
+                    name: "id",
+                    sql_type: "INTEGER",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "name",
+                    sql_type: "TEXT",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "target_kind",
+                    sql_type: "TEXT",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "target_id",
+                    sql_type: "INTEGER",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "period",
+                    sql_type: "TEXT",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "direction",
+                    sql_type: "TEXT",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "target_seconds",
+                    sql_type: "INTEGER",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "window_start_hour",
+                    sql_type: "INTEGER",
+                    not_null: false,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "window_end_hour",
+                    sql_type: "INTEGER",
+                    not_null: false,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "weekdays",
+                    sql_type: "INTEGER",
+                    not_null: true,
+                    default_value: Some("127"),
+                },
+                ExpectedColumn {
+                    name: "is_enabled",
+                    sql_type: "INTEGER",
+                    not_null: true,
+                    default_value: Some("1"),
+                },
+            ],
+        },
         ExpectedTable {
             name: "skipped_apps",
             columns: vec![
@@ -331,6 +449,105 @@ fn get_expected_tables() -> Vec<ExpectedTable> {
                 },
             ],
         },
+        ExpectedTable {
+            name: "clients",
+            columns: vec![
+                ExpectedColumn {
+                    name: "id",
+                    sql_type: "INTEGER",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "name",
+                    sql_type: "TEXT",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "hourly_rate",
+                    sql_type: "REAL",
+                    not_null: false,
+                    default_value: None,
+                },
+            ],
+        },
+        ExpectedTable {
+            name: "projects",
+            columns: vec![
+                ExpectedColumn {
+                    name: "id",
+                    sql_type: "INTEGER",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "name",
+                    sql_type: "TEXT",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "client_id",
+                    sql_type: "INTEGER",
+                    not_null: false,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "billable",
+                    sql_type: "INTEGER",
+                    not_null: true,
+                    default_value: Some("1"),
+                },
+                ExpectedColumn {
+                    name: "hourly_rate",
+                    sql_type: "REAL",
+                    not_null: false,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "regex",
+                    sql_type: "TEXT",
+                    not_null: false,
+                    default_value: None,
+                },
+            ],
+        },
+        ExpectedTable {
+            name: "project_assignments",
+            columns: vec![
+                ExpectedColumn {
+                    name: "id",
+                    sql_type: "INTEGER",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "project_id",
+                    sql_type: "INTEGER",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "start_time",
+                    sql_type: "INTEGER",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "end_time",
+                    sql_type: "INTEGER",
+                    not_null: true,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "created_at",
+                    sql_type: "INTEGER",
+                    not_null: true,
+                    default_value: None,
+                },
+            ],
+        },
         ExpectedTable {
             name: "manual_time_blocks",
             columns: vec![
@@ -376,6 +593,30 @@ fn get_expected_tables() -> Vec<ExpectedTable> {
                     not_null: true,
                     default_value: None,
                 },
+                ExpectedColumn {
+                    name: "project_id",
+                    sql_type: "INTEGER",
+                    not_null: false,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "external_uid",
+                    sql_type: "TEXT",
+                    not_null: false,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "category_id",
+                    sql_type: "INTEGER",
+                    not_null: false,
+                    default_value: None,
+                },
+                ExpectedColumn {
+                    name: "tags",
+                    sql_type: "TEXT",
+                    not_null: true,
+                    default_value: Some("''"),
+                },
             ],
         },
         ExpectedTable {
@@ -439,6 +680,22 @@ fn get_expected_tables() -> Vec<ExpectedTable> {
     ]
 }
 
+/// How a column this version expects reads in a database that does not have it yet: its default,
+/// or NULL when it may be empty. None for unknown columns and required ones without a default.
+pub(crate) fn missing_column_default(table: &str, column: &str) -> Option<&'static str> {
+    let expected = get_expected_tables()
+        .into_iter()
+        .find(|expected| expected.name == table)?
+        .columns
+        .into_iter()
+        .find(|expected| expected.name == column)?;
+    match expected.default_value {
+        Some(default) => Some(default),
+        None if !expected.not_null => Some("NULL"),
+        None => None,
+    }
+}
+
 async fn get_table_schema(
     pool: &SqlitePool,
     table_name: &str,
@@ -583,10 +840,15 @@ async fn create_table_safe(pool: &SqlitePool, table: &ExpectedTable) -> Result<(
         "category" => tables::category::create_table(pool).await?,
         "category_regex" => tables::cat_regex::create_table(pool).await?,
         "app_groups" => tables::app_group::create_table(pool).await?,
+        "key_extractors" => tables::key_extractor::create_table(pool).await?,
+        "goals" => tables::goal::create_table(pool).await?,
         "skipped_apps" => tables::skipped_app::create_table(pool).await?,
         "google_oauth" => tables::google_calendar::create_table(pool).await?,
         "google_calendar_v2" => tables::google_calendar::create_table(pool).await?,
         "manual_time_blocks" => tables::manual_time_block::create_table(pool).await?,
+        "clients" => tables::client::create_table(pool).await?,
+        "projects" => tables::project::create_table(pool).await?,
+        "project_assignments" => tables::project::create_assignments_table(pool).await?,
         "app_metadata" => {
             sqlx::query(
                 "CREATE TABLE IF NOT EXISTS app_metadata (
@@ -0,0 +1,82 @@
+use crate::db::format_duration;
+use crate::db::queries::goals::{evaluate_goals, goal_event, GoalEvent, GoalProgress, GoalStatus};
+use crate::db::tables::settings::get_settings;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+
+const GOAL_CHECK_INTERVAL_SECS: u64 = 60;
+
+async fn notifications_enabled() -> bool {
+    get_settings()
+        .await
+        .ok()
+        .and_then(|settings| {
+            settings
+                .into_iter()
+                .find(|setting| setting.key == "goalNotifications")
+        })
+        .is_none_or(|setting| setting.val != 0)
+}
+
+fn notify(app: &AppHandle, event: GoalEvent, progress: &GoalProgress, show_desktop: bool) {
+    let (event_name, title, body) = match event {
+        GoalEvent::Met => (
+            "goal-met",
+            format!("Goal met: {}", progress.goal.name),
+            format!(
+                "{} reached {} of {}",
+                progress.target_name,
+                format_duration(progress.tracked_seconds),
+                format_duration(progress.goal.target_seconds)
+            ),
+        ),
+        GoalEvent::LimitCrossed => (
+            "goal-limit-crossed",
+            format!("Limit crossed: {}", progress.goal.name),
+            format!(
+                "{} is at {}, over the {} limit",
+                progress.target_name,
+                format_duration(progress.tracked_seconds),
+                format_duration(progress.goal.target_seconds)
+            ),
+        ),
+    };
+    let _ = app.emit(event_name, progress);
+    if !show_desktop {
+        return;
+    }
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        eprintln!("goal notification failed: {e}");
+    }
+}
+
+/// Re-evaluates goals every minute and reports each goal met or limit crossed once per period.
+pub async fn goal_evaluator(app: AppHandle) {
+    // Status of each goal per period, keyed by (goal id, period start).
+    let mut seen: HashMap<(i64, i64), GoalStatus> = HashMap::new();
+    let mut first_pass = true;
+    loop {
+        let now = chrono::Utc::now().timestamp();
+        match evaluate_goals(now, now, None).await {
+            Ok(progress) => {
+                let notify_enabled = notifications_enabled().await;
+                let mut current = HashMap::with_capacity(progress.len());
+                for goal_progress in &progress {
+                    let key = (goal_progress.goal.id, goal_progress.period_start);
+                    let previous = seen.get(&key).copied();
+                    current.insert(key, goal_progress.status);
+                    if let Some(event) = goal_event(previous, goal_progress.status, !first_pass) {
+                        notify(&app, event, goal_progress, notify_enabled);
+                    }
+                }
+                seen = current;
+                first_pass = false;
+                let _ = app.emit("goal-progress", &progress);
+            }
+            Err(e) => eprintln!("goal evaluation failed: {e}"),
+        }
+        tokio::time::sleep(Duration::from_secs(GOAL_CHECK_INTERVAL_SECS)).await;
+    }
+}
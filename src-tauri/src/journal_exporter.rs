@@ -0,0 +1,23 @@
+use crate::db::journal::export_due_journals;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const JOURNAL_CHECK_INTERVAL_SECS: u64 = 300;
+
+/// Writes the daily-notes section for each day once it has ended, checking every five minutes.
+pub async fn journal_exporter(app: AppHandle) {
+    loop {
+        match export_due_journals(chrono::Utc::now().timestamp()).await {
+            Ok(written) if !written.is_empty() => {
+                let paths: Vec<String> = written
+                    .iter()
+                    .map(|path| path.to_string_lossy().to_string())
+                    .collect();
+                let _ = app.emit("journal-exported", &paths);
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("journal export failed: {e}"),
+        }
+        tokio::time::sleep(Duration::from_secs(JOURNAL_CHECK_INTERVAL_SECS)).await;
+    }
+}
@@ -2,28 +2,38 @@ mod app_prefs;
 mod commands;
 mod core;
 mod db;
+mod goal_notifier;
 mod google_oauth;
 mod instance;
+mod journal_exporter;
 mod sync;
 mod tray;
 
 use commands::{apply_update_cmd, check_update_cmd};
 use core::{get_tracking_status, set_tracking_status, supervisor};
 use db::queries::{
-    get_day_statistics, get_total_statistics, get_week, get_week_for_app_filter,
-    get_week_statistics,
+    accept_gap_suggestion, export_attendance_report, get_attendance_report, get_billing_report,
+    get_category_trends, get_day_statistics, get_goal_progress, get_key_report, get_range,
+    get_range_statistics, get_time_zone, get_title_drilldown, get_total_statistics,
+    get_untracked_gaps, get_week, get_week_for_app_filter, get_week_statistics, get_year_heatmap,
+    set_time_zone,
 };
 use db::tables::app_group::{delete_app_group, get_app_groups, insert_app_group, update_app_group};
 use db::tables::app_metadata_kv::{get_server_ip, set_server_ip};
 use db::tables::cat_regex::{
-    delete_cat_regex_by_id, get_cat_regex, get_cat_regex_by_id, insert_cat_regex,
-    update_cat_regex_by_id,
+    delete_cat_regex_by_id, get_cat_regex, get_cat_regex_by_id, get_cat_regex_history,
+    insert_cat_regex, reapply_current_rules, update_cat_regex_by_id,
 };
 use db::tables::category::{
     delete_category_by_id, get_categories, get_category_by_id, insert_category,
-    update_category_by_id,
+    set_category_productivity_weight, update_category_by_id,
 };
+use db::tables::client::{delete_client, get_clients, insert_client, update_client};
 use db::tables::device::{get_local_device_name, insert_devices, set_is_tracking, update_device};
+use db::tables::goal::{delete_goal, get_goals, insert_goal, update_goal};
+use db::tables::key_extractor::{
+    delete_key_extractor, get_key_extractors, insert_key_extractor, update_key_extractor,
+};
 use db::tables::google_calendar::{
     delete_google_calendar, get_google_calendar_by_id, get_google_calendars,
     insert_google_calendar, update_google_calendar,
@@ -38,11 +48,25 @@ use db::tables::manual_time_block::{
     get_running_manual_timer, insert_manual_time_block, start_manual_timer,
     stop_manual_timer, update_manual_time_block, update_manual_timer_title,
 };
+use db::tables::project::{
+    delete_project, delete_project_assignment, get_project_assignments, get_projects,
+    insert_project, insert_project_assignment, update_project,
+};
+use db::activitywatch::import_activitywatch;
+use db::archive::{export_archive, import_archive, inspect_archive};
+use db::charts::{render_heatmap_chart, render_statistics_chart};
+use db::csv_import::{import_csv, preview_csv_import};
+use db::export::export_data;
+use db::ics::{export_ics, import_ics};
+use db::journal::{export_journal, get_journal_config, set_journal_config};
+use db::merge::merge_database;
+use db::rule_pack::{export_rule_pack, import_rule_pack};
 use db::tables::settings::{flip_lock_by_key, get_settings, reset_val_by_key, update_val_by_key};
 use db::tables::skipped_app::{
     count_matching_logs, delete_skipped_app_by_id, get_skipped_apps,
     insert_skipped_app_and_delete_logs, restore_default_skipped_apps, update_skipped_app_by_id,
 };
+use db::timesheet::{export_timesheet, get_timesheet};
 use std::sync::atomic::Ordering;
 use std::sync::{atomic::AtomicBool, Mutex};
 
@@ -211,6 +235,8 @@ pub fn run() {
             });
 
             tauri::async_runtime::spawn(supervisor(app.handle().clone()));
+            tauri::async_runtime::spawn(goal_notifier::goal_evaluator(app.handle().clone()));
+            tauri::async_runtime::spawn(journal_exporter::journal_exporter(app.handle().clone()));
 
             let handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
@@ -280,26 +306,63 @@ pub fn run() {
         })
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_notification::init())
         .invoke_handler(tauri::generate_handler![
             get_categories,
             get_week,
             get_week_for_app_filter,
+            get_range,
+            get_range_statistics,
+            get_time_zone,
+            set_time_zone,
             get_week_statistics,
             get_total_statistics,
             get_day_statistics,
+            get_category_trends,
+            get_year_heatmap,
+            get_attendance_report,
+            export_attendance_report,
+            get_untracked_gaps,
+            accept_gap_suggestion,
+            get_title_drilldown,
+            get_key_report,
+            get_billing_report,
             delete_category_by_id,
             get_category_by_id,
             insert_category,
             update_category_by_id,
+            set_category_productivity_weight,
+            get_goals,
+            insert_goal,
+            update_goal,
+            delete_goal,
+            get_goal_progress,
             get_cat_regex,
             get_cat_regex_by_id,
             delete_cat_regex_by_id,
             insert_cat_regex,
             update_cat_regex_by_id,
+            get_cat_regex_history,
+            reapply_current_rules,
             get_app_groups,
             insert_app_group,
             update_app_group,
             delete_app_group,
+            get_key_extractors,
+            insert_key_extractor,
+            update_key_extractor,
+            delete_key_extractor,
+            get_clients,
+            insert_client,
+            update_client,
+            delete_client,
+            get_projects,
+            insert_project,
+            update_project,
+            delete_project,
+            get_project_assignments,
+            insert_project_assignment,
+            delete_project_assignment,
             get_logs,
             get_log_by_id,
             delete_log_by_id,
@@ -324,6 +387,25 @@ pub fn run() {
             delete_skipped_app_by_id,
             count_matching_logs,
             restore_default_skipped_apps,
+            export_data,
+            export_ics,
+            import_ics,
+            import_activitywatch,
+            preview_csv_import,
+            import_csv,
+            get_timesheet,
+            export_timesheet,
+            export_archive,
+            inspect_archive,
+            import_archive,
+            merge_database,
+            get_journal_config,
+            set_journal_config,
+            export_journal,
+            render_statistics_chart,
+            render_heatmap_chart,
+            export_rule_pack,
+            import_rule_pack,
             get_db_path_cmd,
             get_database_location,
             probe_database_location,
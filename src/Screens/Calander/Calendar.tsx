@@ -22,21 +22,23 @@ import {getDevices, getCalendarDevices, buildDeviceUuidsForFilter, updateDevice,
 import {useBackendSettings} from "../../hooks/useBackendSettings.ts";
 import {getAppMetadata, setAppMetadata} from "../../api/appMetadata.ts";
 import ManualTimeBlockDialog from "./ManualTimeBlockDialog.tsx";
-import {get_running_manual_timer} from "../../api/ManualTimeBlock.ts";
+import {get_running_manual_timer, type ManualTimeMode} from "../../api/ManualTimeBlock.ts";
 import {ManualTimerControl} from "./ManualTimer.tsx";
 
 const INCLUDE_GOOGLE_IN_STATS_KEY = "time-tracker:include-google-in-stats";
 const MANUAL_TIME_IN_CAL_KEY = "time-tracker:manual-time-in-calendar";
 const MANUAL_TIME_IN_STATS_KEY = "time-tracker:manual-time-in-stats";
+const MANUAL_TIME_OVERRIDES_KEY = "time-tracker:manual-time-overrides-tracked";
 
 export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View) => void }) {
     const [rightSideBarView, setRightSideBarView] = useState<SideBarView>("Week")
     const {date, setDate} = useDateStore();
-    const {calendarStartHour, timeBlockSettings} = useBackendSettings();
+    const {calendarStartHour, weekStartDay, timeZone, timeBlockSettings} = useBackendSettings();
     const calendarAppFilterActive = useCalendarAppFilterActive();
     const [includeGoogleInStats, setIncludeGoogleInStats] = useState(true);
     const [manualTimeInCal, setManualTimeInCal] = useState(true);
     const [manualTimeInStats, setManualTimeInStats] = useState(true);
+    const [manualTimeOverrides, setManualTimeOverrides] = useState(false);
     const [showManualTimeDialog, setShowManualTimeDialog] = useState(false);
     const includeGoogleInStatsLoadedRef = useRef(false);
     const [appFilterPrevWeek, setAppFilterPrevWeek] = useState<Date | null>(null);
@@ -128,9 +130,11 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
         Promise.all([
             getAppMetadata(MANUAL_TIME_IN_CAL_KEY),
             getAppMetadata(MANUAL_TIME_IN_STATS_KEY),
-        ]).then(([inCal, inStats]) => {
+            getAppMetadata(MANUAL_TIME_OVERRIDES_KEY),
+        ]).then(([inCal, inStats, overrides]) => {
             if (inCal === "0") setManualTimeInCal(false);
             if (inStats === "0") setManualTimeInStats(false);
+            if (overrides === "1") setManualTimeOverrides(true);
         }).catch(() => {});
     }, []);
 
@@ -150,6 +154,15 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
         });
     }, []);
 
+    const changeManualTimeOverrides = useCallback((next: boolean) => {
+        setManualTimeOverrides(next);
+        setAppMetadata(MANUAL_TIME_OVERRIDES_KEY, next ? "1" : "0").catch(() => {});
+    }, []);
+
+    const manualTimeMode: ManualTimeMode = !manualTimeInStats
+        ? "exclude"
+        : manualTimeOverrides ? "override" : "add";
+
     useEffect(() => {
         if (didAlignInitialWeekToBoundary.current) return;
         didAlignInitialWeekToBoundary.current = true;
@@ -253,7 +266,7 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
         };
     }, [queryClient]);
 
-    const weekStart = getWeekStart(date, calendarStartHour);
+    const weekStart = getWeekStart(date, calendarStartHour, weekStartDay);
     const weekDataQueryEnabled =
         !!weekStart && !isNaN(weekStart.getTime()) && !!selectedEvent;
     const {data: weekData} = useQuery({
@@ -261,6 +274,7 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
             "week",
             formatLocalDateYMD(weekStart),
             calendarStartHour,
+            weekStartDay,
             timeBlockSettings.minLogDuration,
             timeBlockSettings.maxAttachDistance,
             timeBlockSettings.lookaheadWindow,
@@ -326,7 +340,7 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
                     endTime = day_end;
                     title = `${selectedCategory} - ${selectedDate.toLocaleDateString()}`;
                 } else {
-                    const weekRange = getWeekRange(date, calendarStartHour);
+                    const weekRange = getWeekRange(date, calendarStartHour, weekStartDay, timeZone);
                     startTime = weekRange.week_start;
                     endTime = weekRange.week_end;
                     title = `${selectedCategory} - Week`;
@@ -401,7 +415,7 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
         };
 
         fetchCategoryLogs();
-    }, [selectedCategory, rightSideBarView, selectedDate, date, calendarStartHour, timeBlockSettings.minLogDuration]);
+    }, [selectedCategory, rightSideBarView, selectedDate, date, calendarStartHour, weekStartDay, timeZone, timeBlockSettings.minLogDuration]);
 
     useEffect(() => {
         if (rightSideBarView === "CategoryFilter") {
@@ -446,6 +460,9 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
                     apps: [],
                     manualTimeBlockId: clickInfo.event.extendedProps?.manualTimeBlockId as number,
                     notes: clickInfo.event.extendedProps?.notes as string | undefined,
+                    projectId: clickInfo.event.extendedProps?.projectId as number | null | undefined,
+                    categoryId: clickInfo.event.extendedProps?.categoryId as number | null | undefined,
+                    tags: clickInfo.event.extendedProps?.tags as string[] | undefined,
                 });
                 setSelectedDate(null);
                 setSelectedEventLogs([]);
@@ -685,9 +702,9 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
         const calendarApi = calenderRef.current?.getApi();
         if (calendarApi && !isUpdatingFromStore.current) {
             const calendarWeekYmd = formatLocalDateYMD(
-                getWeekStart(calendarApi.getDate(), calendarStartHour)
+                getWeekStart(calendarApi.getDate(), calendarStartHour, weekStartDay)
             );
-            const storeWeekYmd = formatLocalDateYMD(getWeekStart(date, calendarStartHour));
+            const storeWeekYmd = formatLocalDateYMD(getWeekStart(date, calendarStartHour, weekStartDay));
 
             if (calendarWeekYmd !== storeWeekYmd) {
                 isUpdatingFromStore.current = true;
@@ -697,7 +714,7 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
                 }, 100);
             }
         }
-    }, [date, calendarStartHour]);
+    }, [date, calendarStartHour, weekStartDay]);
 
     const handleDatesSet = (dates: DatesSetArg) => {
         if (isUpdatingFromStore.current) {
@@ -711,8 +728,8 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
             calendarDate.setHours(0, 0, 0, 0);
             storeDate.setHours(0, 0, 0, 0);
 
-            const calendarWeekStart = getWeekStart(calendarDate, calendarStartHour);
-            const storeWeekStart = getWeekStart(storeDate, calendarStartHour);
+            const calendarWeekStart = getWeekStart(calendarDate, calendarStartHour, weekStartDay);
+            const storeWeekStart = getWeekStart(storeDate, calendarStartHour, weekStartDay);
 
             if (calendarWeekStart.getTime() !== storeWeekStart.getTime()) {
                 setDate(calendarWeekStart);
@@ -793,7 +810,7 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
     }, [queryClient]);
 
     const goToPrevWeek = () => {
-        const ws = getWeekStart(date, calendarStartHour);
+        const ws = getWeekStart(date, calendarStartHour, weekStartDay);
         const newDate = new Date(ws);
         newDate.setDate(newDate.getDate() - 7);
         setDate(newDate);
@@ -804,7 +821,7 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
     };
 
     const goToNextWeek = () => {
-        const ws = getWeekStart(date, calendarStartHour);
+        const ws = getWeekStart(date, calendarStartHour, weekStartDay);
         const newDate = new Date(ws);
         newDate.setDate(newDate.getDate() + 7);
         setDate(newDate);
@@ -855,8 +872,8 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
         let cancelled = false;
         const findWeeks = async () => {
             setIsResolvingAppFilterWeeks(true);
-            const baseWeek = getWeekStart(date, calendarStartHour);
-            const nowWeek = getWeekStart(adjustInstantToCalendarDayBoundary(new Date(), calendarStartHour), calendarStartHour);
+            const baseWeek = getWeekStart(date, calendarStartHour, weekStartDay);
+            const nowWeek = getWeekStart(adjustInstantToCalendarDayBoundary(new Date(), calendarStartHour), calendarStartHour, weekStartDay);
             const minBoundary = new Date(2000, 0, 1);
             const maxSteps = 520;
 
@@ -913,12 +930,12 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
         return () => {
             cancelled = true;
         };
-    }, [calendarAppFilterActive, date, calendarStartHour, timeBlockSettings, calDeviceUuids]);
+    }, [calendarAppFilterActive, date, calendarStartHour, weekStartDay, timeBlockSettings, calDeviceUuids]);
 
     const appJumpNextDisabled = !calendarAppFilterActive || isResolvingAppFilterWeeks || !appFilterNextWeek;
     const appJumpPrevDisabled = !calendarAppFilterActive || isResolvingAppFilterWeeks || !appFilterPrevWeek;
 
-    const headerWeekStart = getWeekStart(date, calendarStartHour);
+    const headerWeekStart = getWeekStart(date, calendarStartHour, weekStartDay);
     const weekEnd = new Date(headerWeekStart);
     weekEnd.setDate(weekEnd.getDate() + 6);
 
@@ -930,7 +947,7 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
     return (
         <div className="flex flex-col flex-1 min-h-0 w-full">
             <CalenderHeader headerTitle={headerTitle} onClick={goToPrevWeek} d={date} onClick1={goToNextWeek}
-                            onClick2={goToToday} calendarStartHour={calendarStartHour}
+                            onClick2={goToToday} calendarStartHour={calendarStartHour} weekStartDay={weekStartDay}
                             appJumpPrev={jumpToPrevAppWeek} appJumpNext={jumpToNextAppWeek}
                             appJumpPrevDisabled={appJumpPrevDisabled} appJumpNextDisabled={appJumpNextDisabled}
                             timerControl={<ManualTimerControl
@@ -969,6 +986,8 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
                             manualTimeInStats={manualTimeInStats}
                             toggleManualTimeInCal={toggleManualTimeInCal}
                             toggleManualTimeInStats={toggleManualTimeInStats}
+                            manualTimeOverrides={manualTimeOverrides}
+                            setManualTimeOverrides={changeManualTimeOverrides}
                             onTimeBlockContextMenu={openFromContextMenuMany}
                         />
                     </div>
@@ -983,14 +1002,14 @@ export default function Calendar({setCurrentView}: { setCurrentView: (arg0: View
                               googleCalendars={displayCalendars}
                               statsCategoryNames={statsCategoryNames}
                               statsDeviceUuids={statsDeviceUuids}
-                              manualTimeInStats={manualTimeInStats}
+                              manualTimeMode={manualTimeMode}
                 />
             </div>
             {categorizeLayers}
 
             <ManualTimeBlockDialog
                 open={showManualTimeDialog}
-                initialStart={isCurrentWeek(date, calendarStartHour) ? new Date() : getWeekStart(date, calendarStartHour)}
+                initialStart={isCurrentWeek(date, calendarStartHour, weekStartDay) ? new Date() : getWeekStart(date, calendarStartHour, weekStartDay)}
                 onClose={() => setShowManualTimeDialog(false)}
             />
 
@@ -41,7 +41,11 @@ export default function ManualTimeBlockDialog({
     const createMutation = useMutation({
         mutationFn: insert_manual_time_block,
         onSuccess: async () => {
-            await queryClient.invalidateQueries({queryKey: ["manualTimeBlocks"]});
+            await Promise.all([
+                queryClient.invalidateQueries({queryKey: ["manualTimeBlocks"]}),
+                queryClient.invalidateQueries({queryKey: ["week_statistics"]}),
+                queryClient.invalidateQueries({queryKey: ["day_statistics"]}),
+            ]);
             showToast("Manual time added", "success");
             onClose();
         },
@@ -93,7 +93,7 @@ function FinishManualTimerDialog({open, initialTitle, onClose}: {open: boolean;
     const mutation = useMutation({
         mutationFn: async () => { await update_manual_timer_title(title.trim()); return finish_manual_timer(); },
         onSuccess: async () => {
-            await Promise.all([queryClient.invalidateQueries({queryKey: ["runningManualTimer"]}), queryClient.invalidateQueries({queryKey: ["manualTimeBlocks"]})]);
+            await Promise.all([queryClient.invalidateQueries({queryKey: ["runningManualTimer"]}), queryClient.invalidateQueries({queryKey: ["manualTimeBlocks"]}), queryClient.invalidateQueries({queryKey: ["week_statistics"]}), queryClient.invalidateQueries({queryKey: ["day_statistics"]})]);
             showToast("Timer recorded", "success");
             onClose();
         },
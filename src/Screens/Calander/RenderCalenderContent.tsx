@@ -56,6 +56,8 @@ interface RenderCalendarContentProps {
     manualTimeInStats: boolean;
     toggleManualTimeInCal: () => void;
     toggleManualTimeInStats: () => void;
+    manualTimeOverrides: boolean;
+    setManualTimeOverrides: (v: boolean) => void;
     onTimeBlockContextMenu?: (e: globalThis.MouseEvent, appNames: string[]) => void;
 }
 
@@ -87,6 +89,8 @@ export default function RenderCalendarContent({
     manualTimeInStats,
     toggleManualTimeInCal,
     toggleManualTimeInStats,
+    manualTimeOverrides,
+    setManualTimeOverrides,
     onTimeBlockContextMenu,
 }: RenderCalendarContentProps) {
     const queryClient = useQueryClient();
@@ -96,7 +100,7 @@ export default function RenderCalendarContent({
     const [isRelogging, setIsRelogging] = useState(false);
     const [isLeftCollapsed, setIsLeftCollapsed] = useState(false);
 
-    const { calendarStartHour, calendarHeight, timeBlockSettings } = useBackendSettings();
+    const { calendarStartHour, weekStartDay, timeZone, calendarHeight, timeBlockSettings } = useBackendSettings();
     const calendarAppFilter = useCalendarAppFilterActive();
     const slotMinHeightPx = Math.max(12, Math.round((calendarHeight / 100) * 24));
 
@@ -131,7 +135,7 @@ export default function RenderCalendarContent({
         }
     };
 
-    const weekStart = getWeekStart(date, calendarStartHour);
+    const weekStart = getWeekStart(date, calendarStartHour, weekStartDay);
     const slotMinTime = `${String(calendarStartHour).padStart(2, "0")}:00:00`;
     const slotMaxTime = `${String(calendarStartHour + 24).padStart(2, "0")}:00:00`;
     const scrollTime = slotMinTime;
@@ -141,6 +145,7 @@ export default function RenderCalendarContent({
             "week",
             formatLocalDateYMD(weekStart),
             calendarStartHour,
+            weekStartDay,
             timeBlockSettings.minLogDuration,
             timeBlockSettings.maxAttachDistance,
             timeBlockSettings.lookaheadWindow,
@@ -172,6 +177,7 @@ export default function RenderCalendarContent({
             formatLocalDateYMD(weekStart),
             calendarAppFilter,
             calendarStartHour,
+            weekStartDay,
             timeBlockSettings.minLogDuration,
             timeBlockSettings.maxAttachDistance,
             timeBlockSettings.lookaheadWindow,
@@ -189,8 +195,8 @@ export default function RenderCalendarContent({
     });
 
     const weekRange = useMemo(
-        () => getWeekRange(date, calendarStartHour),
-        [date, calendarStartHour]
+        () => getWeekRange(date, calendarStartHour, weekStartDay, timeZone),
+        [date, calendarStartHour, weekStartDay, timeZone]
     );
 
     const {
@@ -398,6 +404,9 @@ export default function RenderCalendarContent({
                         manualTimeBlockId: block.id,
                         manualTitle: block.title,
                         notes: block.notes,
+                        projectId: block.project_id,
+                        categoryId: block.category_id,
+                        tags: block.tags,
                     },
                 };
             });
@@ -614,6 +623,20 @@ export default function RenderCalendarContent({
                             onToggleStats={toggleManualTimeInStats}
                             isLeftCollapsed={isLeftCollapsed}
                         />
+                        <label
+                            className={`flex items-center gap-3 p-2 rounded-lg hover:bg-gray-900/80 cursor-pointer border border-transparent hover:border-gray-800 ${isLeftCollapsed ? "hidden" : ""} ${manualTimeInStats ? "" : "opacity-50"}`}
+                        >
+                            <input
+                                type="checkbox"
+                                checked={manualTimeOverrides}
+                                disabled={!manualTimeInStats}
+                                onChange={(e) => setManualTimeOverrides(e.target.checked)}
+                                className="w-4 h-4 rounded cursor-pointer accent-blue-600"
+                            />
+                            <span className="text-sm text-gray-200 flex-1 leading-snug">
+                                Replace tracked time it overlaps in statistics
+                            </span>
+                        </label>
                     </div>
 
                     <div className="border-t border-gray-700 my-4" />
@@ -708,7 +731,7 @@ export default function RenderCalendarContent({
                         allDaySlot={false}
                         nowIndicator={true}
                         headerToolbar={false}
-                        firstDay={1}
+                        firstDay={(weekStartDay + 1) % 7}
                         datesSet={onDatesSet}
                     />
                     ) : (
@@ -9,6 +9,7 @@ export default function CalanderHeader(props: {
     onClick1: () => void,
     onClick2: () => void,
     calendarStartHour: number,
+    weekStartDay?: number,
     appJumpPrev?: () => void,
     appJumpNext?: () => void,
     appJumpPrevDisabled?: boolean,
@@ -34,12 +35,12 @@ export default function CalanderHeader(props: {
                     ‹
                 </button>
                 <button
-                    className={`px-3 py-1 rounded ${isCurrentWeek(props.d, props.calendarStartHour)
+                    className={`px-3 py-1 rounded ${isCurrentWeek(props.d, props.calendarStartHour, props.weekStartDay)
                         ? "bg-gray-900 text-gray-600 cursor-not-allowed"
                         : "bg-gray-800 text-white hover:bg-gray-700"
                     }`}
                     onClick={props.onClick1}
-                    disabled={isCurrentWeek(props.d, props.calendarStartHour)}
+                    disabled={isCurrentWeek(props.d, props.calendarStartHour, props.weekStartDay)}
                 >
                     ›
                 </button>
@@ -15,12 +15,7 @@ import { useAppCategorizeMenu } from "../../../hooks/useAppCategorizeMenu.tsx";
 import { logRowLeftClickCalendarFilter } from "../../../utils/calendarAppFilterRowClick.ts";
 import { useCalendarAppFilterActive } from "../../../stores/calendarAppFilterStore.ts";
 import { useBackendSettings } from "../../../hooks/useBackendSettings.ts";
-import {
-    get_manual_time_blocks,
-    MANUAL_TIME_COLOR,
-    MANUAL_TIME_LABEL,
-    manualTimeDurationInRange,
-} from "../../../api/ManualTimeBlock.ts";
+import type { ManualTimeMode } from "../../../api/ManualTimeBlock.ts";
 
 interface DayStatisticsSidebarProps {
     selectedDate: Date;
@@ -31,7 +26,7 @@ interface DayStatisticsSidebarProps {
     googleCalendars: GoogleCalendar[];
     statsCategoryNames: Set<string>;
     statsDeviceUuids: string[] | null;
-    manualTimeInStats: boolean;
+    manualTimeMode: ManualTimeMode;
     trailingToolbar?: ReactNode;
 }
 
@@ -39,7 +34,7 @@ type CombinedCategory = {
     category: string;
     total_duration: number;
     color: string | null;
-    source: "tracking" | "google" | "manual";
+    source: "tracking" | "google";
 };
 
 export default function DayStatisticsSidebar({
@@ -51,7 +46,7 @@ export default function DayStatisticsSidebar({
     googleCalendars,
     statsCategoryNames,
     statsDeviceUuids,
-    manualTimeInStats,
+    manualTimeMode,
     trailingToolbar,
 }: DayStatisticsSidebarProps) {
     const statsCalendarIds = useMemo(
@@ -74,11 +69,16 @@ export default function DayStatisticsSidebar({
         error,
         isError,
     } = useQuery({
-        queryKey: ["day_statistics", dayStart, dayEnd, statsDeviceUuids],
+        queryKey: ["day_statistics", dayStart, dayEnd, statsDeviceUuids, manualTimeMode],
         queryFn: async () => {
             if (!dayStart || !dayEnd) return null;
             try {
-                const stats = await get_day_statistics(dayStart, dayEnd, statsDeviceUuids);
+                const stats = await get_day_statistics(
+                    dayStart,
+                    dayEnd,
+                    statsDeviceUuids,
+                    manualTimeMode,
+                );
                 return stats;
             } catch (e) {
                 console.error("[DayStats] queryFn threw:", e);
@@ -99,12 +99,6 @@ export default function DayStatisticsSidebar({
         enabled: includeGoogleInStats && statsCalendarIds.size > 0,
     });
 
-    const {data: manualTimeBlocks = [], isLoading: isLoadingManualTime} = useQuery({
-        queryKey: ["manualTimeBlocks", dayStart, dayEnd],
-        queryFn: async () => get_manual_time_blocks(dayStart, dayEnd + 1),
-        enabled: dayStatsEnabled && manualTimeInStats,
-    });
-
     const calendarMap = useMemo(() => {
         const map = new Map<number, GoogleCalendar>();
         googleCalendars.forEach((c) => map.set(c.id, c));
@@ -147,23 +141,6 @@ export default function DayStatisticsSidebar({
         });
     }, [includeGoogleInStats, isLoadingGoogleEvents, isGoogleEventsError, filteredGoogleEvents, calendarMap]);
 
-    const manualTotalDuration = useMemo(
-        () => manualTimeInStats
-            ? manualTimeBlocks.reduce((sum, block) => sum + manualTimeDurationInRange(block, dayStart, dayEnd + 1), 0)
-            : 0,
-        [manualTimeBlocks, manualTimeInStats, dayStart, dayEnd],
-    );
-
-    const manualCategory = useMemo<CombinedCategory | null>(() => {
-        if (!manualTimeInStats || manualTotalDuration <= 0) return null;
-        return {
-            category: MANUAL_TIME_LABEL,
-            total_duration: manualTotalDuration,
-            color: MANUAL_TIME_COLOR,
-            source: "manual",
-        };
-    }, [manualTimeInStats, manualTotalDuration]);
-
     const topCategories = useMemo(() => {
         if (!dayStats) return [] as CombinedCategory[];
 
@@ -178,12 +155,11 @@ export default function DayStatisticsSidebar({
 
         const combined = [
             ...trackingCategories,
-            ...(manualCategory ? [manualCategory] : []),
             ...(includeGoogleInStats ? googleCategories : []),
         ];
         combined.sort((a, b) => b.total_duration - a.total_duration);
         return combined.slice(0, categorySidebarCount);
-    }, [dayStats, includeGoogleInStats, googleCategories, manualCategory, categorySidebarCount, statsCategoryNames]);
+    }, [dayStats, includeGoogleInStats, googleCategories, categorySidebarCount, statsCategoryNames]);
 
     const maxCategoryDuration = topCategories.length > 0 ? topCategories[0].total_duration : 1;
 
@@ -213,8 +189,8 @@ export default function DayStatisticsSidebar({
         const trackingTotal = dayStats.categories
             .filter((c) => statsCategoryNames.has(c.category))
             .reduce((sum, c) => sum + c.total_duration, 0);
-        return trackingTotal + googleTotalDuration + manualTotalDuration;
-    }, [dayStats, googleTotalDuration, manualTotalDuration, statsCategoryNames]);
+        return trackingTotal + googleTotalDuration;
+    }, [dayStats, googleTotalDuration, statsCategoryNames]);
 
     const filteredDayTopApps = useMemo(() => {
         if (!dayStats?.top_apps) {
@@ -223,7 +199,7 @@ export default function DayStatisticsSidebar({
         return dayStats.top_apps;
     }, [dayStats]);
 
-    if (isLoading || isLoadingManualTime || (!dayStats && !isError)) {
+    if (isLoading || (!dayStats && !isError)) {
         return (
             <div className=" border-l border-gray-700 bg-black p-6 overflow-y-auto nice-scrollbar flex flex-col h-full min-h-0">
                 <div className="flex-1">
@@ -1,6 +1,7 @@
 import {useEffect, useState, type ReactNode} from "react";
-import {useMutation, useQueryClient} from "@tanstack/react-query";
+import {useMutation, useQuery, useQueryClient} from "@tanstack/react-query";
 import {delete_manual_time_block, update_manual_time_block} from "../../../api/ManualTimeBlock.ts";
+import {get_categories} from "../../../api/Category.ts";
 import {useToast} from "../../../Componants/Toast.tsx";
 import {formatDuration} from "../utils.ts";
 import type {CalendarEvent} from "../types.ts";
@@ -9,6 +10,10 @@ function toLocalDateTimeInput(date: Date): string {
     return new Date(date.getTime() - date.getTimezoneOffset() * 60_000).toISOString().slice(0, 16);
 }
 
+function parseTags(value: string): string[] {
+    return value.split(",").map((tag) => tag.trim()).filter((tag) => tag.length > 0);
+}
+
 export default function ManualTimeBlockView({
     selectedEvent,
     setSelectedEvent,
@@ -23,29 +28,53 @@ export default function ManualTimeBlockView({
     const id = selectedEvent.manualTimeBlockId;
     const queryClient = useQueryClient();
     const {showToast} = useToast();
+    // Statistics include manual time, so they change along with the blocks.
+    const invalidateManualTime = () => Promise.all([
+        queryClient.invalidateQueries({queryKey: ["manualTimeBlocks"]}),
+        queryClient.invalidateQueries({queryKey: ["week_statistics"]}),
+        queryClient.invalidateQueries({queryKey: ["day_statistics"]}),
+    ]);
     const [isEditing, setIsEditing] = useState(false);
     const [showDeleteConfirm, setShowDeleteConfirm] = useState(false);
     const [title, setTitle] = useState(selectedEvent.title);
     const [notes, setNotes] = useState(selectedEvent.notes ?? "");
     const [start, setStart] = useState(toLocalDateTimeInput(selectedEvent.start));
     const [end, setEnd] = useState(toLocalDateTimeInput(selectedEvent.end));
+    const [categoryId, setCategoryId] = useState<number | null>(selectedEvent.categoryId ?? null);
+    const [tags, setTags] = useState((selectedEvent.tags ?? []).join(", "));
     const [validationError, setValidationError] = useState<string | null>(null);
 
+    const {data: categories = []} = useQuery({
+        queryKey: ["categories"],
+        queryFn: get_categories,
+    });
+    const categoryName = categories.find((category) => category.id === selectedEvent.categoryId)?.name;
+
     useEffect(() => {
         setTitle(selectedEvent.title);
         setNotes(selectedEvent.notes ?? "");
         setStart(toLocalDateTimeInput(selectedEvent.start));
         setEnd(toLocalDateTimeInput(selectedEvent.end));
+        setCategoryId(selectedEvent.categoryId ?? null);
+        setTags((selectedEvent.tags ?? []).join(", "));
         setValidationError(null);
     }, [selectedEvent]);
 
     const updateMutation = useMutation({
         mutationFn: update_manual_time_block,
         onSuccess: async () => {
-            await queryClient.invalidateQueries({queryKey: ["manualTimeBlocks"]});
+            await invalidateManualTime();
             const startDate = new Date(start);
             const endDate = new Date(end);
-            setSelectedEvent({...selectedEvent, title: title.trim(), notes: notes.trim() || undefined, start: startDate, end: endDate});
+            setSelectedEvent({
+                ...selectedEvent,
+                title: title.trim(),
+                notes: notes.trim() || undefined,
+                start: startDate,
+                end: endDate,
+                categoryId,
+                tags: parseTags(tags),
+            });
             setIsEditing(false);
             showToast("Manual time updated", "success");
         },
@@ -58,7 +87,7 @@ export default function ManualTimeBlockView({
             return delete_manual_time_block(id);
         },
         onSuccess: async () => {
-            await queryClient.invalidateQueries({queryKey: ["manualTimeBlocks"]});
+            await invalidateManualTime();
             setSelectedEvent(null);
             setRightSideBarView("Week");
             showToast("Manual time deleted", "success");
@@ -91,6 +120,9 @@ export default function ManualTimeBlockView({
             notes: notes.trim() || null,
             start_time: Math.floor(startDate.getTime() / 1000),
             end_time: Math.floor(endDate.getTime() / 1000),
+            project_id: selectedEvent.projectId ?? null,
+            category_id: categoryId,
+            tags: parseTags(tags),
         });
     };
 
@@ -118,6 +150,17 @@ export default function ManualTimeBlockView({
                         <span className="mb-1 block text-sm text-gray-400">End</span>
                         <input type="datetime-local" value={end} onChange={(event) => setEnd(event.target.value)} className="w-full rounded-lg border border-gray-700 bg-gray-950 px-3 py-2 text-white outline-none focus:border-sky-500" />
                     </label>
+                    <label className="block">
+                        <span className="mb-1 block text-sm text-gray-400">Category</span>
+                        <select value={categoryId ?? ""} onChange={(event) => setCategoryId(event.target.value ? Number(event.target.value) : null)} className="w-full rounded-lg border border-gray-700 bg-gray-950 px-3 py-2 text-white outline-none focus:border-sky-500">
+                            <option value="">From the title</option>
+                            {categories.map((category) => <option key={category.id} value={category.id}>{category.name}</option>)}
+                        </select>
+                    </label>
+                    <label className="block">
+                        <span className="mb-1 block text-sm text-gray-400">Tags</span>
+                        <input value={tags} placeholder="Comma-separated" onChange={(event) => setTags(event.target.value)} className="w-full rounded-lg border border-gray-700 bg-gray-950 px-3 py-2 text-white outline-none focus:border-sky-500" />
+                    </label>
                     <label className="block">
                         <span className="mb-1 block text-sm text-gray-400">Description or notes</span>
                         <textarea rows={5} value={notes} onChange={(event) => setNotes(event.target.value)} className="w-full resize-y rounded-lg border border-gray-700 bg-gray-950 px-3 py-2 text-white outline-none focus:border-sky-500" />
@@ -134,6 +177,12 @@ export default function ManualTimeBlockView({
                         <h3 className="break-words text-lg font-semibold text-white">{selectedEvent.title}</h3>
                         <p className="mt-2 text-sm text-gray-400">{selectedEvent.start.toLocaleString()} – {selectedEvent.end.toLocaleString()}</p>
                         <p className="mt-1 text-sm font-medium text-sky-400">{formatDuration(Math.floor((selectedEvent.end.getTime() - selectedEvent.start.getTime()) / 1000))}</p>
+                        <p className="mt-2 text-sm text-gray-400">Category: <span className="text-gray-200">{categoryName ?? "From the title"}</span></p>
+                        {selectedEvent.tags && selectedEvent.tags.length > 0 ? (
+                            <div className="mt-2 flex flex-wrap gap-1">
+                                {selectedEvent.tags.map((tag) => <span key={tag} className="rounded bg-gray-800 px-2 py-0.5 text-xs text-gray-300">{tag}</span>)}
+                            </div>
+                        ) : null}
                         <div className="mt-5 border-t border-gray-800 pt-5">
                             <h4 className="text-sm font-medium text-gray-400">Description or notes</h4>
                             <p className="mt-2 whitespace-pre-wrap break-words text-sm text-gray-200">{selectedEvent.notes || "No notes"}</p>
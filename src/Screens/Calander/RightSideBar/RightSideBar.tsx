@@ -11,6 +11,7 @@ import { useEffect, useState } from "react";
 import { getAppMetadata, setAppMetadata } from "../../../api/appMetadata.ts";
 import { useBackendSettings } from "../../../hooks/useBackendSettings.ts";
 import ManualTimeBlockView from "./ManualTimeBlockView.tsx";
+import type {ManualTimeMode} from "../../../api/ManualTimeBlock.ts";
 
 const RIGHT_SIDEBAR_COLLAPSED_KEY = "time-tracker:right-sidebar-collapsed";
 
@@ -33,7 +34,7 @@ export function RightSideBar({
     googleCalendars,
     statsCategoryNames,
     statsDeviceUuids,
-    manualTimeInStats,
+    manualTimeMode,
 }: {
     view: SideBarView,
     setView: (newView: SideBarView) => void,
@@ -51,7 +52,7 @@ export function RightSideBar({
     googleCalendars: GoogleCalendar[],
     statsCategoryNames: Set<string>,
     statsDeviceUuids: string[] | null,
-    manualTimeInStats: boolean,
+    manualTimeMode: ManualTimeMode,
 }) {
     const { date } = useDateStore();
     const { rightSidebarWidth } = useBackendSettings();
@@ -107,7 +108,7 @@ export function RightSideBar({
                         googleCalendars={googleCalendars}
                         statsCategoryNames={statsCategoryNames}
                         statsDeviceUuids={statsDeviceUuids}
-                        manualTimeInStats={manualTimeInStats}
+                        manualTimeMode={manualTimeMode}
                         trailingToolbar={collapseSidebarButton}
                     />}
                     {view === "Day" && selectedDate && <DayStatisticsSidebar
@@ -125,7 +126,7 @@ export function RightSideBar({
                         googleCalendars={googleCalendars}
                         statsCategoryNames={statsCategoryNames}
                         statsDeviceUuids={statsDeviceUuids}
-                        manualTimeInStats={manualTimeInStats}
+                        manualTimeMode={manualTimeMode}
                         trailingToolbar={collapseSidebarButton}
                     />}
                     {view === "Event" && selectedEvent && (
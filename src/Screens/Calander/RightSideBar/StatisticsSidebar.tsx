@@ -15,12 +15,7 @@ import { useAppCategorizeMenu } from "../../../hooks/useAppCategorizeMenu.tsx";
 import { logRowLeftClickCalendarFilter } from "../../../utils/calendarAppFilterRowClick.ts";
 import { useCalendarAppFilterActive } from "../../../stores/calendarAppFilterStore.ts";
 import { useBackendSettings } from "../../../hooks/useBackendSettings.ts";
-import {
-    get_manual_time_blocks,
-    MANUAL_TIME_COLOR,
-    MANUAL_TIME_LABEL,
-    manualTimeDurationInRange,
-} from "../../../api/ManualTimeBlock.ts";
+import type { ManualTimeMode } from "../../../api/ManualTimeBlock.ts";
 
 type DisplayMode = "percentage" | "time";
 
@@ -48,7 +43,7 @@ function inferPreviousTrackingTotal(current: number, pctChange: number | null):
 }
 
 type CombinedCategory = CategoryStat & {
-    source: "tracking" | "google" | "manual";
+    source: "tracking" | "google";
 };
 
 function filterGoogleEventsForStats(
@@ -67,7 +62,7 @@ interface StatisticsSidebarProps {
     googleCalendars: GoogleCalendar[];
     statsCategoryNames: Set<string>;
     statsDeviceUuids: string[] | null;
-    manualTimeInStats: boolean;
+    manualTimeMode: ManualTimeMode;
     trailingToolbar?: ReactNode;
 }
 
@@ -79,7 +74,7 @@ export default function StatisticsSidebar({
     googleCalendars,
     statsCategoryNames,
     statsDeviceUuids,
-    manualTimeInStats,
+    manualTimeMode,
     trailingToolbar,
 }: StatisticsSidebarProps) {
     const statsCalendarIds = useMemo(
@@ -88,16 +83,18 @@ export default function StatisticsSidebar({
     );
     const [displayMode, setDisplayMode] = useState<DisplayMode>("percentage");
     const [showAllApps, setShowAllApps] = useState(false);
-    const { categorySidebarCount, calendarStartHour, uiMinAppDuration } = useBackendSettings();
+    const { categorySidebarCount, calendarStartHour, weekStartDay, timeZone, uiMinAppDuration } = useBackendSettings();
     const { openFromContextMenuMany, categorizeLayers } = useAppCategorizeMenu();
     const calendarAppFilterActive = useCalendarAppFilterActive();
 
-    const { week_start, week_end } = getWeekRange(weekDate, calendarStartHour);
+    const { week_start, week_end } = getWeekRange(weekDate, calendarStartHour, weekStartDay, timeZone);
     const prevAnchor = new Date(week_start * 1000);
     prevAnchor.setDate(prevAnchor.getDate() - 7);
     const { week_start: prevWeekStart, week_end: prevWeekEnd } = getWeekRange(
         prevAnchor,
-        calendarStartHour
+        calendarStartHour,
+        weekStartDay,
+        timeZone
     );
 
     const {
@@ -106,10 +103,15 @@ export default function StatisticsSidebar({
         error,
         isError,
     } = useQuery({
-        queryKey: ["week_statistics", week_start, week_end, calendarStartHour, statsDeviceUuids],
+        queryKey: ["week_statistics", week_start, week_end, calendarStartHour, statsDeviceUuids, manualTimeMode],
         queryFn: async () => {
             try {
-                const stats = await get_week_statistics(week_start, week_end, statsDeviceUuids);
+                const stats = await get_week_statistics(
+                    week_start,
+                    week_end,
+                    statsDeviceUuids,
+                    manualTimeMode,
+                );
                 return stats;
             } catch (e) {
                 console.error("[WeekStats] queryFn threw:", e);
@@ -140,18 +142,6 @@ export default function StatisticsSidebar({
         enabled: includeGoogleInStats && statsCalendarIds.size > 0,
     });
 
-    const {data: manualTimeBlocks = [], isLoading: isLoadingManualTime} = useQuery({
-        queryKey: ["manualTimeBlocks", week_start, week_end],
-        queryFn: () => get_manual_time_blocks(week_start, week_end + 1),
-        enabled: manualTimeInStats,
-    });
-
-    const {data: prevManualTimeBlocks = [], isLoading: isLoadingPrevManualTime} = useQuery({
-        queryKey: ["manualTimeBlocks", prevWeekStart, prevWeekEnd],
-        queryFn: () => get_manual_time_blocks(prevWeekStart, prevWeekEnd + 1),
-        enabled: manualTimeInStats,
-    });
-
     const calendarMap = useMemo(() => {
         const map = new Map<number, GoogleCalendar>();
         googleCalendars.forEach((c) => map.set(c.id, c));
@@ -248,32 +238,6 @@ export default function StatisticsSidebar({
         week_end,
     ]);
 
-    const manualTotalDuration = useMemo(
-        () => manualTimeInStats
-            ? manualTimeBlocks.reduce((sum, block) => sum + manualTimeDurationInRange(block, week_start, week_end + 1), 0)
-            : 0,
-        [manualTimeBlocks, manualTimeInStats, week_start, week_end],
-    );
-
-    const prevManualTotalDuration = useMemo(
-        () => manualTimeInStats
-            ? prevManualTimeBlocks.reduce((sum, block) => sum + manualTimeDurationInRange(block, prevWeekStart, prevWeekEnd + 1), 0)
-            : 0,
-        [prevManualTimeBlocks, manualTimeInStats, prevWeekStart, prevWeekEnd],
-    );
-
-    const manualCategory = useMemo<CombinedCategory | null>(() => {
-        if (!manualTimeInStats || manualTotalDuration <= 0) return null;
-        return {
-            category: MANUAL_TIME_LABEL,
-            total_duration: manualTotalDuration,
-            percentage: 0,
-            percentage_change: percentageChangeVsPrevious(manualTotalDuration, prevManualTotalDuration),
-            color: MANUAL_TIME_COLOR,
-            source: "manual",
-        };
-    }, [manualTimeInStats, manualTotalDuration, prevManualTotalDuration]);
-
     const topCategories = useMemo<CombinedCategory[]>(() => {
         if (!weekStats) return [] as CombinedCategory[];
 
@@ -286,12 +250,11 @@ export default function StatisticsSidebar({
 
         const combined = [
             ...trackingCategories,
-            ...(manualCategory ? [manualCategory] : []),
             ...(includeGoogleInStats ? googleCategories : []),
         ];
         combined.sort((a, b) => b.total_duration - a.total_duration);
         return combined.slice(0, categorySidebarCount);
-    }, [weekStats, includeGoogleInStats, googleCategories, manualCategory, categorySidebarCount, statsCategoryNames]);
+    }, [weekStats, includeGoogleInStats, googleCategories, categorySidebarCount, statsCategoryNames]);
 
     const maxCategoryDuration = topCategories.length > 0 ? topCategories[0].total_duration : 1;
 
@@ -321,44 +284,35 @@ export default function StatisticsSidebar({
         const trackingTotal = weekStats.categories
             .filter((c) => statsCategoryNames.has(c.category))
             .reduce((sum, c) => sum + c.total_duration, 0);
-        return trackingTotal + manualTotalDuration + googleTotalDuration;
-    }, [weekStats, manualTotalDuration, googleTotalDuration, statsCategoryNames]);
+        return trackingTotal + googleTotalDuration;
+    }, [weekStats, googleTotalDuration, statsCategoryNames]);
 
     const combinedTotalTimeChange = useMemo((): number | null => {
-        if (!weekStats || (!includeGoogleInStats && !manualTimeInStats)) return null;
-        if (
-            (includeGoogleInStats && (isLoadingGoogleEvents || isLoadingPrevGoogleEvents || isGoogleEventsError || isPrevGoogleEventsError)) ||
-            (manualTimeInStats && (isLoadingManualTime || isLoadingPrevManualTime))
-        ) {
+        if (!weekStats || !includeGoogleInStats) return null;
+        if (isLoadingGoogleEvents || isLoadingPrevGoogleEvents || isGoogleEventsError || isPrevGoogleEventsError) {
             return null;
         }
         const prevTrack = inferPreviousTrackingTotal(weekStats.total_time, weekStats.total_time_change);
         if (prevTrack === null) return null;
-        const prevCombined = prevTrack + prevGoogleTotalDuration + prevManualTotalDuration;
-        const currCombined = weekStats.total_time + googleTotalDuration + manualTotalDuration;
+        const prevCombined = prevTrack + prevGoogleTotalDuration;
+        const currCombined = weekStats.total_time + googleTotalDuration;
         return percentageChangeVsPrevious(currCombined, prevCombined);
     }, [
         weekStats,
         includeGoogleInStats,
-        manualTimeInStats,
         prevGoogleTotalDuration,
         googleTotalDuration,
-        prevManualTotalDuration,
-        manualTotalDuration,
         isLoadingGoogleEvents,
         isLoadingPrevGoogleEvents,
         isGoogleEventsError,
         isPrevGoogleEventsError,
-        isLoadingManualTime,
-        isLoadingPrevManualTime,
     ]);
 
-    const displayTotalTimeChange =
-        includeGoogleInStats || manualTimeInStats
-            ? isLoadingGoogleEvents || isLoadingPrevGoogleEvents || isLoadingManualTime || isLoadingPrevManualTime
-                ? null
-                : combinedTotalTimeChange
-            : (weekStats?.total_time_change ?? null);
+    const displayTotalTimeChange = includeGoogleInStats
+        ? isLoadingGoogleEvents || isLoadingPrevGoogleEvents
+            ? null
+            : combinedTotalTimeChange
+        : (weekStats?.total_time_change ?? null);
 
     const showTotalChange = displayTotalTimeChange !== null;
 
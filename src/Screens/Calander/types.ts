@@ -8,6 +8,9 @@ export type CalendarEvent = {
     googleCalendarEventId?: string;
     googleCalendarId?: number;
     manualTimeBlockId?: number;
+    projectId?: number | null;
+    categoryId?: number | null;
+    tags?: string[];
     description?: string;
     notes?: string;
     location?: string;
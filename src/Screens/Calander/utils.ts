@@ -20,15 +20,15 @@ export function formatLocalDateYMD(d: Date): string {
     return `${y}-${m}-${day}`;
 }
 
-export function getWeekStart(d: Date, calendarStartHour: number): Date {
-    const { week_start } = getWeekRange(d, calendarStartHour);
+export function getWeekStart(d: Date, calendarStartHour: number, weekStartDay: number = 0): Date {
+    const { week_start } = getWeekRange(d, calendarStartHour, weekStartDay);
     return new Date(week_start * 1000);
 }
 
-export function isCurrentWeek(d: Date, calendarStartHour: number): boolean {
+export function isCurrentWeek(d: Date, calendarStartHour: number, weekStartDay: number = 0): boolean {
     const nowForWeek = adjustInstantToCalendarDayBoundary(new Date(), calendarStartHour);
-    const currentWeekStart = getWeekStart(nowForWeek, calendarStartHour);
-    const selectedWeekStart = getWeekStart(d, calendarStartHour);
+    const currentWeekStart = getWeekStart(nowForWeek, calendarStartHour, weekStartDay);
+    const selectedWeekStart = getWeekStart(d, calendarStartHour, weekStartDay);
     return currentWeekStart.getTime() === selectedWeekStart.getTime();
 }
 
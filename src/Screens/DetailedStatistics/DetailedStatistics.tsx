@@ -176,7 +176,7 @@ export default function DetailedStatistics({onBack}: { onBack: () => void }) {
     const calendarAppFilterActive = useCalendarAppFilterActive();
 
     // Hide apps below this duration in sidebar lists (UI preference, not DB filter for range stats)
-    const { calendarStartHour, uiMinAppDuration, timeBlockSettings } = useBackendSettings();
+    const { calendarStartHour, weekStartDay, timeZone, uiMinAppDuration, timeBlockSettings } = useBackendSettings();
     const minLogDuration = timeBlockSettings.minLogDuration;
 
     // DOM node for right sidebar — used in click-outside handler
@@ -264,8 +264,8 @@ export default function DetailedStatistics({onBack}: { onBack: () => void }) {
     // MEMO: list of week objects for trend chart x-axis and per-week API calls
     const trendWeeks = useMemo(() => {
         if (!rangeStartDate || !rangeEndDate) return []; // not ready yet
-        return enumerateWeekRangesInSpan(rangeStartDate, rangeEndDate, calendarStartHour);
-    }, [rangeStartDate, rangeEndDate, calendarStartHour]);
+        return enumerateWeekRangesInSpan(rangeStartDate, rangeEndDate, calendarStartHour, undefined, weekStartDay, timeZone);
+    }, [rangeStartDate, rangeEndDate, calendarStartHour, weekStartDay, timeZone]);
 
     // PARALLEL QUERIES: one get_week_statistics per week in trend range
     const trendWeekQueries = useQueries({
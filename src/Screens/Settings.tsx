@@ -2,6 +2,7 @@ import { useEffect, useMemo, useRef, useState } from "react";
 import { useQueryClient } from "@tanstack/react-query";
 import { useToast } from "../Componants/Toast.tsx";
 import { useBackendSettings, type SettingField } from "../hooks/useBackendSettings.ts";
+import { getTimeZone, setTimeZone, type TimeZoneSetting as TimeZoneInfo } from "../api/settings.ts";
 import {
     getDatabaseLocation,
     resetDatabaseLocation,
@@ -18,6 +19,7 @@ const SETTINGS_LAYOUT: CategoryDef[] = [
         title: "Calendar",
         fields: [
             { key: "calendarStartHour", label: "Start hour" },
+            { key: "weekStartDay", label: "First day of week (0 = Mon, 6 = Sun)" },
             { key: "calendarHeight", label: "Calendar size (%)" },
             { key: "rightSidebarWidth", label: "Right sidebar width (px)" },
             { key: "categorySidebarCount", label: "Categories in stats sidebar" },
@@ -32,6 +34,21 @@ const SETTINGS_LAYOUT: CategoryDef[] = [
             { key: "minDuration", label: "Min timeblock duration (sec)" },
         ],
     },
+    {
+        title: "Goals",
+        fields: [{ key: "goalNotifications", label: "Desktop notifications (0 = off, 1 = on)" }],
+    },
+    {
+        title: "Attendance",
+        fields: [
+            { key: "dailyTargetMinutes", label: "Daily target (min)" },
+            { key: "breakGapMinutes", label: "Break after idle gap (min)" },
+            { key: "attendanceWorkdays", label: "Workdays (bitmask, 1 = Mon … 64 = Sun)" },
+            { key: "workdayStartHour", label: "Working hours start (hour)" },
+            { key: "workdayEndHour", label: "Working hours end (hour)" },
+            { key: "minGapMinutes", label: "Report untracked gaps over (min)" },
+        ],
+    },
     {
         title: "UI filters",
         fields: [{ key: "uiMinAppDuration", label: "Min app duration (sec)" }],
@@ -344,6 +361,101 @@ function DatabaseLocationSetting() {
     );
 }
 
+function TimeZoneSetting() {
+    const { showToast } = useToast();
+    const queryClient = useQueryClient();
+    const [info, setInfo] = useState<TimeZoneInfo | null>(null);
+    const [draft, setDraft] = useState("");
+    const [busy, setBusy] = useState(false);
+
+    useEffect(() => {
+        getTimeZone()
+            .then((zone) => {
+                setInfo(zone);
+                setDraft(zone.configured ?? "");
+            })
+            .catch((e) => showToast("Failed to load time zone", "error", 5000, toErrorString(e)));
+    }, [showToast]);
+
+    const applyZone = async (timeZone: string | null) => {
+        setBusy(true);
+        try {
+            await setTimeZone(timeZone);
+            const next = await getTimeZone();
+            setInfo(next);
+            setDraft(next.configured ?? "");
+            await queryClient.invalidateQueries();
+            showToast(timeZone ? "Time zone updated" : "Following the system time zone", "success");
+        } catch (e) {
+            showToast("Failed to update time zone", "error", 5000, toErrorString(e));
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    const handleApply = () => {
+        const zone = draft.trim();
+        if (!zone) {
+            showToast("Enter an IANA time zone, such as Europe/Berlin", "error");
+            return;
+        }
+        if (zone === info?.configured) return;
+        void applyZone(zone);
+    };
+
+    return (
+        <div className="bg-gray-900 p-4 rounded">
+            <h2 className="text-lg font-semibold mb-1">Time zone</h2>
+            <p className="text-sm text-gray-400 mb-4">
+                Days and weeks in the calendar, statistics and reports are counted in this zone.
+            </p>
+            <div className="space-y-3">
+                <div className="flex flex-col gap-2">
+                    <span className="text-sm text-gray-300">IANA time zone</span>
+                    <input
+                        type="text"
+                        value={draft}
+                        placeholder={info ? `System (${info.system})` : ""}
+                        disabled={busy || !info}
+                        onChange={(e) => setDraft(e.target.value)}
+                        onKeyDown={(e) => {
+                            if (e.key === "Enter") {
+                                e.currentTarget.blur();
+                                handleApply();
+                            }
+                        }}
+                        className="w-full px-2 py-1.5 bg-gray-800 text-white rounded text-sm font-mono disabled:opacity-50"
+                        spellCheck={false}
+                    />
+                </div>
+                <div className="flex flex-wrap items-center gap-2">
+                    <button
+                        type="button"
+                        disabled={busy || !info || draft.trim() === (info?.configured ?? "")}
+                        onClick={handleApply}
+                        className="px-3 py-1.5 bg-gray-800 hover:bg-gray-700 rounded text-white text-sm font-medium disabled:opacity-40 disabled:pointer-events-none"
+                    >
+                        Apply
+                    </button>
+                    <button
+                        type="button"
+                        disabled={busy || !info?.configured}
+                        onClick={() => void applyZone(null)}
+                        className="px-3 py-1.5 bg-gray-800 hover:bg-gray-700 rounded text-white text-sm font-medium disabled:opacity-40 disabled:pointer-events-none"
+                    >
+                        Use system zone
+                    </button>
+                </div>
+                {info && (
+                    <p className="text-xs text-gray-500 font-mono break-all">
+                        In use: {info.effective}
+                    </p>
+                )}
+            </div>
+        </div>
+    );
+}
+
 export default function Settings() {
     const { showToast } = useToast();
     const { fields, allLocked, setVal, toggleLock, resetField, resetSettings } = useBackendSettings();
@@ -367,6 +479,7 @@ export default function Settings() {
 
             <div className="space-y-6">
                 <DatabaseLocationSetting />
+                <TimeZoneSetting />
                 {SETTINGS_LAYOUT.map((category) => (
                     <div key={category.title} className="bg-gray-900 p-4 rounded">
                         <h2 className="text-lg font-semibold mb-4">{category.title}</h2>
@@ -9,6 +9,7 @@ export type Category = {
     is_visible: boolean;
     in_stats: boolean;
     is_collapsed: boolean;
+    productivity_weight?: number; // -2 to +2
 };
 
 export type NewCategory = {
@@ -19,6 +20,7 @@ export type NewCategory = {
     is_visible: boolean;
     in_stats: boolean;
     is_collapsed: boolean;
+    productivity_weight?: number; // -2 to +2
 };
 
 export async function get_categories(): Promise<Category[]> {
@@ -40,3 +42,7 @@ export async function insert_category(newCat: NewCategory): Promise<number> {
 export async function update_category_by_id(cat: Category): Promise<null> {
     return invokeOrThrow<null>("update_category_by_id", {cat});
 }
+
+export async function set_category_productivity_weight(id: number, weight: number): Promise<null> {
+    return invokeOrThrow<null>("set_category_productivity_weight", {id, weight});
+}
@@ -4,17 +4,25 @@ export type CategoryRegex = {
     id: number;
     cat_id: number;
     regex: string;
+    effective_from?: number | null;
+    effective_until?: number | null;
 };
 
 export type NewCategoryRegex = {
     cat_id: number;
     regex: string;
+    effective_from?: number | null;
+    effective_until?: number | null;
 };
 
 export async function get_cat_regex(): Promise<CategoryRegex[]> {
     return invokeOrThrow<CategoryRegex[]>("get_cat_regex");
 }
 
+export async function get_cat_regex_history(): Promise<CategoryRegex[]> {
+    return invokeOrThrow<CategoryRegex[]>("get_cat_regex_history");
+}
+
 
 export async function get_cat_regex_by_id(id: number): Promise<CategoryRegex> {
     return invokeOrThrow<CategoryRegex>("get_cat_regex_by_id", { id });
@@ -34,3 +42,7 @@ export async function update_cat_regex_by_id(catRegex: CategoryRegex): Promise<n
 
 
 
+
+export async function reapply_current_rules(rangeStart: number, rangeEnd: number): Promise<null> {
+    return invokeOrThrow<null>("reapply_current_rules", { rangeStart, rangeEnd });
+}
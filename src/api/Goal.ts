@@ -0,0 +1,65 @@
+import {invokeOrThrow} from "../utils.ts";
+
+export type GoalTargetKind = "category" | "app_group";
+export type GoalPeriod = "day" | "week";
+export type GoalDirection = "at_least" | "at_most";
+
+export type Goal = {
+    id: number;
+    name: string;
+    target_kind: GoalTargetKind;
+    target_id: number; // Category id or app group id depending on target_kind
+    period: GoalPeriod;
+    direction: GoalDirection;
+    target_seconds: number;
+    window_start_hour: number | null; // Only time from this local hour counts
+    window_end_hour: number | null; // Only time before this local hour counts
+    weekdays: number; // Bit 0 = Monday through bit 6 = Sunday
+    is_enabled: boolean;
+};
+
+export type NewGoal = Omit<Goal, "id" | "is_enabled">;
+
+export type GoalStatus = "in_progress" | "met" | "missed" | "within_limit" | "exceeded" | "inactive";
+
+export type GoalProgress = {
+    goal: Goal;
+    target_name: string;
+    period_start: number;
+    period_end: number; // Inclusive
+    tracked_seconds: number;
+    remaining_seconds: number;
+    progress: number; // 1 means the target was reached
+    status: GoalStatus;
+};
+
+// Events emitted by the background goal evaluator, each with a GoalProgress payload.
+export const GOAL_MET_EVENT = "goal-met";
+export const GOAL_LIMIT_CROSSED_EVENT = "goal-limit-crossed";
+export const GOAL_PROGRESS_EVENT = "goal-progress"; // Payload is GoalProgress[]
+
+export async function get_goals(): Promise<Goal[]> {
+    return invokeOrThrow<Goal[]>("get_goals");
+}
+
+export async function insert_goal(goal: NewGoal): Promise<number> {
+    return invokeOrThrow<number>("insert_goal", {newGoal: goal});
+}
+
+export async function update_goal(goal: Goal): Promise<null> {
+    return invokeOrThrow<null>("update_goal", {goal});
+}
+
+export async function delete_goal(id: number): Promise<null> {
+    return invokeOrThrow<null>("delete_goal", {id});
+}
+
+export async function get_goal_progress(
+    anchor?: Date | null,
+    deviceUuids?: string[] | null,
+): Promise<GoalProgress[]> {
+    return invokeOrThrow<GoalProgress[]>("get_goal_progress", {
+        anchor: anchor ? Math.floor(anchor.getTime() / 1000) : null,
+        deviceUuids: deviceUuids ?? null,
+    });
+}
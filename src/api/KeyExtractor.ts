@@ -0,0 +1,56 @@
+import {invokeOrThrow} from "../utils.ts";
+
+// regex needs a (?P<key>...) group, or named groups referenced by template ("$owner/$repo#$number").
+export type KeyExtractor = {
+    id: number;
+    name: string;
+    regex: string;
+    template: string | null;
+};
+
+export type NewKeyExtractor = Omit<KeyExtractor, "id">;
+
+export type KeyStat = {
+    key: string;
+    total_duration: number;
+    first_seen: number;
+    last_seen: number; // End of the last log
+    title_count: number;
+};
+
+export type KeyReport = {
+    extractor_id: number;
+    extractor_name: string;
+    keys: KeyStat[]; // Most time first
+    matched_duration: number;
+};
+
+export async function get_key_extractors(): Promise<KeyExtractor[]> {
+    return invokeOrThrow<KeyExtractor[]>("get_key_extractors");
+}
+
+export async function insert_key_extractor(keyExtractor: NewKeyExtractor): Promise<number> {
+    return invokeOrThrow<number>("insert_key_extractor", {newKeyExtractor: keyExtractor});
+}
+
+export async function update_key_extractor(keyExtractor: KeyExtractor): Promise<null> {
+    return invokeOrThrow<null>("update_key_extractor", {keyExtractor});
+}
+
+export async function delete_key_extractor(id: number): Promise<null> {
+    return invokeOrThrow<null>("delete_key_extractor", {id});
+}
+
+export async function get_key_report(
+    rangeStart: number,
+    rangeEnd: number,
+    extractorId: number | null = null,
+    deviceUuids: string[] | null = null,
+): Promise<KeyReport[]> {
+    return invokeOrThrow<KeyReport[]>("get_key_report", {
+        rangeStart,
+        rangeEnd,
+        extractorId,
+        deviceUuids,
+    });
+}
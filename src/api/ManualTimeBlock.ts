@@ -11,6 +11,9 @@ export type ManualTimeBlock = {
     end_time: number;
     created_at: number;
     updated_at: number;
+    project_id: number | null;
+    category_id: number | null; // Without one, the title is categorised like an app
+    tags: string[];
 };
 
 export type NewManualTimeBlock = {
@@ -18,8 +21,14 @@ export type NewManualTimeBlock = {
     notes?: string | null;
     start_time: number;
     end_time: number;
+    project_id?: number | null;
+    category_id?: number | null;
+    tags?: string[]; // Tags cannot contain commas
 };
 
+// How statistics and reports count manual time; "override" replaces overlapping tracked time.
+export type ManualTimeMode = "exclude" | "add" | "override";
+
 export type UpdateManualTimeBlock = NewManualTimeBlock & {
     id: number;
 };
@@ -0,0 +1,120 @@
+import {invokeOrThrow} from "../utils.ts";
+
+export type Client = {
+    id: number;
+    name: string;
+    hourly_rate: number | null; // Used by projects without a rate of their own
+};
+
+export type NewClient = Omit<Client, "id">;
+
+export type Project = {
+    id: number;
+    name: string;
+    client_id: number | null;
+    billable: boolean;
+    hourly_rate: number | null; // Falls back to the client's rate
+    regex: string | null; // Window titles assigned to the project automatically
+};
+
+export type NewProject = Omit<Project, "id">;
+
+// Tracked time in the range is assigned to the project, overriding rules.
+export type ProjectAssignment = {
+    id: number;
+    project_id: number;
+    start_time: number;
+    end_time: number;
+    created_at: number;
+};
+
+export type NewProjectAssignment = Pick<ProjectAssignment, "project_id" | "start_time" | "end_time">;
+
+export type ProjectBilling = {
+    project_id: number;
+    project_name: string;
+    client_id: number | null;
+    client_name: string | null;
+    billable: boolean;
+    hourly_rate: number | null;
+    tracked_seconds: number;
+    manual_seconds: number;
+    total_seconds: number;
+    hours: number;
+    amount: number;
+};
+
+export type ClientBilling = {
+    client_id: number | null; // null groups projects without a client
+    client_name: string | null;
+    total_seconds: number;
+    billable_seconds: number;
+    hours: number;
+    amount: number;
+};
+
+export type BillingReport = {
+    range_start: number;
+    range_end: number;
+    projects: ProjectBilling[];
+    clients: ClientBilling[];
+    unassigned_seconds: number;
+    total_seconds: number;
+    billable_seconds: number;
+    total_amount: number;
+};
+
+export async function get_clients(): Promise<Client[]> {
+    return invokeOrThrow<Client[]>("get_clients");
+}
+
+export async function insert_client(client: NewClient): Promise<number> {
+    return invokeOrThrow<number>("insert_client", {newClient: client});
+}
+
+export async function update_client(client: Client): Promise<null> {
+    return invokeOrThrow<null>("update_client", {client});
+}
+
+export async function delete_client(id: number): Promise<null> {
+    return invokeOrThrow<null>("delete_client", {id});
+}
+
+export async function get_projects(): Promise<Project[]> {
+    return invokeOrThrow<Project[]>("get_projects");
+}
+
+export async function insert_project(project: NewProject): Promise<number> {
+    return invokeOrThrow<number>("insert_project", {newProject: project});
+}
+
+export async function update_project(project: Project): Promise<null> {
+    return invokeOrThrow<null>("update_project", {project});
+}
+
+export async function delete_project(id: number): Promise<null> {
+    return invokeOrThrow<null>("delete_project", {id});
+}
+
+export async function get_project_assignments(
+    rangeStart: number,
+    rangeEnd: number,
+): Promise<ProjectAssignment[]> {
+    return invokeOrThrow<ProjectAssignment[]>("get_project_assignments", {rangeStart, rangeEnd});
+}
+
+export async function insert_project_assignment(assignment: NewProjectAssignment): Promise<number> {
+    return invokeOrThrow<number>("insert_project_assignment", {newProjectAssignment: assignment});
+}
+
+export async function delete_project_assignment(id: number): Promise<null> {
+    return invokeOrThrow<null>("delete_project_assignment", {id});
+}
+
+export async function get_billing_report(
+    rangeStart: number,
+    rangeEnd: number,
+    deviceUuids: string[] | null = null,
+): Promise<BillingReport> {
+    return invokeOrThrow<BillingReport>("get_billing_report", {rangeStart, rangeEnd, deviceUuids});
+}
@@ -0,0 +1,23 @@
+import {invokeOrThrow} from "../utils.ts";
+
+export type ImportedHost = {
+    hostname: string;
+    device_uuid: string; // Synthetic device holding the host's logs
+    device_name: string;
+    inserted: number;
+    updated: number;
+    duplicates: number;
+    afk_seconds: number; // Window time dropped because the user was away
+    skipped_seconds: number; // Window time matching a skipped app
+};
+
+export type ActivityWatchImportReport = {
+    hosts: ImportedHost[];
+    ignored_buckets: string[]; // Buckets other than window and afk, e.g. web watchers
+    warnings: string[];
+};
+
+// Accepts the JSON from ActivityWatch's "Export all buckets" or a single bucket export.
+export async function import_activitywatch(path: string): Promise<ActivityWatchImportReport> {
+    return invokeOrThrow<ActivityWatchImportReport>("import_activitywatch", {path});
+}
@@ -0,0 +1,53 @@
+import {invokeOrThrow} from "../utils.ts";
+
+export type ArchiveManifest = {
+    format: string; // Always "time-tracker-archive"
+    archive_version: number;
+    app_version: string;
+    schema_version: number; // Latest database migration when the archive was made
+    created_at: number;
+    tables: Record<string, number>; // Row count per table
+};
+
+export type ArchiveExportOptions = {
+    include_credentials?: boolean; // Google Calendar sign-in tokens
+};
+
+// Merge keeps existing rows; replace empties each archived table first.
+export type ArchiveImportMode = "merge" | "replace";
+
+export type ArchiveTableReport = {
+    table: string;
+    inserted: number;
+    existing: number;
+    conflicts: number; // Same id with different content; the existing row was kept
+    orphaned: number; // Pointed at a row that could not be imported
+};
+
+export type ArchiveImportReport = {
+    manifest: ArchiveManifest;
+    mode: ArchiveImportMode;
+    upgraded_from: number | null; // Schema version of an older archive
+    tables: ArchiveTableReport[];
+    warnings: string[];
+    backup_created: string | null;
+};
+
+// The archive contains the sync device token, so it should be kept private.
+export async function export_archive(
+    path: string,
+    options: ArchiveExportOptions = {},
+): Promise<ArchiveManifest> {
+    return invokeOrThrow<ArchiveManifest>("export_archive", {path, options});
+}
+
+export async function inspect_archive(path: string): Promise<ArchiveManifest> {
+    return invokeOrThrow<ArchiveManifest>("inspect_archive", {path});
+}
+
+export async function import_archive(
+    path: string,
+    mode: ArchiveImportMode,
+): Promise<ArchiveImportReport> {
+    return invokeOrThrow<ArchiveImportReport>("import_archive", {path, mode});
+}
@@ -0,0 +1,63 @@
+import { invokeOrThrow } from "../utils.ts";
+
+export type AttendanceOptions = {
+    device_uuids?: string[] | null;
+    daily_target?: number | null; // Seconds; defaults to the dailyTargetMinutes setting
+    break_gap?: number | null; // Seconds; defaults to the breakGapMinutes setting
+};
+
+export type AttendanceDay = {
+    date: string; // YYYY-MM-DD
+    day_start: number;
+    is_workday: boolean;
+    first_activity: number | null;
+    last_activity: number | null;
+    worked: number;
+    break_time: number;
+    breaks: number;
+    target: number;
+    overtime: number; // Negative when short of the target
+};
+
+export type AttendanceWeek = {
+    week_start: number;
+    worked: number;
+    target: number;
+    overtime: number;
+};
+
+export type AttendanceReport = {
+    daily_target: number;
+    break_gap: number;
+    days: AttendanceDay[];
+    weeks: AttendanceWeek[];
+    total_worked: number;
+    total_overtime: number;
+};
+
+export async function get_attendance_report(
+    rangeStart: number,
+    rangeEnd: number,
+    options: AttendanceOptions = {},
+): Promise<AttendanceReport> {
+    return invokeOrThrow<AttendanceReport>("get_attendance_report", {
+        rangeStart,
+        rangeEnd,
+        options,
+    });
+}
+
+// Writes CSV when the path ends in .csv, JSON otherwise.
+export async function export_attendance_report(
+    path: string,
+    rangeStart: number,
+    rangeEnd: number,
+    options: AttendanceOptions = {},
+): Promise<null> {
+    return invokeOrThrow<null>("export_attendance_report", {
+        path,
+        rangeStart,
+        rangeEnd,
+        options,
+    });
+}
@@ -0,0 +1,30 @@
+import {invokeOrThrow} from "../utils.ts";
+import type {ManualTimeMode} from "./ManualTimeBlock.ts";
+
+export type StatisticsChart = "category_donut" | "hourly" | "day_category";
+
+// Each call returns a standalone SVG document.
+export async function render_statistics_chart(
+    chart: StatisticsChart,
+    rangeStart: number,
+    rangeEnd: number,
+    deviceUuids: string[] | null = null,
+    manualTime: ManualTimeMode | null = null,
+): Promise<string> {
+    return invokeOrThrow<string>("render_statistics_chart", {
+        chart,
+        rangeStart,
+        rangeEnd,
+        deviceUuids,
+        manualTime,
+    });
+}
+
+export async function render_heatmap_chart(
+    year: number,
+    category: string | null = null,
+    appGroup: string | null = null,
+    deviceUuids: string[] | null = null,
+): Promise<string> {
+    return invokeOrThrow<string>("render_heatmap_chart", {year, category, appGroup, deviceUuids});
+}
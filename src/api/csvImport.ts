@@ -0,0 +1,69 @@
+import {invokeOrThrow} from "../utils.ts";
+
+export type CsvPreset = "toggl" | "clockify" | "generic";
+
+// Header names, matched case-insensitively. Leave a field null to ignore it.
+export type ColumnMapping = {
+    title?: string | null;
+    task?: string | null; // Used as the title when the title column is empty
+    notes?: string | null;
+    project?: string | null;
+    client?: string | null;
+    tags?: string | null; // Comma-separated
+    start_date?: string | null; // May hold the date and time together
+    start_time?: string | null;
+    end_date?: string | null;
+    end_time?: string | null;
+    duration?: string | null; // "H:MM:SS" or decimal hours, used without an end
+    date_format?: string | null; // e.g. "%d/%m/%Y", guessed when unset
+};
+
+export type CsvRow = {
+    line: number;
+    title: string;
+    notes: string | null;
+    project: string | null;
+    client: string | null;
+    tags: string[];
+    start_time: number;
+    end_time: number;
+    duplicate: boolean; // Imported before, or repeated earlier in the file
+};
+
+export type CsvRowError = {
+    line: number;
+    message: string;
+};
+
+export type CsvImportPreview = {
+    headers: string[];
+    rows: CsvRow[];
+    errors: CsvRowError[];
+    warnings: string[];
+    new_projects: string[];
+};
+
+export type CsvImportReport = {
+    created: number;
+    duplicates: number;
+    failed: number;
+    projects_created: number;
+    clients_created: number;
+};
+
+// A mapping replaces the preset's columns entirely.
+export async function preview_csv_import(
+    path: string,
+    preset: CsvPreset,
+    mapping: ColumnMapping | null = null,
+): Promise<CsvImportPreview> {
+    return invokeOrThrow<CsvImportPreview>("preview_csv_import", {path, preset, mapping});
+}
+
+export async function import_csv(
+    path: string,
+    preset: CsvPreset,
+    mapping: ColumnMapping | null = null,
+): Promise<CsvImportReport> {
+    return invokeOrThrow<CsvImportReport>("import_csv", {path, preset, mapping});
+}
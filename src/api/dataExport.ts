@@ -0,0 +1,30 @@
+import {invokeOrThrow} from "../utils.ts";
+
+// "statistics" is daily totals per category.
+export type ExportDataset = "logs" | "time_blocks" | "manual_time_blocks" | "statistics";
+
+export type ExportFilter = {
+    device_uuids?: string[] | null;
+    categories?: string[] | null; // Manual time blocks have no category and are kept
+};
+
+export type ExportSummary = {
+    rows: number;
+};
+
+// Writes CSV for .csv, NDJSON for .ndjson/.jsonl and JSON otherwise.
+export async function export_data(
+    path: string,
+    dataset: ExportDataset,
+    rangeStart: number,
+    rangeEnd: number,
+    filter: ExportFilter = {},
+): Promise<ExportSummary> {
+    return invokeOrThrow<ExportSummary>("export_data", {
+        path,
+        dataset,
+        rangeStart,
+        rangeEnd,
+        filter,
+    });
+}
@@ -0,0 +1,46 @@
+import {invokeOrThrow} from "../utils.ts";
+
+export type DatabaseMergeOptions = {
+    device_uuid?: string | null; // Import every log under this device instead of its own
+    device_name?: string | null; // Name for a device the merge has to create
+    dry_run?: boolean; // Report what would change without writing
+};
+
+export type MergedDevice = {
+    source_uuid: string;
+    device_uuid: string;
+    device_name: string;
+    created: boolean;
+    logs_added: number;
+    logs_updated: number; // Same log with a longer duration in the other database
+    logs_duplicate: number;
+};
+
+export type MergeConflict = {
+    kind: "category" | "app_group";
+    name: string;
+    detail: string;
+};
+
+export type DatabaseMergeReport = {
+    devices: MergedDevice[];
+    categories_added: number;
+    categories_matched: number;
+    regexes_added: number;
+    app_groups_added: number;
+    projects_added: number;
+    clients_added: number;
+    manual_blocks_added: number;
+    manual_blocks_duplicate: number;
+    conflicts: MergeConflict[]; // This database's version was kept
+    dry_run: boolean;
+    backup_created: string | null;
+};
+
+// Both databases must be on the same schema version; open the other file with this app first.
+export async function merge_database(
+    path: string,
+    options: DatabaseMergeOptions = {},
+): Promise<DatabaseMergeReport> {
+    return invokeOrThrow<DatabaseMergeReport>("merge_database", {path, options});
+}
@@ -0,0 +1,39 @@
+import { invokeOrThrow } from "../utils.ts";
+
+export type GapSuggestionKind = "calendar_event" | "previous_block" | "next_block" | "recurring";
+
+export type GapSuggestion = {
+    kind: GapSuggestionKind;
+    title: string;
+    start: number;
+    end: number;
+    detail: string | null;
+    category_id: number | null; // Category the accepted block is filed under
+};
+
+export type UntrackedGap = {
+    start: number;
+    end: number;
+    duration: number;
+    suggestions: GapSuggestion[];
+};
+
+// minGap is in seconds and defaults to the minGapMinutes setting.
+export async function get_untracked_gaps(
+    rangeStart: number,
+    rangeEnd: number,
+    minGap: number | null = null,
+    deviceUuids: string[] | null = null,
+): Promise<UntrackedGap[]> {
+    return invokeOrThrow<UntrackedGap[]>("get_untracked_gaps", {
+        rangeStart,
+        rangeEnd,
+        minGap,
+        deviceUuids,
+    });
+}
+
+// Saves the suggestion as a manual time block and returns its id.
+export async function accept_gap_suggestion(suggestion: GapSuggestion): Promise<number> {
+    return invokeOrThrow<number>("accept_gap_suggestion", { suggestion });
+}
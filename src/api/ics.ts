@@ -0,0 +1,34 @@
+import {invokeOrThrow} from "../utils.ts";
+
+export type IcsExportOptions = {
+    device_uuids?: string[] | null;
+    include_time_blocks?: boolean; // Defaults to true
+    include_manual_time_blocks?: boolean; // Defaults to true
+};
+
+export type IcsImportReport = {
+    created: number;
+    updated: number;
+    unchanged: number;
+    skipped: number; // All-day, cancelled, zero-length or exported by this app
+    warnings: string[];
+};
+
+// Returns the number of events written.
+export async function export_ics(
+    path: string,
+    rangeStart: number,
+    rangeEnd: number,
+    options: IcsExportOptions = {},
+): Promise<number> {
+    return invokeOrThrow<number>("export_ics", {path, rangeStart, rangeEnd, options});
+}
+
+// Imports events as manual time blocks; recurrences are expanded up to rangeEnd.
+export async function import_ics(
+    path: string,
+    rangeStart: number,
+    rangeEnd: number,
+): Promise<IcsImportReport> {
+    return invokeOrThrow<IcsImportReport>("import_ics", {path, rangeStart, rangeEnd});
+}
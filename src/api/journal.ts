@@ -0,0 +1,20 @@
+import {invokeOrThrow} from "../utils.ts";
+
+export type JournalConfig = {
+    enabled: boolean; // Export each day automatically once it has ended
+    folder: string | null; // Daily notes folder of the vault
+    top_apps: number;
+};
+
+export async function get_journal_config(): Promise<JournalConfig> {
+    return invokeOrThrow<JournalConfig>("get_journal_config");
+}
+
+export async function set_journal_config(config: JournalConfig): Promise<null> {
+    return invokeOrThrow<null>("set_journal_config", {config});
+}
+
+// Writes or refreshes the marked section of <folder>/<date>.md and returns its path.
+export async function export_journal(date: string): Promise<string> {
+    return invokeOrThrow<string>("export_journal", {date});
+}
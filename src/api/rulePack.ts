@@ -0,0 +1,49 @@
+import {invokeOrThrow} from "../utils.ts";
+
+export type RulePackImportMode = "merge" | "replace";
+
+export type RulePackExportOptions = {
+    categories?: string[] | null;
+    include_app_groups?: boolean;
+    include_skipped_apps?: boolean;
+};
+
+export type RulePackConflict = {
+    kind: "category" | "category_regex" | "app_group";
+    name: string;
+    detail: string;
+};
+
+export type RulePackImportReport = {
+    categories_added: string[];
+    categories_updated: string[];
+    categories_removed: string[];
+    regexes_added: number;
+    app_groups_added: string[];
+    skipped_apps_added: number;
+    conflicts: RulePackConflict[];
+    backup_created: string | null;
+    applied: boolean;
+};
+
+export async function export_rule_pack(
+    path: string,
+    options: RulePackExportOptions = {},
+): Promise<null> {
+    return invokeOrThrow<null>("export_rule_pack", {
+        path,
+        options: {
+            categories: options.categories ?? null,
+            include_app_groups: options.include_app_groups ?? true,
+            include_skipped_apps: options.include_skipped_apps ?? true,
+        },
+    });
+}
+
+export async function import_rule_pack(
+    path: string,
+    mode: RulePackImportMode,
+    dryRun = false, // Report what would change without writing
+): Promise<RulePackImportReport> {
+    return invokeOrThrow<RulePackImportReport>("import_rule_pack", {path, mode, dryRun});
+}
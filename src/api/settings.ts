@@ -24,3 +24,17 @@ export async function flipSettingLock(key: string): Promise<void> {
 export async function resetSettingVal(key: string): Promise<void> {
     await invokeOrThrow("reset_val_by_key", { key });
 }
+
+export type TimeZoneSetting = {
+    configured: string | null; // null follows the system zone
+    effective: string;
+    system: string;
+};
+
+export async function getTimeZone(): Promise<TimeZoneSetting> {
+    return invokeOrThrow<TimeZoneSetting>("get_time_zone");
+}
+
+export async function setTimeZone(timeZone: string | null): Promise<void> {
+    await invokeOrThrow("set_time_zone", { timeZone });
+}
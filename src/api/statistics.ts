@@ -1,4 +1,5 @@
 import { invokeOrThrow } from "../utils.ts";
+import type { ManualTimeMode } from "./ManualTimeBlock.ts";
 
 export type CategoryStat = {
     category: string;
@@ -6,6 +7,7 @@ export type CategoryStat = {
     percentage: number;
     percentage_change: number | null;
     color: string | null;
+    productivity_weight: number;
 };
 
 export type AppStat = {
@@ -21,11 +23,71 @@ export type HourlyStat = {
 };
 
 export type DayCategoryStat = {
-    day: number;
+    day: number; // Position in the week, 0 = configured first day
+    weekday: number; // 0 = Monday … 6 = Sunday
     category: string;
     total_duration: number;
 };
 
+export type ProductivityContributor = {
+    category: string;
+    weight: number;
+    total_duration: number;
+    weighted_duration: number;
+};
+
+export type ProductivityScore = {
+    score: number; // -100 to 100
+    weighted_duration: number;
+    previous_score: number | null;
+    score_change: number | null; // Points vs previous period
+    top_positive: ProductivityContributor[];
+    top_negative: ProductivityContributor[];
+};
+
+export type DayProductivity = {
+    day_start: number;
+    score: number;
+    total_duration: number;
+};
+
+export type HourlySwitchStat = {
+    hour: number; // 0-23
+    switches: number;
+};
+
+export type CategorySessionStat = {
+    category: string;
+    longest_session: number;
+    started_at: number; // Unix timestamp of the longest session's first log
+    sessions: number;
+};
+
+export type FocusSessionBucket = {
+    min_duration: number;
+    max_duration: number | null; // Exclusive; null for the open-ended last bucket
+    sessions: number;
+    total_duration: number;
+};
+
+export type DayFragmentation = {
+    day_start: number;
+    switches: number;
+    sessions: number;
+    total_duration: number;
+    switches_per_hour: number;
+    fragmentation_index: number; // 0 = one unbroken session, approaching 1 = many short ones
+};
+
+export type FocusStatistics = {
+    total_switches: number;
+    switches_per_hour: HourlySwitchStat[];
+    longest_sessions: CategorySessionStat[];
+    session_histogram: FocusSessionBucket[];
+    daily_fragmentation: DayFragmentation[];
+    fragmentation_index: number;
+};
+
 export type WeekStatistics = {
     total_time: number;
     total_time_change: number | null;
@@ -42,6 +104,9 @@ export type WeekStatistics = {
     average_time_active_days: number;
     most_active_day: [number, number] | null; // [timestamp, duration]
     most_inactive_day: [number, number] | null; // [timestamp, duration]
+    productivity: ProductivityScore;
+    daily_productivity: DayProductivity[];
+    focus: FocusStatistics;
 };
 
 export type DayStatistics = {
@@ -49,17 +114,41 @@ export type DayStatistics = {
     categories: CategoryStat[];
     top_apps: AppStat[];
     hourly_distribution: HourlyStat[];
+    productivity: ProductivityScore;
+    focus: FocusStatistics;
+};
+
+export type RangeBucket = "day" | "week" | "month";
+
+export type BucketStat = {
+    start: number;
+    end: number; // Inclusive
+    total_time: number;
+    categories: CategoryStat[];
+    productivity_score: number;
+};
+
+export type RangeStatistics = {
+    range_start: number;
+    range_end: number;
+    bucket: RangeBucket;
+    previous_start: number;
+    previous_end: number;
+    statistics: WeekStatistics;
+    buckets: BucketStat[];
 };
 
 export async function get_week_statistics(
     weekStart: number,
     weekEnd: number,
     deviceUuids?: string[] | null,
+    manualTime?: ManualTimeMode,
 ): Promise<WeekStatistics> {
     return invokeOrThrow<WeekStatistics>("get_week_statistics", {
         weekStart,
         weekEnd,
         deviceUuids: deviceUuids ?? null,
+        manualTime: manualTime ?? null,
     });
 }
 
@@ -71,10 +160,126 @@ export async function get_day_statistics(
     dayStart: number,
     dayEnd: number,
     deviceUuids?: string[] | null,
+    manualTime?: ManualTimeMode,
 ): Promise<DayStatistics> {
     return invokeOrThrow<DayStatistics>("get_day_statistics", {
         dayStart,
         dayEnd,
         deviceUuids: deviceUuids ?? null,
+        manualTime: manualTime ?? null,
+    });
+}
+
+export async function get_range_statistics(
+    rangeStart: number,
+    rangeEnd: number,
+    bucket: RangeBucket,
+    deviceUuids?: string[] | null,
+    manualTime?: ManualTimeMode,
+): Promise<RangeStatistics> {
+    return invokeOrThrow<RangeStatistics>("get_range_statistics", {
+        rangeStart,
+        rangeEnd,
+        bucket,
+        deviceUuids: deviceUuids ?? null,
+        manualTime: manualTime ?? null,
+    });
+}
+
+export type TrendLine = {
+    slope: number; // Seconds per day
+    intercept: number; // Fitted seconds on the first day
+};
+
+export type Streaks = {
+    current: number;
+    longest: number;
+    longest_start: number | null; // Index into TrendSeries.days
+};
+
+export type CategorySeries = {
+    category: string;
+    color: string | null;
+    daily_totals: number[];
+    rolling_7: number[];
+    rolling_28: number[];
+    streaks: Streaks;
+    trend: TrendLine;
+};
+
+export type TrendSeries = {
+    days: number[]; // Start of each day, oldest first
+    min_daily_duration: number;
+    total: CategorySeries;
+    categories: CategorySeries[];
+};
+
+export async function get_category_trends(
+    rangeEnd: number,
+    days: number,
+    minDailyDuration: number,
+    deviceUuids?: string[] | null,
+): Promise<TrendSeries> {
+    return invokeOrThrow<TrendSeries>("get_category_trends", {
+        rangeEnd,
+        days,
+        minDailyDuration,
+        deviceUuids: deviceUuids ?? null,
+    });
+}
+
+export type HeatmapDay = {
+    date: string; // YYYY-MM-DD
+    day_start: number;
+    total_duration: number;
+    level: number; // 0 = no activity, 4 = busiest quarter of active days
+};
+
+export type YearHeatmap = {
+    year: number;
+    days: HeatmapDay[];
+    thresholds: number[]; // Highest total in levels 1, 2 and 3
+    total_duration: number;
+    active_days: number;
+    max_duration: number;
+};
+
+export async function get_year_heatmap(
+    year: number,
+    filter?: { category?: string; appGroup?: string },
+    deviceUuids?: string[] | null,
+): Promise<YearHeatmap> {
+    return invokeOrThrow<YearHeatmap>("get_year_heatmap", {
+        year,
+        category: filter?.category ?? null,
+        appGroup: filter?.appGroup ?? null,
+        deviceUuids: deviceUuids ?? null,
+    });
+}
+
+export type TitleStat = {
+    title: string;
+    total_duration: number;
+    first_seen: number;
+    last_seen: number; // End of the last log
+    sessions: number;
+    raw_titles: string[]; // Logged titles folded into this one, most used first
+};
+
+// Pass exactly one of category or appGroup.
+export async function get_title_drilldown(
+    rangeStart: number,
+    rangeEnd: number,
+    filter: { category?: string; appGroup?: string },
+    normalize: boolean,
+    deviceUuids?: string[] | null,
+): Promise<TitleStat[]> {
+    return invokeOrThrow<TitleStat[]>("get_title_drilldown", {
+        rangeStart,
+        rangeEnd,
+        category: filter.category ?? null,
+        appGroup: filter.appGroup ?? null,
+        normalize,
+        deviceUuids: deviceUuids ?? null,
     });
 }
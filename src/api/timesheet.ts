@@ -0,0 +1,74 @@
+import {invokeOrThrow} from "../utils.ts";
+import type {ManualTimeMode} from "./ManualTimeBlock.ts";
+
+export type TimesheetGrouping = "category" | "project";
+
+export type RoundingMode = "up" | "down" | "nearest";
+
+// Each day's time per item is rounded to a multiple of `minutes`; 0 keeps exact times.
+export type RoundingRule = {
+    minutes: number;
+    mode?: RoundingMode; // Defaults to "up"
+};
+
+export type TimesheetOptions = {
+    title?: string | null;
+    grouping?: TimesheetGrouping;
+    rounding?: RoundingRule;
+    hourly_rate?: number | null; // For category timesheets, or billable projects without a rate
+    include_rates?: boolean; // Project timesheets use project and client rates
+    currency?: string | null;
+    device_uuids?: string[] | null;
+    manual_time?: ManualTimeMode;
+    template_path?: string | null; // Overrides report_templates/timesheet.{html,md}
+};
+
+export type TimesheetEntry = {
+    date: string; // YYYY-MM-DD
+    label: string;
+    seconds: number;
+    rounded_seconds: number;
+    hourly_rate: number | null;
+    amount: number | null;
+};
+
+export type TimesheetTotal = {
+    label: string;
+    seconds: number;
+    rounded_seconds: number;
+    amount: number | null;
+    color: string | null; // Category colour, for category timesheets
+};
+
+export type Timesheet = {
+    title: string;
+    range_start: number;
+    range_end: number;
+    first_date: string | null;
+    last_date: string | null;
+    rounding: RoundingRule;
+    currency: string | null;
+    entries: TimesheetEntry[];
+    totals: TimesheetTotal[];
+    total_seconds: number;
+    total_rounded_seconds: number;
+    total_amount: number | null; // null when nothing has a rate
+};
+
+export async function get_timesheet(
+    rangeStart: number,
+    rangeEnd: number,
+    options: TimesheetOptions = {},
+): Promise<Timesheet> {
+    return invokeOrThrow<Timesheet>("get_timesheet", {rangeStart, rangeEnd, options});
+}
+
+// Writes Markdown for .md/.markdown paths and HTML otherwise.
+export async function export_timesheet(
+    path: string,
+    rangeStart: number,
+    rangeEnd: number,
+    options: TimesheetOptions = {},
+): Promise<Timesheet> {
+    return invokeOrThrow<Timesheet>("export_timesheet", {path, rangeStart, rangeEnd, options});
+}
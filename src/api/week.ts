@@ -56,3 +56,15 @@ export async function get_week_for_app_filter(
     return result.map(transformTimeBlock);
 }
 
+export async function get_range(
+    rangeStart: number,
+    rangeEnd: number,
+    deviceUuids?: string[] | null,
+): Promise<TimeBlock[]> {
+    const result = await invokeOrThrow<TimeBlockBackend[]>("get_range", {
+        rangeStart,
+        rangeEnd,
+        deviceUuids: deviceUuids ?? null,
+    });
+    return result.map(transformTimeBlock);
+}
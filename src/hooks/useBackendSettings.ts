@@ -3,6 +3,7 @@ import { useQuery, useQueryClient } from "@tanstack/react-query";
 import {
     flipSettingLock,
     getSettings,
+    getTimeZone,
     resetSettingVal,
     updateSettingVal,
 } from "../api/settings.ts";
@@ -27,6 +28,8 @@ export type BackendSettings = {
     fields: Record<string, SettingField>;
     allLocked: boolean;
     calendarStartHour: number;
+    weekStartDay: number; // 0 = Monday … 6 = Sunday
+    timeZone: string; // IANA zone days and weeks are counted in
     calendarHeight: number;
     rightSidebarWidth: number;
     categorySidebarCount: number;
@@ -39,9 +42,11 @@ export type BackendSettings = {
 };
 
 const SETTINGS_QUERY_KEY = ["settings"];
+const TIME_ZONE_QUERY_KEY = ["time_zone"];
 
 const LOADING_FALLBACK: Record<string, number> = {
     calendarStartHour: 8,
+    weekStartDay: 0,
     calendarHeight: 100,
     rightSidebarWidth: 480,
     categorySidebarCount: 5,
@@ -58,6 +63,10 @@ export function useBackendSettings(): BackendSettings {
         queryKey: SETTINGS_QUERY_KEY,
         queryFn: getSettings,
     });
+    const { data: timeZoneSetting } = useQuery({
+        queryKey: TIME_ZONE_QUERY_KEY,
+        queryFn: getTimeZone,
+    });
 
     const fields = useMemo(() => {
         const map: Record<string, SettingField> = {};
@@ -103,6 +112,8 @@ export function useBackendSettings(): BackendSettings {
         fields,
         allLocked,
         calendarStartHour: valueOf("calendarStartHour"),
+        weekStartDay: valueOf("weekStartDay"),
+        timeZone: timeZoneSetting?.effective ?? Intl.DateTimeFormat().resolvedOptions().timeZone,
         calendarHeight: valueOf("calendarHeight"),
         rightSidebarWidth: valueOf("rightSidebarWidth"),
         categorySidebarCount: valueOf("categorySidebarCount"),
@@ -7,13 +7,68 @@ export async function invokeOrThrow<T>(
     return await invoke<T>(command, args);
 }
 
+const DAY_MS = 24 * 60 * 60 * 1000;
+const zoneFormatters = new Map<string, Intl.DateTimeFormat>();
+
+/** Wall-clock fields of an instant in an IANA time zone. */
+function zonedParts(ms: number, timeZone: string) {
+    let formatter = zoneFormatters.get(timeZone);
+    if (!formatter) {
+        formatter = new Intl.DateTimeFormat("en-US", {
+            timeZone,
+            hourCycle: "h23",
+            year: "numeric",
+            month: "numeric",
+            day: "numeric",
+            hour: "numeric",
+            minute: "numeric",
+            second: "numeric",
+        });
+        zoneFormatters.set(timeZone, formatter);
+    }
+    const parts = formatter.formatToParts(new Date(ms));
+    const value = (type: Intl.DateTimeFormatPartTypes) =>
+        Number(parts.find((part) => part.type === type)?.value ?? 0);
+    return {
+        year: value("year"),
+        month: value("month") - 1,
+        day: value("day"),
+        hour: value("hour"),
+        minute: value("minute"),
+        second: value("second"),
+    };
+}
+
+function timeZoneOffsetMs(ms: number, timeZone: string): number {
+    const p = zonedParts(ms, timeZone);
+    const wholeSeconds = Math.floor(ms / 1000) * 1000;
+    return Date.UTC(p.year, p.month, p.day, p.hour, p.minute, p.second) - wholeSeconds;
+}
+
+/**
+ * Unix seconds of a wall-clock hour in an IANA time zone, resolved like the backend:
+ * repeated times take the first occurrence and times skipped by DST move past the gap.
+ */
+function zonedHourToUnix(year: number, month: number, day: number, hour: number, timeZone: string): number {
+    const wall = Date.UTC(year, month, day, hour);
+    const before = timeZoneOffsetMs(wall - DAY_MS, timeZone);
+    const after = timeZoneOffsetMs(wall + DAY_MS, timeZone);
+    const valid = [wall - before, wall - after].filter(
+        (ms) => ms + timeZoneOffsetMs(ms, timeZone) === wall
+    );
+    return Math.floor((valid.length > 0 ? Math.min(...valid) : wall - before) / 1000);
+}
+
 /**
  * Calculate the start and end timestamps for the week containing the given date
- * Week starts on Monday and ends on Sunday
+ * Week starts on weekStartDay (0 = Monday … 6 = Sunday, matching the backend setting)
+ * Weeks are counted in timeZone (the configured IANA zone) when given, else in the browser's zone
  */
 export function getWeekRange(
     date: Date,
-    calendarStartHour: number = 6
+    calendarStartHour: number = 6,
+    weekStartDay: number = 0,
+    timeZone?: string
 ): {
     week_start: number;
     week_end: number;
@@ -21,15 +76,28 @@ export function getWeekRange(
     const h = Number.isFinite(calendarStartHour)
         ? Math.min(23, Math.max(0, Math.floor(calendarStartHour)))
         : 6;
+    const firstDay = Number.isFinite(weekStartDay)
+        ? Math.min(6, Math.max(0, Math.floor(weekStartDay)))
+        : 0;
+
+    if (timeZone) {
+        const { year, month, day } = zonedParts(date.getTime(), timeZone);
+        const dow = (new Date(Date.UTC(year, month, day)).getUTCDay() + 6) % 7;
+        const first = day - ((dow - firstDay + 7) % 7);
+        return {
+            week_start: zonedHourToUnix(year, month, first, h, timeZone),
+            week_end: zonedHourToUnix(year, month, first + 7, h, timeZone) - 1,
+        };
+    }
 
     const y = date.getFullYear();
     const m = date.getMonth();
     const day = date.getDate();
-    const dow = date.getDay();
-    const offsetToMonday = dow === 0 ? -6 : 1 - dow;
-    const mondayCal = new Date(y, m, day + offsetToMonday, 0, 0, 0, 0);
+    const dowFromMonday = (date.getDay() + 6) % 7;
+    const offsetToFirstDay = -((dowFromMonday - firstDay + 7) % 7);
+    const firstDayCal = new Date(y, m, day + offsetToFirstDay, 0, 0, 0, 0);
 
-    const weekStart = new Date(mondayCal);
+    const weekStart = new Date(firstDayCal);
     weekStart.setHours(h, 0, 0, 0);
 
     const weekEndExclusive = new Date(weekStart);
@@ -78,8 +146,13 @@ export function getCalendarDayRangeUnix(
     };
 }
 
-export function getWeekStartDate(date: Date, calendarStartHour: number): Date {
-    const { week_start } = getWeekRange(date, calendarStartHour);
+export function getWeekStartDate(
+    date: Date,
+    calendarStartHour: number,
+    weekStartDay: number = 0,
+    timeZone?: string
+): Date {
+    const { week_start } = getWeekRange(date, calendarStartHour, weekStartDay, timeZone);
     return new Date(week_start * 1000);
 }
 
@@ -87,16 +160,18 @@ export function enumerateWeekRangesInSpan(
     rangeStart: Date,
     rangeEnd: Date,
     calendarStartHour: number,
-    maxWeeks = 24
+    maxWeeks = 24,
+    weekStartDay: number = 0,
+    timeZone?: string
 ): { week_start: number; week_end: number }[] {
-    let cursor = getWeekStartDate(rangeStart, calendarStartHour);
-    const endCursor = getWeekStartDate(rangeEnd, calendarStartHour);
+    let cursor = getWeekStartDate(rangeStart, calendarStartHour, weekStartDay, timeZone);
+    const endCursor = getWeekStartDate(rangeEnd, calendarStartHour, weekStartDay, timeZone);
     const out: { week_start: number; week_end: number }[] = [];
     while (cursor.getTime() <= endCursor.getTime()) {
-        out.push(getWeekRange(cursor, calendarStartHour));
+        out.push(getWeekRange(cursor, calendarStartHour, weekStartDay, timeZone));
         const next = new Date(cursor);
         next.setDate(next.getDate() + 7);
-        cursor = getWeekStartDate(next, calendarStartHour);
+        cursor = getWeekStartDate(next, calendarStartHour, weekStartDay, timeZone);
     }
     if (out.length > maxWeeks) {
         return out.slice(out.length - maxWeeks);